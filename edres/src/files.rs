@@ -1,8 +1,30 @@
 //! Utility functions for working with output files.
 
-use std::path::Path;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
 
-use edres_core::Error;
+use edres_core::{codegen, options::OutputOptions, value::Datetime, Error, Options};
+
+/// The start of the line of the provenance banner which records
+/// when a file was generated.
+const TIMESTAMP_PREFIX: &str = "// Generated at: ";
+
+/// Utility function to find the file that output for `path` is
+/// written to.
+///
+/// This is `path` itself, unless [`OutputOptions::out_dir_relative`]
+/// is set, in which case it is relative to `$OUT_DIR`.
+pub fn resolve_destination(path: &Path, options: &OutputOptions) -> Result<PathBuf, Error> {
+    if !options.out_dir_relative {
+        return Ok(path.to_owned());
+    }
+
+    let out_dir = std::env::var_os("OUT_DIR").ok_or(Error::OutDirNotSet)?;
+    Ok(Path::new(&out_dir).join(path))
+}
 
 /// Utility function to create parent directories of a path.
 ///
@@ -20,15 +42,22 @@ pub fn ensure_destination(path: &Path, create_dirs: bool) -> Result<(), Error> {
 
 /// Utility function to write output to a file, optionally only
 /// if changed.
+///
+/// The header and provenance banner from the options are written
+/// before the output. `sources` are the paths the output was
+/// generated from, if any.
 pub fn write_destination(
     destination: &Path,
-    output: String,
-    write_only_if_changed: bool,
+    output: &str,
+    sources: &[&Path],
+    options: &OutputOptions,
 ) -> Result<(), Error> {
-    let should_write = if write_only_if_changed {
+    let output = format!("{}{}", banner(sources, options), output);
+
+    let should_write = if options.write_only_if_changed {
         let existing = std::fs::read_to_string(destination);
         match existing {
-            Ok(existing) => existing != output,
+            Ok(existing) => !without_timestamp(&existing).eq(without_timestamp(&output)),
             Err(_) => true,
         }
     } else {
@@ -40,3 +69,163 @@ pub fn write_destination(
     }
     Ok(())
 }
+
+/// Utility function to check that a file contains the given
+/// output, without writing it (see [`OutputOptions::check_only`]).
+///
+/// This is the same comparison as [`write_destination`] uses, so
+/// timestamps are ignored. If the file differs, the error says
+/// where.
+pub fn check_destination(
+    destination: &Path,
+    output: &str,
+    sources: &[&Path],
+    options: &OutputOptions,
+) -> Result<(), Error> {
+    let output = format!("{}{}", banner(sources, options), output);
+    let existing = match std::fs::read_to_string(destination) {
+        Ok(existing) => existing,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            return Err(Error::OutOfDate(
+                destination.to_owned(),
+                "the file does not exist".into(),
+            ))
+        }
+        Err(error) => return Err(error.into()),
+    };
+
+    let mut existing_lines = without_timestamp(&existing);
+    let mut output_lines = without_timestamp(&output);
+    let mut line = 0;
+    let summary = loop {
+        line += 1;
+        match (existing_lines.next(), output_lines.next()) {
+            (None, None) => return Ok(()),
+            (Some(a), Some(b)) if a == b => (),
+            (Some(_), Some(_)) => break format!("line {} differs", line),
+            (Some(_), None) => break format!("unexpected lines from line {}", line),
+            (None, Some(_)) => break format!("missing lines from line {}", line),
+        }
+    };
+    Err(Error::OutOfDate(destination.to_owned(), summary))
+}
+
+/// Utility function to generate output from the files in a
+/// directory, using the [`OutputOptions::cache_dir`] if set.
+///
+/// If the files, options, and `kind` of output are unchanged
+/// since the output for `destination` was last cached, the cached
+/// output is returned instead of calling `generate`.
+///
+/// The cache isn't used when
+/// [`ParseOptions::include_directive`](edres_core::options::ParseOptions::include_directive)
+/// is set, because included files can be outside the directory.
+pub fn generate_cached<F>(
+    kind: &str,
+    dir: &Path,
+    name: &str,
+    destination: &Path,
+    options: &Options,
+    generate: F,
+) -> Result<String, Error>
+where
+    F: FnOnce() -> Result<String, Error>,
+{
+    let (Some(cache_dir), None) = (&options.output.cache_dir, &options.parse.include_directive)
+    else {
+        return generate();
+    };
+
+    let hash = format!("{:016x}", input_hash(kind, dir, name, options)?);
+    let cache_path = cache_path(cache_dir, destination);
+    if let Ok(cached) = std::fs::read_to_string(&cache_path) {
+        if let Some((cached_hash, output)) = cached.split_once('\n') {
+            if cached_hash == hash {
+                return Ok(output.to_owned());
+            }
+        }
+    }
+
+    let output = generate()?;
+    std::fs::create_dir_all(cache_dir)?;
+    std::fs::write(&cache_path, format!("{}\n{}", hash, output))?;
+    Ok(output)
+}
+
+/// A hash of everything that affects the output generated from
+/// the files in a directory.
+///
+/// This is only used to detect changes, so it doesn't need to be
+/// stable between versions of Rust.
+fn input_hash(kind: &str, dir: &Path, name: &str, options: &Options) -> Result<u64, Error> {
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    kind.hash(&mut hasher);
+    dir.hash(&mut hasher);
+    name.hash(&mut hasher);
+    format!("{:?}", options).hash(&mut hasher);
+    if let Some(index_file) = &options.files.index_file {
+        std::fs::read(dir.join(index_file.as_ref()))
+            .ok()
+            .hash(&mut hasher);
+    }
+    for path in codegen::files_in_dir(dir, options)? {
+        path.hash(&mut hasher);
+        std::fs::read(&path)?.hash(&mut hasher);
+    }
+    Ok(hasher.finish())
+}
+
+/// The cache file for a destination, named after a hash of its
+/// path so that destinations with the same file name don't clash.
+fn cache_path(cache_dir: &Path, destination: &Path) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    destination.hash(&mut hasher);
+    let file_name = destination
+        .file_stem()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    cache_dir.join(format!("{}-{:016x}.cache", file_name, hasher.finish()))
+}
+
+/// The comment lines written at the top of an output file.
+fn banner(sources: &[&Path], options: &OutputOptions) -> String {
+    let mut banner = String::new();
+    if let Some(header) = &options.header {
+        for line in header.lines() {
+            banner.push_str(format!("// {}", line).trim_end());
+            banner.push('\n');
+        }
+    }
+
+    if options.provenance {
+        banner.push_str(&format!(
+            "// This file was generated by edres {}. Do not edit it by hand.\n",
+            env!("CARGO_PKG_VERSION")
+        ));
+        if !sources.is_empty() {
+            banner.push_str("// Sources:\n");
+            for source in sources {
+                banner.push_str(&format!("//     {}\n", source.display()));
+            }
+        }
+        if options.timestamp {
+            let seconds = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs() as i64);
+            banner.push_str(&format!(
+                "{}{}\n",
+                TIMESTAMP_PREFIX,
+                Datetime::from_unix_timestamp(seconds)
+            ));
+        }
+    }
+    banner
+}
+
+/// The lines of an output file, except the timestamp.
+fn without_timestamp(output: &str) -> impl Iterator<Item = &str> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with(TIMESTAMP_PREFIX))
+}
@@ -2,7 +2,7 @@
 //!
 //! then `edres` turns your markup files into structs.
 //!
-//! This crate has three main related functionalities:
+//! This crate has five main related functionalities:
 //!
 //! 1.  Generate a set of structs representing the contents of
 //!     a single markup file.
@@ -12,6 +12,10 @@
 //! 3.  Generate an enum to represent the files of a directory
 //!     (and optionally structs to represent the contents of those
 //!     files).
+//! 4.  Generate a bitflags struct from a list of flag names in a
+//!     single markup file.
+//! 5.  Generate enums for the locales and keys of a string table
+//!     from a directory of per-locale files.
 //!
 //! The crate is mainly intended to be used in `build.rs` build
 //! scripts, but could also be used inside a proc-macro. (See the
@@ -21,13 +25,28 @@
 //! serde features to support the kinds of files you're using:
 //!
 //! 1. `json`
-//! 2. `toml`
-//! 3. `yaml`
+//! 2. `json5`
+//! 3. `toml`
+//! 4. `yaml`
+//! 5. `xml`
+//! 6. `msgpack`
 //!
 //! There are two sets of functions provided at the top level:
-//! the `create_` functions which will write a Rust source file,
-//! and the `generate_` functions which simply return Rust code as
-//! a string.
+//! the `create_` functions which will write a Rust source file
+//! (and also return the code they wrote), and the `generate_`
+//! functions which simply return Rust code as a string.
+//!
+//! Each `create_` function has a `_with` variant, which takes a
+//! hook to post-process the code before it is written.
+//!
+//! Some `generate_` functions also have a `_with_report` variant,
+//! which lists warnings (like empty sequences, or renamed fields)
+//! for build scripts to print.
+//!
+//! Source files can declare options for their own keys (like type
+//! overrides and doc comments) in a top-level `__edres` section,
+//! which is left out of the generated structs. (See
+//! [`directives::InlineDirectives`].)
 //!
 //! # Examples
 //!
@@ -150,15 +169,57 @@
 //!     }
 //! ).to_string());
 //! ```
+//!
+//! ## Writing to `OUT_DIR`
+//!
+//! Build scripts usually write generated code to `$OUT_DIR`. With
+//! [`OutputOptions::out_dir_relative`], destination paths are
+//! relative to it:
+//!
+//! ```no_run
+//! # use edres::*;
+//! // build.rs
+//! create_structs(
+//!     "config.toml",
+//!     "config.rs",
+//!     "Config",
+//!     &Options {
+//!         output: OutputOptions {
+//!             out_dir_relative: true,
+//!             ..OutputOptions::new()
+//!         },
+//!         ..Options::new()
+//!     },
+//! ).unwrap();
+//! ```
+//!
+//! The code can then be included in the crate with the same path,
+//! using [`include_generated!`]:
+//!
+//! ```ignore
+//! // src/lib.rs
+//! edres::include_generated!("config.rs");
+//! ```
 
 mod files;
+pub mod manifest;
+#[cfg(feature = "watch")]
+pub mod watch;
 
-#[cfg(not(any(feature = "json", feature = "toml", feature = "yaml",)))]
+#[cfg(not(any(
+    feature = "json",
+    feature = "toml",
+    feature = "yaml",
+    feature = "xml",
+    feature = "msgpack",
+)))]
 compile_error!(
-    "The edres crate requires at least one parsing feature to be enabled:\n {json, toml, yaml}"
+    "The edres crate requires at least one parsing feature to be enabled:\n {json, toml, yaml, xml, msgpack}"
 );
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use proc_macro2::TokenStream;
 
 #[cfg(feature = "proc-macros")]
 pub use edres_macros::{
@@ -166,6 +227,7 @@ pub use edres_macros::{
 };
 
 pub use edres_core::*;
+pub use manifest::Manifest;
 
 /// Generate Rust code that defines a set of structs based on a
 /// given markup file.
@@ -175,38 +237,232 @@ pub fn generate_structs<SrcPath: AsRef<Path>, Name: AsRef<str>>(
     options: &Options,
 ) -> Result<String, Error> {
     let path = src_path.as_ref();
-    let value = parsing::parse_source_file(path, &options.parse)?.assume_struct()?;
-    let tokens = codegen::define_structs(&value, struct_name.as_ref(), Some(path), options)?;
-    Ok(tokens.to_string())
+    let spanned = parsing::parse_source_file_spanned(path, &options.parse)?;
+    define_structs_for_value(
+        spanned.value.clone(),
+        struct_name.as_ref(),
+        Some(path),
+        options,
+    )
+    .map_err(|error| spanned.locate_error(error))
+}
+
+/// Generate Rust code that defines a set of structs based on the
+/// value at the given key path (like `server.limits`) within a
+/// markup file.
+///
+/// Key paths in the options (like [`ParseOptions::type_overrides`])
+/// are relative to that value.
+pub fn generate_structs_with_root<SrcPath: AsRef<Path>, Name: AsRef<str>, KeyPath: AsRef<str>>(
+    src_path: SrcPath,
+    struct_name: Name,
+    key_path: KeyPath,
+    options: &Options,
+) -> Result<String, Error> {
+    let path = src_path.as_ref();
+    let spanned = parsing::parse_source_file_spanned(path, &options.parse)?;
+    let define = || {
+        let key_path = key_path.as_ref();
+        let mut value = spanned.value.clone();
+        let options = &directives::InlineDirectives::extract(&mut value)?
+            .under_root(key_path)
+            .apply(options);
+        let value = value.into_key_path(key_path)?;
+        let tokens = define_structs_for_root(value, struct_name.as_ref(), Some(path), options)
+            .map_err(|error| error.under_key(key_path))?;
+        finish_output(tokens, options)
+    };
+    define().map_err(|error| spanned.locate_error(error))
+}
+
+/// Generate Rust code that defines a set of structs based on a
+/// sequence of markup files, layered on top of each other.
+///
+/// Later files override the values in earlier ones (like a
+/// `production.yaml` overriding a `base.yaml`). See
+/// [`parsing::merge_values`] for how they are merged.
+pub fn generate_structs_from_layers<SrcPath: AsRef<Path>, Name: AsRef<str>>(
+    src_paths: &[SrcPath],
+    struct_name: Name,
+    options: &Options,
+) -> Result<String, Error> {
+    let value = parsing::parse_layered_source_files(src_paths, &options.parse)?;
+    define_structs_for_value(value, struct_name.as_ref(), None, options)
 }
 
 /// Generate Rust code that defines a set of structs based on the
 /// given markup source.
+///
+/// Binary formats (like MessagePack) cannot be given as a string, so
+/// use [`generate_structs_from_bytes`] for them instead.
 pub fn generate_structs_from_source<Source: AsRef<str>, Name: AsRef<str>>(
     source: Source,
     struct_name: Name,
     format: Format,
     options: &Options,
 ) -> Result<String, Error> {
-    let value = parsing::parse_source(source.as_ref(), format, &options.parse)?.assume_struct()?;
-    let tokens = codegen::define_structs(&value, struct_name.as_ref(), None, options)?;
-    Ok(tokens.to_string())
+    let value = parsing::parse_source(source.as_ref(), format, &options.parse)?;
+    define_structs_for_value(value, struct_name.as_ref(), None, options)
+}
+
+/// Generate Rust code that defines a set of structs based on the
+/// raw bytes of a markup source, in any format (including binary
+/// ones, like MessagePack).
+///
+/// See [`parsing::parse_bytes`].
+pub fn generate_structs_from_bytes<Bytes: AsRef<[u8]>, Name: AsRef<str>>(
+    bytes: Bytes,
+    struct_name: Name,
+    format: Format,
+    options: &Options,
+) -> Result<String, Error> {
+    let value = parsing::parse_bytes(bytes.as_ref(), format, &options.parse)?;
+    define_structs_for_value(value, struct_name.as_ref(), None, options)
+}
+
+/// Like [`generate_structs`], but also returns a report of any
+/// surprising choices made for the generated types (see
+/// [`report::GenerationReport`]).
+pub fn generate_structs_with_report<SrcPath: AsRef<Path>, Name: AsRef<str>>(
+    src_path: SrcPath,
+    struct_name: Name,
+    options: &Options,
+) -> Result<(String, report::GenerationReport), Error> {
+    let path = src_path.as_ref();
+    let (spanned, options, report) = parse_with_report(
+        |options| parsing::parse_source_file_spanned(path, options),
+        options,
+    )
+    .map_err(|error| error.in_file(path))?;
+    let define = || {
+        let tokens = define_structs_for_root(
+            spanned.value.clone(),
+            struct_name.as_ref(),
+            Some(path),
+            &options,
+        )?;
+        finish_output(tokens, &options)
+    };
+    let output = define().map_err(|error| spanned.locate_error(error))?;
+    Ok((output, report))
+}
+
+/// Like [`generate_structs_from_source`], but also returns a report
+/// of any surprising choices made for the generated types (see
+/// [`report::GenerationReport`]).
+///
+/// # Examples
+/// ```
+/// # use edres::{report::Warning, *};
+/// # #[cfg(feature = "json")] {
+/// let (_, report) = generate_structs_from_source_with_report(
+///     r#"{ "items": [] }"#,
+///     "MyStruct",
+///     Format::Json,
+///     &Options::minimal(),
+/// ).unwrap();
+///
+/// assert_eq!(report.warnings, [Warning::EmptySequence { key_path: "items".into() }]);
+/// report.print_cargo_warnings();
+/// # }
+/// ```
+pub fn generate_structs_from_source_with_report<Source: AsRef<str>, Name: AsRef<str>>(
+    source: Source,
+    struct_name: Name,
+    format: Format,
+    options: &Options,
+) -> Result<(String, report::GenerationReport), Error> {
+    let parse = |options: &ParseOptions| {
+        parsing::parse_source(source.as_ref(), format, options).map(value::SpannedValue::new)
+    };
+    let (spanned, options, report) = parse_with_report(parse, options)?;
+    let tokens = define_structs_for_root(spanned.value, struct_name.as_ref(), None, &options)?;
+    Ok((finish_output(tokens, &options)?, report))
+}
+
+/// Parses the value to generate structs from, along with the options
+/// including its inline directives, and the report for it.
+///
+/// The source is parsed without skipping any keys, so the report can
+/// list them, and they are removed afterwards. It is only parsed
+/// again if floats are narrowed, since the report needs their
+/// original values.
+fn parse_with_report<F>(
+    mut parse: F,
+    options: &Options,
+) -> Result<(value::SpannedValue, Options, report::GenerationReport), Error>
+where
+    F: FnMut(&ParseOptions) -> Result<value::SpannedValue, Error>,
+{
+    let raw_options = report::GenerationReport::raw_parse_options(&options.parse);
+    let mut raw = parse(&raw_options)?;
+    let options = directives::InlineDirectives::take(&mut raw.value)?.apply(options);
+    let mut spanned = match options.parse.default_float_size == raw_options.default_float_size {
+        true => raw.clone(),
+        false => {
+            let mut spanned = parse(&options.parse)?;
+            directives::InlineDirectives::take(&mut spanned.value)?;
+            spanned
+        }
+    };
+    parsing::remove_ignored_keys(&mut spanned.value, &options.parse);
+    let report = report::GenerationReport::for_value(&raw.value, &spanned.value, &options);
+    Ok((spanned, options, report))
+}
+
+/// Defines structs for the root value of a file, which must be a
+/// map, or a sequence if [`StructOptions::allow_root_sequence`] is
+/// set.
+fn define_structs_for_value(
+    mut value: value::Value,
+    struct_name: &str,
+    src_path: Option<&Path>,
+    options: &Options,
+) -> Result<String, Error> {
+    let options = &extract_directives(&mut value, options)?;
+    let tokens = define_structs_for_root(value, struct_name, src_path, options)?;
+    finish_output(tokens, options)
+}
+
+/// Like [`define_structs_for_value`], for a value which has no
+/// inline directives.
+fn define_structs_for_root(
+    value: value::Value,
+    struct_name: &str,
+    src_path: Option<&Path>,
+    options: &Options,
+) -> Result<TokenStream, Error> {
+    use value::Value;
+
+    match value {
+        Value::Vec(items) | Value::Array(_, items) if options.structs.allow_root_sequence => {
+            codegen::define_structs_from_sequence(&items, struct_name, options)
+        }
+        value => codegen::define_structs(&value.assume_struct()?, struct_name, src_path, options),
+    }
 }
 
 /// Generate Rust code that defines a set of structs based on the
 /// contents of the files in the given directory.
+///
+/// If [`OutputOptions::module_per_file`] is set, each file gets its
+/// own module containing its own set of structs instead.
 pub fn generate_structs_from_files<DirPath: AsRef<Path>, Name: AsRef<str>>(
     dir_path: DirPath,
     struct_name: Name,
     options: &Options,
 ) -> Result<String, Error> {
-    let tokens = codegen::define_structs_from_file_contents(
-        dir_path.as_ref(),
-        struct_name.as_ref(),
-        None,
-        options,
-    )?;
-    Ok(tokens.to_string())
+    let tokens = if options.output.module_per_file {
+        codegen::define_modules_from_files(dir_path.as_ref(), struct_name.as_ref(), None, options)?
+    } else {
+        codegen::define_structs_from_file_contents(
+            dir_path.as_ref(),
+            struct_name.as_ref(),
+            None,
+            options,
+        )?
+    };
+    finish_output(tokens, options)
 }
 
 /// Generate Rust code that defines an enum based on the map keys
@@ -217,22 +473,95 @@ pub fn generate_enum<SrcPath: AsRef<Path>, Name: AsRef<str>>(
     options: &Options,
 ) -> Result<String, Error> {
     let path = src_path.as_ref();
-    let value = parsing::parse_source_file(path, &options.parse)?.assume_struct()?;
-    let tokens = codegen::define_enum_from_keys(&value, enum_name.as_ref(), Some(path), options)?;
-    Ok(tokens.to_string())
+    let spanned = parsing::parse_source_file_spanned(path, &options.parse)?;
+    define_enum_for_value(spanned.value.clone(), enum_name.as_ref(), path, options)
+        .map_err(|error| spanned.locate_error(error))
+}
+
+fn define_enum_for_value(
+    mut value: value::Value,
+    enum_name: &str,
+    src_path: &Path,
+    options: &Options,
+) -> Result<String, Error> {
+    let options = &extract_directives(&mut value, options)?;
+    let value = value.assume_map()?;
+    let tokens = codegen::define_enum_from_keys(&value, enum_name, Some(src_path), options)?;
+    finish_output(tokens, options)
+}
+
+/// Generate Rust code that defines several enums, each based on
+/// the map keys of the value at a key path (like `items.weapons`)
+/// within a single markup file.
+///
+/// Each target is a key path, and the name of the enum to generate
+/// for it. The file is only parsed once.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use edres::*;
+/// let generated_code = generate_enums_from_keys_at(
+///     "items.yaml",
+///     &[("weapons", "Weapon"), ("armors", "Armor")],
+///     &Options::new(),
+/// ).unwrap();
+/// ```
+pub fn generate_enums_from_keys_at<SrcPath, KeyPath, Name>(
+    src_path: SrcPath,
+    targets: &[(KeyPath, Name)],
+    options: &Options,
+) -> Result<String, Error>
+where
+    SrcPath: AsRef<Path>,
+    KeyPath: AsRef<str>,
+    Name: AsRef<str>,
+{
+    let path = src_path.as_ref();
+    let spanned = parsing::parse_source_file_spanned(path, &options.parse)?;
+    let define_enums = || {
+        let mut value = spanned.value.clone();
+        let directives = directives::InlineDirectives::extract(&mut value)?;
+        let mut names = std::collections::HashSet::new();
+        let mut tokens = TokenStream::new();
+        for (key_path, enum_name) in targets {
+            let enum_name = enum_name.as_ref();
+            if !names.insert(enum_name) {
+                return Err(Error::DuplicateName(enum_name.to_owned()));
+            }
+            let key_path = key_path.as_ref();
+            let data = value.clone().into_key_path(key_path)?.assume_map()?;
+            tokens.extend(
+                codegen::define_enum_from_keys(
+                    &data,
+                    enum_name,
+                    Some(path),
+                    &directives.under_root(key_path).apply(options),
+                )
+                .map_err(|error| error.under_key(key_path))?,
+            );
+        }
+        Ok(tokens)
+    };
+    let tokens = define_enums().map_err(|error| spanned.locate_error(error))?;
+    finish_output(tokens, options)
 }
 
 /// Generate Rust code that defines an enum based on the map keys
 /// of the given markup source.
+///
+/// Binary formats (like MessagePack) cannot be given as a string.
 pub fn generate_enum_from_source<Source: AsRef<str>, Name: AsRef<str>>(
     source: Source,
     enum_name: Name,
     format: Format,
     options: &Options,
 ) -> Result<String, Error> {
-    let value = parsing::parse_source(source.as_ref(), format, &options.parse)?.assume_struct()?;
-    let tokens = codegen::define_enum_from_keys(&value, enum_name.as_ref(), None, options)?;
-    Ok(tokens.to_string())
+    let mut value = parsing::parse_source(source.as_ref(), format, &options.parse)?;
+    let options = &extract_directives(&mut value, options)?;
+    let tokens =
+        codegen::define_enum_from_keys(&value.assume_map()?, enum_name.as_ref(), None, options)?;
+    finish_output(tokens, options)
 }
 
 /// Generate Rust code that defines an enum based on the file names
@@ -244,26 +573,298 @@ pub fn generate_enum_from_filenames<DirPath: AsRef<Path>, Name: AsRef<str>>(
 ) -> Result<String, Error> {
     let tokens =
         codegen::define_enum_from_filenames(dir_path.as_ref(), enum_name.as_ref(), options)?;
-    Ok(tokens.to_string())
+    finish_output(tokens, options)
+}
+
+/// Generate Rust code that defines an enum based on the top-level
+/// keys of the files within the given directory.
+///
+/// See [`codegen::define_enum_from_file_keys`] for details.
+pub fn generate_enum_from_file_keys<DirPath: AsRef<Path>, Name: AsRef<str>>(
+    dir_path: DirPath,
+    enum_name: Name,
+    options: &Options,
+) -> Result<String, Error> {
+    let tokens =
+        codegen::define_enum_from_file_keys(dir_path.as_ref(), enum_name.as_ref(), options)?;
+    finish_output(tokens, options)
+}
+
+/// Generate Rust code that defines an enum for each subdirectory of
+/// the given directory, and an enum of the subdirectories.
+///
+/// See [`codegen::define_enums_from_subdirs`] for details.
+pub fn generate_enums_from_subdirs<DirPath: AsRef<Path>, Name: AsRef<str>>(
+    dir_path: DirPath,
+    kind_enum_name: Name,
+    options: &Options,
+) -> Result<String, Error> {
+    let tokens =
+        codegen::define_enums_from_subdirs(dir_path.as_ref(), kind_enum_name.as_ref(), options)?;
+    finish_output(tokens, options)
+}
+
+/// Generate Rust code that defines an enum based on a set of named
+/// sources, as if they were files with those names.
+///
+/// Each item is a file name, its source, and the format of that
+/// source. No files are read, and binary formats (like MessagePack)
+/// cannot be given as strings.
+pub fn generate_enum_from_sources<'a, Sources, Name>(
+    sources: Sources,
+    enum_name: Name,
+    options: &Options,
+) -> Result<String, Error>
+where
+    Sources: IntoIterator<Item = (&'a str, &'a str, Format)>,
+    Name: AsRef<str>,
+{
+    let tokens = codegen::define_enum_from_sources(sources, enum_name.as_ref(), options)?;
+    finish_output(tokens, options)
+}
+
+/// Generate Rust code that defines enums for the locales and keys
+/// of a string table, based on the per-locale files (like
+/// `en.yaml`) within the given directory.
+///
+/// See [`codegen::define_string_table`] for details.
+pub fn generate_string_table<DirPath: AsRef<Path>, LangName: AsRef<str>, KeyName: AsRef<str>>(
+    dir_path: DirPath,
+    lang_enum_name: LangName,
+    key_enum_name: KeyName,
+    options: &Options,
+) -> Result<String, Error> {
+    let tokens = codegen::define_string_table(
+        dir_path.as_ref(),
+        lang_enum_name.as_ref(),
+        key_enum_name.as_ref(),
+        options,
+    )?;
+    finish_output(tokens, options)
+}
+
+/// Generate Rust code that defines a bitflags struct based on
+/// the list of flags in the given markup file.
+///
+/// See [`codegen::define_bitflags`] for the format of the file.
+pub fn generate_bitflags<SrcPath: AsRef<Path>, Name: AsRef<str>>(
+    src_path: SrcPath,
+    flags_name: Name,
+    options: &Options,
+) -> Result<String, Error> {
+    let path = src_path.as_ref();
+    let spanned = parsing::parse_source_file_spanned(path, &options.parse)?;
+    define_bitflags_for_value(spanned.value.clone(), flags_name.as_ref(), path, options)
+        .map_err(|error| spanned.locate_error(error))
+}
+
+fn define_bitflags_for_value(
+    mut value: value::Value,
+    flags_name: &str,
+    src_path: &Path,
+    options: &Options,
+) -> Result<String, Error> {
+    let options = &extract_directives(&mut value, options)?;
+    let tokens = codegen::define_bitflags(&value, flags_name, Some(src_path), options)?;
+    finish_output(tokens, options)
+}
+
+/// Generate Rust code that defines a bitflags struct based on
+/// the list of flags in the given markup source.
+///
+/// Binary formats (like MessagePack) cannot be given as a string.
+pub fn generate_bitflags_from_source<Source: AsRef<str>, Name: AsRef<str>>(
+    source: Source,
+    flags_name: Name,
+    format: Format,
+    options: &Options,
+) -> Result<String, Error> {
+    let mut value = parsing::parse_source(source.as_ref(), format, &options.parse)?;
+    let options = &extract_directives(&mut value, options)?;
+    let tokens = codegen::define_bitflags(&value, flags_name.as_ref(), None, options)?;
+    finish_output(tokens, options)
+}
+
+/// The path of the file that the `create_` functions write to for
+/// the given destination path.
+///
+/// This is only different from `dest_path` if
+/// [`OutputOptions::out_dir_relative`] is set, in which case it is
+/// joined to `$OUT_DIR`.
+pub fn destination_path<DestPath: AsRef<Path>>(
+    dest_path: DestPath,
+    options: &Options,
+) -> Result<PathBuf, Error> {
+    files::resolve_destination(dest_path.as_ref(), &options.output)
+}
+
+/// Include a file generated in `$OUT_DIR` by a build script.
+///
+/// This is shorthand for
+/// `include!(concat!(env!("OUT_DIR"), "/", path))`, for use with
+/// [`OutputOptions::out_dir_relative`].
+///
+/// ```ignore
+/// edres::include_generated!("config.rs");
+/// ```
+#[macro_export]
+macro_rules! include_generated {
+    ($path:literal) => {
+        include!(concat!(env!("OUT_DIR"), "/", $path));
+    };
+}
+
+/// Applies the module wrapper and attributes from the
+/// [`OutputOptions`] to generated code.
+/// Removes the inline directives (see
+/// [`directives::InlineDirectives`]) from a parsed root value,
+/// returning the options with them applied.
+fn extract_directives(value: &mut value::Value, options: &Options) -> Result<Options, Error> {
+    Ok(directives::InlineDirectives::extract(value)?.apply(options))
+}
+
+fn finish_output(tokens: TokenStream, options: &Options) -> Result<String, Error> {
+    Ok(codegen::wrap_output(tokens, options)?.to_string())
+}
+
+/// Write generated code to a file, after passing it through the
+/// `post_process` hook, and return what was written (without the
+/// header and provenance banner).
+fn write_output<F>(
+    dest_path: &Path,
+    output: String,
+    sources: &[&Path],
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = post_process(output)?;
+    let dest_path = &files::resolve_destination(dest_path, &options.output)?;
+    if options.output.check_only {
+        files::check_destination(dest_path, &output, sources, &options.output)?;
+        return Ok(output);
+    }
+    files::ensure_destination(dest_path, options.output.create_dirs)?;
+    files::write_destination(dest_path, &output, sources, &options.output)?;
+
+    Ok(output)
 }
 
 /// Create a Rust source file that defines a set of structs
 /// based on a given markup file.
+///
+/// Returns the generated code, so it can be used without
+/// generating it again.
 pub fn create_structs<SrcPath: AsRef<Path>, DestPath: AsRef<Path>, Name: AsRef<str>>(
     src_path: SrcPath,
     dest_path: DestPath,
     struct_name: Name,
     options: &Options,
-) -> Result<(), Error> {
-    let output = generate_structs(src_path, struct_name, options)?;
-    files::ensure_destination(dest_path.as_ref(), options.output.create_dirs)?;
-    files::write_destination(
+) -> Result<String, Error> {
+    create_structs_with(src_path, dest_path, struct_name, options, Ok)
+}
+
+/// Like [`create_structs`], but passes the generated code through
+/// `post_process` before writing it.
+pub fn create_structs_with<SrcPath, DestPath, Name, F>(
+    src_path: SrcPath,
+    dest_path: DestPath,
+    struct_name: Name,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    SrcPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = generate_structs(&src_path, struct_name, options)?;
+    write_output(
         dest_path.as_ref(),
         output,
-        options.output.write_only_if_changed,
-    )?;
+        &[src_path.as_ref()],
+        options,
+        post_process,
+    )
+}
+
+/// Create a Rust source file that defines a set of structs based
+/// on the value at the given key path (like `server.limits`)
+/// within a markup file.
+pub fn create_structs_with_root<
+    SrcPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    KeyPath: AsRef<str>,
+>(
+    src_path: SrcPath,
+    dest_path: DestPath,
+    struct_name: Name,
+    key_path: KeyPath,
+    options: &Options,
+) -> Result<String, Error> {
+    create_structs_with_root_with(src_path, dest_path, struct_name, key_path, options, Ok)
+}
 
-    Ok(())
+/// Like [`create_structs_with_root`], but passes the generated
+/// code through `post_process` before writing it.
+pub fn create_structs_with_root_with<SrcPath, DestPath, Name, KeyPath, F>(
+    src_path: SrcPath,
+    dest_path: DestPath,
+    struct_name: Name,
+    key_path: KeyPath,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    SrcPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    KeyPath: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = generate_structs_with_root(&src_path, struct_name, key_path, options)?;
+    write_output(
+        dest_path.as_ref(),
+        output,
+        &[src_path.as_ref()],
+        options,
+        post_process,
+    )
+}
+
+/// Create a Rust source file that defines a set of structs based
+/// on a sequence of markup files, layered on top of each other.
+pub fn create_structs_from_layers<SrcPath: AsRef<Path>, DestPath: AsRef<Path>, Name: AsRef<str>>(
+    src_paths: &[SrcPath],
+    dest_path: DestPath,
+    struct_name: Name,
+    options: &Options,
+) -> Result<String, Error> {
+    create_structs_from_layers_with(src_paths, dest_path, struct_name, options, Ok)
+}
+
+/// Like [`create_structs_from_layers`], but passes the generated
+/// code through `post_process` before writing it.
+pub fn create_structs_from_layers_with<SrcPath, DestPath, Name, F>(
+    src_paths: &[SrcPath],
+    dest_path: DestPath,
+    struct_name: Name,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    SrcPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = generate_structs_from_layers(src_paths, struct_name, options)?;
+    let sources = src_paths.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+    write_output(dest_path.as_ref(), output, &sources, options, post_process)
 }
 
 /// Create a Rust source file that defines a set of structs based
@@ -274,35 +875,110 @@ pub fn create_structs_from_source<Source: AsRef<str>, DestPath: AsRef<Path>, Nam
     struct_name: Name,
     format: Format,
     options: &Options,
-) -> Result<(), Error> {
+) -> Result<String, Error> {
+    create_structs_from_source_with(source, dest_path, struct_name, format, options, Ok)
+}
+
+/// Like [`create_structs_from_source`], but passes the generated
+/// code through `post_process` before writing it.
+pub fn create_structs_from_source_with<Source, DestPath, Name, F>(
+    source: Source,
+    dest_path: DestPath,
+    struct_name: Name,
+    format: Format,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    Source: AsRef<str>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
     let output = generate_structs_from_source(source, struct_name, format, options)?;
-    files::ensure_destination(dest_path.as_ref(), options.output.create_dirs)?;
-    files::write_destination(
-        dest_path.as_ref(),
-        output,
-        options.output.write_only_if_changed,
-    )?;
+    write_output(dest_path.as_ref(), output, &[], options, post_process)
+}
 
-    Ok(())
+/// Create a Rust source file that defines a set of structs based
+/// on the raw bytes of a markup source.
+///
+/// See [`generate_structs_from_bytes`].
+pub fn create_structs_from_bytes<Bytes: AsRef<[u8]>, DestPath: AsRef<Path>, Name: AsRef<str>>(
+    bytes: Bytes,
+    dest_path: DestPath,
+    struct_name: Name,
+    format: Format,
+    options: &Options,
+) -> Result<String, Error> {
+    create_structs_from_bytes_with(bytes, dest_path, struct_name, format, options, Ok)
+}
+
+/// Like [`create_structs_from_bytes`], but passes the generated
+/// code through `post_process` before writing it.
+pub fn create_structs_from_bytes_with<Bytes, DestPath, Name, F>(
+    bytes: Bytes,
+    dest_path: DestPath,
+    struct_name: Name,
+    format: Format,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    Bytes: AsRef<[u8]>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = generate_structs_from_bytes(bytes, struct_name, format, options)?;
+    write_output(dest_path.as_ref(), output, &[], options, post_process)
 }
 
 /// Create a Rust source file that defines a set of structs based
 /// on the contents of the files in the given directory.
+///
+/// If [`OutputOptions::cache_dir`] is set, the files are only
+/// parsed again when they (or the options) have changed.
 pub fn create_structs_from_files<DirPath: AsRef<Path>, DestPath: AsRef<Path>, Name: AsRef<str>>(
     dir_path: DirPath,
     dest_path: DestPath,
     struct_name: Name,
     options: &Options,
-) -> Result<(), Error> {
-    let output = generate_structs_from_files(dir_path, struct_name, options)?;
-    files::ensure_destination(dest_path.as_ref(), options.output.create_dirs)?;
-    files::write_destination(
+) -> Result<String, Error> {
+    create_structs_from_files_with(dir_path, dest_path, struct_name, options, Ok)
+}
+
+/// Like [`create_structs_from_files`], but passes the generated
+/// code through `post_process` before writing it.
+///
+/// The cache stores the code from before `post_process`.
+pub fn create_structs_from_files_with<DirPath, DestPath, Name, F>(
+    dir_path: DirPath,
+    dest_path: DestPath,
+    struct_name: Name,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    DirPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = files::generate_cached(
+        "structs",
+        dir_path.as_ref(),
+        struct_name.as_ref(),
         dest_path.as_ref(),
-        output,
-        options.output.write_only_if_changed,
+        options,
+        || generate_structs_from_files(&dir_path, &struct_name, options),
     )?;
-
-    Ok(())
+    write_output(
+        dest_path.as_ref(),
+        output,
+        &[dir_path.as_ref()],
+        options,
+        post_process,
+    )
 }
 
 /// Create a Rust source file that defines an enum based on the
@@ -312,16 +988,80 @@ pub fn create_enum<SrcPath: AsRef<Path>, DestPath: AsRef<Path>, Name: AsRef<str>
     dest_path: DestPath,
     enum_name: Name,
     options: &Options,
-) -> Result<(), Error> {
-    let output = generate_enum(src_path, enum_name, options)?;
-    files::ensure_destination(dest_path.as_ref(), options.output.create_dirs)?;
-    files::write_destination(
+) -> Result<String, Error> {
+    create_enum_with(src_path, dest_path, enum_name, options, Ok)
+}
+
+/// Like [`create_enum`], but passes the generated code through
+/// `post_process` before writing it.
+pub fn create_enum_with<SrcPath, DestPath, Name, F>(
+    src_path: SrcPath,
+    dest_path: DestPath,
+    enum_name: Name,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    SrcPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = generate_enum(&src_path, enum_name, options)?;
+    write_output(
         dest_path.as_ref(),
         output,
-        options.output.write_only_if_changed,
-    )?;
+        &[src_path.as_ref()],
+        options,
+        post_process,
+    )
+}
 
-    Ok(())
+/// Create a Rust source file that defines several enums, each
+/// based on the map keys of the value at a key path within a single
+/// markup file.
+///
+/// Returns the generated code, so it can be used without
+/// generating it again.
+pub fn create_enums_from_keys_at<SrcPath, DestPath, KeyPath, Name>(
+    src_path: SrcPath,
+    dest_path: DestPath,
+    targets: &[(KeyPath, Name)],
+    options: &Options,
+) -> Result<String, Error>
+where
+    SrcPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    KeyPath: AsRef<str>,
+    Name: AsRef<str>,
+{
+    create_enums_from_keys_at_with(src_path, dest_path, targets, options, Ok)
+}
+
+/// Like [`create_enums_from_keys_at`], but passes the generated
+/// code through `post_process` before writing it.
+pub fn create_enums_from_keys_at_with<SrcPath, DestPath, KeyPath, Name, F>(
+    src_path: SrcPath,
+    dest_path: DestPath,
+    targets: &[(KeyPath, Name)],
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    SrcPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    KeyPath: AsRef<str>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = generate_enums_from_keys_at(&src_path, targets, options)?;
+    write_output(
+        dest_path.as_ref(),
+        output,
+        &[src_path.as_ref()],
+        options,
+        post_process,
+    )
 }
 
 /// Create a Rust source file that defines an enum based on the
@@ -332,33 +1072,326 @@ pub fn create_enum_from_source<Source: AsRef<str>, DestPath: AsRef<Path>, Name:
     enum_name: Name,
     format: Format,
     options: &Options,
-) -> Result<(), Error> {
-    let output = generate_enum_from_source(source, enum_name, format, options)?;
-    files::ensure_destination(dest_path.as_ref(), options.output.create_dirs)?;
-    files::write_destination(
-        dest_path.as_ref(),
-        output,
-        options.output.write_only_if_changed,
-    )?;
+) -> Result<String, Error> {
+    create_enum_from_source_with(source, dest_path, enum_name, format, options, Ok)
+}
 
-    Ok(())
+/// Like [`create_enum_from_source`], but passes the generated code
+/// through `post_process` before writing it.
+pub fn create_enum_from_source_with<Source, DestPath, Name, F>(
+    source: Source,
+    dest_path: DestPath,
+    enum_name: Name,
+    format: Format,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    Source: AsRef<str>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = generate_enum_from_source(source, enum_name, format, options)?;
+    write_output(dest_path.as_ref(), output, &[], options, post_process)
 }
 
 /// Create a Rust source file that defines an enum based on the
 /// file names within the given directory.
+///
+/// If [`OutputOptions::cache_dir`] is set, the files are only
+/// parsed again when they (or the options) have changed.
 pub fn create_enum_from_filenames<DirPath: AsRef<Path>, DestPath: AsRef<Path>, Name: AsRef<str>>(
     dir_path: DirPath,
     dest_path: DestPath,
     enum_name: Name,
     options: &Options,
-) -> Result<(), Error> {
-    let output = generate_enum_from_filenames(dir_path, enum_name, options)?;
-    files::ensure_destination(dest_path.as_ref(), options.output.create_dirs)?;
-    files::write_destination(
+) -> Result<String, Error> {
+    create_enum_from_filenames_with(dir_path, dest_path, enum_name, options, Ok)
+}
+
+/// Like [`create_enum_from_filenames`], but passes the generated
+/// code through `post_process` before writing it.
+///
+/// The cache stores the code from before `post_process`.
+pub fn create_enum_from_filenames_with<DirPath, DestPath, Name, F>(
+    dir_path: DirPath,
+    dest_path: DestPath,
+    enum_name: Name,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    DirPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = files::generate_cached(
+        "enum",
+        dir_path.as_ref(),
+        enum_name.as_ref(),
+        dest_path.as_ref(),
+        options,
+        || generate_enum_from_filenames(&dir_path, &enum_name, options),
+    )?;
+    write_output(
+        dest_path.as_ref(),
+        output,
+        &[dir_path.as_ref()],
+        options,
+        post_process,
+    )
+}
+
+/// Create a Rust source file that defines an enum for each
+/// subdirectory of the given directory, and an enum of the
+/// subdirectories.
+///
+/// The output is not cached, even if [`OutputOptions::cache_dir`]
+/// is set.
+pub fn create_enums_from_subdirs<DirPath: AsRef<Path>, DestPath: AsRef<Path>, Name: AsRef<str>>(
+    dir_path: DirPath,
+    dest_path: DestPath,
+    kind_enum_name: Name,
+    options: &Options,
+) -> Result<String, Error> {
+    create_enums_from_subdirs_with(dir_path, dest_path, kind_enum_name, options, Ok)
+}
+
+/// Like [`create_enums_from_subdirs`], but passes the generated code
+/// through `post_process` before writing it.
+pub fn create_enums_from_subdirs_with<DirPath, DestPath, Name, F>(
+    dir_path: DirPath,
+    dest_path: DestPath,
+    kind_enum_name: Name,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    DirPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = generate_enums_from_subdirs(&dir_path, kind_enum_name, options)?;
+    write_output(
         dest_path.as_ref(),
         output,
-        options.output.write_only_if_changed,
+        &[dir_path.as_ref()],
+        options,
+        post_process,
+    )
+}
+
+/// Create a Rust source file that defines an enum based on the
+/// top-level keys of the files within the given directory.
+///
+/// If [`OutputOptions::cache_dir`] is set, the files are only
+/// parsed again when they (or the options) have changed.
+pub fn create_enum_from_file_keys<DirPath: AsRef<Path>, DestPath: AsRef<Path>, Name: AsRef<str>>(
+    dir_path: DirPath,
+    dest_path: DestPath,
+    enum_name: Name,
+    options: &Options,
+) -> Result<String, Error> {
+    create_enum_from_file_keys_with(dir_path, dest_path, enum_name, options, Ok)
+}
+
+/// Like [`create_enum_from_file_keys`], but passes the generated
+/// code through `post_process` before writing it.
+///
+/// The cache stores the code from before `post_process`.
+pub fn create_enum_from_file_keys_with<DirPath, DestPath, Name, F>(
+    dir_path: DirPath,
+    dest_path: DestPath,
+    enum_name: Name,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    DirPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = files::generate_cached(
+        "enum_from_file_keys",
+        dir_path.as_ref(),
+        enum_name.as_ref(),
+        dest_path.as_ref(),
+        options,
+        || generate_enum_from_file_keys(&dir_path, &enum_name, options),
     )?;
+    write_output(
+        dest_path.as_ref(),
+        output,
+        &[dir_path.as_ref()],
+        options,
+        post_process,
+    )
+}
+
+/// Create a Rust source file that defines an enum based on a set
+/// of named sources, as if they were files with those names.
+pub fn create_enum_from_sources<'a, Sources, DestPath, Name>(
+    sources: Sources,
+    dest_path: DestPath,
+    enum_name: Name,
+    options: &Options,
+) -> Result<String, Error>
+where
+    Sources: IntoIterator<Item = (&'a str, &'a str, Format)>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+{
+    create_enum_from_sources_with(sources, dest_path, enum_name, options, Ok)
+}
+
+/// Like [`create_enum_from_sources`], but passes the generated
+/// code through `post_process` before writing it.
+pub fn create_enum_from_sources_with<'a, Sources, DestPath, Name, F>(
+    sources: Sources,
+    dest_path: DestPath,
+    enum_name: Name,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    Sources: IntoIterator<Item = (&'a str, &'a str, Format)>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = generate_enum_from_sources(sources, enum_name, options)?;
+    write_output(dest_path.as_ref(), output, &[], options, post_process)
+}
+
+/// Create a Rust source file that defines enums for the locales
+/// and keys of a string table, based on the per-locale files
+/// within the given directory.
+pub fn create_string_table<DirPath, DestPath, LangName, KeyName>(
+    dir_path: DirPath,
+    dest_path: DestPath,
+    lang_enum_name: LangName,
+    key_enum_name: KeyName,
+    options: &Options,
+) -> Result<String, Error>
+where
+    DirPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    LangName: AsRef<str>,
+    KeyName: AsRef<str>,
+{
+    create_string_table_with(
+        dir_path,
+        dest_path,
+        lang_enum_name,
+        key_enum_name,
+        options,
+        Ok,
+    )
+}
+
+/// Like [`create_string_table`], but passes the generated code
+/// through `post_process` before writing it.
+pub fn create_string_table_with<DirPath, DestPath, LangName, KeyName, F>(
+    dir_path: DirPath,
+    dest_path: DestPath,
+    lang_enum_name: LangName,
+    key_enum_name: KeyName,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    DirPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    LangName: AsRef<str>,
+    KeyName: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = generate_string_table(&dir_path, lang_enum_name, key_enum_name, options)?;
+    write_output(
+        dest_path.as_ref(),
+        output,
+        &[dir_path.as_ref()],
+        options,
+        post_process,
+    )
+}
+
+/// Create a Rust source file that defines a bitflags struct based
+/// on the list of flags in the given markup file.
+pub fn create_bitflags<SrcPath: AsRef<Path>, DestPath: AsRef<Path>, Name: AsRef<str>>(
+    src_path: SrcPath,
+    dest_path: DestPath,
+    flags_name: Name,
+    options: &Options,
+) -> Result<String, Error> {
+    create_bitflags_with(src_path, dest_path, flags_name, options, Ok)
+}
+
+/// Like [`create_bitflags`], but passes the generated code through
+/// `post_process` before writing it.
+pub fn create_bitflags_with<SrcPath, DestPath, Name, F>(
+    src_path: SrcPath,
+    dest_path: DestPath,
+    flags_name: Name,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    SrcPath: AsRef<Path>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = generate_bitflags(&src_path, flags_name, options)?;
+    write_output(
+        dest_path.as_ref(),
+        output,
+        &[src_path.as_ref()],
+        options,
+        post_process,
+    )
+}
+
+/// Create a Rust source file that defines a bitflags struct based
+/// on the list of flags in the given markup source.
+pub fn create_bitflags_from_source<Source: AsRef<str>, DestPath: AsRef<Path>, Name: AsRef<str>>(
+    source: Source,
+    dest_path: DestPath,
+    flags_name: Name,
+    format: Format,
+    options: &Options,
+) -> Result<String, Error> {
+    create_bitflags_from_source_with(source, dest_path, flags_name, format, options, Ok)
+}
+
+/// Like [`create_bitflags_from_source`], but passes the generated
+/// code through `post_process` before writing it.
+pub fn create_bitflags_from_source_with<Source, DestPath, Name, F>(
+    source: Source,
+    dest_path: DestPath,
+    flags_name: Name,
+    format: Format,
+    options: &Options,
+    post_process: F,
+) -> Result<String, Error>
+where
+    Source: AsRef<str>,
+    DestPath: AsRef<Path>,
+    Name: AsRef<str>,
+    F: FnOnce(String) -> Result<String, Error>,
+{
+    let output = generate_bitflags_from_source(source, flags_name, format, options)?;
+    write_output(dest_path.as_ref(), output, &[], options, post_process)
+}
 
-    Ok(())
+/// Generate every target in the given manifest file (like
+/// `edres.toml`).
+///
+/// See the [`manifest`] module for the format of the file.
+pub fn run_manifest<ManifestPath: AsRef<Path>>(manifest_path: ManifestPath) -> Result<(), Error> {
+    Manifest::from_file(manifest_path)?.run().map(drop)
 }
@@ -0,0 +1,249 @@
+//! Declarative configuration for generating several files at once.
+//!
+//! A manifest (like `edres.toml`) contains a set of [`Options`]
+//! shared by every target, and a list of targets to generate:
+//!
+//! ```toml
+//! [options]
+//! serde_support = "yes"
+//!
+//! [options.structs]
+//! derived_traits = ["Debug", "Clone"]
+//!
+//! [[targets]]
+//! kind = "structs"
+//! input = "data/config.toml"
+//! name = "Config"
+//! dest = "src/gen/config.rs"
+//!
+//! [[targets]]
+//! kind = "enum_from_filenames"
+//! input = "data/levels"
+//! name = "Level"
+//! dest = "src/gen/levels.rs"
+//! ```
+//!
+//! A target can have its own `options`, which are used instead of
+//! the shared ones.
+//!
+//! See [`run_manifest`](crate::run_manifest).
+
+use std::path::{Path, PathBuf};
+
+use edres_core::{
+    options::ParseOptions, parsing, report::Warning, value::SpannedValue, Error, Options,
+};
+use serde::{Deserialize, Serialize};
+
+/// A set of generation targets, and the options they share.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Manifest {
+    /// The options used to generate every target.
+    pub options: Options,
+
+    /// The targets to generate, in order.
+    pub targets: Vec<Target>,
+}
+
+/// A single file to generate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Target {
+    /// Which kind of code to generate.
+    pub kind: TargetKind,
+
+    /// The markup file to read, or the directory for
+    /// [`TargetKind::StructsFromFiles`],
+    /// [`TargetKind::EnumFromFilenames`], and
+    /// [`TargetKind::EnumFromFileKeys`].
+    pub input: PathBuf,
+
+    /// The name of the generated struct or enum.
+    pub name: String,
+
+    /// The Rust source file to write.
+    pub dest: PathBuf,
+
+    /// The options to generate this target with, instead of the
+    /// options of the manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<Options>,
+}
+
+/// The kinds of code a [`Target`] can generate, corresponding to
+/// the `create_` functions in the root of this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetKind {
+    /// See [`create_structs`](crate::create_structs).
+    Structs,
+
+    /// See [`create_structs_from_files`](crate::create_structs_from_files).
+    StructsFromFiles,
+
+    /// See [`create_enum`](crate::create_enum).
+    Enum,
+
+    /// See [`create_enum_from_filenames`](crate::create_enum_from_filenames).
+    EnumFromFilenames,
+
+    /// See [`create_enum_from_file_keys`](crate::create_enum_from_file_keys).
+    EnumFromFileKeys,
+
+    /// See [`create_bitflags`](crate::create_bitflags).
+    Bitflags,
+}
+
+impl Manifest {
+    /// Load a manifest from a markup file, whose format is inferred
+    /// from its extension.
+    ///
+    /// The `input` and `dest` paths of each target are relative to
+    /// the directory containing the manifest.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Manifest, Error> {
+        let path = path.as_ref();
+        let mut manifest: Manifest = parsing::deserialize_source_file(path)?;
+        if let Some(dir) = path.parent() {
+            for target in manifest.targets.iter_mut() {
+                target.input = dir.join(&target.input);
+                target.dest = dir.join(&target.dest);
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Generate every target, stopping at the first error.
+    ///
+    /// Targets generated from a single markup file share the parsed
+    /// value of the file, so each file is only parsed once (for each
+    /// set of [`ParseOptions`]).
+    pub fn run(&self) -> Result<ManifestReport, Error> {
+        let mut report = ManifestReport::default();
+        let mut parsed = ParsedFiles::default();
+        for target in &self.targets {
+            let options = target.options.as_ref().unwrap_or(&self.options);
+            let warnings = target.run_with(options, &mut parsed)?;
+            report.warnings.extend(
+                warnings
+                    .into_iter()
+                    .map(|warning| (target.dest.clone(), warning)),
+            );
+            report.written.push(target.dest.clone());
+        }
+        report.files_parsed = parsed.0.len();
+        Ok(report)
+    }
+}
+
+/// The combined results of running every target in a [`Manifest`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ManifestReport {
+    /// The `dest` of each target, in order.
+    pub written: Vec<PathBuf>,
+
+    /// The warnings for the targets generating structs (see
+    /// [`GenerationReport`]), with the `dest` of their target.
+    ///
+    /// [`GenerationReport`]: edres_core::report::GenerationReport
+    pub warnings: Vec<(PathBuf, Warning)>,
+
+    /// How many times markup files were parsed for the targets
+    /// which share them.
+    ///
+    /// To find warnings, targets generating structs parse their
+    /// file without ignoring keys or narrowing floats (see
+    /// [`GenerationReport::raw_parse_options`]). They only parse it
+    /// again with their own options if those narrow floats.
+    ///
+    /// [`GenerationReport::raw_parse_options`]: edres_core::report::GenerationReport::raw_parse_options
+    pub files_parsed: usize,
+}
+
+impl ManifestReport {
+    /// Prints each warning as a `cargo:warning=` line, so that cargo
+    /// shows it when run from a build script.
+    pub fn print_cargo_warnings(&self) {
+        for (dest, warning) in &self.warnings {
+            println!("cargo:warning={}: {}", dest.display(), warning);
+        }
+    }
+}
+
+/// The values parsed from markup files, by their paths and the
+/// options they were parsed with.
+#[derive(Default)]
+struct ParsedFiles(Vec<(PathBuf, ParseOptions, SpannedValue)>);
+
+impl ParsedFiles {
+    fn get(&mut self, path: &Path, options: &ParseOptions) -> Result<&SpannedValue, Error> {
+        let cached = self.0.iter().position(|(cached_path, cached_options, _)| {
+            cached_path == path && cached_options == options
+        });
+        let index = match cached {
+            Some(index) => index,
+            None => {
+                let spanned = parsing::parse_source_file_spanned(path, options)?;
+                self.0.push((path.to_owned(), options.clone(), spanned));
+                self.0.len() - 1
+            }
+        };
+        Ok(&self.0[index].2)
+    }
+}
+
+impl Target {
+    /// Generate this target with the given options.
+    pub fn run(&self, options: &Options) -> Result<(), Error> {
+        self.run_with(options, &mut ParsedFiles::default())
+            .map(drop)
+    }
+
+    fn run_with(&self, options: &Options, parsed: &mut ParsedFiles) -> Result<Vec<Warning>, Error> {
+        let (input, dest, name) = (&self.input, &self.dest, self.name.as_str());
+        let mut warnings = vec![];
+        let output = match self.kind {
+            TargetKind::Structs => {
+                let (spanned, options, report) = crate::parse_with_report(
+                    |options| parsed.get(input, options).cloned(),
+                    options,
+                )
+                .map_err(|error| error.in_file(input))?;
+                warnings = report.warnings;
+                let define = || {
+                    let tokens = crate::define_structs_for_root(
+                        spanned.value.clone(),
+                        name,
+                        Some(input),
+                        &options,
+                    )?;
+                    crate::finish_output(tokens, &options)
+                };
+                define().map_err(|error| spanned.locate_error(error))?
+            }
+            TargetKind::Enum => {
+                let spanned = parsed.get(input, &options.parse)?;
+                crate::define_enum_for_value(spanned.value.clone(), name, input, options)
+                    .map_err(|error| spanned.locate_error(error))?
+            }
+            TargetKind::Bitflags => {
+                let spanned = parsed.get(input, &options.parse)?;
+                crate::define_bitflags_for_value(spanned.value.clone(), name, input, options)
+                    .map_err(|error| spanned.locate_error(error))?
+            }
+            TargetKind::StructsFromFiles => {
+                crate::create_structs_from_files(input, dest, name, options)?;
+                return Ok(warnings);
+            }
+            TargetKind::EnumFromFilenames => {
+                crate::create_enum_from_filenames(input, dest, name, options)?;
+                return Ok(warnings);
+            }
+            TargetKind::EnumFromFileKeys => {
+                crate::create_enum_from_file_keys(input, dest, name, options)?;
+                return Ok(warnings);
+            }
+        };
+        crate::write_output(dest, output, &[input], options, Ok)?;
+        Ok(warnings)
+    }
+}
@@ -0,0 +1,164 @@
+//! Utilities for re-running generation when input files change.
+//!
+//! This is useful for development loops outside of a build script,
+//! like a standalone binary which regenerates code while you edit
+//! its inputs. Changes are detected with the [`notify`] crate.
+
+use std::{
+    collections::BTreeSet,
+    convert::Infallible,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use edres_core::{codegen, Error, Options};
+use notify::{RecursiveMode, Watcher};
+
+/// How long files must stop changing for before `callback` is
+/// called, so that a burst of writes (like an editor saving
+/// several files) only regenerates once.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// The longest a burst of changes is collected for, so that files
+/// which never stop changing still regenerate.
+const MAX_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Watch the files in a directory, calling `callback` with the
+/// paths of the files that changed whenever any of them do.
+///
+/// The files are chosen as for [`codegen::files_in_dir`], so the
+/// [`FilesOptions`](crate::FilesOptions) are respected, and the
+/// index file (if any) is watched as well. Added and removed files
+/// count as changes.
+///
+/// `callback` is also called once at the start, with every file.
+/// Errors returned from `callback`, from the watcher, or from
+/// reading the directory after the start, are printed to stderr and
+/// watching carries on, so that a mistake in an input file can be
+/// fixed without restarting. This function only returns if the
+/// directory can't be read or watched at the start.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use edres::Options;
+/// let options = Options::new();
+/// edres::watch::watch_dir("data/items", &options, |_changed| {
+///     edres::create_enum("data/items", "src/items.rs", "Item", &options)?;
+///     Ok(())
+/// })
+/// .unwrap();
+/// ```
+pub fn watch_dir<P, F>(dir: P, options: &Options, mut callback: F) -> Result<Infallible, Error>
+where
+    P: AsRef<Path>,
+    F: FnMut(&[PathBuf]) -> Result<(), Error>,
+{
+    let dir = dir.as_ref();
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(watch_error)?;
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .map_err(watch_error)?;
+    if let Some(index_dir) = index_path(dir, options).as_deref().and_then(Path::parent) {
+        if index_dir != dir {
+            watcher
+                .watch(index_dir, RecursiveMode::NonRecursive)
+                .map_err(watch_error)?;
+        }
+    }
+
+    let mut current = watched_paths(dir, options)?;
+    let all = current.iter().cloned().collect::<Vec<_>>();
+    report(callback(&all));
+
+    loop {
+        let event = receiver.recv().expect("the watcher keeps the channel open");
+        let mut events = vec![event];
+        let start = Instant::now();
+        while start.elapsed() < MAX_DEBOUNCE {
+            match receiver.recv_timeout(DEBOUNCE) {
+                Ok(event) => events.push(event),
+                Err(_) => break,
+            }
+        }
+
+        let mut event_paths = BTreeSet::new();
+        for event in events {
+            match event {
+                Ok(event) => event_paths.extend(event.paths.iter().map(|path| normalize(path))),
+                Err(error) => report(Err(watch_error(error))),
+            }
+        }
+
+        let latest = match watched_paths(dir, options) {
+            Ok(latest) => latest,
+            Err(error) => {
+                report(Err(error));
+                continue;
+            }
+        };
+        let changed = current
+            .union(&latest)
+            .filter(|path| event_paths.contains(&normalize(path)))
+            .cloned()
+            .collect::<Vec<_>>();
+        current = latest;
+        if !changed.is_empty() {
+            report(callback(&changed));
+        }
+    }
+}
+
+fn index_path(dir: &Path, options: &Options) -> Option<PathBuf> {
+    options
+        .files
+        .index_file
+        .as_ref()
+        .map(|index_file| dir.join(index_file.as_ref()))
+}
+
+/// The input files in the directory, and its index file.
+fn watched_paths(dir: &Path, options: &Options) -> Result<BTreeSet<PathBuf>, Error> {
+    let mut paths = codegen::files_in_dir(dir, options)?
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    paths.extend(index_path(dir, options));
+    Ok(paths)
+}
+
+/// The path with its parent directory made canonical, so that the
+/// paths in events can be compared to the watched paths, even for
+/// files which have been removed.
+fn normalize(path: &Path) -> PathBuf {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return path.to_owned();
+    };
+    let parent = match parent.as_os_str().is_empty() {
+        true => Path::new("."),
+        false => parent,
+    };
+    std::fs::canonicalize(parent)
+        .map(|parent| parent.join(name))
+        .unwrap_or_else(|_| path.to_owned())
+}
+
+fn watch_error(error: notify::Error) -> Error {
+    match error.kind {
+        notify::ErrorKind::Io(error) => Error::Io(error),
+        _ => Error::Io(std::io::Error::other(error)),
+    }
+}
+
+fn report(result: Result<(), Error>) {
+    if let Err(error) = result {
+        let mut message = format!("edres: {}", error);
+        let mut source = std::error::Error::source(&error);
+        while let Some(error) = source {
+            message.push_str(&format!(": {}", error));
+            source = error.source();
+        }
+        eprintln!("{}", message);
+    }
+}
@@ -1,4 +1,7 @@
-use edres::{Format, Options};
+use edres::{
+    report::Warning, ArrayMerge, Casing, EnumOptions, FlagsOptions, FloatSize, Format, Options,
+    OutputOptions, ParseOptions, StructOptions, UIntSize, ValuesStructOptions,
+};
 use proc_macro2::TokenStream;
 use quote::quote;
 
@@ -6,9 +9,17 @@ fn assert_str(s: String, q: TokenStream) {
     assert_eq!(s, q.to_string());
 }
 
-fn assert_file(p: &str, q: TokenStream) {
+/// Read a generated file, without the comments at the top.
+fn read_generated(p: &str) -> String {
     let s = std::fs::read_to_string(p).unwrap();
-    assert_str(s, q);
+    s.lines()
+        .skip_while(|line| line.starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn assert_file(p: &str, q: TokenStream) {
+    assert_str(read_generated(p), q);
 }
 
 #[test]
@@ -27,6 +38,78 @@ pub fn generate_structs() {
     );
 }
 
+#[test]
+pub fn generate_structs_with_root() {
+    let s = edres::generate_structs_with_root(
+        "tests/data/nested.yaml",
+        "Limits",
+        "server.limits",
+        &Options::minimal(),
+    )
+    .unwrap();
+    assert_str(
+        s,
+        quote!(
+            #[allow(non_camel_case_types)]
+            pub struct Limits {
+                pub connections: i64,
+                pub timeout: f64,
+            }
+        ),
+    );
+
+    let error = edres::generate_structs_with_root(
+        "tests/data/nested.yaml",
+        "Limits",
+        "server.limit",
+        &Options::minimal(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Key `server.limit` not found (available keys: host, limits) \
+        (in file `tests/data/nested.yaml`)"
+    );
+}
+
+#[test]
+pub fn generate_structs_from_sequence() {
+    let options = Options {
+        structs: StructOptions {
+            struct_data_const_name: Some("DATA".into()),
+            allow_root_sequence: true,
+            ..StructOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let s = edres::generate_structs("tests/data/sequence.yaml", "Item", &options).unwrap();
+    assert_str(
+        s,
+        quote!(
+            #[allow(non_camel_case_types)]
+            pub struct Item {
+                pub name: std::borrow::Cow<'static, str>,
+                pub number: i64,
+            }
+
+            pub const DATA: &[Item] = &[
+                Item {
+                    name: std::borrow::Cow::Borrowed("first"),
+                    number: 1i64,
+                },
+                Item {
+                    name: std::borrow::Cow::Borrowed("second"),
+                    number: 2i64,
+                },
+            ];
+        ),
+    );
+
+    let error = edres::generate_structs("tests/data/sequence.yaml", "Item", &Options::minimal())
+        .unwrap_err();
+    assert!(matches!(error.file_path(), Some(path) if path.ends_with("sequence.yaml")));
+}
+
 #[test]
 pub fn generate_structs_from_source() {
     let source = include_str!("data/struct.yaml");
@@ -45,6 +128,305 @@ pub fn generate_structs_from_source() {
     );
 }
 
+#[cfg(feature = "msgpack")]
+#[test]
+pub fn generate_structs_from_bytes() {
+    // {"name": "first", "number": 1}
+    let bytes = b"\x82\xa4name\xa5first\xa6number\x01";
+    let s =
+        edres::generate_structs_from_bytes(bytes, "Struct", Format::Msgpack, &Options::minimal())
+            .unwrap();
+    assert_str(
+        s,
+        quote!(
+            #[allow(non_camel_case_types)]
+            pub struct Struct {
+                pub name: std::borrow::Cow<'static, str>,
+                pub number: i64,
+            }
+        ),
+    );
+
+    let error =
+        edres::generate_structs_from_source("\x01", "Struct", Format::Msgpack, &Options::minimal())
+            .unwrap_err();
+    assert!(
+        matches!(error, edres::Error::BinaryFormat(Format::Msgpack)),
+        "{:?}",
+        error
+    );
+}
+
+#[test]
+pub fn generate_structs_with_yaml_aliases() {
+    let source = "
+spawn: &origin [0, 0]
+exit: *origin
+enemies:
+  - &guard
+    health: 10
+    position: [1, 2]
+  - health: 50
+    position: [3, 4]
+  - *guard
+";
+    let s = edres::generate_structs_from_source(
+        source,
+        "Level",
+        Format::Yaml,
+        &Options {
+            parse: ParseOptions {
+                max_array_size: Some(2),
+                ..ParseOptions::new()
+            },
+            structs: StructOptions {
+                struct_data_const_name: Some("DATA".into()),
+                tuple_structs: vec![
+                    ("spawn".into(), "Point".into()),
+                    ("exit".into(), "Point".into()),
+                ]
+                .into(),
+                ..StructOptions::minimal()
+            },
+            output: OutputOptions {
+                dedup_values: true,
+                ..OutputOptions::new()
+            },
+            ..Options::minimal()
+        },
+    )
+    .unwrap();
+    assert_str(
+        s,
+        quote!(
+            #[allow(non_camel_case_types)]
+            pub struct Level {
+                pub spawn: Point,
+                pub exit: Point,
+                pub enemies: std::borrow::Cow<'static, [Level__enemies]>,
+            }
+
+            #[allow(non_camel_case_types)]
+            pub struct Point(pub i64, pub i64);
+
+            #[allow(non_camel_case_types)]
+            pub struct Level__enemies {
+                pub health: i64,
+                pub position: [i64; 2usize],
+            }
+
+            impl Level {
+                pub const DATA: Level = Level {
+                    spawn: Point_0,
+                    exit: Point_0,
+                    enemies: std::borrow::Cow::Borrowed(&[
+                        Level__enemies_0,
+                        Level__enemies { health: 50i64, position: [3i64, 4i64,], },
+                        Level__enemies_0,
+                    ]),
+                };
+            }
+
+            #[allow(non_upper_case_globals)]
+            const Point_0: Point = Point(0i64, 0i64);
+
+            #[allow(non_upper_case_globals)]
+            const Level__enemies_0: Level__enemies =
+                Level__enemies { health: 10i64, position: [1i64, 2i64,], };
+        ),
+    );
+}
+
+#[test]
+pub fn generate_structs_with_inline_directives() {
+    let s = edres::generate_structs("tests/data/directives.yaml", "Config", &Options::minimal())
+        .unwrap();
+    assert_str(
+        s,
+        quote!(
+            #[allow(non_camel_case_types)]
+            pub struct Config {
+                pub kind: std::borrow::Cow<'static, str>,
+                #[doc = " The port to listen on."]
+                #[doc = " Defaults to 8080."]
+                pub port: u16,
+            }
+        ),
+    );
+}
+
+#[test]
+pub fn generate_structs_with_report_and_inline_directives() {
+    let (s, report) = edres::generate_structs_with_report(
+        "tests/data/directives.yaml",
+        "Config",
+        &Options::minimal(),
+    )
+    .unwrap();
+    assert_eq!(
+        s,
+        edres::generate_structs("tests/data/directives.yaml", "Config", &Options::minimal())
+            .unwrap()
+    );
+    assert_eq!(
+        report.warnings,
+        [
+            Warning::RenamedField {
+                key_path: "type".into(),
+                name: "kind".into(),
+            },
+            Warning::SkippedKey {
+                key_path: "_notes".into(),
+            },
+        ]
+    );
+
+    let source = "__edres:\n  skip: _notes\nratio: 0.1\n_notes: internal\n";
+    let options = Options {
+        parse: ParseOptions {
+            default_float_size: FloatSize::F32,
+            ..ParseOptions::new()
+        },
+        ..Options::minimal()
+    };
+    let (s, report) =
+        edres::generate_structs_from_source_with_report(source, "Config", Format::Yaml, &options)
+            .unwrap();
+    assert_str(
+        s,
+        quote!(
+            #[allow(non_camel_case_types)]
+            pub struct Config {
+                pub ratio: f32,
+            }
+        ),
+    );
+    assert_eq!(
+        report.warnings,
+        [
+            Warning::LossyNumber {
+                key_path: "ratio".into(),
+                value: "0.1".into(),
+                type_name: "f32",
+            },
+            Warning::SkippedKey {
+                key_path: "_notes".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+pub fn generate_structs_with_root_and_inline_directives() {
+    let s = edres::generate_structs_with_root(
+        "tests/data/rooted_directives.yaml",
+        "Server",
+        "server",
+        &Options::minimal(),
+    )
+    .unwrap();
+    assert_str(
+        s,
+        quote!(
+            #[allow(non_camel_case_types)]
+            pub struct Server {
+                pub port: u16,
+            }
+        ),
+    );
+}
+
+#[test]
+pub fn generate_enum_with_inline_directives() {
+    let options = Options {
+        enums: EnumOptions {
+            variant_casing: Some(Casing::Pascal),
+            ..EnumOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let s = edres::generate_enum("tests/data/directives.yaml", "Key", &options).unwrap();
+    assert_str(
+        s,
+        quote!(
+            pub enum Key {
+                Type,
+                Port,
+            }
+        ),
+    );
+}
+
+#[test]
+pub fn generate_from_files_with_inline_directives() {
+    let options = Options {
+        structs: StructOptions {
+            struct_data_const_name: Some("DATA".into()),
+            ..StructOptions::minimal()
+        },
+        enums: EnumOptions {
+            all_values_const_name: Some("VALUES".into()),
+            values_struct: Some(ValuesStructOptions::minimal()),
+            ..EnumOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let s = edres::generate_structs_from_files("tests/data/directive_files", "Entry", &options)
+        .unwrap();
+    assert_str(
+        s,
+        quote!(
+            #[allow(non_camel_case_types)]
+            pub struct Entry {
+                pub name: std::borrow::Cow<'static, str>,
+                pub size: u8,
+            }
+
+            pub const DATA: &[Entry] = &[
+                Entry {
+                    name: std::borrow::Cow::Borrowed("First"),
+                    size: 1u8,
+                },
+                Entry {
+                    name: std::borrow::Cow::Borrowed("Second"),
+                    size: 2u8,
+                },
+            ];
+        ),
+    );
+
+    let s = edres::generate_enum_from_filenames("tests/data/directive_files", "Entry", &options)
+        .unwrap();
+    assert_str(
+        s,
+        quote!(
+            pub enum Entry {
+                First,
+                Second,
+            }
+
+            impl Entry {
+                pub const VALUES: &'static [Entry__Value] = &[
+                    Entry__Value {
+                        name: std::borrow::Cow::Borrowed("First"),
+                        size: 1u8,
+                    },
+                    Entry__Value {
+                        name: std::borrow::Cow::Borrowed("Second"),
+                        size: 2u8,
+                    },
+                ];
+            }
+
+            #[allow(non_camel_case_types)]
+            pub struct Entry__Value {
+                pub name: std::borrow::Cow<'static, str>,
+                pub size: u8,
+            }
+        ),
+    );
+}
+
 #[test]
 pub fn generate_structs_from_files() {
     let s = edres::generate_structs_from_files("tests/data/files", "Struct", &Options::minimal())
@@ -75,6 +457,80 @@ pub fn generate_enum() {
     );
 }
 
+#[test]
+pub fn generate_enums_from_keys_at() {
+    let options = Options {
+        enums: EnumOptions {
+            values_struct: Some(ValuesStructOptions::minimal()),
+            ..EnumOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let s = edres::generate_enums_from_keys_at(
+        "tests/data/items.yaml",
+        &[("weapons", "Weapon"), ("armors", "Armor")],
+        &options,
+    )
+    .unwrap();
+    assert_str(
+        s,
+        quote!(
+            pub enum Weapon {
+                Sword,
+                Bow,
+            }
+
+            #[allow(non_camel_case_types)]
+            pub struct Weapon__Value {
+                pub damage: i64,
+            }
+
+            pub enum Armor {
+                Helmet,
+            }
+
+            #[allow(non_camel_case_types)]
+            pub struct Armor__Value {
+                pub defense: i64,
+            }
+        ),
+    );
+
+    let error = edres::generate_enums_from_keys_at(
+        "tests/data/items.yaml",
+        &[("weapons", "Item"), ("armors", "Item")],
+        &options,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Generated name `Item` is used more than once (in file `tests/data/items.yaml`)"
+    );
+}
+
+#[test]
+pub fn generate_enum_with_error_location() {
+    let options = Options {
+        enums: EnumOptions {
+            nested: true,
+            ..EnumOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let error =
+        edres::generate_enum("tests/data/categories.toml", "Category", &options).unwrap_err();
+    assert_eq!(error.key_path(), Some("gold"));
+    assert_eq!(
+        error.location(),
+        Some(edres::value::Location { line: 1, column: 8 })
+    );
+    assert_eq!(
+        error.to_string(),
+        "Expected value to be a struct but found `i64` instead (at key `gold`) \
+        (in file `tests/data/categories.toml:1:8`)"
+    );
+}
+
 #[test]
 pub fn generate_enum_from_source() {
     let source = include_str!("data/enum.yaml");
@@ -106,6 +562,85 @@ pub fn generate_enum_from_filenames() {
     );
 }
 
+#[test]
+pub fn generate_enum_from_sources() {
+    let s = edres::generate_enum_from_sources(
+        [
+            ("alpha.yaml", "name: Alpha", Format::Yaml),
+            ("beta.yaml", "name: Beta", Format::Yaml),
+        ],
+        "Enum",
+        &Options {
+            enums: EnumOptions {
+                all_values_const_name: Some("VALUES".into()),
+                values_struct: Some(ValuesStructOptions::minimal()),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        },
+    )
+    .unwrap();
+    assert_str(
+        s,
+        quote!(
+            pub enum Enum {
+                Alpha,
+                Beta,
+            }
+
+            impl Enum {
+                pub const VALUES: &'static [Enum__Value] = &[
+                    Enum__Value {
+                        name: std::borrow::Cow::Borrowed("Alpha"),
+                    },
+                    Enum__Value {
+                        name: std::borrow::Cow::Borrowed("Beta"),
+                    },
+                ];
+            }
+
+            #[allow(non_camel_case_types)]
+            pub struct Enum__Value {
+                pub name: std::borrow::Cow<'static, str>,
+            }
+        ),
+    );
+}
+
+#[test]
+pub fn generate_bitflags() {
+    let s = edres::generate_bitflags(
+        "tests/data/flags.yaml",
+        "Permissions",
+        &Options {
+            flags: FlagsOptions {
+                repr: UIntSize::U8,
+                ..FlagsOptions::minimal()
+            },
+            ..Options::minimal()
+        },
+    )
+    .unwrap();
+    assert!(s.starts_with(
+        &quote!(
+            pub struct Permissions(u8);
+        )
+        .to_string()
+    ));
+    assert!(s.contains(
+        &quote!(
+            pub const WRITE: Self = Self(1 << 1);
+        )
+        .to_string()
+    ));
+    assert!(s.contains(
+        &quote!(
+            pub const READ_WRITE: Self = Self(Self::READ.0 | Self::WRITE.0);
+        )
+        .to_string()
+    ));
+}
+
 #[test]
 pub fn create_structs() {
     edres::create_structs(
@@ -127,6 +662,90 @@ pub fn create_structs() {
     );
 }
 
+#[test]
+pub fn create_structs_with_root() {
+    edres::create_structs_with_root(
+        "tests/data/nested.yaml",
+        "tests/output/test7.rs",
+        "Limits",
+        "server.limits",
+        &Options::minimal(),
+    )
+    .unwrap();
+    assert_file(
+        "tests/output/test7.rs",
+        quote!(
+            #[allow(non_camel_case_types)]
+            pub struct Limits {
+                pub connections: i64,
+                pub timeout: f64,
+            }
+        ),
+    );
+}
+
+#[test]
+pub fn generate_structs_from_layers() {
+    let layers = [
+        "tests/data/layers/base.yaml",
+        "tests/data/layers/production.toml",
+    ];
+    let options = Options {
+        structs: StructOptions {
+            struct_data_const_name: Some("DATA".into()),
+            ..StructOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let s = edres::generate_structs_from_layers(&layers, "Config", &options).unwrap();
+    assert_str(
+        s,
+        quote!(
+            #[allow(non_camel_case_types)]
+            pub struct Config {
+                pub server: Config__server,
+                pub features: std::borrow::Cow<'static, [std::borrow::Cow<'static, str>]>,
+            }
+
+            #[allow(non_camel_case_types)]
+            pub struct Config__server {
+                pub host: std::borrow::Cow<'static, str>,
+                pub port: i64,
+                pub tls: bool,
+            }
+
+            impl Config {
+                pub const DATA: Config = Config {
+                    server: Config__server {
+                        host: std::borrow::Cow::Borrowed("localhost"),
+                        port: 443i64,
+                        tls: true,
+                    },
+                    features: std::borrow::Cow::Borrowed(&[
+                        std::borrow::Cow::Borrowed("metrics"),
+                    ]),
+                };
+            }
+        ),
+    );
+
+    let options = Options {
+        parse: ParseOptions {
+            layer_array_merge: ArrayMerge::Append,
+            ..ParseOptions::new()
+        },
+        ..options
+    };
+    let s = edres::generate_structs_from_layers(&layers, "Config", &options).unwrap();
+    assert!(s.contains(
+        &quote!(features: std::borrow::Cow::Borrowed(&[
+            std::borrow::Cow::Borrowed("logging"),
+            std::borrow::Cow::Borrowed("metrics"),
+        ]))
+        .to_string()
+    ));
+}
+
 #[test]
 pub fn create_structs_from_source() {
     let source = include_str!("data/struct.yaml");
@@ -232,3 +851,303 @@ pub fn create_enum_from_filenames() {
         ),
     );
 }
+
+#[test]
+pub fn create_enum_with_post_process() {
+    let output = edres::create_enum_with(
+        "tests/data/enum.yaml",
+        "tests/output/test12.rs",
+        "Enum",
+        &Options::minimal(),
+        |output| Ok(output.replace("pub enum", "pub (crate) enum")),
+    )
+    .unwrap();
+    let expected = quote!(
+        pub(crate) enum Enum {
+            First,
+            Second,
+        }
+    );
+    assert_str(output, expected.clone());
+    assert_file("tests/output/test12.rs", expected);
+}
+
+#[test]
+pub fn create_structs_with_header() {
+    let options = Options {
+        output: OutputOptions {
+            header: Some("Config for the server.\n\nSee README.md.".into()),
+            ..OutputOptions::new()
+        },
+        ..Options::minimal()
+    };
+    edres::create_structs(
+        "tests/data/struct.yaml",
+        "tests/output/test10.rs",
+        "Struct",
+        &options,
+    )
+    .unwrap();
+
+    let s = std::fs::read_to_string("tests/output/test10.rs").unwrap();
+    let lines = s.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines[..6],
+        [
+            "// Config for the server.",
+            "//",
+            "// See README.md.",
+            concat!(
+                "// This file was generated by edres ",
+                env!("CARGO_PKG_VERSION"),
+                ". Do not edit it by hand."
+            ),
+            "// Sources:",
+            "//     tests/data/struct.yaml",
+        ]
+    );
+    assert!(lines[6].starts_with("// Generated at: "));
+
+    let options = Options {
+        output: OutputOptions {
+            provenance: false,
+            ..options.output
+        },
+        ..options
+    };
+    edres::create_structs(
+        "tests/data/struct.yaml",
+        "tests/output/test10.rs",
+        "Struct",
+        &options,
+    )
+    .unwrap();
+
+    let s = std::fs::read_to_string("tests/output/test10.rs").unwrap();
+    assert!(s.starts_with("// Config for the server.\n//\n// See README.md.\n#"));
+}
+
+#[test]
+pub fn create_structs_from_files_with_cache() {
+    let cache_dir = std::path::Path::new("tests/output/cache");
+    let _ = std::fs::remove_dir_all(cache_dir);
+    let options = Options {
+        output: OutputOptions {
+            cache_dir: Some(cache_dir.into()),
+            ..OutputOptions::new()
+        },
+        ..Options::minimal()
+    };
+    let create = |struct_name| {
+        edres::create_structs_from_files(
+            "tests/data/files",
+            "tests/output/test11.rs",
+            struct_name,
+            &options,
+        )
+        .unwrap();
+        read_generated("tests/output/test11.rs")
+    };
+
+    let expected =
+        edres::generate_structs_from_files("tests/data/files", "Struct", &options).unwrap();
+    assert_eq!(create("Struct"), expected);
+
+    // Prove that unchanged inputs use the cached output.
+    let cache_file = std::fs::read_dir(cache_dir)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let cached = std::fs::read_to_string(&cache_file).unwrap();
+    let (hash, _) = cached.split_once('\n').unwrap();
+    std::fs::write(&cache_file, format!("{}\npub struct Cached;", hash)).unwrap();
+    assert_eq!(create("Struct"), "pub struct Cached;");
+
+    // Changed options generate new output.
+    let expected =
+        edres::generate_structs_from_files("tests/data/files", "Other", &options).unwrap();
+    assert_eq!(create("Other"), expected);
+
+    // Included files could be anywhere, so they aren't cached.
+    let _ = std::fs::remove_dir_all(cache_dir);
+    let options = Options {
+        parse: ParseOptions {
+            include_directive: Some("!include".into()),
+            ..options.parse.clone()
+        },
+        ..options.clone()
+    };
+    edres::create_structs_from_files(
+        "tests/data/files",
+        "tests/output/test11.rs",
+        "Struct",
+        &options,
+    )
+    .unwrap();
+    assert!(!cache_dir.exists());
+}
+
+#[test]
+pub fn create_structs_in_out_dir() {
+    let options = Options {
+        output: OutputOptions {
+            out_dir_relative: true,
+            ..OutputOptions::new()
+        },
+        ..Options::minimal()
+    };
+    std::env::set_var("OUT_DIR", "tests/output/out_dir");
+    edres::create_structs("tests/data/struct.yaml", "test12.rs", "Struct", &options).unwrap();
+    let path = edres::destination_path("test12.rs", &options).unwrap();
+    assert_eq!(path, std::path::Path::new("tests/output/out_dir/test12.rs"));
+    assert_file(
+        "tests/output/out_dir/test12.rs",
+        quote!(
+            #[allow(non_camel_case_types)]
+            pub struct Struct {
+                pub name: std::borrow::Cow<'static, str>,
+                pub number: i64,
+            }
+        ),
+    );
+}
+
+#[test]
+pub fn check_structs_are_up_to_date() {
+    let check_options = Options {
+        output: OutputOptions {
+            check_only: true,
+            ..OutputOptions::new()
+        },
+        ..Options::minimal()
+    };
+    let _ = std::fs::remove_file("tests/output/test13.rs");
+    let error = edres::create_structs(
+        "tests/data/struct.yaml",
+        "tests/output/test13.rs",
+        "Struct",
+        &check_options,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Generated file `tests/output/test13.rs` is out of date: the file does not exist"
+    );
+    assert!(!std::path::Path::new("tests/output/test13.rs").exists());
+
+    edres::create_structs(
+        "tests/data/struct.yaml",
+        "tests/output/test13.rs",
+        "Struct",
+        &Options::minimal(),
+    )
+    .unwrap();
+    edres::create_structs(
+        "tests/data/struct.yaml",
+        "tests/output/test13.rs",
+        "Struct",
+        &check_options,
+    )
+    .unwrap();
+
+    let error = edres::create_structs(
+        "tests/data/struct.yaml",
+        "tests/output/test13.rs",
+        "Renamed",
+        &check_options,
+    )
+    .unwrap_err();
+    assert!(matches!(error, edres::Error::OutOfDate(..)));
+}
+
+#[test]
+pub fn run_manifest() {
+    let expected_options = Options {
+        source_path_const_name: Some("SOURCE".into()),
+        structs: StructOptions {
+            derived_traits: vec![].into(),
+            struct_data_const_name: Some("DATA".into()),
+            ..StructOptions::new()
+        },
+        enums: EnumOptions {
+            derived_traits: vec!["Debug".into()].into(),
+            impl_default: false,
+            impl_display: false,
+            impl_from_str: false,
+            all_values_const_name: Some("VALUES".into()),
+            values_struct: Some(ValuesStructOptions {
+                struct_name: Some("EnumValue".into()),
+                ..ValuesStructOptions::new()
+            }),
+            get_value_fn_name: Some("get".into()),
+            ..EnumOptions::new()
+        },
+        ..Options::new()
+    };
+
+    let manifest = edres::Manifest::from_file("tests/data/edres.toml").unwrap();
+    assert_eq!(manifest.options, expected_options);
+    assert_eq!(
+        manifest.targets[0].input,
+        std::path::Path::new("tests/data/struct.yaml")
+    );
+
+    let report = manifest.run().unwrap();
+    assert_eq!(report.written.len(), 3);
+    assert_eq!(report.warnings, []);
+    assert_eq!(report.files_parsed, 2);
+    assert_eq!(
+        read_generated("tests/output/test14.rs"),
+        edres::generate_enum(
+            "tests/data/struct.yaml",
+            "StructKey",
+            &Options {
+                source_path_const_name: Some("SOURCE".into()),
+                ..Options::new()
+            }
+        )
+        .unwrap(),
+    );
+
+    edres::run_manifest("tests/data/edres.toml").unwrap();
+    assert_eq!(
+        read_generated("tests/output/test8.rs"),
+        edres::generate_structs("tests/data/struct.yaml", "Struct", &expected_options).unwrap(),
+    );
+    assert_eq!(
+        read_generated("tests/output/test9.rs"),
+        edres::generate_enum("tests/data/enum.yaml", "Enum", &expected_options).unwrap(),
+    );
+}
+
+#[cfg(feature = "watch")]
+#[test]
+pub fn watch_dir() {
+    use std::{path::PathBuf, sync::mpsc, time::Duration};
+
+    let dir = "tests/output/watch";
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(format!("{}/a.yaml", dir), "number: 1").unwrap();
+
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        edres::watch::watch_dir(dir, &Options::minimal(), |changed| {
+            sender.send(changed.to_vec()).unwrap();
+            Ok(())
+        })
+    });
+    let next_change = || receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    let path = |name| PathBuf::from(format!("{}/{}", dir, name));
+
+    assert_eq!(next_change(), vec![path("a.yaml")]);
+
+    std::fs::write(format!("{}/b.yaml", dir), "number: 2").unwrap();
+    assert_eq!(next_change(), vec![path("b.yaml")]);
+
+    std::fs::write(format!("{}/a.yaml", dir), "number: 100").unwrap();
+    std::fs::remove_file(format!("{}/b.yaml", dir)).unwrap();
+    assert_eq!(next_change(), vec![path("a.yaml"), path("b.yaml")]);
+}
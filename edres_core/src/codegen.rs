@@ -6,16 +6,27 @@
 //! directly. Instead, you should use the functions in the
 //! top level of this crate.
 
-use std::path::Path;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
 
-use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use indexmap::{IndexMap, IndexSet};
+use proc_macro2::{Delimiter, Group, Ident, Literal, Spacing, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
 
 use crate::{
+    directives::InlineDirectives,
     error::Error,
-    options::{Options, SerdeSupport},
-    parsing,
-    value::{Map, Struct, Value},
+    options::{
+        AccessorStyle, Casing, EnumOptions, EnumRepr, FieldOrder, FieldSelection, FlagsStyle,
+        IntegerKeyOptions, Options, PathRoot, SerdeDefaults, SerdeSupport, StructNaming,
+        StructOptions, UIntSize, ValuesStructOptions, VariantSort, Visibility,
+    },
+    parsing, validation,
+    value::{Datetime, Map, Struct, Value},
     Format,
 };
 
@@ -66,33 +77,83 @@ pub fn define_structs(
     source_file_path: Option<&Path>,
     options: &Options,
 ) -> Result<TokenStream, Error> {
-    let derives = derive_attribute(
-        options.structs.derived_traits.as_ref(),
-        options.serde_support,
-        false,
-    );
-
-    let struct_tokens = define_structs_inner(data, struct_name, options, derives.as_ref())?;
+    validation::validate_options(options)?;
+    let vis = visibility(options)?;
+    let struct_tokens = define_structs_inner(data, struct_name, "", options)?;
 
     let mut inherents = vec![];
+    let mut shared_tokens = vec![];
     if let (Some(source_file_path), Some(const_name)) =
         (source_file_path, options.source_path_const_name.as_ref())
     {
         let source_file_path = source_file_path.display().to_string();
         let source_path_const_name = format_ident!("{}", const_name);
         inherents.push(quote! {
-            pub const #source_path_const_name: &'static str = #source_file_path;
+            #vis const #source_path_const_name: &'static str = #source_file_path;
         });
     }
     if let Some(const_name) = &options.structs.struct_data_const_name {
-        let struct_value = define_struct_value(data, struct_name)?;
+        if let Some(key_path) = kept_map_path(data, "", options) {
+            return Err(Error::MapInConst(key_path));
+        }
+        let struct_value = define_struct_value(data, struct_name, "", options)?;
+        let (struct_value, shared) = dedup_values(vec![struct_value], options);
         let struct_name = format_ident!("{}", struct_name);
         let const_name = format_ident!("{}", const_name);
 
         inherents.push(quote! {
-            pub const #const_name: #struct_name = #struct_value;
+            #vis const #const_name: #struct_name = #(#struct_value)*;
+        });
+        shared_tokens = shared;
+    }
+
+    if let Some(fn_name) = &options.structs.validate_fn_name {
+        let fn_name = format_ident!("{}", fn_name);
+        let (_, string) = map_types(options);
+        let checks = struct_checks(data, quote!(self), "", options);
+        inherents.push(quote! {
+            #vis fn #fn_name(&self) -> Result<(), #string> {
+                #(#checks)*
+                Ok(())
+            }
+        });
+    }
+
+    let default_tokens = options
+        .structs
+        .impl_default
+        .then(|| define_struct_value(data, struct_name, "", options))
+        .transpose()?
+        .map(|struct_value| {
+            let struct_name = format_ident!("{}", struct_name);
+            quote! {
+                impl Default for #struct_name {
+                    fn default() -> Self {
+                        #struct_value
+                    }
+                }
+            }
+        })
+        .into_iter();
+
+    let mut builder_tokens = None;
+    if options.structs.generate_builder {
+        let builder_name = format_ident!("{}Builder", struct_name);
+        inherents.push(quote! {
+            #vis fn builder() -> #builder_name {
+                #builder_name::new()
+            }
         });
+        builder_tokens = Some(define_builder(data, struct_name, options)?);
     }
+    let builder_tokens = builder_tokens.into_iter();
+
+    let convert_tokens = options
+        .structs
+        .convert_into
+        .as_ref()
+        .map(|type_path| define_conversion(data, struct_name, type_path, options))
+        .into_iter();
 
     let struct_name = format_ident!("{}", struct_name);
     let inherent_tokens = (!inherents.is_empty())
@@ -108,38 +169,248 @@ pub fn define_structs(
     Ok(quote! {
         #struct_tokens
         #(#inherent_tokens)*
+        #(#default_tokens)*
+        #(#builder_tokens)*
+        #(#convert_tokens)*
+        #(#shared_tokens)*
+    })
+}
+
+/// Defines a `From` impl converting a struct into an existing type
+/// with the same fields. (See [`StructOptions::convert_into`].)
+///
+/// [`StructOptions::convert_into`]: crate::options::StructOptions::convert_into
+fn define_conversion(
+    data: &Struct,
+    struct_name: &str,
+    type_path: &str,
+    options: &Options,
+) -> TokenStream {
+    let leading = type_path.starts_with("::").then(|| quote!(::));
+    let segments = type_path
+        .trim_start_matches("::")
+        .split("::")
+        .map(|segment| format_ident!("{}", segment));
+    let type_path = quote!(#leading #(#segments)::*);
+    let newtype = is_newtype(data, options);
+    let fields = struct_fields(data, "", options)
+        .into_iter()
+        .map(|(key, _)| {
+            let field_name = field_name(key, key, options);
+            let field = match newtype {
+                true => quote!(0),
+                false => field_name.to_token_stream(),
+            };
+            quote!(#field_name: value.#field.into())
+        });
+    let struct_name = format_ident!("{}", struct_name);
+    quote! {
+        impl From<#struct_name> for #type_path {
+            fn from(value: #struct_name) -> Self {
+                #type_path {
+                    #(#fields,)*
+                }
+            }
+        }
+    }
+}
+
+/// Defines a builder for a struct, which starts with the values in
+/// `data`. (See [`StructOptions::generate_builder`].)
+///
+/// [`StructOptions::generate_builder`]: crate::options::StructOptions::generate_builder
+fn define_builder(
+    data: &Struct,
+    struct_name: &str,
+    options: &Options,
+) -> Result<TokenStream, Error> {
+    let vis = visibility(options)?;
+    let fields = struct_fields(data, "", options);
+    let field_names = fields
+        .iter()
+        .map(|(key, _)| field_name(key, key, options).to_string())
+        .collect::<Vec<_>>();
+    ensure_unique_names(
+        ["new", "build"]
+            .into_iter()
+            .chain(field_names.iter().map(String::as_str)),
+    )?;
+
+    let newtype = is_newtype(data, options);
+    let inner = Literal::usize_unsuffixed(0);
+    let setters = fields
+        .iter()
+        .map(|(key, value)| {
+            let field_name = field_name(key, key, options);
+            let decl = type_of_value(
+                value,
+                struct_name,
+                Some(key),
+                None,
+                key,
+                options,
+                &mut vec![],
+            )?;
+            let field = match newtype {
+                true => quote!(0),
+                false => field_name.to_token_stream(),
+            };
+            Ok(quote! {
+                #vis fn #field_name(mut self, #field_name: #decl) -> Self {
+                    self.#inner.#field = #field_name;
+                    self
+                }
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let struct_value = define_struct_value(data, struct_name, "", options)?;
+    let builder_name = format_ident!("{}Builder", struct_name);
+    let struct_name = format_ident!("{}", struct_name);
+    Ok(quote! {
+        #vis struct #builder_name(#struct_name);
+
+        impl #builder_name {
+            #vis fn new() -> Self {
+                #builder_name(#struct_value)
+            }
+
+            #(#setters)*
+
+            #vis fn build(self) -> #struct_name {
+                self.0
+            }
+        }
+
+        impl Default for #builder_name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
     })
 }
 
 fn define_structs_inner(
     data: &Struct,
     struct_name: &str,
+    key_path: &str,
     options: &Options,
-    derives: Option<&TokenStream>,
 ) -> Result<TokenStream, Error> {
+    let vis = visibility(options)?;
+    let field_vis = visibility_tokens(&options.structs.field_visibility)?;
+    let derives = derive_attribute(
+        supported_derives(data, struct_name, key_path, options)?,
+        options.serde_support,
+        false,
+    );
+
     let mut fields = vec![];
+    let mut decls = vec![];
+    let mut inherents = vec![];
     let mut sub_structs = vec![];
+    let newtype = is_newtype(data, options);
+    let tuple_struct = tuple_struct_name(key_path, options).is_some();
+    let positional = newtype || tuple_struct;
+    let serde_defaults = match options.serde_support.should_derive_ser_de() {
+        Some((_, true)) if !positional => options.structs.serde_defaults,
+        _ => SerdeDefaults::None,
+    };
 
-    for (key, value) in data.0.iter() {
-        let field_name = format_ident!("{}", key);
-        let decl = type_of_value(value, struct_name, Some(key), None, &mut sub_structs)?;
-        fields.push(quote!(pub #field_name : #decl));
+    for (key, value) in struct_fields(data, key_path, options) {
+        let decl = type_of_value(
+            value,
+            struct_name,
+            Some(key),
+            None,
+            &child_path(key_path, key),
+            options,
+            &mut sub_structs,
+        )?;
+        if tuple_struct {
+            decls.push(decl);
+            continue;
+        }
+        let field_path = child_path(key_path, key);
+        let field_name = field_name(key, &field_path, options);
+        if options.structs.generate_accessors {
+            let field = match newtype {
+                true => quote!(0),
+                false => field_name.to_token_stream(),
+            };
+            inherents.push(quote! {
+                #vis const fn #field_name(&self) -> &#decl {
+                    &self.#field
+                }
+            });
+        }
+        let default_attribute = match serde_defaults {
+            SerdeDefaults::None => None,
+            SerdeDefaults::DefaultTrait => Some(quote!(#[serde(default)])),
+            SerdeDefaults::FromConst => {
+                let fn_name = format_ident!("default_{}", field_name);
+                let path = format!("{}::{}", struct_name, fn_name);
+                let value = define_value(
+                    value,
+                    struct_name,
+                    Some(key),
+                    None,
+                    &child_path(key_path, key),
+                    options,
+                )?;
+                inherents.push(quote! {
+                    fn #fn_name() -> #decl {
+                        #value
+                    }
+                });
+                Some(quote!(#[serde(default = #path)]))
+            }
+        }
+        .into_iter();
+        let rename = serde_rename(key, &field_path, options).into_iter();
+        let docs = field_docs(&field_path, options);
+        fields.push(
+            quote!(#(#docs)* #(#rename)* #(#default_attribute)* #field_vis #field_name : #decl),
+        );
+        decls.push(decl);
     }
 
-    let sub_structs: Vec<TokenStream> = sub_structs
-        .iter()
-        .map(|(name, value)| define_structs_inner(value, name, options, derives))
-        .collect::<Result<_, Error>>()?;
+    let sub_structs = define_sub_structs(&sub_structs, options)?;
 
     let struct_name = format_ident!("{}", struct_name);
     let derives = derives.into_iter();
+    let deny_unknown_fields = (options.structs.validate_fn_name.is_some()
+        && !positional
+        && matches!(
+            options.serde_support.should_derive_ser_de(),
+            Some((_, true))
+        ))
+    .then(|| quote!(#[serde(deny_unknown_fields)]))
+    .into_iter();
+    let extra_attributes = attribute_contents(&options.structs.extra_attributes)?;
+
+    let inherent_tokens = (!inherents.is_empty())
+        .then(|| {
+            quote! {
+                impl #struct_name {
+                    #(#inherents)*
+                }
+            }
+        })
+        .into_iter();
+
+    let body = match positional {
+        true => quote!((#(#field_vis #decls),*);),
+        false => quote!({ #(#fields ,)* }),
+    };
 
     let tokens = quote!(
         #[allow(non_camel_case_types)]
         #(#derives)*
-        pub struct #struct_name {
-            #(#fields ,)*
-        }
+        #(#deny_unknown_fields)*
+        #(#[#extra_attributes])*
+        #vis struct #struct_name #body
+
+        #(#inherent_tokens)*
 
         #(#sub_structs)*
     );
@@ -147,44 +418,449 @@ fn define_structs_inner(
     Ok(tokens)
 }
 
+/// Define the structs (and enums) nested within a struct or enum.
+fn define_sub_structs(
+    sub_structs: &NewStructs,
+    options: &Options,
+) -> Result<Vec<TokenStream>, Error> {
+    // Several fields can share a tuple struct, so identical tuple
+    // structs are only defined once.
+    let nested_options = nested_struct_options(options);
+    let mut defined: Vec<(&str, TokenStream)> = vec![];
+    for (name, path, value) in sub_structs {
+        let tokens = match is_tagged_enum(value) {
+            true => define_tagged_enum(value, name, path, &nested_options)?,
+            false => define_structs_inner(value, name, path, &nested_options)?,
+        };
+        let shared = tuple_struct_name(path, options).is_some()
+            && defined.iter().any(|(other, other_tokens)| {
+                other == name && other_tokens.to_string() == tokens.to_string()
+            });
+        if !shared {
+            defined.push((name, tokens));
+        }
+    }
+    ensure_unique_names(defined.iter().map(|(name, _)| name))?;
+    Ok(defined.into_iter().map(|(_, tokens)| tokens).collect())
+}
+
+/// The variants of the enum for a sequence of tagged structs (see
+/// [`HeterogeneousArrayMode::Tagged`]), as a struct with a field
+/// for each tag.
+///
+/// Each field holds the first struct with that tag, with the types
+/// of its optional fields filled in from the others.
+///
+/// [`HeterogeneousArrayMode::Tagged`]: crate::options::HeterogeneousArrayMode::Tagged
+fn tagged_variants(values: &[Value]) -> Struct {
+    let mut variants = Struct::default();
+    for value in values {
+        if let Some(tag) = value.tag() {
+            match variants.0.get_mut(tag) {
+                Some(variant) => fill_empty_options(variant, value),
+                None => {
+                    variants.0.insert(tag.to_owned(), value.clone());
+                }
+            }
+        }
+    }
+    variants
+}
+
+/// Whether a new struct is really the variants of an enum (see
+/// [`tagged_variants`]).
+fn is_tagged_enum(data: &Struct) -> bool {
+    !data.0.is_empty() && data.0.values().all(|value| value.tag().is_some())
+}
+
+/// The name of the variant for a tag.
+fn variant_name(tag: &str, options: &Options) -> String {
+    apply_casing(options.enums.variant_casing.unwrap_or(Casing::Pascal), tag)
+}
+
+/// Define the enum for a sequence of tagged structs, from the
+/// variants returned by [`tagged_variants`].
+fn define_tagged_enum(
+    variants: &Struct,
+    enum_name: &str,
+    key_path: &str,
+    options: &Options,
+) -> Result<TokenStream, Error> {
+    let vis = visibility(options)?;
+    let float_path = variants
+        .0
+        .values()
+        .find_map(|value| float_key_path(value, key_path, options));
+    let derives = derive_attribute(
+        derives_without_floats(float_path, enum_name, options)?,
+        options.serde_support,
+        false,
+    )
+    .into_iter();
+    let serde = options.serde_support.should_derive_ser_de().is_some();
+
+    let mut names = vec![];
+    let mut variant_tokens = vec![];
+    let mut sub_structs = vec![];
+    let mut tag_attribute = None;
+    for (tag, variant) in &variants.0 {
+        let Value::Tagged(tag_key, fields) = variant else {
+            continue;
+        };
+        let name = variant_name(tag, options);
+        let struct_name = nested_struct_name(enum_name, Some(&name), None, options);
+        let variant_path = child_path(key_path, tag);
+        let mut field_tokens = vec![];
+        for (key, value) in struct_fields(fields, &variant_path, options) {
+            if key == tag_key {
+                continue;
+            }
+            let decl = type_of_value(
+                value,
+                &struct_name,
+                Some(key),
+                None,
+                &child_path(&variant_path, key),
+                options,
+                &mut sub_structs,
+            )?;
+            let field_path = child_path(&variant_path, key);
+            let field_name = field_name(key, &field_path, options);
+            let rename = serde_rename(key, &field_path, options).into_iter();
+            let docs = field_docs(&field_path, options);
+            field_tokens.push(quote!(#(#docs)* #(#rename)* #field_name: #decl));
+        }
+
+        let ident = format_ident!("{}", name);
+        let rename = (serde && name != *tag)
+            .then(|| quote!(#[serde(rename = #tag)]))
+            .into_iter();
+        variant_tokens.push(quote! {
+            #(#rename)*
+            #ident { #(#field_tokens,)* }
+        });
+        names.push(name);
+        tag_attribute = serde.then(|| quote!(#[serde(tag = #tag_key)]));
+    }
+    ensure_unique_names(&names)?;
+
+    let sub_structs = define_sub_structs(&sub_structs, options)?;
+    let enum_name = format_ident!("{}", enum_name);
+    let tag_attribute = tag_attribute.into_iter();
+    let extra_attributes = attribute_contents(&options.structs.extra_attributes)?;
+    Ok(quote! {
+        #[allow(non_camel_case_types)]
+        #(#derives)*
+        #(#tag_attribute)*
+        #(#[#extra_attributes])*
+        #vis enum #enum_name {
+            #(#variant_tokens,)*
+        }
+
+        #(#sub_structs)*
+    })
+}
+
+/// The options for the structs nested within a struct, taking
+/// [`StructOptions::nested_derived_traits`] and
+/// [`StructOptions::nested_extra_attributes`] into account.
+///
+/// [`StructOptions::nested_derived_traits`]: crate::options::StructOptions::nested_derived_traits
+/// [`StructOptions::nested_extra_attributes`]: crate::options::StructOptions::nested_extra_attributes
+fn nested_struct_options(options: &Options) -> Cow<'_, Options> {
+    let structs = &options.structs;
+    if structs.nested_derived_traits.is_none() && structs.nested_extra_attributes.is_none() {
+        return Cow::Borrowed(options);
+    }
+    Cow::Owned(Options {
+        structs: StructOptions {
+            derived_traits: structs
+                .nested_derived_traits
+                .clone()
+                .unwrap_or_else(|| structs.derived_traits.clone()),
+            extra_attributes: structs
+                .nested_extra_attributes
+                .clone()
+                .unwrap_or_else(|| structs.extra_attributes.clone()),
+            ..structs.clone()
+        },
+        ..options.clone()
+    })
+}
+
+/// The options for generating the values struct of an enum, which
+/// replace [`Options::structs`] and (optionally)
+/// [`Options::serde_support`].
+fn values_struct_options(options: &Options, vs_options: &ValuesStructOptions) -> Options {
+    Options {
+        structs: vs_options.struct_options.clone(),
+        serde_support: vs_options.serde_support.unwrap_or(options.serde_support),
+        ..options.clone()
+    }
+}
+
+/// Check that `derive` is one of the `derived_traits`, because the
+/// code generated for `option` needs it.
+fn require_derive(
+    derived_traits: &[Cow<'static, str>],
+    derive: &'static str,
+    option: &'static str,
+) -> Result<(), Error> {
+    match derived_traits
+        .iter()
+        .any(|name| name.rsplit("::").next() == Some(derive))
+    {
+        true => Ok(()),
+        false => Err(Error::RequiresDerive(option, derive)),
+    }
+}
+
+/// Traits which can't be derived for structs containing floats.
+const FLOAT_INCOMPATIBLE_DERIVES: &[&str] = &["Eq", "Ord", "Hash"];
+
+/// Strict and reserved keywords, which can only be used as field
+/// names in their raw form.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Keywords which can't be used as raw identifiers either.
+const NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "Self", "super", "_"];
+
+/// The [`StructOptions::derived_traits`] which can be derived for a
+/// struct with the given fields. Unsupported ones are an error,
+/// unless [`StructOptions::skip_unsupported_derives`] is set.
+///
+/// [`StructOptions::derived_traits`]: crate::options::StructOptions::derived_traits
+/// [`StructOptions::skip_unsupported_derives`]: crate::options::StructOptions::skip_unsupported_derives
+fn supported_derives<'a>(
+    data: &Struct,
+    struct_name: &str,
+    key_path: &str,
+    options: &'a Options,
+) -> Result<Vec<&'a str>, Error> {
+    let float_path = data
+        .0
+        .iter()
+        .find_map(|(key, value)| float_key_path(value, &child_path(key_path, key), options));
+    derives_without_floats(float_path, struct_name, options)
+}
+
+/// The traits to derive for a type, leaving out those which don't
+/// support the float at `float_path` (if there is one).
+fn derives_without_floats<'a>(
+    float_path: Option<String>,
+    struct_name: &str,
+    options: &'a Options,
+) -> Result<Vec<&'a str>, Error> {
+    let mut derives = vec![];
+    for derive in options.structs.derived_traits.iter() {
+        let trait_name = derive.rsplit("::").next().unwrap_or(derive);
+        match &float_path {
+            Some(float_path) if FLOAT_INCOMPATIBLE_DERIVES.contains(&trait_name) => {
+                if !options.structs.skip_unsupported_derives {
+                    return Err(Error::UnsupportedDerive {
+                        derive: derive.to_string(),
+                        struct_name: struct_name.to_owned(),
+                        key_path: float_path.clone(),
+                    });
+                }
+            }
+            _ => derives.push(derive.as_ref()),
+        }
+    }
+    Ok(derives)
+}
+
+/// The key path of the first float within a value, following the
+/// same rules as [`type_of_value`] (so type overrides are taken
+/// into account).
+fn float_key_path(value: &Value, key_path: &str, options: &Options) -> Option<String> {
+    let is_container = matches!(value, Value::Option(_) | Value::Array(..) | Value::Vec(_));
+    if let Some(type_name) = type_override(key_path, options).filter(|_| !is_container) {
+        let (outer, _) = split_type_override(type_name);
+        return matches!(outer, "f32" | "f64").then(|| key_path.to_owned());
+    }
+
+    if let Some(fields) = tuple_struct_fields(value, key_path, options) {
+        return fields
+            .0
+            .iter()
+            .find_map(|(key, value)| float_key_path(value, &child_path(key_path, key), options));
+    }
+
+    match value {
+        Value::F32(_) | Value::F64(_) => Some(key_path.to_owned()),
+        Value::Option(Some(value)) => float_key_path(value, key_path, options),
+        Value::Array(_, values) | Value::Vec(values)
+            if matches!(values.first(), Some(Value::Tagged(..))) =>
+        {
+            tagged_variants(values)
+                .0
+                .values()
+                .find_map(|value| float_key_path(value, key_path, options))
+        }
+        Value::Array(_, values) | Value::Vec(values) => values
+            .first()
+            .and_then(|value| float_key_path(value, key_path, options)),
+        Value::Tuple(values) => values.iter().enumerate().find_map(|(i, value)| {
+            float_key_path(value, &child_path(key_path, &i.to_string()), options)
+        }),
+        Value::Struct(mapping) if keep_as_map(key_path, options) => map_values(mapping)
+            .ok()?
+            .first()
+            .and_then(|value| float_key_path(value, &child_path(key_path, "*"), options)),
+        Value::Struct(mapping) => mapping
+            .0
+            .iter()
+            .find_map(|(key, value)| float_key_path(value, &child_path(key_path, key), options)),
+        Value::Tagged(_, fields) => {
+            let variant_path = child_path(key_path, value.tag().unwrap_or_default());
+            fields.0.iter().find_map(|(key, value)| {
+                float_key_path(value, &child_path(&variant_path, key), options)
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Checks that the fields of `expr` are present wherever they are
+/// present in `data`. (See [`StructOptions::validate_fn_name`].)
+///
+/// [`StructOptions::validate_fn_name`]: crate::options::StructOptions::validate_fn_name
+fn struct_checks(
+    data: &Struct,
+    expr: TokenStream,
+    key_path: &str,
+    options: &Options,
+) -> Vec<TokenStream> {
+    let tuple_struct = tuple_struct_name(key_path, options).is_some();
+    struct_fields(data, key_path, options)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, (key, value))| {
+            let field = match (tuple_struct, is_newtype(data, options)) {
+                (true, _) => Literal::usize_unsuffixed(i).into_token_stream(),
+                (false, true) => quote!(0),
+                (false, false) => {
+                    field_name(key, &child_path(key_path, key), options).into_token_stream()
+                }
+            };
+            value_checks(
+                value,
+                quote!(#expr.#field),
+                &child_path(key_path, key),
+                options,
+            )
+        })
+        .collect()
+}
+
+fn value_checks(
+    value: &Value,
+    expr: TokenStream,
+    key_path: &str,
+    options: &Options,
+) -> Vec<TokenStream> {
+    let is_container = matches!(value, Value::Option(_) | Value::Array(..) | Value::Vec(_));
+    if type_override(key_path, options).is_some() && !is_container {
+        return vec![];
+    }
+
+    if let Some(fields) = tuple_struct_fields(value, key_path, options) {
+        return struct_checks(&fields, expr, key_path, options);
+    }
+
+    match value {
+        Value::Struct(_) if keep_as_map(key_path, options) => vec![],
+        Value::Struct(fields) => struct_checks(fields, expr, key_path, options),
+        Value::Option(Some(value)) => {
+            let checks = value_checks(value, quote!(value), key_path, options);
+            let (_, string) = map_types(options);
+            let message = format!("Missing value at `{}`", key_path);
+            let binding = match checks.is_empty() {
+                true => quote!(_),
+                false => quote!(value),
+            };
+            vec![quote! {
+                match &#expr {
+                    Some(#binding) => {
+                        #(#checks)*
+                    }
+                    None => return Err(#string::from(#message)),
+                }
+            }]
+        }
+        Value::Tuple(values) => values
+            .iter()
+            .enumerate()
+            .flat_map(|(i, value)| {
+                let index = Literal::usize_unsuffixed(i);
+                value_checks(
+                    value,
+                    quote!(#expr.#index),
+                    &child_path(key_path, &i.to_string()),
+                    options,
+                )
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
 fn define_structs_for_value(
     data: &Value,
     root_struct_name: &str,
+    key_path: &str,
     options: &Options,
     dest: &mut Vec<TokenStream>,
 ) -> Result<(), Error> {
-    let derives = derive_attribute(
-        options.structs.derived_traits.as_ref(),
-        options.serde_support,
-        false,
-    );
-
     match data {
         Value::Option(Some(value)) => {
-            define_structs_for_value(value, root_struct_name, options, dest)
+            define_structs_for_value(value, root_struct_name, key_path, options, dest)
         }
         Value::Option(None) => Ok(()),
         Value::Tuple(values) => {
             for (i, value) in values.iter().enumerate() {
-                let struct_name = format!("{}__{}", root_struct_name, i);
-                define_structs_for_value(value, &struct_name, options, dest)?;
+                let struct_name = nested_struct_name(root_struct_name, None, Some(i), options);
+                let key_path = child_path(key_path, &i.to_string());
+                define_structs_for_value(value, &struct_name, &key_path, options, dest)?;
             }
             Ok(())
         }
-        Value::Array(_size, values) => match values.first() {
-            Some(value) => define_structs_for_value(value, root_struct_name, options, dest),
-            None => Ok(()),
-        },
-        Value::Vec(values) => match values.first() {
-            Some(value) => define_structs_for_value(value, root_struct_name, options, dest),
+        Value::Array(_, values) | Value::Vec(values) => match values.first() {
+            Some(Value::Tagged(..)) => {
+                let variants = tagged_variants(values);
+                dest.push(define_tagged_enum(
+                    &variants,
+                    root_struct_name,
+                    key_path,
+                    options,
+                )?);
+                Ok(())
+            }
+            Some(value) => {
+                define_structs_for_value(value, root_struct_name, key_path, options, dest)
+            }
             None => Ok(()),
         },
+        Value::Tagged(..) => define_structs_for_value(
+            &Value::Vec(vec![data.clone()]),
+            root_struct_name,
+            key_path,
+            options,
+            dest,
+        ),
         Value::Struct(fields) => {
             dest.push(define_structs_inner(
                 fields,
                 root_struct_name,
+                key_path,
                 options,
-                derives.as_ref(),
             )?);
             Ok(())
         }
@@ -192,89 +868,399 @@ fn define_structs_for_value(
     }
 }
 
+/// Where the values associated with enum variants come from.
+enum ValuesSource {
+    /// No values struct is generated.
+    None,
+
+    /// The values are embedded in a const slice.
+    Const,
+
+    /// The values are deserialized at runtime on first access,
+    /// using the given expressions (one per variant).
+    Lazy(Vec<TokenStream>),
+}
+
+/// A method which re-reads the values of enum variants from their
+/// files at runtime (see [`FilesOptions::load_fn_name`]).
+///
+/// [`FilesOptions::load_fn_name`]: crate::options::FilesOptions::load_fn_name
+struct LoadFn {
+    fn_name: TokenStream,
+    error_name: TokenStream,
+
+    /// The path of the file of each variant, and the function to
+    /// deserialize it with.
+    loaders: Vec<(String, TokenStream)>,
+}
+
+/// Each variant is paired with the key (or file name) it came from,
+/// and may have aliases (see [`EnumOptions::alias_key`]).
+#[allow(clippy::too_many_arguments)]
 fn define_enum_from_variants_and_values<'a, IK, IV, S>(
     variants: IK,
     values: IV,
-    use_values: bool,
+    aliases: &[Vec<String>],
+    values_source: ValuesSource,
+    load_fn: Option<LoadFn>,
     enum_name: &str,
     source_file_path: Option<&Path>,
     options: &Options,
     mut inherents: Vec<TokenStream>,
 ) -> Result<TokenStream, Error>
 where
-    IK: IntoIterator<Item = S>,
-    IK::IntoIter: Clone,
+    IK: IntoIterator<Item = (S, S)>,
     IV: IntoIterator<Item = &'a Value>,
     S: AsRef<str>,
 {
+    let strum_derives = match options.enums.strum_support {
+        true => options.enums.strum_derives.as_ref(),
+        false => &[],
+    };
+    let strum_derives_trait = |name: &str| strum_derives.iter().any(|derive| derive == name);
+    let impl_display = options.enums.impl_display && !strum_derives_trait("Display");
+    let impl_from_str = options.enums.impl_from_str && !strum_derives_trait("EnumString");
+    let impl_as_ref_str = options.enums.impl_as_ref_str && !strum_derives_trait("AsRefStr");
+    let impl_into_str = options.enums.impl_as_ref_str && !strum_derives_trait("IntoStaticStr");
+    let all_variants_const_name = options
+        .enums
+        .all_variants_const_name
+        .as_ref()
+        .filter(|_| !strum_derives_trait("EnumIter"));
+
+    let vis = visibility(options)?;
+    let core = core_crate(options);
     let derives = derive_attribute(
         options.enums.derived_traits.as_ref(),
         options.serde_support,
-        options.enums.impl_display,
+        impl_display,
     )
     .into_iter();
+    let strum_derives = strum_derives.iter().map(|derive| {
+        let derive = format_ident!("{}", derive.as_ref());
+        quote!(strum::#derive)
+    });
+    let strum_derives = (options.enums.strum_support && !options.enums.strum_derives.is_empty())
+        .then(|| quote!(#[derive(#(#strum_derives),*)]))
+        .into_iter();
     let enum_name = format_ident!("{}", enum_name);
-    let variants = variants.into_iter();
-    let enum_variants = variants.clone().map(|s| format_ident!("{}", s.as_ref()));
-
-    // Inherent impl block
+    let (variants, keys): (Vec<String>, Vec<String>) = variants
+        .into_iter()
+        .map(|(variant, key)| (variant.as_ref().to_owned(), key.as_ref().to_owned()))
+        .unzip();
+    let variants = variants.iter();
+    ensure_unique_names(variants.clone())?;
+    let aliases = (0..keys.len())
+        .map(|i| aliases.get(i).map(Vec::as_slice).unwrap_or_default())
+        .collect::<Vec<_>>();
+    let mut names = keys
+        .iter()
+        .chain(variants.clone())
+        .collect::<std::collections::HashSet<_>>();
+    for alias in aliases.iter().copied().flatten() {
+        if !names.insert(alias) {
+            return Err(Error::DuplicateName(alias.clone()));
+        }
+    }
+    let enum_variants =
+        variants
+            .clone()
+            .zip(&keys)
+            .zip(&aliases)
+            .map(|((variant, key), aliases)| {
+                let strum_serialize = (options.enums.strum_support
+                    && (variant != key || !aliases.is_empty()))
+                .then(|| quote!(#[strum(serialize = #key #(, serialize = #aliases)*)]))
+                .into_iter();
+                let variant = format_ident!("{}", variant);
+                quote!(#(#strum_serialize)* #variant)
+            });
+    let index = variant_index(variants.clone(), options);
+
+    // Inherent impl block
     if let (Some(source_file_path), Some(const_name)) =
         (source_file_path, options.source_path_const_name.as_ref())
     {
         let source_file_path = source_file_path.display().to_string();
         let source_path_const_name = format_ident!("{}", const_name);
         inherents.push(quote! {
-            pub const #source_path_const_name: &'static str = #source_file_path;
+            #vis const #source_path_const_name: &'static str = #source_file_path;
+        });
+    }
+    if options.enums.repr.is_some() {
+        inherents.push(quote! {
+            #vis const fn as_index(self) -> usize {
+                self as usize
+            }
+        });
+    }
+    #[cfg(feature = "phf")]
+    if let Some(const_name) = &options.enums.key_map_const_name {
+        require_derive(
+            &options.enums.derived_traits,
+            "Copy",
+            "EnumOptions::key_map_const_name",
+        )?;
+        let const_name = format_ident!("{}", const_name.as_ref());
+        let (keys, enum_variants) = variant_names(variants.as_slice(), &keys, &aliases);
+        inherents.push(quote! {
+            #vis const #const_name: phf::Map<&'static str, Self> = phf::phf_map! {
+                #(#keys => Self::#enum_variants,)*
+            };
+
+            #vis fn from_key_str(key: &str) -> Option<Self> {
+                Self::#const_name.get(key).copied()
+            }
+        });
+    }
+
+    if options.enums.impl_index {
+        let count = variants.clone().count();
+        let indices = 0..count;
+        let enum_variants = variants.clone().map(|s| format_ident!("{}", s));
+        inherents.push(quote! {
+            #vis const COUNT: usize = #count;
+
+            #vis const fn index(self) -> usize {
+                #index
+            }
+
+            #vis const fn from_index(index: usize) -> Option<Self> {
+                match index {
+                    #(#indices => Some(Self::#enum_variants),)*
+                    _ => None,
+                }
+            }
+        });
+    }
+    if options.enums.impl_iter {
+        let body = match all_variants_const_name {
+            Some(const_name) => {
+                require_derive(
+                    &options.enums.derived_traits,
+                    "Copy",
+                    "EnumOptions::impl_iter",
+                )?;
+                let const_name = format_ident!("{}", const_name);
+                quote!(Self::#const_name.iter().copied())
+            }
+            None => {
+                let enum_variants = variants.clone().map(|s| format_ident!("{}", s));
+                quote!(#core::iter::IntoIterator::into_iter([#(Self::#enum_variants,)*]))
+            }
+        };
+        inherents.push(quote! {
+            #vis fn iter() -> impl #core::iter::Iterator<Item = Self> {
+                #body
+            }
+        });
+    }
+    // The string for each variant, which matches its `Display`
+    // (see `EnumOptions::display_casing`).
+    let variant_names = match options.enums.display_casing {
+        Some(casing) => keys.iter().map(|key| apply_casing(casing, key)).collect(),
+        None => variants.clone().cloned().collect::<Vec<_>>(),
+    };
+    let variant_strs = |expr: TokenStream| {
+        let enum_variants = variants.clone().map(|s| format_ident!("{}", s));
+        let names = &variant_names;
+        quote! {
+            match #expr {
+                #(#enum_name::#enum_variants => #names,)*
+            }
+        }
+    };
+    if let Some(fn_name) = &options.enums.static_str_fn_name {
+        let fn_name = format_ident!("{}", fn_name.as_ref());
+        let body = variant_strs(quote!(self));
+        inherents.push(quote! {
+            #vis const fn #fn_name(self) -> &'static str {
+                #body
+            }
         });
     }
-    if let Some(const_name) = &options.enums.all_variants_const_name {
+    if let Some(const_name) = all_variants_const_name {
         let const_name = format_ident!("{}", const_name);
-        let enum_variants = variants.clone().map(|s| format_ident!("{}", s.as_ref()));
+        let enum_variants = variants.clone().map(|s| format_ident!("{}", s));
         inherents.push(quote! {
-            pub const #const_name: &'static [Self] = &[
+            #vis const #const_name: &'static [Self] = &[
                 #(Self::#enum_variants,)*
             ];
         });
     }
-    let new_struct_tokens = match (use_values, &options.enums.values_struct) {
-        (true, Some(vs_options)) => {
+    let new_struct_tokens = match (values_source, &options.enums.values_struct) {
+        (ValuesSource::None, _) | (_, None) => vec![],
+        (values_source, Some(vs_options)) => {
+            let value_options = values_struct_options(options, vs_options);
             let struct_name = vs_options
                 .struct_name
                 .as_ref()
                 .map(|s| s.to_string())
-                .unwrap_or_else(|| format!("{}__Value", enum_name));
-            let value_options = Options {
-                structs: vs_options.struct_options.clone(),
-                ..options.clone()
-            };
-            let (value_type, values, new_struct_tokens) =
-                establish_types_for_values(values.into_iter(), &struct_name, &value_options)?;
+                .unwrap_or_else(|| {
+                    nested_struct_name(&enum_name.to_string(), Some("Value"), None, &value_options)
+                });
+            let values = values
+                .into_iter()
+                .map(|value| select_fields(value, "", &vs_options.field_selection))
+                .collect::<Vec<_>>();
+            let (value_type, values, mut new_struct_tokens) =
+                establish_types_for_values(&values, &struct_name, &value_options)?;
+            let struct_count = new_struct_tokens.len();
+            if matches!(values_source, ValuesSource::Const)
+                && options.enums.all_values_const_name.is_some()
+            {
+                ensure_no_maps(&values, &value_options)?;
+            }
             let values = values
                 .iter()
-                .map(|value| define_value(value, &struct_name, None, None))
+                .map(|value| define_value(value, &struct_name, None, None, "", &value_options))
                 .collect::<Result<Vec<_>, _>>()?;
 
-            if let Some(const_name) = &options.enums.all_values_const_name {
+            let mut has_get_value_fn = false;
+            if let ValuesSource::Lazy(loaders) = values_source {
+                if let Some(get_value_fn_name) = &options.enums.get_value_fn_name {
+                    has_get_value_fn = true;
+                    let get_value_fn_name = format_ident!("{}", get_value_fn_name);
+                    let count = loaders.len();
+                    let cells = (0..count).map(|_| quote!(std::sync::OnceLock::new()));
+                    let enum_variants = variants.clone().map(|s| format_ident!("{}", s));
+                    inherents.push(quote! {
+                        #vis fn #get_value_fn_name(self) -> &'static #value_type {
+                            static VALUES: [std::sync::OnceLock<#value_type>; #count] = [
+                                #(#cells,)*
+                            ];
+                            VALUES[#index].get_or_init(|| match self {
+                                #(Self::#enum_variants => #loaders,)*
+                            })
+                        }
+                    });
+                }
+            } else if let Some(const_name) = &options.enums.all_values_const_name {
                 let const_name = format_ident!("{}", const_name);
-                inherents.push(quote! {
-                    pub const #const_name: &'static [#value_type] = &[
-                        #(#values,)*
-                    ];
+                let count = values.len();
+                let (values, shared_tokens) = dedup_values(values, options);
+                new_struct_tokens.extend(shared_tokens);
+                inherents.push(match options.enums.values_array {
+                    true => quote! {
+                        #vis const #const_name: [#value_type; #count] = [
+                            #(#values,)*
+                        ];
+                    },
+                    false => quote! {
+                        #vis const #const_name: &'static [#value_type] = &[
+                            #(#values,)*
+                        ];
+                    },
                 });
 
                 if let Some(get_value_fn_name) = &options.enums.get_value_fn_name {
+                    has_get_value_fn = true;
                     let get_value_fn_name = format_ident!("{}", get_value_fn_name);
+                    // Indexing into an array const would borrow a
+                    // temporary copy of it, so borrow the whole
+                    // array in a const first.
+                    let body = match (options.enums.static_values, options.enums.values_array) {
+                        (true, true) => quote! {
+                            static VALUES: [#value_type; #count] = #enum_name::#const_name;
+                            &VALUES[#index]
+                        },
+                        (true, false) => quote! {
+                            static VALUES: &[#value_type] = #enum_name::#const_name;
+                            &VALUES[#index]
+                        },
+                        (false, true) => quote! {
+                            const VALUES: &[#value_type; #count] = &#enum_name::#const_name;
+                            &VALUES[#index]
+                        },
+                        (false, false) => quote!(&Self::#const_name[#index]),
+                    };
+                    let constness = (!options.enums.static_values).then(|| quote!(const));
                     inherents.push(quote! {
-                        pub const fn #get_value_fn_name(self) -> &'static #value_type {
-                            &Self::#const_name[self as usize]
+                        #vis #constness fn #get_value_fn_name(self) -> &'static #value_type {
+                            #body
                         }
                     });
                 }
             }
 
+            if let Some(LoadFn {
+                fn_name,
+                error_name,
+                loaders,
+            }) = load_fn
+            {
+                let arms = loaders.iter().zip(variants.clone()).map(|((path, from_str), variant)| {
+                    let variant = format_ident!("{}", variant);
+                    quote! {
+                        Self::#variant => {
+                            let source = std::fs::read_to_string(#path).map_err(#error_name::Io)?;
+                            #from_str(&source).map_err(|error| #error_name::Deserialize(Box::new(error)))
+                        }
+                    }
+                });
+                inherents.push(quote! {
+                    #vis fn #fn_name(self) -> Result<#value_type, #error_name> {
+                        match self {
+                            #(#arms)*
+                        }
+                    }
+                });
+            }
+
+            if let (true, Some(get_value_fn_name), Some(owned_value_fn_name)) = (
+                has_get_value_fn,
+                &options.enums.get_value_fn_name,
+                &options.enums.owned_value_fn_name,
+            ) {
+                let option = "EnumOptions::owned_value_fn_name";
+                if struct_count > 0 {
+                    require_derive(&value_options.structs.derived_traits, "Clone", option)?;
+                }
+                if let (true, Some(nested)) = (
+                    struct_count > 1,
+                    &value_options.structs.nested_derived_traits,
+                ) {
+                    require_derive(nested, "Clone", option)?;
+                }
+                let get_value_fn_name = format_ident!("{}", get_value_fn_name);
+                let owned_value_fn_name = format_ident!("{}", owned_value_fn_name);
+                inherents.push(quote! {
+                    #vis fn #owned_value_fn_name(self) -> #value_type {
+                        self.#get_value_fn_name().clone()
+                    }
+                });
+            }
+
+            if let (true, Some(get_value_fn_name), Some(value_map_fn_name)) = (
+                has_get_value_fn,
+                &options.enums.get_value_fn_name,
+                &options.enums.value_map_fn_name,
+            ) {
+                if options.output.no_std {
+                    return Err(Error::UnsupportedInNoStd("EnumOptions::value_map_fn_name"));
+                }
+                for derive in ["Copy", "Eq", "Hash"] {
+                    require_derive(
+                        &options.enums.derived_traits,
+                        derive,
+                        "EnumOptions::value_map_fn_name",
+                    )?;
+                }
+                let get_value_fn_name = format_ident!("{}", get_value_fn_name);
+                let value_map_fn_name = format_ident!("{}", value_map_fn_name);
+                let enum_variants = variants.clone().map(|s| format_ident!("{}", s));
+                inherents.push(quote! {
+                    #vis fn #value_map_fn_name() -> std::collections::HashMap<Self, &'static #value_type> {
+                        [#(Self::#enum_variants,)*]
+                            .into_iter()
+                            .map(|variant| (variant, variant.#get_value_fn_name()))
+                            .collect()
+                    }
+                });
+            }
+
             new_struct_tokens
         }
-        _ => vec![],
     };
     let new_struct_tokens = new_struct_tokens.into_iter();
 
@@ -288,13 +1274,16 @@ where
         })
         .into_iter();
 
-    // Manually implemented traits
+    // Manually implemented traits (an empty enum has no default)
     let default_tokens = options
         .enums
         .impl_default
-        .then(|| {
-            let first_variant = format_ident!("{}", variants.clone().next().unwrap().as_ref());
+        .then(|| variants.clone().next())
+        .flatten()
+        .map(|first_variant| {
+            let first_variant = format_ident!("{}", first_variant);
             quote! {
+                #[allow(clippy::derivable_impls)]
                 impl Default for #enum_name {
                     fn default() -> Self {
                         Self::#first_variant
@@ -304,32 +1293,61 @@ where
         })
         .into_iter();
 
-    let display_tokens = options
+    let try_from_index_tokens = options
         .enums
-        .impl_display
+        .impl_try_from_index
         .then(|| {
+            let indices = 0..variants.clone().count();
+            let enum_variants = variants.clone().map(|s| format_ident!("{}", s));
             quote! {
-                impl std::fmt::Display for #enum_name {
-                    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                        <Self as std::fmt::Debug>::fmt(self, f)
+                impl #core::convert::TryFrom<usize> for #enum_name {
+                    type Error = usize;
+
+                    fn try_from(index: usize) -> #core::result::Result<Self, Self::Error> {
+                        match index {
+                            #(#indices => Ok(Self::#enum_variants),)*
+                            _ => Err(index),
+                        }
+                    }
+                }
+            }
+        })
+        .into_iter();
+    let display_tokens = impl_display
+        .then(|| {
+            let body = match options.enums.display_casing {
+                Some(_) => {
+                    let body = variant_strs(quote!(self));
+                    quote!(f.write_str(#body))
+                }
+                None => quote!(<Self as #core::fmt::Debug>::fmt(self, f)),
+            };
+            quote! {
+                impl #core::fmt::Display for #enum_name {
+                    fn fmt(&self, f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                        #body
                     }
                 }
             }
         })
         .into_iter();
 
-    let from_str_tokens = options
-        .enums
-        .impl_from_str
+    let from_str_names = match impl_from_str {
+        true => from_str_names(variants.as_slice(), &keys, &aliases, options)?,
+        false => vec![],
+    };
+    let from_str_tokens = impl_from_str
         .then(|| {
-            let enum_variants = variants.clone().map(|s| format_ident!("{}", s.as_ref()));
-            let enum_strings = variants.map(|s| s.as_ref().to_string());
+            let (enum_strings, enum_variants): (Vec<_>, Vec<_>) = from_str_names
+                .iter()
+                .map(|(name, variant)| (name, format_ident!("{}", variant)))
+                .unzip();
 
             quote! {
-                impl std::str::FromStr for #enum_name {
+                impl #core::str::FromStr for #enum_name {
                     type Err = ();
 
-                    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+                    fn from_str(s: &str) -> #core::result::Result<Self, Self::Err> {
                         Ok(match s {
                             #(#enum_strings => Self::#enum_variants,)*
                             _ => return Err(())
@@ -340,15 +1358,59 @@ where
         })
         .into_iter();
 
+    let as_ref_str_tokens = impl_as_ref_str
+        .then(|| {
+            let body = variant_strs(quote!(*self));
+            quote! {
+                impl #core::convert::AsRef<str> for #enum_name {
+                    fn as_ref(&self) -> &str {
+                        #body
+                    }
+                }
+            }
+        })
+        .into_iter();
+    let into_str_tokens = impl_into_str
+        .then(|| {
+            let body = variant_strs(quote!(value));
+            quote! {
+                impl #core::convert::From<#enum_name> for &'static str {
+                    fn from(value: #enum_name) -> Self {
+                        #body
+                    }
+                }
+            }
+        })
+        .into_iter();
+
+    let repr = options.enums.repr.map(|repr| {
+        let repr = match repr {
+            EnumRepr::U8 => quote!(u8),
+            EnumRepr::U16 => quote!(u16),
+            EnumRepr::U32 => quote!(u32),
+            EnumRepr::U64 => quote!(u64),
+            EnumRepr::USize => quote!(usize),
+        };
+        quote!(#[repr(#repr)])
+    });
+    let repr = repr.into_iter();
+    let extra_attributes = attribute_contents(&options.enums.extra_attributes)?;
+
     let tokens = quote! {
         #(#derives)*
-        pub enum #enum_name {
+        #(#strum_derives)*
+        #(#repr)*
+        #(#[#extra_attributes])*
+        #vis enum #enum_name {
             #(#enum_variants,)*
         }
         #(#inherent_tokens)*
         #(#default_tokens)*
+        #(#try_from_index_tokens)*
         #(#display_tokens)*
         #(#from_str_tokens)*
+        #(#as_ref_str_tokens)*
+        #(#into_str_tokens)*
         #(#new_struct_tokens)*
     };
     Ok(tokens)
@@ -358,8 +1420,8 @@ where
 ///
 /// While you can manually create a `Map`, the intended way to
 /// use this crate is to either use the functions in the root of
-/// this crate, or use the `parsing` module to read `Map`s
-/// from markup files.
+/// this crate, or use the `parsing` module to read values from
+/// markup files (and convert them with `Map::from`).
 ///
 /// # Examples
 ///
@@ -389,15 +1451,368 @@ pub fn define_enum_from_keys(
     source_file_path: Option<&Path>,
     options: &Options,
 ) -> Result<TokenStream, Error> {
-    define_enum_from_variants_and_values(
-        data.0.keys(),
+    validation::validate_options(options)?;
+    let casing = options.enums.variant_casing.unwrap_or(Casing::Preserve);
+    let data = sort_variants(data, casing, &options.enums.sort_variants_by)?;
+    let (data, aliases) = &take_aliases(data, options)?;
+    let (variants, inherents, integer_key_tokens) = match &options.enums.integer_keys {
+        Some(integer_keys) => integer_key_items(data, enum_name, integer_keys, options)?,
+        None => (
+            data.0
+                .keys()
+                .map(|key| apply_casing(casing, key))
+                .collect::<Vec<_>>(),
+            vec![],
+            quote!(),
+        ),
+    };
+
+    if !options.enums.nested || data.0.is_empty() {
+        let tokens = define_enum_from_variants_and_values(
+            variants.iter().zip(data.0.keys()),
+            data.0.values(),
+            aliases,
+            ValuesSource::Const,
+            None,
+            enum_name,
+            source_file_path,
+            options,
+            inherents,
+        )?;
+        return Ok(quote! {
+            #tokens
+            #integer_key_tokens
+        });
+    }
+
+    let vis = visibility(options)?;
+    let inner_options = Options {
+        enums: EnumOptions {
+            nested: false,
+            integer_keys: None,
+            ..options.enums.clone()
+        },
+        ..options.clone()
+    };
+    let inner_names = variants
+        .iter()
+        .map(|variant| nested_struct_name(enum_name, Some(variant), None, options))
+        .collect::<Vec<_>>();
+    ensure_unique_names(inner_names.iter().map(String::as_str).chain([enum_name]))?;
+
+    // The outer enum has a const for each variant, listing the
+    // variants of its inner enum.
+    let index_names = variants
+        .iter()
+        .map(|variant| apply_casing(Casing::Snake, variant).to_uppercase())
+        .collect::<Vec<_>>();
+    let outer_const_names = [
+        &options.enums.all_variants_const_name,
+        #[cfg(feature = "phf")]
+        &options.enums.key_map_const_name,
+    ];
+    ensure_unique_names(
+        index_names
+            .iter()
+            .map(String::as_str)
+            .chain(outer_const_names.into_iter().flatten().map(|name| &**name)),
+    )?;
+
+    let mut inners = vec![];
+    for (key, value) in data.0.iter() {
+        let Value::Struct(items) = value else {
+            return Err(Error::ExpectedStruct(value.type_name()).under_key(key));
+        };
+        let inner = sort_variants(
+            &items.clone().into(),
+            casing,
+            &options.enums.sort_variants_by,
+        )
+        .and_then(|items| take_aliases(items, options))
+        .map_err(|e| e.under_key(key))?;
+        inners.push(inner);
+    }
+    let inner_variants = inners
+        .iter()
+        .map(|(items, _)| {
+            items
+                .0
+                .keys()
+                .map(|key| apply_casing(casing, key))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut inherents = inherents;
+    for ((index_name, inner_name), inner_variants) in
+        index_names.iter().zip(&inner_names).zip(&inner_variants)
+    {
+        let index_name = format_ident!("{}", index_name);
+        let inner_name = format_ident!("{}", inner_name);
+        let inner_variants = inner_variants
+            .iter()
+            .map(|variant| format_ident!("{}", variant));
+        inherents.push(quote! {
+            #vis const #index_name: &'static [#inner_name] = &[#(#inner_name::#inner_variants,)*];
+        });
+    }
+    let outer_tokens = define_enum_from_variants_and_values(
+        variants.iter().zip(data.0.keys()),
         data.0.values(),
-        true,
+        aliases,
+        ValuesSource::None,
+        None,
         enum_name,
         source_file_path,
         options,
-        vec![],
-    )
+        inherents,
+    )?;
+
+    let outer_name = format_ident!("{}", enum_name);
+    let mut inner_tokens = vec![];
+    for (((key, (items, item_aliases)), inner_variants), (variant, inner_name)) in data
+        .0
+        .keys()
+        .zip(&inners)
+        .zip(&inner_variants)
+        .zip(variants.iter().zip(&inner_names))
+    {
+        let variant = format_ident!("{}", variant);
+        inner_tokens.push(
+            define_enum_from_variants_and_values(
+                inner_variants.iter().zip(items.0.keys()),
+                items.0.values(),
+                item_aliases,
+                ValuesSource::Const,
+                None,
+                inner_name,
+                None,
+                &inner_options,
+                vec![quote! {
+                    #vis const PARENT: #outer_name = #outer_name::#variant;
+                }],
+            )
+            .map_err(|e| e.under_key(key))?,
+        );
+    }
+
+    Ok(quote! {
+        #outer_tokens
+        #integer_key_tokens
+        #(#inner_tokens)*
+    })
+}
+
+/// The variant names for an enum with integer keys (see
+/// [`EnumOptions::integer_keys`]), along with its method returning
+/// the key, and its `TryFrom` impl.
+fn integer_key_items(
+    data: &Map,
+    enum_name: &str,
+    integer_keys: &IntegerKeyOptions,
+    options: &Options,
+) -> Result<(Vec<String>, Vec<TokenStream>, TokenStream), Error> {
+    let vis = visibility(options)?;
+    let core = core_crate(options);
+    let (key_type, bits) = flags_repr(integer_keys.key_type);
+    let max = match bits {
+        128 => u128::MAX,
+        bits => (1 << bits) - 1,
+    };
+    let codes = data
+        .0
+        .keys()
+        .map(|key| match key.parse::<u128>() {
+            Ok(code) if code <= max => Ok(code),
+            _ => Err(Error::InvalidIntegerKey(key.clone(), bits).under_key(key)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let variants = codes
+        .iter()
+        .map(|code| format!("{}{}", integer_keys.variant_prefix, code))
+        .collect::<Vec<_>>();
+
+    let enum_name = format_ident!("{}", enum_name);
+    let code_fn_name = format_ident!("{}", integer_keys.code_fn_name.as_ref());
+    let variant_idents = variants
+        .iter()
+        .map(|variant| format_ident!("{}", variant))
+        .collect::<Vec<_>>();
+    let literals = codes
+        .iter()
+        .map(|&code| Literal::u128_unsuffixed(code))
+        .collect::<Vec<_>>();
+    let code_fn = quote! {
+        #vis const fn #code_fn_name(self) -> #key_type {
+            match self {
+                #(Self::#variant_idents => #literals,)*
+            }
+        }
+    };
+    let try_from = quote! {
+        impl #core::convert::TryFrom<#key_type> for #enum_name {
+            type Error = #key_type;
+
+            fn try_from(code: #key_type) -> Result<Self, Self::Error> {
+                match code {
+                    #(#literals => Ok(Self::#variant_idents),)*
+                    _ => Err(code),
+                }
+            }
+        }
+    };
+    Ok((variants, vec![code_fn], try_from))
+}
+
+/// Remove the [`EnumOptions::alias_key`] field from the value of
+/// each entry of a map, returning the aliases of each entry.
+fn take_aliases(mut data: Map, options: &Options) -> Result<(Map, Vec<Vec<String>>), Error> {
+    let Some(alias_key) = &options.enums.alias_key else {
+        return Ok((data, vec![]));
+    };
+
+    let mut aliases = vec![];
+    for (key, value) in &mut data.0 {
+        let alias = match value {
+            Value::Struct(fields) => fields.0.shift_remove(alias_key.as_ref()),
+            _ => None,
+        };
+        let items = match alias {
+            None => vec![],
+            Some(Value::String(alias)) => vec![Value::String(alias)],
+            Some(Value::Vec(items) | Value::Array(_, items) | Value::Tuple(items)) => items,
+            Some(other) => vec![other],
+        };
+        let entry_aliases = items
+            .into_iter()
+            .map(|item| match item {
+                Value::String(alias) => Ok(alias),
+                other => Err(Error::ExpectedString(other.type_name())
+                    .under_key(alias_key)
+                    .under_key(key)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        aliases.push(entry_aliases);
+    }
+    Ok((data, aliases))
+}
+
+/// Pair each variant with its name (or key), and each of its
+/// aliases - all of the strings which should parse to it.
+#[cfg(feature = "phf")]
+fn variant_names<'a>(
+    variants: &[String],
+    names: &'a [String],
+    aliases: &[&'a [String]],
+) -> (Vec<&'a String>, Vec<Ident>) {
+    variants
+        .iter()
+        .zip(names)
+        .zip(aliases)
+        .flat_map(|((variant, name), aliases)| {
+            let variant = format_ident!("{}", variant);
+            std::iter::once(name)
+                .chain(aliases.iter())
+                .map(move |name| (name, variant.clone()))
+        })
+        .unzip()
+}
+
+/// The strings `FromStr` accepts, with the variant for each,
+/// following [`EnumOptions::from_str_casing`].
+///
+/// It is an error if the same string would be accepted for
+/// different variants.
+fn from_str_names<'a>(
+    variants: &'a [String],
+    keys: &[String],
+    aliases: &[&[String]],
+    options: &Options,
+) -> Result<Vec<(String, &'a String)>, Error> {
+    let casings = &options.enums.from_str_casing;
+    let mut names = IndexMap::new();
+    for ((variant, key), aliases) in variants.iter().zip(keys).zip(aliases) {
+        let spellings = match casings.is_empty() {
+            true => vec![variant.clone()],
+            false => casings
+                .iter()
+                .map(|&casing| apply_casing(casing, key))
+                .collect(),
+        };
+        for name in spellings.into_iter().chain(aliases.iter().cloned()) {
+            match names.get(&name) {
+                Some(&other) if other != variant => return Err(Error::DuplicateName(name)),
+                _ => names.insert(name, variant),
+            };
+        }
+    }
+    Ok(names.into_iter().collect())
+}
+
+/// Reorder the entries of a map according to
+/// [`EnumOptions::sort_variants_by`].
+fn sort_variants(data: &Map, casing: Casing, sort: &VariantSort) -> Result<Map, Error> {
+    let mut entries = data.0.iter().collect::<Vec<_>>();
+    match sort {
+        VariantSort::SourceOrder => return Ok(data.clone()),
+        VariantSort::Alphabetical => {
+            entries.sort_by_cached_key(|(key, _)| apply_casing(casing, key));
+        }
+        VariantSort::ByValueKey(key_path) => {
+            let sort_values = entries
+                .iter()
+                .map(|(key, value)| {
+                    (*value)
+                        .clone()
+                        .into_key_path(key_path)
+                        .map_err(|error| error.under_key(key))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let order = |a: &Value, b: &Value| {
+                let number = |value: &Value| {
+                    Some(match *value {
+                        Value::I8(x) => x as f64,
+                        Value::I16(x) => x as f64,
+                        Value::I32(x) => x as f64,
+                        Value::I64(x) => x as f64,
+                        Value::I128(x) => x as f64,
+                        Value::ISize(x) => x as f64,
+                        Value::U8(x) => x as f64,
+                        Value::U16(x) => x as f64,
+                        Value::U32(x) => x as f64,
+                        Value::U64(x) => x as f64,
+                        Value::U128(x) => x as f64,
+                        Value::USize(x) => x as f64,
+                        Value::F32(x) => x as f64,
+                        Value::F64(x) => x,
+                        _ => return None,
+                    })
+                };
+                match (a, b) {
+                    (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+                    (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+                    (a, b) => number(a)?.partial_cmp(&number(b)?),
+                }
+            };
+
+            let mut indices = (0..entries.len()).collect::<Vec<_>>();
+            let mut incomparable = false;
+            indices.sort_by(|&a, &b| {
+                order(&sort_values[a], &sort_values[b]).unwrap_or_else(|| {
+                    incomparable = true;
+                    std::cmp::Ordering::Equal
+                })
+            });
+            if incomparable {
+                return Err(Error::IncomparableSortValues(key_path.to_string()));
+            }
+            entries = indices.into_iter().map(|i| entries[i]).collect();
+        }
+    }
+    Ok(Map(entries
+        .into_iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()))
 }
 
 /// Define a set of Rust structs based on the values of the
@@ -405,8 +1820,8 @@ pub fn define_enum_from_keys(
 ///
 /// While you can manually create a `Map`, the intended way to
 /// use this crate is to either use the functions in the root of
-/// this crate, or use the `parsing` module to read `Map`s
-/// from markup files.
+/// this crate, or use the `parsing` module to read values from
+/// markup files (and convert them with `Map::from`).
 ///
 /// # Examples
 ///
@@ -438,21 +1853,85 @@ pub fn define_structs_from_values(
     struct_name: &str,
     options: &Options,
 ) -> Result<TokenStream, Error> {
+    validation::validate_options(options)?;
+    define_structs_from_items(data.0.values(), struct_name, options)
+}
+
+/// Define a set of Rust structs based on the items of the given
+/// sequence.
+///
+/// This is useful for files which contain a sequence at the top
+/// level, rather than a map. If
+/// [`StructOptions::struct_data_const_name`] is set, the items are
+/// stored in a const slice.
+///
+/// # Examples
+///
+/// ```
+/// # use edres_core::{codegen, Options, StructOptions, value::*};
+/// # use quote::quote;
+/// let tokens = codegen::define_structs_from_sequence(
+///     &[
+///         Value::Struct(Struct::from_pairs([("id", Value::I32(1))])),
+///         Value::Struct(Struct::from_pairs([("id", Value::I32(2))])),
+///     ],
+///     "Item",
+///     &Options {
+///         structs: StructOptions {
+///             struct_data_const_name: Some("DATA".into()),
+///             ..StructOptions::minimal()
+///         },
+///         ..Options::minimal()
+///     },
+/// ).unwrap();
+///
+/// assert_eq!(tokens.to_string(), quote!(
+///     #[allow(non_camel_case_types)]
+///     pub struct Item {
+///         pub id: i32,
+///     }
+///
+///     pub const DATA: &[Item] = &[
+///         Item { id: 1i32, },
+///         Item { id: 2i32, },
+///     ];
+/// ).to_string());
+/// ```
+///
+/// [`StructOptions::struct_data_const_name`]: crate::options::StructOptions::struct_data_const_name
+pub fn define_structs_from_sequence(
+    data: &[Value],
+    struct_name: &str,
+    options: &Options,
+) -> Result<TokenStream, Error> {
+    validation::validate_options(options)?;
+    define_structs_from_items(data, struct_name, options)
+}
+
+fn define_structs_from_items<'a, I: IntoIterator<Item = &'a Value>>(
+    items: I,
+    struct_name: &str,
+    options: &Options,
+) -> Result<TokenStream, Error> {
+    let vis = visibility(options)?;
     let (value_type, values, new_struct_tokens) =
-        establish_types_for_values(data.0.values(), struct_name, options)?;
+        establish_types_for_values(items, struct_name, options)?;
 
     let mut const_tokens = None;
     if let Some(const_name) = &options.structs.struct_data_const_name {
+        ensure_no_maps(&values, options)?;
         let const_name = format_ident!("{}", const_name);
         let values = values
             .iter()
-            .map(|value| define_value(value, struct_name, None, None))
+            .map(|value| define_value(value, struct_name, None, None, "", options))
             .collect::<Result<Vec<_>, _>>()?;
+        let (values, shared_tokens) = dedup_values(values, options);
 
         const_tokens = Some(quote! {
-            pub const #const_name: &[#value_type] = &[
+            #vis const #const_name: &[#value_type] = &[
                 #(#values,)*
             ];
+            #(#shared_tokens)*
         });
     }
     let const_tokens = const_tokens.into_iter();
@@ -463,37 +1942,154 @@ pub fn define_structs_from_values(
     })
 }
 
-// NOTE: This is a weird function.
-// It returns:
-// 1. the (unified) type of the values
-// 2. a list of the actual values as data
-// 3. any new structs defined
-fn establish_types_for_values<'a, I: IntoIterator<Item = &'a Value>>(
-    values: I,
-    struct_name: &str,
-    options: &Options,
-) -> Result<(TokenStream, Vec<Value>, Vec<TokenStream>), Error> {
-    let mut values = values.into_iter().map(Clone::clone).collect::<Vec<_>>();
-    if values.is_empty() {
-        return Err(Error::ExpectedValuesInMap);
-    }
-    parsing::unify_values(&mut values)?;
-    let first = &values[0];
-
-    let mut new_structs = vec![];
-    define_structs_for_value(first, struct_name, options, &mut new_structs)?;
+/// Get the Rust type of a single value, along with the
+/// definitions of any structs that type refers to.
+///
+/// Any structs are named based on `struct_name`, in the same way
+/// as [`define_structs`].
+///
+/// # Examples
+///
+/// ```
+/// # use edres_core::{codegen, Options, value::*};
+/// # use quote::quote;
+/// let (value_type, structs) = codegen::type_tokens(
+///     &Value::Vec(vec![
+///         Value::Struct(Struct::from_pairs([
+///             ("name", Value::String("one".into())),
+///         ])),
+///     ]),
+///     "Item",
+///     &Options::minimal(),
+/// ).unwrap();
+///
+/// assert_eq!(
+///     value_type.to_string(),
+///     quote!(std::borrow::Cow<'static, [Item]>).to_string(),
+/// );
+/// assert_eq!(structs.to_string(), quote!(
+///     #[allow(non_camel_case_types)]
+///     pub struct Item {
+///         pub name: std::borrow::Cow<'static, str>,
+///     }
+/// ).to_string());
+/// ```
+pub fn type_tokens(
+    value: &Value,
+    struct_name: &str,
+    options: &Options,
+) -> Result<(TokenStream, TokenStream), Error> {
+    let mut new_structs = vec![];
+    define_structs_for_value(value, struct_name, "", options, &mut new_structs)?;
+
+    let mut unused = vec![];
+    let value_type = type_of_value(value, struct_name, None, None, "", options, &mut unused)?;
+
+    Ok((value_type, quote!(#(#new_structs)*)))
+}
+
+/// Get a const expression for a single value.
+///
+/// The `struct_name` should match the one passed to
+/// [`type_tokens`], so that the expression refers to the same
+/// structs.
+///
+/// # Examples
+///
+/// ```
+/// # use edres_core::{codegen, Options, value::*};
+/// # use quote::quote;
+/// let tokens = codegen::value_tokens(
+///     &Value::Struct(Struct::from_pairs([
+///         ("number", Value::I32(10)),
+///     ])),
+///     "Item",
+///     &Options::minimal(),
+/// ).unwrap();
+///
+/// assert_eq!(tokens.to_string(), quote!(
+///     Item {
+///         number: 10i32,
+///     }
+/// ).to_string());
+/// ```
+pub fn value_tokens(
+    value: &Value,
+    struct_name: &str,
+    options: &Options,
+) -> Result<TokenStream, Error> {
+    define_value(value, struct_name, None, None, "", options)
+}
+
+// NOTE: This is a weird function.
+// It returns:
+// 1. the (unified) type of the values
+// 2. a list of the actual values as data
+// 3. any new structs defined
+fn establish_types_for_values<'a, I: IntoIterator<Item = &'a Value>>(
+    values: I,
+    struct_name: &str,
+    options: &Options,
+) -> Result<(TokenStream, Vec<Value>, Vec<TokenStream>), Error> {
+    let mut values = values.into_iter().map(Clone::clone).collect::<Vec<_>>();
+    if values.is_empty() {
+        return Err(Error::ExpectedValuesInMap);
+    }
+    parsing::unify_values(&mut values)?;
+    let mut first = values[0].clone();
+    for value in &values[1..] {
+        fill_empty_options(&mut first, value);
+    }
+
+    let mut new_structs = vec![];
+    if let Value::Tagged(..) = first {
+        // Every value is a variant of the same enum.
+        let variants = tagged_variants(&values);
+        new_structs.push(define_tagged_enum(&variants, struct_name, "", options)?);
+    } else {
+        define_structs_for_value(&first, struct_name, "", options, &mut new_structs)?;
+    }
 
     let mut unused = vec![];
-    let value_type = type_of_value(first, struct_name, None, None, &mut unused)?;
+    let value_type = type_of_value(&first, struct_name, None, None, "", options, &mut unused)?;
 
     Ok((value_type, values, new_structs))
 }
 
-// TODO: Doesn't fail if dir doesn't exist :/
-// TODO: Should be from manifest root
+/// Replace `None` values within `value` with the corresponding
+/// values from `other`, so that the types of optional fields can be
+/// inferred from any of a set of unified values.
+fn fill_empty_options(value: &mut Value, other: &Value) {
+    match (value, other) {
+        (value @ Value::Option(None), Value::Option(Some(_))) => *value = other.clone(),
+        (Value::Option(Some(value)), Value::Option(Some(other))) => {
+            fill_empty_options(value, other)
+        }
+        (Value::Struct(fields), Value::Struct(others))
+        | (Value::Tagged(_, fields), Value::Tagged(_, others)) => {
+            for (key, field) in fields.0.iter_mut() {
+                if let Some(other) = others.0.get(key) {
+                    fill_empty_options(field, other);
+                }
+            }
+        }
+        (Value::Tuple(items), Value::Tuple(others)) => {
+            for (item, other) in items.iter_mut().zip(others) {
+                fill_empty_options(item, other);
+            }
+        }
+        _ => (),
+    }
+}
+
 /// Define Rust enum based on the file names within the given
 /// directory.
 ///
+/// It is an error if the directory does not exist, or if it has
+/// no matching files (unless [`FilesOptions::allow_empty`] is set).
+///
+/// [`FilesOptions::allow_empty`]: crate::options::FilesOptions::allow_empty
+///
 /// # Examples
 ///
 /// ```no_run
@@ -520,33 +2116,69 @@ pub fn define_enum_from_filenames(
     enum_name: &str,
     options: &Options,
 ) -> Result<TokenStream, Error> {
-    use case::CaseExt;
-    use ignore::WalkBuilder;
-
-    let filepaths: Vec<String> = {
-        let walk = WalkBuilder::new(root)
-            .max_depth(Some(1))
-            .sort_by_file_name(std::ffi::OsStr::cmp)
-            .filter_entry(|entry| entry.file_type().map(|ft| ft.is_file()).unwrap_or(false))
-            .build();
-
-        walk.into_iter()
-            .skip(1)
-            .map(|entry| entry.map(|entry| entry.path().to_string_lossy().into_owned()))
-            .collect::<Result<Vec<_>, _>>()?
+    validation::validate_options(options)?;
+    let vis = visibility(options)?;
+    let casing = options
+        .enums
+        .variant_casing
+        .unwrap_or(Casing::SnakeToPascal);
+    if !root.is_dir() {
+        return Err(Error::DirectoryNotFound(root.to_owned()));
+    }
+    let files = indexed_files_in_dir(root, options)?;
+    let mut extra_attributes = vec![];
+    if files.is_empty() {
+        if !options.files.allow_empty {
+            return Err(Error::NoFilesMatched(root.to_owned()));
+        }
+        extra_attributes.push("non_exhaustive");
+    }
+    #[cfg(feature = "bevy")]
+    if options
+        .files
+        .bevy
+        .as_ref()
+        .is_some_and(|bevy| bevy.derive_reflect)
+    {
+        extra_attributes.push("derive(bevy::reflect::Reflect)");
+    }
+    let extended_options;
+    let options = match extra_attributes.is_empty() {
+        true => options,
+        false => {
+            let extra_attributes = options
+                .enums
+                .extra_attributes
+                .iter()
+                .cloned()
+                .chain(extra_attributes.into_iter().map(Cow::Borrowed))
+                .collect::<Vec<_>>();
+            extended_options = Options {
+                enums: EnumOptions {
+                    extra_attributes: extra_attributes.into(),
+                    ..options.enums.clone()
+                },
+                ..options.clone()
+            };
+            &extended_options
+        }
     };
+    let filepaths: Vec<String> = files
+        .iter()
+        .map(|(path, _)| path.to_string_lossy().into_owned())
+        .collect();
 
     let filenames: Vec<String> = filepaths
         .iter()
-        .map(|path| {
-            <String as AsRef<Path>>::as_ref(path)
-                .file_stem()
-                .map(|name| name.to_string_lossy().into_owned())
-                .ok_or_else(|| Error::UnsupportedFilePath(path.to_string()))
-                .map(|s| s.to_camel())
+        .zip(files)
+        .map(|(path, (_, variant))| match variant {
+            Some(variant) => Ok(variant),
+            None => variant_from_filename(path, casing),
         })
         .collect::<Result<Vec<_>, _>>()?;
+    let filenames = resolve_variant_collisions(filenames, &filepaths, options)?;
 
+    let index = variant_index(&filenames, options);
     let mut extra_inherents = vec![];
 
     if let Some(const_name) = &options.files.file_paths_const_name {
@@ -558,14 +2190,14 @@ pub fn define_enum_from_filenames(
             .map(|fn_name| {
                 let fn_name = format_ident!("{}", fn_name);
                 quote! {
-                    pub const fn #fn_name(self) -> &'static str { Self::#const_name[self as usize] }
+                    #vis const fn #fn_name(self) -> &'static str { Self::#const_name[#index] }
                 }
             })
             .into_iter();
 
         let filepaths = filepaths.iter();
         extra_inherents.push(quote! {
-            pub const #const_name: &'static [&'static str] = &[
+            #vis const #const_name: &'static [&'static str] = &[
                 #(#filepaths,)*
             ];
             #(#get_fn)*
@@ -573,16 +2205,21 @@ pub fn define_enum_from_filenames(
     }
     if let Some(const_name) = &options.files.file_bytes_const_name {
         let const_name = format_ident!("{}", const_name);
-        let get_fn = options.files.get_bytes_fn_name.as_ref().map(|fn_name| {
-            let fn_name = format_ident!("{}", fn_name);
-            quote! {
-                pub const fn #fn_name(self) -> &'static [u8] { Self::#const_name[self as usize] }
-            }
-        }).into_iter();
+        let get_fn = options
+            .files
+            .get_bytes_fn_name
+            .as_ref()
+            .map(|fn_name| {
+                let fn_name = format_ident!("{}", fn_name);
+                quote! {
+                    #vis const fn #fn_name(self) -> &'static [u8] { Self::#const_name[#index] }
+                }
+            })
+            .into_iter();
 
-        let filepaths = filepaths.iter();
+        let filepaths = filepaths.iter().map(|path| include_path(path, options));
         extra_inherents.push(quote! {
-            pub const #const_name: &'static [&'static [u8]] = &[
+            #vis const #const_name: &'static [&'static [u8]] = &[
                 #(include_bytes!(#filepaths),)*
             ];
             #(#get_fn)*
@@ -597,467 +2234,5652 @@ pub fn define_enum_from_filenames(
             .map(|fn_name| {
                 let fn_name = format_ident!("{}", fn_name);
                 quote! {
-                    pub const fn #fn_name(self) -> &'static str { Self::#const_name[self as usize] }
+                    #vis const fn #fn_name(self) -> &'static str { Self::#const_name[#index] }
                 }
             })
             .into_iter();
 
-        let filepaths = filepaths.iter();
+        let filepaths = filepaths.iter().map(|path| include_path(path, options));
         extra_inherents.push(quote! {
-            pub const #const_name: &'static [&'static str] = &[
+            #vis const #const_name: &'static [&'static str] = &[
                 #(include_str!(#filepaths),)*
             ];
             #(#get_fn)*
         });
     }
+    for (const_name, fn_name, parts) in [
+        (
+            &options.files.file_stems_const_name,
+            &options.files.get_stem_fn_name,
+            filepaths
+                .iter()
+                .map(|path| file_stem(path))
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        (
+            &options.files.file_extensions_const_name,
+            &options.files.get_extension_fn_name,
+            filepaths
+                .iter()
+                .map(|path| {
+                    Path::new(path)
+                        .extension()
+                        .map(|ext| ext.to_string_lossy().into_owned())
+                        .unwrap_or_default()
+                })
+                .collect(),
+        ),
+    ] {
+        let Some(const_name) = const_name else {
+            continue;
+        };
+        let const_name = format_ident!("{}", const_name);
+        let get_fn = fn_name
+            .as_ref()
+            .map(|fn_name| {
+                let fn_name = format_ident!("{}", fn_name);
+                quote! {
+                    #vis const fn #fn_name(self) -> &'static str { Self::#const_name[#index] }
+                }
+            })
+            .into_iter();
+
+        extra_inherents.push(quote! {
+            #vis const #const_name: &'static [&'static str] = &[
+                #(#parts,)*
+            ];
+            #(#get_fn)*
+        });
+    }
+
+    let mut extra_tokens = vec![];
+    if options.enums.impl_from_path {
+        let core = core_crate(options);
+        let enum_name = format_ident!("{}", enum_name);
+        extra_tokens.push(quote! {
+            impl<'a> #core::convert::TryFrom<&'a str> for #enum_name {
+                type Error = &'a str;
+
+                fn try_from(path: &'a str) -> Result<Self, Self::Error> {
+                    Self::from_path(path).ok_or(path)
+                }
+            }
+        });
+
+        let arms = filepaths.iter().zip(&filenames).map(|(path, variant)| {
+            let stripped = Path::new(path).with_extension("").display().to_string();
+            let variant = format_ident!("{}", variant);
+            quote!(#path | #stripped => Some(Self::#variant),)
+        });
+        extra_inherents.push(quote! {
+            #vis fn from_path(path: &str) -> Option<Self> {
+                match path {
+                    #(#arms)*
+                    _ => None,
+                }
+            }
+        });
+    }
+
+    #[cfg(feature = "bevy")]
+    if let Some(bevy) = &options.files.bevy {
+        let (inherent, tokens) = bevy_items(enum_name, &filepaths, &index, bevy, options)?;
+        extra_inherents.push(inherent);
+        extra_tokens.extend(tokens);
+    }
+
+    let lazy_values = options.files.lazy_values && options.enums.values_struct.is_some();
+    let load_values = options.files.load_fn_name.is_some() && options.enums.values_struct.is_some();
+    let values_serde_support = options
+        .enums
+        .values_struct
+        .as_ref()
+        .and_then(|vs_options| vs_options.serde_support)
+        .unwrap_or(options.serde_support);
+    for (enabled, option) in [
+        (lazy_values, "FilesOptions::lazy_values"),
+        (load_values, "FilesOptions::load_fn_name"),
+    ] {
+        if enabled && !matches!(values_serde_support.should_derive_ser_de(), Some((_, true))) {
+            return Err(Error::RequiresSerdeDeserialize(option));
+        }
+        if enabled && options.output.no_std {
+            return Err(Error::UnsupportedInNoStd(option));
+        }
+    }
 
     let mut values = vec![];
-    if options.enums.all_values_const_name.is_some() {
-        values = values_from_file_contents(root, None, options)?;
+    let values_options;
+    let mut options = options;
+    if options.enums.all_values_const_name.is_some() || lazy_values || load_values {
+        (values, values_options) = values_from_file_contents(root, None, options)?;
+        options = &values_options;
     }
 
-    let use_values = !values.is_empty();
+    let overrides = FormatOverrides::new(options)?;
+    let load_fn = match &options.files.load_fn_name {
+        Some(fn_name) if load_values => {
+            let error_name = format_ident!("{}LoadError", enum_name);
+            extra_tokens.push(quote! {
+                #[derive(Debug)]
+                #vis enum #error_name {
+                    Io(std::io::Error),
+                    Deserialize(Box<dyn std::error::Error + Send + Sync>),
+                }
 
-    define_enum_from_variants_and_values(
-        filenames.into_iter(),
+                impl std::fmt::Display for #error_name {
+                    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        match self {
+                            Self::Io(error) => write!(f, "Failed to read file: {}", error),
+                            Self::Deserialize(error) => write!(f, "Failed to deserialize file: {}", error),
+                        }
+                    }
+                }
+
+                impl std::error::Error for #error_name {
+                    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                        match self {
+                            Self::Io(error) => Some(error),
+                            Self::Deserialize(error) => Some(&**error),
+                        }
+                    }
+                }
+            });
+
+            let loaders = filepaths
+                .iter()
+                .map(|path| {
+                    let format = file_format(Path::new(path), None, &overrides)?;
+                    let from_str =
+                        runtime_from_str(format).ok_or(Error::DeserializeUnsupported(format))?;
+                    Ok((path.clone(), from_str))
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            Some(LoadFn {
+                fn_name: format_ident!("{}", fn_name).into_token_stream(),
+                error_name: error_name.into_token_stream(),
+                loaders,
+            })
+        }
+        _ => None,
+    };
+
+    let values_source = if values.is_empty() {
+        ValuesSource::None
+    } else if lazy_values {
+        let loaders = filepaths
+            .iter()
+            .map(|path| {
+                let format = file_format(Path::new(path), None, &overrides)?;
+                let from_str =
+                    runtime_from_str(format).ok_or(Error::LazyValuesUnsupported(format))?;
+                let include_path = include_path(path, options);
+                Ok(quote! {
+                    #from_str(include_str!(#include_path)).expect(concat!("Failed to deserialize ", #path))
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        ValuesSource::Lazy(loaders)
+    } else {
+        ValuesSource::Const
+    };
+
+    let stems = filepaths
+        .iter()
+        .map(|path| file_stem(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    let enum_tokens = define_enum_from_variants_and_values(
+        filenames.iter().zip(&stems),
         values.iter(),
-        use_values,
+        &[],
+        values_source,
+        load_fn,
         enum_name,
         Some(root),
         options,
         extra_inherents,
-    )
+    )?;
+    Ok(quote! {
+        #enum_tokens
+        #(#extra_tokens)*
+    })
 }
 
-fn values_from_file_contents(
+/// Define a Rust enum for each immediate subdirectory of the given
+/// directory (as with [`define_enum_from_filenames`]), along with an
+/// enum of the subdirectories themselves.
+///
+/// The enums for the subdirectories are named after them, and so
+/// are the variants of `kind_enum_name`. Its `paths` method returns
+/// the file paths in each subdirectory.
+///
+/// Hidden subdirectories are skipped. It is an error if the directory
+/// does not exist, if it has no subdirectories (unless
+/// [`FilesOptions::allow_empty`] is set), or if a subdirectory would
+/// have the same name as `kind_enum_name`.
+///
+/// [`FilesOptions::allow_empty`]: crate::options::FilesOptions::allow_empty
+///
+/// # Examples
+///
+/// ```no_run
+/// # use edres_core::{codegen, Options};
+/// # use quote::quote;
+/// let tokens = codegen::define_enums_from_subdirs(
+///     "./assets".as_ref(),
+///     "AssetKind",
+///     &Options::minimal(),
+/// ).unwrap();
+///
+/// // Assuming that ./assets contains items/sword.toml and
+/// // spells/fireball.toml:
+///
+/// assert_eq!(tokens.to_string(), quote!(
+///     pub enum Items {
+///         Sword,
+///     }
+///
+///     pub enum Spells {
+///         Fireball,
+///     }
+///
+///     pub enum AssetKind {
+///         Items,
+///         Spells,
+///     }
+///
+///     impl AssetKind {
+///         pub const fn paths(self) -> &'static [&'static str] {
+///             match self {
+///                 Self::Items => &["./assets/items/sword.toml"],
+///                 Self::Spells => &["./assets/spells/fireball.toml"],
+///             }
+///         }
+///     }
+/// ).to_string());
+/// ```
+pub fn define_enums_from_subdirs(
     root: &Path,
-    format: Option<Format>,
+    kind_enum_name: &str,
     options: &Options,
-) -> Result<Vec<Value>, Error> {
-    use ignore::WalkBuilder;
+) -> Result<TokenStream, Error> {
+    validation::validate_options(options)?;
+    let vis = visibility(options)?;
+    let casing = options
+        .enums
+        .variant_casing
+        .unwrap_or(Casing::SnakeToPascal);
+    if !root.is_dir() {
+        return Err(Error::DirectoryNotFound(root.to_owned()));
+    }
 
-    let walk = WalkBuilder::new(root)
-        .max_depth(Some(1))
-        .sort_by_file_name(std::ffi::OsStr::cmp)
-        .filter_entry(|entry| entry.file_type().map(|ft| ft.is_file()).unwrap_or(false))
-        .build();
+    let mut subdirs = vec![];
+    for entry in std::fs::read_dir(root)? {
+        let entry = entry?;
+        let dir_name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type()?.is_dir() && !dir_name.starts_with('.') {
+            subdirs.push((dir_name, entry.path()));
+        }
+    }
+    subdirs.sort();
+    if subdirs.is_empty() && !options.files.allow_empty {
+        return Err(Error::NoFilesMatched(root.to_owned()));
+    }
 
-    walk.into_iter()
-        .skip(1)
-        .map(|entry| parsing::parse_source_file_with_format(entry?.path(), format, &options.parse))
-        .collect::<Result<Vec<_>, _>>()
+    let names = subdirs
+        .iter()
+        .map(|(dir_name, _)| apply_casing(casing, dir_name))
+        .collect::<Vec<_>>();
+    ensure_unique_names(names.iter().map(String::as_str).chain([kind_enum_name]))?;
+
+    let mut enums = vec![];
+    let mut paths = vec![];
+    for (name, (_, subdir)) in names.iter().zip(&subdirs) {
+        enums.push(define_enum_from_filenames(subdir, name, options)?);
+        let subdir_paths = indexed_files_in_dir(subdir, options)?
+            .into_iter()
+            .map(|(path, _)| path.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        paths.push(subdir_paths);
+    }
+
+    let variants = names.iter().map(|name| format_ident!("{}", name));
+    let registry = match subdirs.is_empty() {
+        true => vec![],
+        false => vec![quote! {
+            #vis const fn paths(self) -> &'static [&'static str] {
+                match self {
+                    #(Self::#variants => &[#(#paths),*],)*
+                }
+            }
+        }],
+    };
+
+    let enum_options = Options {
+        enums: EnumOptions {
+            nested: false,
+            values_struct: None,
+            all_values_const_name: None,
+            get_value_fn_name: None,
+            owned_value_fn_name: None,
+            value_map_fn_name: None,
+            ..options.enums.clone()
+        },
+        source_path_const_name: None,
+        ..options.clone()
+    };
+    let kind_tokens = define_enum_from_variants_and_values(
+        names
+            .iter()
+            .zip(subdirs.iter().map(|(dir_name, _)| dir_name)),
+        std::iter::empty(),
+        &[],
+        ValuesSource::None,
+        None,
+        kind_enum_name,
+        None,
+        &enum_options,
+        registry,
+    )?;
+
+    Ok(quote! {
+        #(#enums)*
+        #kind_tokens
+    })
 }
 
-/// Define a set of Rust structs based on the contents of all
-/// files in the given directory.
+/// Define a Rust enum based on the top-level keys of every file in
+/// the given directory.
+///
+/// This is useful when several files share a set of keys (like
+/// per-locale or per-environment configs). The enum has a variant
+/// for each key found in any of the files, in the order they are
+/// first found. With [`FilesOptions::intersect_keys`], it only has
+/// variants for the keys found in every file.
+///
+/// Because each file can have a different value for a key, the
+/// enum has no values. Variants are named as for
+/// [`define_enum_from_keys`].
+///
+/// [`FilesOptions::intersect_keys`]: crate::options::FilesOptions::intersect_keys
 ///
 /// # Examples
 ///
 /// ```no_run
-/// # use edres_core::{codegen, Options, value::*};
+/// # use edres_core::{codegen, Options};
 /// # use quote::quote;
-/// let tokens = codegen::define_structs_from_file_contents(
+/// let tokens = codegen::define_enum_from_file_keys(
 ///     "./my_dir".as_ref(),
-///     "FileStruct",
-///     None,
+///     "Setting",
 ///     &Options::minimal(),
 /// ).unwrap();
 ///
-/// // Assuming that the toml files in ./my_dir look like:
+/// // Assuming that ./my_dir contains dev.toml and prod.toml:
 /// //
-/// //  field_a = 1
-/// //  field_b = 2
+/// //  # dev.toml
+/// //  debug = true
+/// //  port = 8080
+/// //
+/// //  # prod.toml
+/// //  port = 80
+/// //  workers = 8
 ///
 /// assert_eq!(tokens.to_string(), quote!(
-///     #[allow(non_camel_case_types)]
-///     pub enum FileStruct {
-///         pub field_a: i64,
-///         pub field_b: i64,
+///     pub enum Setting {
+///         debug,
+///         port,
+///         workers,
 ///     }
 /// ).to_string());
 /// ```
-pub fn define_structs_from_file_contents(
+pub fn define_enum_from_file_keys(
     root: &Path,
-    struct_name: &str,
-    format: Option<Format>,
+    enum_name: &str,
     options: &Options,
 ) -> Result<TokenStream, Error> {
-    let values = values_from_file_contents(root, format, options)?;
-    let (value_type, values, new_struct_tokens) =
-        establish_types_for_values(values.iter(), struct_name, options)?;
+    validation::validate_options(options)?;
+    let casing = options.enums.variant_casing.unwrap_or(Casing::Preserve);
 
-    let mut const_tokens = None;
-    if let Some(const_name) = &options.structs.struct_data_const_name {
-        let const_name = format_ident!("{}", const_name);
-        let values = values
-            .iter()
-            .map(|value| define_value(value, struct_name, None, None))
-            .collect::<Result<Vec<_>, _>>()?;
-
-        const_tokens = Some(quote! {
-            pub const #const_name: &[#value_type] = &[
-                #(#values,)*
-            ];
+    let overrides = FormatOverrides::new(options)?;
+    let mut keys: Option<IndexSet<String>> = None;
+    for path in files_in_dir(root, options)? {
+        let file_keys = parse_file(&path, None, &overrides, options)?
+            .0
+            .assume_struct()
+            .map_err(|error| error.in_file(&path))?
+            .0
+            .into_keys()
+            .collect::<IndexSet<_>>();
+        keys = Some(match keys {
+            None => file_keys,
+            Some(keys) if options.files.intersect_keys => {
+                keys.intersection(&file_keys).cloned().collect()
+            }
+            Some(keys) => keys.union(&file_keys).cloned().collect(),
         });
     }
-    let const_tokens = const_tokens.into_iter();
 
-    Ok(quote! {
-        #(#new_struct_tokens)*
-        #(#const_tokens)*
-    })
+    let data = Map(keys
+        .unwrap_or_default()
+        .into_iter()
+        .map(|key| (key, Value::Unit))
+        .collect());
+    let data = sort_variants(&data, casing, &options.enums.sort_variants_by)?;
+    let variants = data
+        .0
+        .keys()
+        .map(|key| apply_casing(casing, key))
+        .collect::<Vec<_>>();
+
+    define_enum_from_variants_and_values(
+        variants.iter().zip(data.0.keys()),
+        data.0.values(),
+        &[],
+        ValuesSource::None,
+        None,
+        enum_name,
+        Some(root),
+        options,
+        vec![],
+    )
 }
 
-fn derive_attribute<S: AsRef<str>, I: IntoIterator<Item = S>>(
-    trait_list: I,
-    serde_support: SerdeSupport,
-    require_debug: bool,
-) -> Option<TokenStream> {
-    fn format_derive(s: &str) -> TokenStream {
-        match s.split_once("::") {
-            Some((crate_name, trait_name)) => {
-                let crate_name = format_ident!("{}", crate_name);
-                let trait_name = format_ident!("{}", trait_name);
-                quote!(#crate_name :: #trait_name)
-            }
-            None => {
-                let tokens = format_ident!("{}", s);
-                quote!(#tokens)
-            }
+/// Define a Rust enum based on a set of named sources, as if they
+/// were the contents of files with those names.
+///
+/// This works like [`define_enum_from_filenames`], but without
+/// reading from the filesystem. Because there are no files, the
+/// [`FilesOptions`] are ignored.
+///
+/// [`FilesOptions`]: crate::options::FilesOptions
+///
+/// # Examples
+///
+/// ```
+/// # use edres_core::{codegen, Format, Options};
+/// # use quote::quote;
+/// let tokens = codegen::define_enum_from_sources(
+///     [
+///         ("file_a.json", r#"{ "number": 1 }"#, Format::Json),
+///         ("file_b.json", r#"{ "number": 2 }"#, Format::Json),
+///     ],
+///     "FileEnum",
+///     &Options::minimal(),
+/// ).unwrap();
+///
+/// assert_eq!(tokens.to_string(), quote!(
+///     pub enum FileEnum {
+///         FileA,
+///         FileB,
+///     }
+/// ).to_string());
+/// ```
+pub fn define_enum_from_sources<'a, I>(
+    sources: I,
+    enum_name: &str,
+    options: &Options,
+) -> Result<TokenStream, Error>
+where
+    I: IntoIterator<Item = (&'a str, &'a str, Format)>,
+{
+    validation::validate_options(options)?;
+    let casing = options
+        .enums
+        .variant_casing
+        .unwrap_or(Casing::SnakeToPascal);
+    let use_values = options.enums.all_values_const_name.is_some();
+
+    let mut variants = vec![];
+    let mut names = vec![];
+    let mut stems = vec![];
+    let mut values = vec![];
+    let mut directives = InlineDirectives::default();
+    for (name, source, format) in sources {
+        variants.push(variant_from_filename(name, casing)?);
+        names.push(name.to_owned());
+        stems.push(file_stem(name)?);
+        if use_values {
+            let mut parse = || {
+                let mut value = parsing::parse_source(source, format, &options.parse)?;
+                directives.extend(InlineDirectives::extract(&mut value)?);
+                Ok(value)
+            };
+            values.push(parse().map_err(|error: Error| error.in_file(Path::new(name)))?);
         }
     }
+    let options = &directives.apply(options);
 
-    let mut derives = vec![];
-    let mut deriving_debug = false;
-    for item in trait_list {
-        let item = item.as_ref();
-        if item == "Debug" {
-            deriving_debug = true;
-        }
+    let variants = resolve_variant_collisions(variants, &names, options)?;
 
-        derives.push(format_derive(item));
-    }
+    let values_source = match values.is_empty() {
+        true => ValuesSource::None,
+        false => ValuesSource::Const,
+    };
 
-    let (ser, de) = serde_support
-        .should_derive_ser_de()
-        .unwrap_or((false, false));
-    if ser {
-        derives.push(format_derive("serde::Serialize"));
-    }
-    if de {
-        derives.push(format_derive("serde::Deserialize"));
+    define_enum_from_variants_and_values(
+        variants.iter().zip(&stems),
+        values.iter(),
+        &[],
+        values_source,
+        None,
+        enum_name,
+        None,
+        options,
+        vec![],
+    )
+}
+
+/// Define a pair of Rust enums, for the locales and keys of a
+/// string table, based on a directory of per-locale files (like
+/// `en.yaml` and `de.yaml`).
+///
+/// Every file must contain the same keys, with string values.
+/// Nested maps are flattened, so the key `title` in the map `menu`
+/// becomes `menu.title`. Variants of both enums are named with
+/// [`EnumOptions::variant_casing`], or [`Casing::Pascal`] if it is
+/// not set.
+///
+/// The locale enum gets a const `STRINGS` table, and a method
+/// `get(self, key)` to look up the string for a key. Like other
+/// accessors, it follows [`EnumOptions::accessor_style`].
+///
+/// # Examples
+///
+/// ```no_run
+/// # use edres_core::{codegen, Options};
+/// # use quote::quote;
+/// /* Files in dir:
+/// en.yaml - greeting: Hello
+/// de.yaml - greeting: Hallo
+/// */
+///
+/// let tokens = codegen::define_string_table(
+///     "dir".as_ref(),
+///     "Lang",
+///     "StringKey",
+///     &Options::minimal(),
+/// ).unwrap();
+///
+/// assert_eq!(tokens.to_string(), quote!(
+///     pub enum Lang {
+///         De,
+///         En,
+///     }
+///
+///     impl Lang {
+///         pub const STRINGS: [[&'static str; 1usize]; 2usize] = [
+///             ["Hallo"],
+///             ["Hello"],
+///         ];
+///
+///         pub const fn get(self, key: StringKey) -> &'static str {
+///             Self::STRINGS[self as usize][key as usize]
+///         }
+///     }
+///
+///     pub enum StringKey {
+///         Greeting,
+///     }
+/// ).to_string());
+/// ```
+///
+/// [`EnumOptions::variant_casing`]: crate::options::EnumOptions::variant_casing
+/// [`EnumOptions::accessor_style`]: crate::options::EnumOptions::accessor_style
+pub fn define_string_table(
+    root: &Path,
+    lang_enum_name: &str,
+    key_enum_name: &str,
+    options: &Options,
+) -> Result<TokenStream, Error> {
+    validation::validate_options(options)?;
+    let casing = options.enums.variant_casing.unwrap_or(Casing::Pascal);
+
+    let overrides = FormatOverrides::new(options)?;
+    let mut locales = vec![];
+    for path in files_in_dir(root, options)? {
+        let (value, _) = parse_file(&path, None, &overrides, options)?;
+        let mut strings = IndexMap::new();
+        flatten_strings(&value, "", &mut strings).map_err(|error| error.in_file(&path))?;
+        let path = path.display().to_string();
+        locales.push((file_stem(&path)?, strings));
     }
 
-    if !deriving_debug && require_debug {
-        derives.push(format_derive("Debug"));
+    let keys = locales
+        .iter()
+        .flat_map(|(_, strings)| strings.keys())
+        .collect::<IndexSet<_>>();
+    let missing = locales
+        .iter()
+        .filter_map(|(locale, strings)| {
+            let missing = keys
+                .iter()
+                .filter(|key| !strings.contains_key(key.as_str()))
+                .map(|key| key.to_string())
+                .collect::<Vec<_>>();
+            (!missing.is_empty()).then(|| (locale.clone(), missing))
+        })
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        return Err(Error::MissingStringKeys(missing));
     }
 
-    (!derives.is_empty()).then(|| quote!(#[derive(#(#derives),*)]))
-}
+    let lang_variants = locales
+        .iter()
+        .map(|(locale, _)| apply_casing(casing, locale))
+        .collect::<Vec<_>>();
+    let key_variants = keys
+        .iter()
+        .map(|key| apply_casing(casing, key))
+        .collect::<Vec<_>>();
 
-fn type_of_value<'a>(
-    value: &'a Value,
-    struct_name: &str,
-    under_key: Option<&str>,
-    under_index: Option<usize>,
-    new_structs: &mut Vec<(String, &'a Struct)>,
-) -> Result<TokenStream, Error> {
-    Ok(match value {
-        Value::Unit => quote!(()),
-        Value::Bool(_) => quote!(bool),
-        Value::Char(_) => quote!(char),
-        Value::I8(_) => quote!(i8),
-        Value::I16(_) => quote!(i16),
-        Value::I32(_) => quote!(i32),
-        Value::I64(_) => quote!(i64),
-        Value::I128(_) => quote!(i128),
-        Value::ISize(_) => quote!(isize),
-        Value::U8(_) => quote!(u8),
-        Value::U16(_) => quote!(u16),
-        Value::U32(_) => quote!(u32),
-        Value::U64(_) => quote!(u64),
-        Value::U128(_) => quote!(u128),
-        Value::USize(_) => quote!(usize),
-        Value::F32(_) => quote!(f32),
-        Value::F64(_) => quote!(f64),
-        Value::String(_) => quote!(std::borrow::Cow<'static, str>),
-        Value::Option(x) => match x {
-            Some(value) => {
-                let inner_type = type_of_value(value, struct_name, under_key, None, new_structs)?;
-                quote!(Option<#inner_type>)
-            }
-            None => quote!(Option<()>),
-        },
-        Value::Array(len, values) => {
-            assert_eq!(values.len(), *len);
-            match values.len() {
-                0 => quote!([(); #len]),
-                _ => {
-                    let inner_type =
-                        type_of_value(&values[0], struct_name, under_key, None, new_structs)?;
-                    quote!([#inner_type; #len])
-                }
-            }
+    let vis = visibility(options)?;
+    let key_enum = format_ident!("{}", key_enum_name);
+    let key_count = keys.len();
+    let lang_count = locales.len();
+    let rows = locales.iter().map(|(_, strings)| {
+        let strings = keys.iter().map(|key| &strings[key.as_str()]);
+        quote!([#(#strings),*])
+    });
+    let lang_index = variant_index(&lang_variants, options);
+    let key_index = value_index(quote!(key), quote!(#key_enum), &key_variants, options);
+    let table = quote! {
+        #vis const STRINGS: [[&'static str; #key_count]; #lang_count] = [
+            #(#rows),*
+        ];
+
+        #vis const fn get(self, key: #key_enum) -> &'static str {
+            Self::STRINGS[#lang_index][#key_index]
         }
-        Value::Vec(values) => match values.len() {
-            0 => quote!(std::borrow::Cow<'static, [()]>),
-            _ => {
-                let inner_type =
-                    type_of_value(&values[0], struct_name, under_key, None, new_structs)?;
-                quote!(std::borrow::Cow<'static, [#inner_type]>)
-            }
+    };
+
+    let enum_options = Options {
+        enums: EnumOptions {
+            nested: false,
+            values_struct: None,
+            all_values_const_name: None,
+            get_value_fn_name: None,
+            owned_value_fn_name: None,
+            value_map_fn_name: None,
+            ..options.enums.clone()
         },
-        Value::Tuple(values) => {
-            let types_in_tuple = values
-                .iter()
-                .enumerate()
-                .map(|(i, v)| type_of_value(v, struct_name, under_key, Some(i), new_structs))
-                .collect::<Result<Vec<_>, Error>>()?;
-            quote!((#(#types_in_tuple),*))
-        }
-        Value::Struct(mapping) => {
-            let key = match under_key {
-                None => String::new(),
-                Some(k) => format!("__{}", k),
-            };
-            let index = match under_index {
-                None => String::new(),
-                Some(i) => format!("__{}", i),
-            };
-            let name = format!("{}{}{}", struct_name, key, index);
-            let ident = format_ident!("{}", name);
-            new_structs.push((name, mapping));
+        source_path_const_name: None,
+        ..options.clone()
+    };
+    let lang_tokens = define_enum_from_variants_and_values(
+        lang_variants
+            .iter()
+            .zip(locales.iter().map(|(locale, _)| locale)),
+        std::iter::empty(),
+        &[],
+        ValuesSource::None,
+        None,
+        lang_enum_name,
+        None,
+        &enum_options,
+        vec![table],
+    )?;
+    let key_tokens = define_enum_from_variants_and_values(
+        key_variants.iter().zip(keys.iter().copied()),
+        std::iter::empty(),
+        &[],
+        ValuesSource::None,
+        None,
+        key_enum_name,
+        None,
+        &enum_options,
+        vec![],
+    )?;
 
-            quote!(#ident)
-        }
+    Ok(quote! {
+        #lang_tokens
+        #key_tokens
     })
 }
 
-fn define_value(
+/// Collect the strings in a string table file, by their (dotted)
+/// key paths.
+fn flatten_strings(
     value: &Value,
-    struct_name: &str,
-    under_key: Option<&str>,
-    under_index: Option<usize>,
-) -> Result<TokenStream, Error> {
-    Ok(match value {
-        Value::Unit => quote!(()),
-        Value::Bool(x) => quote!(#x),
-        Value::Char(x) => quote!(#x),
-        Value::I8(x) => quote!(#x),
-        Value::I16(x) => quote!(#x),
-        Value::I32(x) => quote!(#x),
-        Value::I64(x) => quote!(#x),
-        Value::I128(x) => quote!(#x),
-        Value::ISize(x) => quote!(#x),
-        Value::U8(x) => quote!(#x),
-        Value::U16(x) => quote!(#x),
-        Value::U32(x) => quote!(#x),
-        Value::U64(x) => quote!(#x),
-        Value::U128(x) => quote!(#x),
-        Value::USize(x) => quote!(#x),
-        Value::F32(x) => quote!(#x),
-        Value::F64(x) => quote!(#x),
-        Value::String(x) => quote!(std::borrow::Cow::Borrowed(#x)),
-        Value::Option(x) => match x {
-            Some(x) => {
-                let value = define_value(x, struct_name, under_key, under_index)?;
-                quote!(Some(#value))
+    key_path: &str,
+    strings: &mut IndexMap<String, String>,
+) -> Result<(), Error> {
+    match value {
+        Value::Struct(Struct(fields)) => {
+            for (key, value) in fields {
+                let key_path = match key_path {
+                    "" => key.clone(),
+                    _ => format!("{}.{}", key_path, key),
+                };
+                flatten_strings(value, &key_path, strings).map_err(|error| error.under_key(key))?;
             }
-            None => quote!(None),
-        },
-        Value::Array(_, values) => {
-            let values = values
-                .iter()
-                .map(|value| define_value(value, struct_name, under_key, under_index))
-                .collect::<Result<Vec<_>, Error>>()?;
-            quote!([#(#values,)*])
-        }
-        Value::Vec(values) => {
-            let values = values
-                .iter()
-                .map(|value| define_value(value, struct_name, under_key, under_index))
-                .collect::<Result<Vec<_>, Error>>()?;
-            quote!(std::borrow::Cow::Borrowed(&[#(#values,)*]))
-        }
-        Value::Tuple(values) => {
-            let values = values
-                .iter()
-                .enumerate()
-                .map(|(i, value)| define_value(value, struct_name, under_key, Some(i)))
-                .collect::<Result<Vec<_>, Error>>()?;
-            quote!((#(#values),*))
+            Ok(())
         }
-        Value::Struct(fields) => {
-            let key = match under_key {
-                None => String::new(),
-                Some(k) => format!("__{}", k),
-            };
-            let index = match under_index {
-                None => String::new(),
-                Some(i) => format!("__{}", i),
-            };
-            let name = format!("{}{}{}", struct_name, key, index);
-            define_struct_value(fields, &name)?
+        Value::String(string) => {
+            strings.insert(key_path.to_owned(), string.clone());
+            Ok(())
         }
-    })
+        value => Err(Error::ExpectedString(value.type_name())),
+    }
 }
 
-fn define_struct_value(data: &Struct, struct_name: &str) -> Result<TokenStream, Error> {
-    let mut fields = vec![];
+/// Define a Rust bitflags struct based on a list of flag names.
+///
+/// `data` is either a sequence of flag names, or a map whose keys
+/// are flag names and whose values are `null` (or `true`, for
+/// formats like TOML without `null`). Each flag is assigned the
+/// next bit, in source order. In a map, a key whose value is a list
+/// of names (of earlier flags or groups) is a group instead,
+/// combining their bits.
+///
+/// Flags are named in `SCREAMING_SNAKE_CASE`. (See
+/// [`FlagsOptions`] for how they are represented.)
+///
+/// [`FlagsOptions`]: crate::options::FlagsOptions
+///
+/// # Examples
+/// ```
+/// # use edres_core::{codegen, parsing, Format, Options};
+/// # use quote::quote;
+/// let value = parsing::parse_source(
+///     "read: null\nwrite: null\nread_write: [read, write]",
+///     Format::Yaml,
+///     &Default::default(),
+/// ).unwrap();
+///
+/// let tokens = codegen::define_bitflags(
+///     &value,
+///     "Permissions",
+///     None,
+///     &Options::minimal(),
+/// ).unwrap();
+///
+/// assert_eq!(tokens.to_string(), quote!(
+///     pub struct Permissions(u32);
+///
+///     impl Permissions {
+///         pub const READ: Self = Self(1 << 0);
+///         pub const WRITE: Self = Self(1 << 1);
+///         pub const READ_WRITE: Self = Self(Self::READ.0 | Self::WRITE.0);
+///
+///         pub const fn empty() -> Self {
+///             Self(0)
+///         }
+///
+///         pub const fn all() -> Self {
+///             Self(3)
+///         }
+///
+///         pub const fn bits(self) -> u32 {
+///             self.0
+///         }
+///
+///         pub const fn from_bits_retain(bits: u32) -> Self {
+///             Self(bits)
+///         }
+///
+///         pub const fn contains(self, other: Self) -> bool {
+///             self.0 & other.0 == other.0
+///         }
+///
+///         pub const fn union(self, other: Self) -> Self {
+///             Self(self.0 | other.0)
+///         }
+///     }
+///
+///     impl std::ops::BitOr for Permissions {
+///         type Output = Self;
+///
+///         fn bitor(self, other: Self) -> Self {
+///             self.union(other)
+///         }
+///     }
+/// ).to_string());
+/// ```
+pub fn define_bitflags(
+    data: &Value,
+    flags_name: &str,
+    source_file_path: Option<&Path>,
+    options: &Options,
+) -> Result<TokenStream, Error> {
+    validation::validate_options(options)?;
+    let vis = visibility(options)?;
+    let (repr, width) = flags_repr(options.flags.repr);
+
+    let entries = flag_entries(data)?;
+    let names = entries
+        .iter()
+        .map(|(key, _)| flag_const_name(key))
+        .collect::<Vec<_>>();
+    ensure_unique_names(&names)?;
+
+    let bitflags_style = match options.flags.style {
+        FlagsStyle::Consts => false,
+        #[cfg(feature = "bitflags")]
+        FlagsStyle::Bitflags => true,
+    };
+    let mut flag_count = 0;
+    let mut all_bits = 0_u128;
+    let mut flags = vec![];
+    for (i, (key, group)) in entries.iter().enumerate() {
+        let name = format_ident!("{}", names[i]);
+        let bits = match group {
+            None => {
+                if flag_count >= width {
+                    return Err(Error::TooManyFlags(width));
+                }
+                all_bits |= 1 << flag_count;
+                let bit = Literal::usize_unsuffixed(flag_count);
+                flag_count += 1;
+                quote!(1 << #bit)
+            }
+            Some(members) if members.is_empty() => quote!(0),
+            Some(members) => {
+                let members = members
+                    .iter()
+                    .map(
+                        |member| match entries[..i].iter().position(|(key, _)| key == member) {
+                            Some(index) => Ok(format_ident!("{}", names[index])),
+                            None => Err(Error::UnknownFlag(member.clone()).under_key(key)),
+                        },
+                    )
+                    .collect::<Result<Vec<_>, _>>()?;
+                match bitflags_style {
+                    true => quote!(#(Self::#members.bits())|*),
+                    false => quote!(#(Self::#members.0)|*),
+                }
+            }
+        };
+        flags.push((name, bits));
+    }
+
+    let derives = derive_attribute(
+        options.flags.derived_traits.as_ref(),
+        options.serde_support,
+        false,
+    )
+    .into_iter();
+    let flags_name = format_ident!("{}", flags_name);
+
+    let mut inherents = vec![];
+    if let (Some(source_file_path), Some(const_name)) =
+        (source_file_path, options.source_path_const_name.as_ref())
+    {
+        let source_file_path = source_file_path.display().to_string();
+        let source_path_const_name = format_ident!("{}", const_name);
+        inherents.push(quote! {
+            #vis const #source_path_const_name: &'static str = #source_file_path;
+        });
+    }
+
+    if bitflags_style {
+        let flags = flags
+            .iter()
+            .map(|(name, bits)| quote!(const #name = #bits;));
+        let inherents = (!inherents.is_empty())
+            .then(|| {
+                quote! {
+                    impl #flags_name {
+                        #(#inherents)*
+                    }
+                }
+            })
+            .into_iter();
+        return Ok(quote! {
+            bitflags::bitflags! {
+                #(#derives)*
+                #vis struct #flags_name: #repr {
+                    #(#flags)*
+                }
+            }
+
+            #(#inherents)*
+        });
+    }
+
+    let flags = flags
+        .iter()
+        .map(|(name, bits)| quote!(#vis const #name: Self = Self(#bits);));
+    let all_bits = Literal::u128_unsuffixed(all_bits);
+    let core = core_crate(options);
+    Ok(quote! {
+        #(#derives)*
+        #vis struct #flags_name(#repr);
+
+        impl #flags_name {
+            #(#flags)*
+            #(#inherents)*
+
+            #vis const fn empty() -> Self {
+                Self(0)
+            }
+
+            #vis const fn all() -> Self {
+                Self(#all_bits)
+            }
+
+            #vis const fn bits(self) -> #repr {
+                self.0
+            }
+
+            #vis const fn from_bits_retain(bits: #repr) -> Self {
+                Self(bits)
+            }
+
+            #vis const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            #vis const fn union(self, other: Self) -> Self {
+                Self(self.0 | other.0)
+            }
+        }
+
+        impl #core::ops::BitOr for #flags_name {
+            type Output = Self;
+
+            fn bitor(self, other: Self) -> Self {
+                self.union(other)
+            }
+        }
+    })
+}
+
+/// The flags (with no members) and groups (with the names of
+/// their members) defined by the data for a bitflags struct.
+type FlagEntries = Vec<(String, Option<Vec<String>>)>;
+
+fn flag_entries(data: &Value) -> Result<FlagEntries, Error> {
+    fn names(values: &[Value]) -> Result<Vec<String>, Error> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| match value {
+                Value::String(name) => Ok(name.clone()),
+                value => Err(Error::InvalidFlag(value.type_name()).under_index(i)),
+            })
+            .collect()
+    }
+
+    match data {
+        Value::Vec(values) | Value::Array(_, values) | Value::Tuple(values) => Ok(names(values)?
+            .into_iter()
+            .map(|name| (name, None))
+            .collect()),
+        Value::Struct(Struct(map)) => map
+            .iter()
+            .map(|(key, value)| {
+                let group = match value {
+                    Value::Option(None) | Value::Bool(true) => None,
+                    Value::Vec(values) | Value::Array(_, values) | Value::Tuple(values) => {
+                        Some(names(values).map_err(|error| error.under_key(key))?)
+                    }
+                    value => {
+                        return Err(Error::InvalidFlag(value.type_name()).under_key(key));
+                    }
+                };
+                Ok((key.clone(), group))
+            })
+            .collect(),
+        value => Err(Error::InvalidFlag(value.type_name())),
+    }
+}
+
+/// The name of the const for a flag, like `READ_WRITE` for
+/// `readWrite`.
+fn flag_const_name(name: &str) -> String {
+    use case::CaseExt;
+
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_snake().to_uppercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// The integer type which stores the bits of a bitflags struct,
+/// and how many flags it can store.
+fn flags_repr(repr: UIntSize) -> (TokenStream, usize) {
+    match repr {
+        UIntSize::U8 => (quote!(u8), 8),
+        UIntSize::U16 => (quote!(u16), 16),
+        UIntSize::U32 => (quote!(u32), 32),
+        UIntSize::U64 => (quote!(u64), 64),
+        UIntSize::U128 => (quote!(u128), 128),
+        UIntSize::USize => (quote!(usize), 32),
+    }
+}
+
+/// An expression for the index of `self` among the given variants,
+/// according to [`EnumOptions::accessor_style`].
+fn variant_index<S, I>(variants: I, options: &Options) -> TokenStream
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = S>,
+{
+    value_index(quote!(self), quote!(Self), variants, options)
+}
+
+/// An expression for the index of `value` (of the enum `enum_type`)
+/// among the given variants, as for [`variant_index`].
+fn value_index<S, I>(
+    value: TokenStream,
+    enum_type: TokenStream,
+    variants: I,
+    options: &Options,
+) -> TokenStream
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = S>,
+{
+    match options.enums.accessor_style {
+        AccessorStyle::IndexSlice => quote!(#value as usize),
+        AccessorStyle::MatchArms => {
+            let arms = variants.into_iter().enumerate().map(|(i, variant)| {
+                let variant = format_ident!("{}", variant.as_ref());
+                quote!(#enum_type::#variant => #i,)
+            });
+            quote! {
+                match #value {
+                    #(#arms)*
+                }
+            }
+        }
+    }
+}
+
+/// The function used to deserialize values of the given format at
+/// runtime, if there is one.
+fn runtime_from_str(format: Format) -> Option<TokenStream> {
+    match format {
+        #[cfg(feature = "json")]
+        Format::Json => Some(quote!(serde_json::from_str)),
+
+        #[cfg(feature = "json5")]
+        Format::Json5 => Some(quote!(json5::from_str)),
+
+        #[cfg(feature = "toml")]
+        Format::Toml => Some(quote!(toml::from_str)),
+
+        #[cfg(feature = "yaml")]
+        Format::Yaml => Some(quote!(serde_yaml::from_str)),
+
+        #[cfg(feature = "xml")]
+        Format::Xml => None,
+
+        // Binary files can't be embedded with `include_str!`.
+        #[cfg(feature = "msgpack")]
+        Format::Msgpack => None,
+    }
+}
+
+/// The `handle_path` method of a file enum, and its preload plugin
+/// (see [`FilesOptions::bevy`]).
+///
+/// [`FilesOptions::bevy`]: crate::options::FilesOptions::bevy
+#[cfg(feature = "bevy")]
+fn bevy_items(
+    enum_name: &str,
+    filepaths: &[String],
+    index: &TokenStream,
+    bevy: &crate::options::BevyOptions,
+    options: &Options,
+) -> Result<(TokenStream, Option<TokenStream>), Error> {
+    let vis = visibility(options)?;
+    let handle_paths = filepaths
+        .iter()
+        .map(|path| {
+            Path::new(path)
+                .strip_prefix(bevy.asset_root.as_ref())
+                .map(|path| path.to_string_lossy().replace('\\', "/"))
+                .map_err(|_| Error::UnsupportedFilePath(path.clone()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let fn_name = format_ident!("{}", bevy.handle_path_fn_name.as_ref());
+    let inherent = quote! {
+        #vis const fn #fn_name(self) -> &'static str {
+            const HANDLE_PATHS: &[&str] = &[#(#handle_paths,)*];
+            HANDLE_PATHS[#index]
+        }
+    };
+
+    let plugin = bevy.preload_plugin_name.as_ref().map(|plugin_name| {
+        let plugin_name = format_ident!("{}", plugin_name.as_ref());
+        let handles_name = format_ident!("{}Handles", enum_name);
+        quote! {
+            #vis struct #plugin_name;
+
+            #[derive(bevy::prelude::Resource)]
+            #vis struct #handles_name(#vis Vec<bevy::asset::UntypedHandle>);
+
+            impl bevy::prelude::Plugin for #plugin_name {
+                fn build(&self, app: &mut bevy::prelude::App) {
+                    app.add_systems(
+                        bevy::prelude::Startup,
+                        |mut commands: bevy::prelude::Commands,
+                         server: bevy::prelude::Res<bevy::prelude::AssetServer>| {
+                            let handles = [#(#handle_paths,)*]
+                                .into_iter()
+                                .map(|path| server.load_untyped(path).untyped())
+                                .collect();
+                            commands.insert_resource(#handles_name(handles));
+                        },
+                    );
+                }
+            }
+        }
+    });
+    Ok((inherent, plugin))
+}
+
+/// The path to pass to `include_bytes!` or `include_str!` to embed
+/// the given file, according to [`FilesOptions::path_root`].
+///
+/// [`FilesOptions::path_root`]: crate::options::FilesOptions::path_root
+fn include_path(path: &str, options: &Options) -> TokenStream {
+    match &options.files.path_root {
+        _ if Path::new(path).is_absolute() => quote!(#path),
+        PathRoot::ManifestDir => quote!(concat!(env!("CARGO_MANIFEST_DIR"), "/", #path)),
+        PathRoot::OutDir => quote!(concat!(env!("OUT_DIR"), "/", #path)),
+        PathRoot::Custom(root) => {
+            let path = root.join(path).to_string_lossy().into_owned();
+            quote!(#path)
+        }
+    }
+}
+
+fn variant_from_filename(path: &str, casing: Casing) -> Result<String, Error> {
+    file_stem(path).map(|name| apply_casing(casing, &name))
+}
+
+/// Check that no two files have the same variant name, or (with
+/// [`FilesOptions::disambiguate_variants`]) give later files
+/// numeric suffixes until their names are unique.
+///
+/// [`FilesOptions::disambiguate_variants`]: crate::options::FilesOptions::disambiguate_variants
+fn resolve_variant_collisions(
+    variants: Vec<String>,
+    paths: &[String],
+    options: &Options,
+) -> Result<Vec<String>, Error> {
+    if options.files.disambiguate_variants {
+        let mut taken = variants
+            .iter()
+            .cloned()
+            .collect::<std::collections::HashSet<_>>();
+        let mut seen = std::collections::HashSet::new();
+        return Ok(variants
+            .into_iter()
+            .map(|variant| {
+                if seen.insert(variant.clone()) {
+                    return variant;
+                }
+                let unique = (2..)
+                    .map(|suffix| format!("{}{}", variant, suffix))
+                    .find(|candidate| !taken.contains(candidate))
+                    .unwrap();
+                taken.insert(unique.clone());
+                seen.insert(unique.clone());
+                unique
+            })
+            .collect());
+    }
+
+    let mut paths_by_variant = IndexMap::<&str, Vec<String>>::new();
+    for (variant, path) in variants.iter().zip(paths) {
+        paths_by_variant
+            .entry(variant)
+            .or_default()
+            .push(path.clone());
+    }
+    match paths_by_variant
+        .into_iter()
+        .find(|(_, paths)| paths.len() > 1)
+    {
+        Some((variant, paths)) => Err(Error::VariantCollision {
+            variant: variant.to_owned(),
+            paths,
+        }),
+        None => Ok(variants),
+    }
+}
+
+fn file_stem(path: &str) -> Result<String, Error> {
+    Path::new(path)
+        .file_stem()
+        .map(|name| name.to_string_lossy().into_owned())
+        .ok_or_else(|| Error::UnsupportedFilePath(path.to_owned()))
+}
+
+/// The paths of the files in the given directory which are used
+/// for generation, sorted by file name.
+///
+/// Only files directly within the directory are included, and
+/// they are filtered by [`FilesOptions::include_globs`] and
+/// [`FilesOptions::exclude_globs`].
+///
+/// [`FilesOptions::include_globs`]: crate::options::FilesOptions::include_globs
+/// [`FilesOptions::exclude_globs`]: crate::options::FilesOptions::exclude_globs
+///
+/// If the directory has a [`FilesOptions::index_file`], it decides
+/// which files are used and their order instead.
+///
+/// [`FilesOptions::index_file`]: crate::options::FilesOptions::index_file
+pub fn files_in_dir(root: &Path, options: &Options) -> Result<Vec<PathBuf>, Error> {
+    Ok(indexed_files_in_dir(root, options)?
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect())
+}
+
+/// Like [`files_in_dir`], but with the explicit variant name of
+/// each file from the index file, if it has one.
+fn indexed_files_in_dir(
+    root: &Path,
+    options: &Options,
+) -> Result<Vec<(PathBuf, Option<String>)>, Error> {
+    let paths = walk_dir(root, options)?;
+    match file_index(root, options)? {
+        None => Ok(paths.into_iter().map(|path| (path, None)).collect()),
+        Some((index_path, index)) => {
+            apply_file_index(root, paths, &index).map_err(|error| error.in_file(&index_path))
+        }
+    }
+}
+
+/// The contents of a [`FilesOptions::index_file`].
+///
+/// [`FilesOptions::index_file`]: crate::options::FilesOptions::index_file
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileIndex {
+    #[serde(default)]
+    files: Option<Vec<FileIndexEntry>>,
+
+    #[serde(default)]
+    skip: Vec<String>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum FileIndexEntry {
+    File(String),
+    Named { file: String, variant: String },
+}
+
+/// Reads the index file of a directory, if it has one.
+fn file_index(root: &Path, options: &Options) -> Result<Option<(PathBuf, FileIndex)>, Error> {
+    let Some(index_file) = &options.files.index_file else {
+        return Ok(None);
+    };
+    let index_path = root.join(index_file.as_ref());
+    if !index_path.is_file() {
+        return Ok(None);
+    }
+    let index = parsing::deserialize_source_file(&index_path)?;
+    Ok(Some((index_path, index)))
+}
+
+/// Orders and filters the paths in a directory according to its
+/// index, and checks that they match.
+fn apply_file_index(
+    root: &Path,
+    paths: Vec<PathBuf>,
+    index: &FileIndex,
+) -> Result<Vec<(PathBuf, Option<String>)>, Error> {
+    let file_name = |path: &PathBuf| {
+        path.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let present = paths.iter().map(file_name).collect::<Vec<_>>();
+    let mut missing = index
+        .skip
+        .iter()
+        .filter(|skip| !present.contains(skip))
+        .cloned()
+        .collect::<Vec<_>>();
+    let used = paths
+        .into_iter()
+        .zip(present.iter())
+        .filter(|(_, name)| !index.skip.contains(name))
+        .collect::<Vec<_>>();
+
+    let Some(entries) = &index.files else {
+        if !missing.is_empty() {
+            return Err(Error::FileIndexMismatch {
+                missing,
+                unlisted: vec![],
+            });
+        }
+        return Ok(used.into_iter().map(|(path, _)| (path, None)).collect());
+    };
+
+    let listed = entries
+        .iter()
+        .map(|entry| match entry {
+            FileIndexEntry::File(file) => (file, None),
+            FileIndexEntry::Named { file, variant } => (file, Some(variant.clone())),
+        })
+        .collect::<Vec<_>>();
+    missing.extend(
+        listed
+            .iter()
+            .filter(|(file, _)| !used.iter().any(|(_, name)| name == file))
+            .map(|(file, _)| file.to_string()),
+    );
+    let unlisted = used
+        .iter()
+        .filter(|(_, name)| !listed.iter().any(|(file, _)| file == name))
+        .map(|(_, name)| name.to_string())
+        .collect::<Vec<_>>();
+    if !missing.is_empty() || !unlisted.is_empty() {
+        return Err(Error::FileIndexMismatch { missing, unlisted });
+    }
+
+    Ok(listed
+        .into_iter()
+        .map(|(file, variant)| (root.join(file), variant))
+        .collect())
+}
+
+fn walk_dir(root: &Path, options: &Options) -> Result<Vec<PathBuf>, Error> {
+    use ignore::WalkBuilder;
+
+    let index_file = options.files.index_file.clone();
+
+    let include = glob_set(&options.files.include_globs)?;
+    let exclude = glob_set(&options.files.exclude_globs)?;
+    let overridden = match options.files.skip_unknown_formats {
+        true => Some(glob_set(&override_globs(options))?),
+        false => None,
+    };
+
+    let walk = WalkBuilder::new(root)
+        .max_depth(Some(1))
+        .sort_by_file_name(std::ffi::OsStr::cmp)
+        .filter_entry(move |entry| {
+            let name = entry.file_name();
+            entry.file_type().map(|ft| ft.is_file()).unwrap_or(false)
+                && index_file.as_deref().is_none_or(|index| *name != *index)
+                && include.as_ref().is_none_or(|globs| globs.is_match(name))
+                && !exclude.as_ref().is_some_and(|globs| globs.is_match(name))
+                && overridden.as_ref().is_none_or(|globs| {
+                    globs.as_ref().is_some_and(|globs| globs.is_match(name))
+                        || Format::from_filename(Path::new(name)).is_ok()
+                })
+        })
+        .build();
+
+    walk.into_iter()
+        .skip(1)
+        .map(|entry| entry.map(|entry| entry.into_path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::from)
+}
+
+fn glob_set(globs: &[Cow<'static, str>]) -> Result<Option<GlobSet>, Error> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob)?);
+    }
+    Ok(Some(builder.build()?))
+}
+
+/// Hoists struct literals which appear more than once in `values`
+/// into shared consts, if [`OutputOptions::dedup_values`] is set.
+///
+/// Returns the rewritten values, and the definitions of the shared
+/// consts.
+///
+/// [`OutputOptions::dedup_values`]: crate::options::OutputOptions::dedup_values
+fn dedup_values(
+    values: Vec<TokenStream>,
+    options: &Options,
+) -> (Vec<TokenStream>, Vec<TokenStream>) {
+    if !options.output.dedup_values {
+        return (values, vec![]);
+    }
+
+    let mut counts = HashMap::new();
+    for value in &values {
+        count_struct_literals(value.clone(), &mut counts);
+    }
+    let mut shared = SharedValues {
+        counts,
+        names: HashMap::new(),
+        type_counts: HashMap::new(),
+        definitions: vec![],
+    };
+    let values = values
+        .into_iter()
+        .map(|value| shared.replace_struct_literals(value))
+        .collect();
+    (values, shared.definitions)
+}
+
+/// Splits a token stream into struct literals (like
+/// `Name { field: value }`, or `Name(value)` for tuple structs and
+/// newtypes) and other tokens.
+fn struct_literals(tokens: TokenStream) -> Vec<Result<(Ident, Group), TokenTree>> {
+    // Type names are capitalized, unlike functions, but generated
+    // values can also contain these enum variants.
+    let is_tuple_struct = |ident: &Ident| {
+        let name = ident.to_string();
+        name.starts_with(char::is_uppercase) && !matches!(name.as_str(), "Some" | "Ok" | "Err")
+    };
+
+    let mut tokens = tokens.into_iter().peekable();
+    let mut parts = vec![];
+    // Only `::` separates paths, since a single `:` comes before
+    // the value of a field.
+    let mut after_joint_colon = false;
+    let mut after_path_separator = false;
+    while let Some(token) = tokens.next() {
+        let (is_colon, is_joint) = match &token {
+            TokenTree::Punct(punct) => (punct.as_char() == ':', punct.spacing() == Spacing::Joint),
+            _ => (false, false),
+        };
+        let is_separator = is_colon && after_joint_colon;
+        after_joint_colon = is_colon && is_joint;
+        match (token, tokens.peek()) {
+            (TokenTree::Ident(ident), Some(TokenTree::Group(group)))
+                if !after_path_separator
+                    && (group.delimiter() == Delimiter::Brace
+                        || (group.delimiter() == Delimiter::Parenthesis
+                            && is_tuple_struct(&ident))) =>
+            {
+                let group = group.clone();
+                tokens.next();
+                parts.push(Ok((ident, group)));
+            }
+            (token, _) => parts.push(Err(token)),
+        }
+        after_path_separator = is_separator;
+    }
+    parts
+}
+
+fn count_struct_literals(tokens: TokenStream, counts: &mut HashMap<String, usize>) {
+    for part in struct_literals(tokens) {
+        match part {
+            Ok((ident, group)) => {
+                // A repeated literal is only defined once, so the
+                // literals inside it are only counted once.
+                let count = counts.entry(quote!(#ident #group).to_string()).or_default();
+                *count += 1;
+                if *count == 1 {
+                    count_struct_literals(group.stream(), counts);
+                }
+            }
+            Err(TokenTree::Group(group)) => count_struct_literals(group.stream(), counts),
+            Err(_) => (),
+        }
+    }
+}
+
+/// The struct literals found by [`dedup_values`], and the shared
+/// consts defined for the repeated ones.
+struct SharedValues {
+    counts: HashMap<String, usize>,
+    names: HashMap<String, Ident>,
+    type_counts: HashMap<String, usize>,
+    definitions: Vec<TokenStream>,
+}
+
+impl SharedValues {
+    /// Replaces the outermost repeated struct literals with the
+    /// names of shared consts.
+    fn replace_struct_literals(&mut self, tokens: TokenStream) -> TokenStream {
+        struct_literals(tokens)
+            .into_iter()
+            .map(|part| match part {
+                Ok((ident, group)) => {
+                    let key = quote!(#ident #group).to_string();
+                    match self.counts.get(&key) {
+                        Some(&count) if count > 1 => self.shared_name(key, ident, group),
+                        _ => {
+                            let group = self.replace_in_group(&group);
+                            quote!(#ident #group)
+                        }
+                    }
+                }
+                Err(TokenTree::Group(group)) => self.replace_in_group(&group).into_token_stream(),
+                Err(token) => token.into_token_stream(),
+            })
+            .collect()
+    }
+
+    fn replace_in_group(&mut self, group: &Group) -> Group {
+        let mut new_group = Group::new(
+            group.delimiter(),
+            self.replace_struct_literals(group.stream()),
+        );
+        new_group.set_span(group.span());
+        new_group
+    }
+
+    fn shared_name(&mut self, key: String, type_name: Ident, group: Group) -> TokenStream {
+        if let Some(name) = self.names.get(&key) {
+            return name.to_token_stream();
+        }
+
+        let index = self.type_counts.entry(type_name.to_string()).or_default();
+        let name = format_ident!("{}_{}", type_name, *index);
+        *index += 1;
+        self.names.insert(key, name.clone());
+
+        let group = self.replace_in_group(&group);
+        self.definitions.push(quote! {
+            #[allow(non_upper_case_globals)]
+            const #name: #type_name = #type_name #group;
+        });
+        name.to_token_stream()
+    }
+}
+
+fn override_globs(options: &Options) -> Vec<Cow<'static, str>> {
+    options
+        .files
+        .format_overrides
+        .iter()
+        .map(|(glob, _)| glob.clone())
+        .collect()
+}
+
+/// The globs of [`FilesOptions::format_overrides`], compiled once
+/// for all of the files in a directory.
+///
+/// [`FilesOptions::format_overrides`]: crate::options::FilesOptions::format_overrides
+struct FormatOverrides {
+    globs: Option<GlobSet>,
+    formats: Vec<Format>,
+}
+
+impl FormatOverrides {
+    fn new(options: &Options) -> Result<Self, Error> {
+        Ok(FormatOverrides {
+            globs: glob_set(&override_globs(options))?,
+            formats: options
+                .files
+                .format_overrides
+                .iter()
+                .map(|(_, format)| *format)
+                .collect(),
+        })
+    }
+}
+
+/// The format of a file in a directory: from the first matching
+/// [`FilesOptions::format_overrides`], or else the `format` given
+/// for the whole directory, or else the file's extension.
+///
+/// [`FilesOptions::format_overrides`]: crate::options::FilesOptions::format_overrides
+fn file_format(
+    path: &Path,
+    format: Option<Format>,
+    overrides: &FormatOverrides,
+) -> Result<Format, Error> {
+    let matched = path
+        .file_name()
+        .zip(overrides.globs.as_ref())
+        .and_then(|(name, globs)| globs.matches(name).into_iter().min());
+    if let Some(index) = matched {
+        return Ok(overrides.formats[index]);
+    }
+    match format {
+        Some(format) => Ok(format),
+        None => Format::from_filename(path),
+    }
+}
+
+/// Parses a file in a directory, using the format from
+/// [`file_format`], and removes its inline directives (see
+/// [`InlineDirectives`]).
+fn parse_file(
+    path: &Path,
+    format: Option<Format>,
+    overrides: &FormatOverrides,
+    options: &Options,
+) -> Result<(Value, InlineDirectives), Error> {
+    let format = file_format(path, format, overrides).map_err(|error| error.in_file(path))?;
+    let mut value = parsing::parse_source_file_with_format(path, Some(format), &options.parse)?;
+    let directives = InlineDirectives::extract(&mut value).map_err(|error| error.in_file(path))?;
+    Ok((value, directives))
+}
+
+/// Parse the files in a directory, returning their values along
+/// with the options, with all of their inline directives applied.
+fn values_from_file_contents(
+    root: &Path,
+    format: Option<Format>,
+    options: &Options,
+) -> Result<(Vec<Value>, Options), Error> {
+    let paths = files_in_dir(root, options)?;
+    let overrides = FormatOverrides::new(options)?;
+    let parse = |path: &PathBuf| parse_file(path, format, &overrides, options);
+
+    // Collecting from a parallel iterator still preserves the
+    // (sorted) order of the paths.
+    #[cfg(feature = "rayon")]
+    let parsed: Result<Vec<_>, Error> = {
+        use rayon::prelude::*;
+        paths.par_iter().map(parse).collect()
+    };
+
+    #[cfg(not(feature = "rayon"))]
+    let parsed: Result<Vec<_>, Error> = paths.iter().map(parse).collect();
+
+    let mut values = vec![];
+    let mut directives = InlineDirectives::default();
+    for (value, file_directives) in parsed? {
+        values.push(value);
+        directives.extend(file_directives);
+    }
+    parsing::unify_values(&mut values)?;
+
+    let sources = paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>();
+    let conflicts = parsing::find_type_conflicts(sources.iter().map(String::as_str).zip(&values));
+    match conflicts.is_empty() {
+        true => Ok((values, directives.apply(options))),
+        false => Err(Error::TypeConflicts(conflicts)),
+    }
+}
+
+/// Define a set of Rust structs based on the contents of all
+/// files in the given directory.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use edres_core::{codegen, Options, value::*};
+/// # use quote::quote;
+/// let tokens = codegen::define_structs_from_file_contents(
+///     "./my_dir".as_ref(),
+///     "FileStruct",
+///     None,
+///     &Options::minimal(),
+/// ).unwrap();
+///
+/// // Assuming that the toml files in ./my_dir look like:
+/// //
+/// //  field_a = 1
+/// //  field_b = 2
+///
+/// assert_eq!(tokens.to_string(), quote!(
+///     #[allow(non_camel_case_types)]
+///     pub enum FileStruct {
+///         pub field_a: i64,
+///         pub field_b: i64,
+///     }
+/// ).to_string());
+/// ```
+pub fn define_structs_from_file_contents(
+    root: &Path,
+    struct_name: &str,
+    format: Option<Format>,
+    options: &Options,
+) -> Result<TokenStream, Error> {
+    validation::validate_options(options)?;
+    let vis = visibility(options)?;
+    let (values, options) = &values_from_file_contents(root, format, options)?;
+    let (value_type, values, new_struct_tokens) =
+        establish_types_for_values(values.iter(), struct_name, options)?;
+
+    let mut const_tokens = None;
+    if let Some(const_name) = &options.structs.struct_data_const_name {
+        ensure_no_maps(&values, options)?;
+        let const_name = format_ident!("{}", const_name);
+        let values = values
+            .iter()
+            .map(|value| define_value(value, struct_name, None, None, "", options))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (values, shared_tokens) = dedup_values(values, options);
+
+        const_tokens = Some(quote! {
+            #vis const #const_name: &[#value_type] = &[
+                #(#values,)*
+            ];
+            #(#shared_tokens)*
+        });
+    }
+    let const_tokens = const_tokens.into_iter();
+
+    Ok(quote! {
+        #(#new_struct_tokens)*
+        #(#const_tokens)*
+    })
+}
+
+/// Define a module for each file in the given directory, each
+/// containing a set of Rust structs based on that file's contents.
+///
+/// Unlike [`define_structs_from_file_contents`], the files do not
+/// need to share the same shape, because each one gets its own
+/// independent set of structs.
+///
+/// Each module is named after its file, in snake case. Names which
+/// are keywords are escaped as field names are (like `r#type`), and
+/// it is an error for a name not to be an identifier (like `2024`)
+/// or for two files to have the same name.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use edres_core::{codegen, Options, value::*};
+/// # use quote::quote;
+/// let tokens = codegen::define_modules_from_files(
+///     "./my_dir".as_ref(),
+///     "Config",
+///     None,
+///     &Options::minimal(),
+/// ).unwrap();
+///
+/// // Assuming that ./my_dir contains file_a.toml and file_b.toml:
+/// //
+/// //  # file_a.toml
+/// //  field_a = 1
+/// //
+/// //  # file_b.toml
+/// //  field_b = "two"
+///
+/// assert_eq!(tokens.to_string(), quote!(
+///     pub mod file_a {
+///         #[allow(non_camel_case_types)]
+///         pub struct Config {
+///             pub field_a: i64,
+///         }
+///     }
+///
+///     pub mod file_b {
+///         #[allow(non_camel_case_types)]
+///         pub struct Config {
+///             pub field_b: std::borrow::Cow<'static, str>,
+///         }
+///     }
+/// ).to_string());
+/// ```
+pub fn define_modules_from_files(
+    root: &Path,
+    struct_name: &str,
+    format: Option<Format>,
+    options: &Options,
+) -> Result<TokenStream, Error> {
+    validation::validate_options(options)?;
+    let vis = visibility(options)?;
+    use case::CaseExt;
+
+    let paths = files_in_dir(root, options)?;
+    let module_names = paths
+        .iter()
+        .map(|path| {
+            let stem = path
+                .file_stem()
+                .map(|name| name.to_string_lossy().into_owned())
+                .ok_or_else(|| Error::UnsupportedFilePath(path.display().to_string()))?;
+            let module_name = stem.to_snake();
+            if !validation::is_identifier(&module_name) {
+                return Err(Error::InvalidModuleName(stem).in_file(path));
+            }
+            Ok(module_name)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    ensure_unique_names(&module_names)?;
+
+    let overrides = FormatOverrides::new(options)?;
+    let mut modules = vec![];
+    for (path, module_name) in paths.iter().zip(&module_names) {
+        let module_name = keyword_safe_ident(module_name);
+
+        let (value, directives) = parse_file(path, format, &overrides, options)?;
+        let value = value.assume_struct().map_err(|error| error.in_file(path))?;
+        let struct_tokens =
+            define_structs(&value, struct_name, Some(path), &directives.apply(options))
+                .map_err(|error| error.in_file(path))?;
+
+        modules.push(quote! {
+            #vis mod #module_name {
+                #struct_tokens
+            }
+        });
+    }
+
+    Ok(quote! {
+        #(#modules)*
+    })
+}
+
+/// Wrap generated code in the module and inner attributes given
+/// by [`OutputOptions::wrap_in_module`] and
+/// [`OutputOptions::inner_attributes`].
+///
+/// If neither is set, the tokens are returned unchanged.
+///
+/// [`OutputOptions::wrap_in_module`]: crate::options::OutputOptions::wrap_in_module
+/// [`OutputOptions::inner_attributes`]: crate::options::OutputOptions::inner_attributes
+///
+/// # Examples
+///
+/// ```
+/// # use edres_core::{codegen, options::*};
+/// # use quote::quote;
+/// let tokens = codegen::wrap_output(
+///     quote!(pub struct Config;),
+///     &Options {
+///         output: OutputOptions {
+///             wrap_in_module: Some("config".into()),
+///             inner_attributes: vec!["allow(clippy::all)".into()].into(),
+///             ..OutputOptions::new()
+///         },
+///         ..Options::minimal()
+///     },
+/// ).unwrap();
+///
+/// assert_eq!(tokens.to_string(), quote!(
+///     pub mod config {
+///         #![allow(clippy::all)]
+///         pub struct Config;
+///     }
+/// ).to_string());
+/// ```
+pub fn wrap_output(tokens: TokenStream, options: &Options) -> Result<TokenStream, Error> {
+    validation::validate_options(options)?;
+    let attributes = attribute_contents(&options.output.inner_attributes)?;
+    let attributes = attributes.iter().map(|attribute| quote!(#![#attribute]));
+
+    Ok(match &options.output.wrap_in_module {
+        Some(module_name) => {
+            let vis = visibility(options)?;
+            let module_name = format_ident!("{}", module_name.as_ref());
+            quote! {
+                #vis mod #module_name {
+                    #(#attributes)*
+                    #tokens
+                }
+            }
+        }
+        None => quote! {
+            #(#attributes)*
+            #tokens
+        },
+    })
+}
+
+/// Parse attributes given in the options, without the surrounding
+/// `#[...]` or `#![...]`.
+fn attribute_contents(attributes: &[Cow<'static, str>]) -> Result<Vec<TokenStream>, Error> {
+    attributes
+        .iter()
+        .map(|attribute| {
+            attribute
+                .parse::<TokenStream>()
+                .map_err(|_| Error::InvalidAttribute(attribute.to_string()))
+        })
+        .collect()
+}
+
+fn derive_attribute<S: AsRef<str>, I: IntoIterator<Item = S>>(
+    trait_list: I,
+    serde_support: SerdeSupport,
+    require_debug: bool,
+) -> Option<TokenStream> {
+    fn format_derive(s: &str) -> TokenStream {
+        match s.split_once("::") {
+            Some((crate_name, trait_name)) => {
+                let crate_name = format_ident!("{}", crate_name);
+                let trait_name = format_ident!("{}", trait_name);
+                quote!(#crate_name :: #trait_name)
+            }
+            None => {
+                let tokens = format_ident!("{}", s);
+                quote!(#tokens)
+            }
+        }
+    }
+
+    let mut derives = vec![];
+    let mut deriving_debug = false;
+    for item in trait_list {
+        let item = item.as_ref();
+        if item == "Debug" {
+            deriving_debug = true;
+        }
+
+        derives.push(format_derive(item));
+    }
+
+    let (ser, de) = serde_support
+        .should_derive_ser_de()
+        .unwrap_or((false, false));
+    if ser {
+        derives.push(format_derive("serde::Serialize"));
+    }
+    if de {
+        derives.push(format_derive("serde::Deserialize"));
+    }
+
+    if !deriving_debug && require_debug {
+        derives.push(format_derive("Debug"));
+    }
+
+    (!derives.is_empty()).then(|| quote!(#[derive(#(#derives),*)]))
+}
+
+/// The parts of a value selected by [`FieldSelection`]. Only the
+/// fields of structs (and not sequences) are selected from.
+fn select_fields(value: &Value, key_path: &str, selection: &FieldSelection) -> Value {
+    let Value::Struct(data) = value else {
+        return value.clone();
+    };
+    let fields = data.0.iter().filter_map(|(key, value)| {
+        let path = child_path(key_path, key);
+        let is_parent = |selected: &Cow<str>| {
+            selected
+                .strip_prefix(path.as_str())
+                .is_some_and(|rest| rest.starts_with('.'))
+        };
+        let value = match selection {
+            FieldSelection::All => value.clone(),
+            FieldSelection::Include(paths) if paths.iter().any(|p| *p == path) => value.clone(),
+            FieldSelection::Include(paths) if paths.iter().any(is_parent) => {
+                select_fields(value, &path, selection)
+            }
+            FieldSelection::Include(_) => return None,
+            FieldSelection::Exclude(paths) if paths.iter().any(|p| *p == path) => return None,
+            FieldSelection::Exclude(_) => select_fields(value, &path, selection),
+        };
+        Some((key.clone(), value))
+    });
+    Value::Struct(Struct(fields.collect()))
+}
+
+pub(crate) fn child_path(parent: &str, key: &str) -> String {
+    match parent {
+        "" => key.to_owned(),
+        parent => format!("{}.{}", parent, key),
+    }
+}
+
+pub(crate) fn type_override<'a>(key_path: &str, options: &'a Options) -> Option<&'a str> {
+    options
+        .parse
+        .type_overrides
+        .iter()
+        .find(|(path, _)| path == key_path)
+        .map(|(_, type_name)| type_name.as_ref())
+}
+
+pub(crate) fn is_numeric_type(type_name: &str) -> bool {
+    matches!(
+        type_name,
+        "i8" | "i16"
+            | "i32"
+            | "i64"
+            | "i128"
+            | "isize"
+            | "u8"
+            | "u16"
+            | "u32"
+            | "u64"
+            | "u128"
+            | "usize"
+            | "f32"
+            | "f64"
+    )
+}
+
+/// Splits a type override into the outer type and, for newtypes
+/// written like `MyId(u32)`, the explicit inner type.
+fn split_type_override(type_name: &str) -> (&str, Option<&str>) {
+    let type_name = type_name.trim();
+    match type_name.strip_suffix(')').and_then(|t| t.split_once('(')) {
+        Some((outer, inner)) => (outer.trim(), Some(inner.trim())),
+        None => (type_name, None),
+    }
+}
+
+fn parse_type_override(key_path: &str, type_name: &str) -> Result<TokenStream, Error> {
+    type_name
+        .parse()
+        .map_err(|_| Error::InvalidTypeOverride(key_path.into(), type_name.into()))
+}
+
+/// The visibility of generated items.
+fn visibility(options: &Options) -> Result<TokenStream, Error> {
+    visibility_tokens(&options.output.visibility)
+}
+
+fn visibility_tokens(visibility: &Visibility) -> Result<TokenStream, Error> {
+    Ok(match visibility {
+        Visibility::Pub => quote!(pub),
+        Visibility::PubCrate => quote!(pub(crate)),
+        Visibility::Private => quote!(),
+        Visibility::Custom(vis) => vis
+            .parse()
+            .map_err(|_| Error::InvalidVisibility(vis.to_string()))?,
+    })
+}
+
+/// The crate to take `alloc` items (like `Cow`) from in generated
+/// code.
+fn alloc_crate(options: &Options) -> TokenStream {
+    match (options.output.runtime_crate, options.output.no_std) {
+        (true, _) => quote!(::edres_runtime),
+        (false, true) => quote!(alloc),
+        (false, false) => quote!(std),
+    }
+}
+
+/// The crate to take `core` items (like `fmt`) from in generated
+/// code.
+fn core_crate(options: &Options) -> TokenStream {
+    match (options.output.runtime_crate, options.output.no_std) {
+        (true, _) => quote!(::edres_runtime),
+        (false, true) => quote!(core),
+        (false, false) => quote!(std),
+    }
+}
+
+/// The map and string types used for mappings kept as maps.
+fn map_types(options: &Options) -> (TokenStream, TokenStream) {
+    if options.output.runtime_crate {
+        return (
+            quote!(::edres_runtime::collections::BTreeMap),
+            quote!(::edres_runtime::string::String),
+        );
+    }
+    match options.output.no_std {
+        true => (
+            quote!(alloc::collections::BTreeMap),
+            quote!(alloc::string::String),
+        ),
+        false => (quote!(std::collections::BTreeMap), quote!(String)),
+    }
+}
+
+fn apply_casing(casing: Casing, name: &str) -> String {
+    use case::CaseExt;
+
+    match casing {
+        Casing::Preserve => name.to_owned(),
+        Casing::Pascal => name
+            .split(|c: char| !c.is_alphanumeric())
+            .flat_map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars))
+                    .into_iter()
+                    .flatten()
+            })
+            .collect(),
+        Casing::SnakeToPascal => name.to_camel(),
+        Casing::ScreamingToPascal => name.to_lowercase().to_camel(),
+        Casing::Snake => apply_casing(Casing::Pascal, name).to_snake(),
+        Casing::Kebab => apply_casing(Casing::Snake, name).replace('_', "-"),
+    }
+}
+
+fn nested_struct_name(
+    struct_name: &str,
+    under_key: Option<&str>,
+    under_index: Option<usize>,
+    options: &Options,
+) -> String {
+    let (key, index) = match options.structs.nested_struct_naming {
+        StructNaming::Underscored => {
+            let separator = &options.structs.nested_name_separator;
+            (
+                under_key.map(|k| format!("{}{}", separator, k)),
+                under_index.map(|i| format!("{}{}", separator, i)),
+            )
+        }
+        StructNaming::Pascal => (
+            under_key.map(|k| apply_casing(Casing::Pascal, k)),
+            under_index.map(|i| i.to_string()),
+        ),
+    };
+    format!(
+        "{}{}{}",
+        struct_name,
+        key.unwrap_or_default(),
+        index.unwrap_or_default()
+    )
+}
+
+fn ensure_unique_names<S: AsRef<str>, I: IntoIterator<Item = S>>(names: I) -> Result<(), Error> {
+    let mut seen = std::collections::HashSet::new();
+    for name in names {
+        if !seen.insert(name.as_ref().to_owned()) {
+            return Err(Error::DuplicateName(name.as_ref().to_owned()));
+        }
+    }
+    Ok(())
+}
+
+/// Structs which need to be defined, with their names and key
+/// paths.
+type NewStructs<'a> = Vec<(String, String, Cow<'a, Struct>)>;
+
+pub(crate) fn keep_as_map(key_path: &str, options: &Options) -> bool {
+    options
+        .structs
+        .keep_as_map
+        .iter()
+        .any(|path| path == key_path)
+}
+
+/// The name of the tuple struct for the sequence at a key path, if
+/// there is one. (See [`StructOptions::tuple_structs`].)
+///
+/// [`StructOptions::tuple_structs`]: crate::options::StructOptions::tuple_structs
+fn tuple_struct_name<'a>(key_path: &str, options: &'a Options) -> Option<&'a str> {
+    options
+        .structs
+        .tuple_structs
+        .iter()
+        .find(|(path, _)| path == key_path)
+        .map(|(_, name)| name.as_ref())
+}
+
+/// The name given to the field at a key path, if it has been renamed.
+/// (See [`StructOptions::field_renames`].)
+///
+/// [`StructOptions::field_renames`]: crate::options::StructOptions::field_renames
+fn renamed_field<'a>(key_path: &str, options: &'a Options) -> Option<&'a str> {
+    options
+        .structs
+        .field_renames
+        .iter()
+        .find(|(path, _)| path == key_path)
+        .map(|(_, name)| name.as_ref())
+}
+
+/// The identifier of the field for `key`, at a key path.
+///
+/// Keys which are Rust keywords become raw identifiers (like
+/// `r#type`), or get a trailing underscore if they can't be raw
+/// (like `self_`).
+pub(crate) fn field_name(key: &str, key_path: &str, options: &Options) -> Ident {
+    match renamed_field(key_path, options) {
+        Some(name) => format_ident!("{}", name),
+        None => keyword_safe_ident(key),
+    }
+}
+
+/// The identifier for `name`, made raw (like `r#type`) if it is a
+/// keyword, or given a trailing underscore if it can't be raw (like
+/// `self_`).
+fn keyword_safe_ident(name: &str) -> Ident {
+    if NON_RAW_KEYWORDS.contains(&name) {
+        format_ident!("{}_", name)
+    } else if KEYWORDS.contains(&name) {
+        format_ident!("r#{}", name)
+    } else {
+        format_ident!("{}", name)
+    }
+}
+
+/// The `serde(rename)` attribute for a field, if serde is supported
+/// and the field's name doesn't match its key.
+///
+/// Raw identifiers don't need renaming, as serde already strips the
+/// `r#` prefix.
+fn serde_rename(key: &str, key_path: &str, options: &Options) -> Option<TokenStream> {
+    options.serde_support.should_derive_ser_de()?;
+    let name = field_name(key, key_path, options).to_string();
+    (name.strip_prefix("r#").unwrap_or(&name) != key).then(|| quote!(#[serde(rename = #key)]))
+}
+
+/// The `#[doc]` attributes for the field at a key path, from
+/// [`StructOptions::field_docs`].
+fn field_docs(key_path: &str, options: &Options) -> Vec<TokenStream> {
+    options
+        .structs
+        .field_docs
+        .iter()
+        .find(|(path, _)| path == key_path)
+        .into_iter()
+        .flat_map(|(_, doc)| doc.lines())
+        .map(|line| {
+            let line = format!(" {}", line);
+            quote!(#[doc = #line])
+        })
+        .collect()
+}
+
+/// The fields of the tuple struct for a fixed-length sequence, named
+/// after their positions, if the value is one and it has a tuple
+/// struct.
+fn tuple_struct_fields(value: &Value, key_path: &str, options: &Options) -> Option<Struct> {
+    let values = match value {
+        Value::Array(_, values) | Value::Tuple(values) => values,
+        _ => return None,
+    };
+    tuple_struct_name(key_path, options)?;
+    Some(Struct(
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (i.to_string(), value.clone()))
+            .collect(),
+    ))
+}
+
+/// The values of a mapping that is kept as a map, unified so that
+/// they all have the same type.
+fn map_values(mapping: &Struct) -> Result<Vec<Value>, Error> {
+    let mut values: Vec<Value> = mapping.0.values().cloned().collect();
+    parsing::unify_values(&mut values)?;
+    Ok(values)
+}
+
+/// Find the key path of the first map within the given struct,
+/// if there is one. Maps can't be built in a const, so this is
+/// checked before generating one.
+fn kept_map_path(data: &Struct, key_path: &str, options: &Options) -> Option<String> {
+    fn in_value(value: &Value, key_path: &str, options: &Options) -> Option<String> {
+        match value {
+            Value::Struct(data) => kept_map_path(data, key_path, options),
+            Value::Option(Some(value)) => in_value(value, key_path, options),
+            Value::Array(_, values) | Value::Vec(values) => values
+                .iter()
+                .find_map(|value| in_value(value, key_path, options)),
+            Value::Tuple(values) => values.iter().enumerate().find_map(|(i, value)| {
+                in_value(value, &child_path(key_path, &i.to_string()), options)
+            }),
+            Value::Tagged(_, data) => {
+                let variant_path = child_path(key_path, value.tag().unwrap_or_default());
+                kept_map_path(data, &variant_path, options)
+            }
+            _ => None,
+        }
+    }
+
+    if keep_as_map(key_path, options) {
+        return Some(key_path.to_owned());
+    }
+    data.0
+        .iter()
+        .find_map(|(key, value)| in_value(value, &child_path(key_path, key), options))
+}
+
+fn ensure_no_maps<'a, I: IntoIterator<Item = &'a Value>>(
+    values: I,
+    options: &Options,
+) -> Result<(), Error> {
+    for value in values {
+        let key_path = match value {
+            Value::Struct(data) => kept_map_path(data, "", options),
+            Value::Tagged(_, data) => kept_map_path(data, value.tag().unwrap_or_default(), options),
+            _ => None,
+        };
+        if let Some(key_path) = key_path {
+            return Err(Error::MapInConst(key_path));
+        }
+    }
+    Ok(())
+}
+
+/// Types which can only be constructed by allocating, so can't be
+/// used in a const. (See [`StructOptions::require_const_compatible`].)
+///
+/// [`StructOptions::require_const_compatible`]: crate::options::StructOptions::require_const_compatible
+const ALLOCATING_TYPES: &[&str] = &[
+    "Arc",
+    "BTreeMap",
+    "BTreeSet",
+    "BinaryHeap",
+    "Box",
+    "HashMap",
+    "HashSet",
+    "LinkedList",
+    "Rc",
+    "String",
+    "Vec",
+    "VecDeque",
+];
+
+/// Checks that the value at a key path (but not its contents) can
+/// be defined in a const. (See
+/// [`StructOptions::require_const_compatible`].)
+///
+/// [`StructOptions::require_const_compatible`]: crate::options::StructOptions::require_const_compatible
+fn ensure_const_compatible(value: &Value, key_path: &str, options: &Options) -> Result<(), Error> {
+    let incompatible = |reason: String| Err(Error::NotConstCompatible(key_path.into(), reason));
+    let is_container = matches!(value, Value::Option(_) | Value::Array(..) | Value::Vec(_));
+    if let Some(type_name) = type_override(key_path, options).filter(|_| !is_container) {
+        let (outer, _) = split_type_override(type_name);
+        let name = outer.split(['<', ' ']).next().unwrap_or_default();
+        let name = name.rsplit("::").next().unwrap_or_default();
+        if ALLOCATING_TYPES.contains(&name) {
+            return incompatible(format!("a `{}`", name));
+        }
+    }
+    match value {
+        Value::Struct(_) if keep_as_map(key_path, options) => incompatible("a map".into()),
+        _ => Ok(()),
+    }
+}
+
+fn type_of_value<'a>(
+    value: &'a Value,
+    struct_name: &str,
+    under_key: Option<&str>,
+    under_index: Option<usize>,
+    key_path: &str,
+    options: &Options,
+    new_structs: &mut NewStructs<'a>,
+) -> Result<TokenStream, Error> {
+    let is_container = matches!(value, Value::Option(_) | Value::Array(..) | Value::Vec(_));
+    if let Some(type_name) = type_override(key_path, options).filter(|_| !is_container) {
+        let (outer, inner) = split_type_override(type_name);
+        if inner.is_none() && !is_numeric_type(outer) {
+            // Still infer the wrapped type, so that any structs it
+            // needs are defined.
+            infer_type_of_value(
+                value,
+                struct_name,
+                under_key,
+                under_index,
+                key_path,
+                options,
+                new_structs,
+            )?;
+        }
+        return parse_type_override(key_path, outer);
+    }
+
+    infer_type_of_value(
+        value,
+        struct_name,
+        under_key,
+        under_index,
+        key_path,
+        options,
+        new_structs,
+    )
+}
+
+fn infer_type_of_value<'a>(
+    value: &'a Value,
+    struct_name: &str,
+    under_key: Option<&str>,
+    under_index: Option<usize>,
+    key_path: &str,
+    options: &Options,
+    new_structs: &mut NewStructs<'a>,
+) -> Result<TokenStream, Error> {
+    let is_container = matches!(value, Value::Option(_) | Value::Array(..) | Value::Vec(_));
+    if keep_as_map(key_path, options) && !is_container && !matches!(value, Value::Struct(_)) {
+        return Err(Error::ExpectedMapping(key_path.into()));
+    }
+    if let Some(name) = tuple_struct_name(key_path, options) {
+        if let Some(fields) = tuple_struct_fields(value, key_path, options) {
+            let ident = format_ident!("{}", name);
+            new_structs.push((name.to_owned(), key_path.to_owned(), Cow::Owned(fields)));
+            return Ok(quote!(#ident));
+        }
+        if !matches!(value, Value::Option(_) | Value::Vec(_)) {
+            return Err(Error::ExpectedFixedSequence(key_path.into()));
+        }
+    }
+
+    Ok(match value {
+        Value::Unit => quote!(()),
+        Value::Bool(_) => quote!(bool),
+        Value::Char(_) => quote!(char),
+        Value::I8(_) => quote!(i8),
+        Value::I16(_) => quote!(i16),
+        Value::I32(_) => quote!(i32),
+        Value::I64(_) => quote!(i64),
+        Value::I128(_) => quote!(i128),
+        Value::ISize(_) => quote!(isize),
+        Value::U8(_) => quote!(u8),
+        Value::U16(_) => quote!(u16),
+        Value::U32(_) => quote!(u32),
+        Value::U64(_) => quote!(u64),
+        Value::U128(_) => quote!(u128),
+        Value::USize(_) => quote!(usize),
+        Value::Datetime(datetime) => datetime_type(datetime),
+        Value::Decimal(_) => quote!(rust_decimal::Decimal),
+        Value::F32(_) => quote!(f32),
+        Value::F64(_) => quote!(f64),
+        Value::String(_) => {
+            let alloc = alloc_crate(options);
+            quote!(#alloc::borrow::Cow<'static, str>)
+        }
+        Value::Option(x) => match x {
+            Some(value) => {
+                let inner_type = type_of_value(
+                    value,
+                    struct_name,
+                    under_key,
+                    None,
+                    key_path,
+                    options,
+                    new_structs,
+                )?;
+                quote!(Option<#inner_type>)
+            }
+            None => quote!(Option<()>),
+        },
+        Value::Array(len, values) => {
+            assert_eq!(values.len(), *len);
+            match values.first() {
+                None => quote!([(); #len]),
+                Some(Value::Tagged(..)) => {
+                    let inner_type = tagged_enum_type(
+                        values,
+                        struct_name,
+                        under_key,
+                        key_path,
+                        options,
+                        new_structs,
+                    );
+                    quote!([#inner_type; #len])
+                }
+                Some(value) => {
+                    let inner_type = type_of_value(
+                        value,
+                        struct_name,
+                        under_key,
+                        None,
+                        key_path,
+                        options,
+                        new_structs,
+                    )?;
+                    quote!([#inner_type; #len])
+                }
+            }
+        }
+        Value::Vec(values) => match values.first() {
+            None => {
+                let alloc = alloc_crate(options);
+                quote!(#alloc::borrow::Cow<'static, [()]>)
+            }
+            Some(Value::Tagged(..)) => {
+                let inner_type = tagged_enum_type(
+                    values,
+                    struct_name,
+                    under_key,
+                    key_path,
+                    options,
+                    new_structs,
+                );
+                let alloc = alloc_crate(options);
+                quote!(#alloc::borrow::Cow<'static, [#inner_type]>)
+            }
+            Some(value) => {
+                let inner_type = type_of_value(
+                    value,
+                    struct_name,
+                    under_key,
+                    None,
+                    key_path,
+                    options,
+                    new_structs,
+                )?;
+                let alloc = alloc_crate(options);
+                quote!(#alloc::borrow::Cow<'static, [#inner_type]>)
+            }
+        },
+        Value::Tuple(values) => {
+            let types_in_tuple = values
+                .iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    type_of_value(
+                        v,
+                        struct_name,
+                        under_key,
+                        Some(i),
+                        &child_path(key_path, &i.to_string()),
+                        options,
+                        new_structs,
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            quote!((#(#types_in_tuple),*))
+        }
+        Value::Struct(mapping) if keep_as_map(key_path, options) => {
+            let values = map_values(mapping)?;
+            let inner_type = match values.first() {
+                Some(value) => {
+                    // The unified values are temporary, so any structs
+                    // they need must own their data.
+                    let mut item_structs = vec![];
+                    let inner_type = type_of_value(
+                        value,
+                        struct_name,
+                        under_key,
+                        under_index,
+                        &child_path(key_path, "*"),
+                        options,
+                        &mut item_structs,
+                    )?;
+                    new_structs.extend(
+                        item_structs
+                            .into_iter()
+                            .map(|(name, path, data)| (name, path, Cow::Owned(data.into_owned()))),
+                    );
+                    inner_type
+                }
+                None => quote!(()),
+            };
+            let (map, string) = map_types(options);
+            quote!(#map<#string, #inner_type>)
+        }
+        Value::Struct(mapping) => {
+            let name = nested_struct_name(struct_name, under_key, under_index, options);
+            let ident = format_ident!("{}", name);
+            new_structs.push((name, key_path.to_owned(), Cow::Borrowed(mapping)));
+
+            quote!(#ident)
+        }
+        Value::Tagged(..) => tagged_enum_type(
+            std::slice::from_ref(value),
+            struct_name,
+            under_key,
+            key_path,
+            options,
+            new_structs,
+        ),
+    })
+}
+
+/// The type of the enum for a sequence of tagged structs, adding
+/// its variants (see [`tagged_variants`]) to the new structs.
+fn tagged_enum_type(
+    values: &[Value],
+    struct_name: &str,
+    under_key: Option<&str>,
+    key_path: &str,
+    options: &Options,
+    new_structs: &mut NewStructs,
+) -> TokenStream {
+    let name = nested_struct_name(struct_name, under_key, None, options);
+    let ident = format_ident!("{}", name);
+    let variants = tagged_variants(values);
+    new_structs.push((name, key_path.to_owned(), Cow::Owned(variants)));
+    quote!(#ident)
+}
+
+fn define_value(
+    value: &Value,
+    struct_name: &str,
+    under_key: Option<&str>,
+    under_index: Option<usize>,
+    key_path: &str,
+    options: &Options,
+) -> Result<TokenStream, Error> {
+    if options.structs.require_const_compatible {
+        ensure_const_compatible(value, key_path, options)?;
+    }
+    let is_container = matches!(value, Value::Option(_) | Value::Array(..) | Value::Vec(_));
+    if let Some(type_name) = type_override(key_path, options).filter(|_| !is_container) {
+        let incompatible = || Error::IncompatibleTypeOverride(key_path.into(), type_name.into());
+        let cast = |type_name: &str| {
+            parsing::cast_number(value, type_name)
+                .ok_or_else(incompatible)
+                .and_then(|value| {
+                    define_inferred_value(&value, struct_name, None, None, key_path, options)
+                })
+        };
+
+        return match split_type_override(type_name) {
+            (outer, None) if is_numeric_type(outer) => cast(outer),
+            (outer, inner) => {
+                let outer = parse_type_override(key_path, outer)?;
+                let inner = match inner {
+                    Some(inner) if is_numeric_type(inner) => cast(inner)?,
+                    _ => define_inferred_value(
+                        value,
+                        struct_name,
+                        under_key,
+                        under_index,
+                        key_path,
+                        options,
+                    )?,
+                };
+                Ok(quote!(#outer(#inner)))
+            }
+        };
+    }
+
+    define_inferred_value(
+        value,
+        struct_name,
+        under_key,
+        under_index,
+        key_path,
+        options,
+    )
+}
+
+fn define_inferred_value(
+    value: &Value,
+    struct_name: &str,
+    under_key: Option<&str>,
+    under_index: Option<usize>,
+    key_path: &str,
+    options: &Options,
+) -> Result<TokenStream, Error> {
+    if let Some(fields) = tuple_struct_fields(value, key_path, options) {
+        let name = tuple_struct_name(key_path, options).unwrap_or_default();
+        return define_struct_value(&fields, name, key_path, options);
+    }
+
+    Ok(match value {
+        Value::Unit => quote!(()),
+        Value::Bool(x) => quote!(#x),
+        Value::Char(x) => quote!(#x),
+        Value::I8(x) => quote!(#x),
+        Value::I16(x) => quote!(#x),
+        Value::I32(x) => quote!(#x),
+        Value::I64(x) => quote!(#x),
+        Value::I128(x) => quote!(#x),
+        Value::ISize(x) => quote!(#x),
+        Value::U8(x) => quote!(#x),
+        Value::U16(x) => quote!(#x),
+        Value::U32(x) => quote!(#x),
+        Value::Datetime(datetime) => datetime_value(datetime),
+        Value::Decimal(source) => decimal_value(source)?,
+        Value::U64(x) => quote!(#x),
+        Value::U128(x) => quote!(#x),
+        Value::USize(x) => quote!(#x),
+        Value::F32(x) => non_finite_float(f64::from(*x), quote!(f32)).unwrap_or_else(|| quote!(#x)),
+        Value::F64(x) => non_finite_float(*x, quote!(f64)).unwrap_or_else(|| quote!(#x)),
+        Value::String(x) => {
+            let alloc = alloc_crate(options);
+            quote!(#alloc::borrow::Cow::Borrowed(#x))
+        }
+        Value::Option(x) => match x {
+            Some(x) => {
+                let value =
+                    define_value(x, struct_name, under_key, under_index, key_path, options)?;
+                quote!(Some(#value))
+            }
+            None => quote!(None),
+        },
+        Value::Array(_, values) => {
+            let values = values
+                .iter()
+                .map(|value| {
+                    define_value(
+                        value,
+                        struct_name,
+                        under_key,
+                        under_index,
+                        key_path,
+                        options,
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            quote!([#(#values,)*])
+        }
+        Value::Vec(values) => {
+            let values = values
+                .iter()
+                .map(|value| {
+                    define_value(
+                        value,
+                        struct_name,
+                        under_key,
+                        under_index,
+                        key_path,
+                        options,
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let alloc = alloc_crate(options);
+            quote!(#alloc::borrow::Cow::Borrowed(&[#(#values,)*]))
+        }
+        Value::Tuple(values) => {
+            let values = values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    let key_path = child_path(key_path, &i.to_string());
+                    define_value(value, struct_name, under_key, Some(i), &key_path, options)
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            quote!((#(#values),*))
+        }
+        Value::Struct(mapping) if keep_as_map(key_path, options) => {
+            let item_path = child_path(key_path, "*");
+            let keys = mapping.0.keys();
+            let values = map_values(mapping)?
+                .iter()
+                .map(|value| {
+                    define_value(
+                        value,
+                        struct_name,
+                        under_key,
+                        under_index,
+                        &item_path,
+                        options,
+                    )
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let (map, string) = map_types(options);
+            quote!(#map::from([
+                #((#string::from(#keys), #values),)*
+            ]))
+        }
+        Value::Struct(fields) => {
+            let name = nested_struct_name(struct_name, under_key, under_index, options);
+            define_struct_value(fields, &name, key_path, options)?
+        }
+        Value::Tagged(tag_key, fields) => {
+            let enum_name = nested_struct_name(struct_name, under_key, None, options);
+            let tag = value.tag().unwrap_or_default();
+            let variant = variant_name(tag, options);
+            let variant_struct_name = nested_struct_name(&enum_name, Some(&variant), None, options);
+            let variant_path = child_path(key_path, tag);
+            let fields = struct_fields(fields, &variant_path, options)
+                .into_iter()
+                .filter(|(key, _)| *key != tag_key)
+                .map(|(key, value)| {
+                    let value = define_value(
+                        value,
+                        &variant_struct_name,
+                        Some(key),
+                        None,
+                        &child_path(&variant_path, key),
+                        options,
+                    )?;
+                    let key = field_name(key, &child_path(&variant_path, key), options);
+                    Ok(quote!(#key: #value,))
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let enum_name = format_ident!("{}", enum_name);
+            let variant = format_ident!("{}", variant);
+            quote!(#enum_name::#variant { #(#fields)* })
+        }
+    })
+}
+
+fn define_struct_value(
+    data: &Struct,
+    struct_name: &str,
+    key_path: &str,
+    options: &Options,
+) -> Result<TokenStream, Error> {
+    let mut fields = vec![];
+
+    for (key, value) in struct_fields(data, key_path, options) {
+        let value = define_value(
+            value,
+            struct_name,
+            Some(key),
+            None,
+            &child_path(key_path, key),
+            options,
+        )?;
+        fields.push((key, value));
+    }
+
+    let struct_name = format_ident!("{}", struct_name);
+    if tuple_struct_name(key_path, options).is_some() {
+        let values = fields.iter().map(|(_, value)| value);
+        return Ok(quote!(#struct_name(#(#values),*)));
+    }
+    if is_newtype(data, options) {
+        let value = &fields[0].1;
+        return Ok(quote!(#struct_name(#value)));
+    }
+
+    let fields = fields.iter().map(|(key, value)| {
+        let key = field_name(key, &child_path(key_path, key), options);
+        quote!(#key: #value,)
+    });
+    Ok(quote! {
+        #struct_name {
+            #(#fields)*
+        }
+    })
+}
+
+/// A const expression constructing the `rust_decimal::Decimal` for
+/// a decimal number. (See [`ParseOptions::decimal_strings`].)
+///
+/// [`ParseOptions::decimal_strings`]: crate::options::ParseOptions
+fn decimal_value(source: &str) -> Result<TokenStream, Error> {
+    let (negative, mantissa, scale) =
+        parsing::decimal_parts(source).ok_or_else(|| Error::InvalidDecimal(source.into()))?;
+    let (lo, mid, hi) = (
+        Literal::u32_unsuffixed(mantissa as u32),
+        Literal::u32_unsuffixed((mantissa >> 32) as u32),
+        Literal::u32_unsuffixed((mantissa >> 64) as u32),
+    );
+    let scale = Literal::u32_unsuffixed(scale);
+    Ok(quote!(rust_decimal::Decimal::from_parts(#lo, #mid, #hi, #negative, #scale)))
+}
+
+/// The `chrono` type for a datetime. (See [`DatetimeHandling`].)
+///
+/// [`DatetimeHandling`]: crate::options::DatetimeHandling
+fn datetime_type(datetime: &Datetime) -> TokenStream {
+    match (datetime.date, datetime.time, datetime.offset_minutes) {
+        (_, _, Some(_)) => quote!(chrono::DateTime<chrono::FixedOffset>),
+        (Some(_), Some(_), None) => quote!(chrono::NaiveDateTime),
+        (Some(_), None, None) => quote!(chrono::NaiveDate),
+        (None, _, None) => quote!(chrono::NaiveTime),
+    }
+}
+
+/// The constant for a float which can't be written as a literal,
+/// because it is infinite or NaN.
+fn non_finite_float(x: f64, float_type: TokenStream) -> Option<TokenStream> {
+    match x {
+        x if x.is_nan() => Some(quote!(#float_type::NAN)),
+        x if x == f64::INFINITY => Some(quote!(#float_type::INFINITY)),
+        x if x == f64::NEG_INFINITY => Some(quote!(#float_type::NEG_INFINITY)),
+        _ => None,
+    }
+}
+
+/// A const expression constructing the `chrono` value for a
+/// datetime.
+fn datetime_value(datetime: &Datetime) -> TokenStream {
+    let date = |(year, month, day): (i32, u32, u32)| {
+        let (year, month, day) = (
+            Literal::i32_unsuffixed(year),
+            Literal::u32_unsuffixed(month),
+            Literal::u32_unsuffixed(day),
+        );
+        quote!(chrono::NaiveDate::from_ymd_opt(#year, #month, #day).unwrap())
+    };
+    let time = |(hour, minute, second, nanosecond): (u32, u32, u32, u32)| {
+        let (hour, minute, second, nanosecond) = (
+            Literal::u32_unsuffixed(hour),
+            Literal::u32_unsuffixed(minute),
+            Literal::u32_unsuffixed(second),
+            Literal::u32_unsuffixed(nanosecond),
+        );
+        quote!(chrono::NaiveTime::from_hms_nano_opt(#hour, #minute, #second, #nanosecond).unwrap())
+    };
+    let naive = |datetime: Datetime| match (datetime.date, datetime.time) {
+        (Some(d), Some(t)) => {
+            let (d, t) = (date(d), time(t));
+            quote!(chrono::NaiveDateTime::new(#d, #t))
+        }
+        (Some(d), None) => date(d),
+        (None, t) => time(t.unwrap_or_default()),
+    };
+
+    match datetime.offset_minutes {
+        Some(offset) => {
+            let utc = naive(datetime.to_utc());
+            let offset = Literal::i32_unsuffixed(offset * 60);
+            quote! {
+                chrono::DateTime::from_naive_utc_and_offset(
+                    #utc,
+                    chrono::FixedOffset::east_opt(#offset).unwrap(),
+                )
+            }
+        }
+        None => naive(*datetime),
+    }
+}
+
+/// The fields of a struct, in the order given by
+/// [`StructOptions::sort_fields`].
+///
+/// The fields of tuple structs are always kept in order.
+///
+/// [`StructOptions::sort_fields`]: crate::options::StructOptions::sort_fields
+fn struct_fields<'a>(
+    data: &'a Struct,
+    key_path: &str,
+    options: &Options,
+) -> Vec<(&'a String, &'a Value)> {
+    let mut fields = data.0.iter().collect::<Vec<_>>();
+    if options.structs.sort_fields == FieldOrder::Alphabetical
+        && tuple_struct_name(key_path, options).is_none()
+    {
+        fields.sort_by_key(|(key, _)| *key);
+    }
+    fields
+}
+
+/// Whether the struct for this mapping should be a newtype. (See
+/// [`StructOptions::newtype_single_field`].)
+///
+/// [`StructOptions::newtype_single_field`]: crate::options::StructOptions::newtype_single_field
+fn is_newtype(data: &Struct, options: &Options) -> bool {
+    options.structs.newtype_single_field && data.0.len() == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::*;
+    use pretty_assertions::assert_eq;
+
+    fn assert_tokens(a: TokenStream, b: TokenStream) {
+        assert_eq!(a.to_string(), b.to_string())
+    }
+
+    #[test]
+    fn type_declarations() {
+        let whatever = &mut vec![];
+        let options = &Options::default();
+
+        fn some_struct() -> Value {
+            Value::Struct(Struct([("key".into(), Value::Unit)].into_iter().collect()))
+        }
+
+        let a_struct = some_struct();
+        let a_vec = Value::Vec(vec![some_struct(), some_struct()]);
+        let a_tuple = Value::Tuple(vec![some_struct(), some_struct()]);
+
+        assert_tokens(
+            type_of_value(&Value::Unit, "unused", None, None, "", options, whatever).unwrap(),
+            quote!(()),
+        );
+        assert_tokens(
+            type_of_value(&Value::F32(1.), "unused", None, None, "", options, whatever).unwrap(),
+            quote!(f32),
+        );
+        assert_tokens(
+            type_of_value(&a_struct, "StructName", None, None, "", options, whatever).unwrap(),
+            quote!(StructName),
+        );
+        assert_tokens(
+            type_of_value(&a_vec, "StructName", None, None, "", options, whatever).unwrap(),
+            quote!(std::borrow::Cow<'static, [StructName]>),
+        );
+        assert_tokens(
+            type_of_value(&a_tuple, "StructName", None, None, "", options, whatever).unwrap(),
+            quote!((StructName__0, StructName__1)),
+        );
+        assert_tokens(
+            type_of_value(
+                &Value::Struct(Struct(
+                    [(
+                        "nest".into(),
+                        Value::Tuple(vec![Value::Unit, some_struct()]),
+                    )]
+                    .into_iter()
+                    .collect(),
+                )),
+                "StructName",
+                None,
+                None,
+                "",
+                options,
+                whatever,
+            )
+            .unwrap(),
+            quote!(StructName),
+        );
+    }
+
+    #[test]
+    #[cfg(rustfmt_skip)]
+    fn value_declarations() {
+        let options = &Options::default();
+
+        fn some_struct() -> Value {
+            Value::Struct(Struct([("key".into(), Value::Unit)].into_iter().collect()))
+        };
+
+        let a_struct = some_struct();
+        let a_vec = Value::Vec(vec![some_struct(), some_struct()]);
+        let a_tuple = Value::Tuple(vec![some_struct(), some_struct()]);
+
+        assert_tokens(
+            define_value(&Value::Unit, options, "unused", None, None).unwrap(),
+            quote!(()),
+        );
+        assert_tokens(
+            define_value(&Value::F32(1.), options, "unused", None, None).unwrap(),
+            quote!(1f32),
+        );
+        assert_tokens(
+            define_value(&a_struct, options, "StructName", None, None).unwrap(),
+            quote!(StructName { key: () }),
+        );
+        assert_tokens(
+            define_value(&a_vec, options, "StructName", None, None).unwrap(),
+            quote!(std::borrow::Cow::Borrowed(&[
+                StructName { key: () },
+                StructName { key: () },
+            ])),
+        );
+        assert_tokens(
+            define_value(&a_tuple, options, "StructName", None, None).unwrap(),
+            quote!((StructName__0 { key: () }, StructName__1 { key: () })),
+        );
+        assert_tokens(
+            define_value(
+                &Value::Struct(Struct(
+                    [(
+                        "nest".into(),
+                        Value::Tuple(vec![Value::Unit, some_struct()]),
+                    )]
+                    .into_iter()
+                    .collect(),
+                )),
+                options,
+                "StructName",
+                None,
+                None,
+            )
+            .unwrap(),
+            quote!(StructName {
+                nest: ((), StructName__nest__1 { key: () }),
+            }),
+        );
+    }
+
+    #[test]
+    fn empty_struct() {
+        let fields = Struct([].into_iter().collect());
+        let result = define_structs(&fields, "Struct", None, &Options::minimal()).unwrap();
+        assert_tokens(
+            result,
+            quote!(
+                #[allow(non_camel_case_types)]
+                pub struct Struct {}
+            ),
+        );
+    }
+
+    #[test]
+    fn empty_struct_with_path() {
+        let fields = Struct([].into_iter().collect());
+        let result = define_structs(
+            &fields,
+            "Struct",
+            Some("./path/to/file.toml".as_ref()),
+            &Options {
+                source_path_const_name: Some("SOURCE_PATH".into()),
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote!(
+                #[allow(non_camel_case_types)]
+                pub struct Struct {}
+
+                impl Struct {
+                    pub const SOURCE_PATH: &'static str = "./path/to/file.toml";
+                }
+            ),
+        );
+    }
+
+    #[test]
+    fn big_struct() {
+        let fields = Struct(
+            [
+                ("a".into(), Value::Unit),
+                ("b".into(), Value::Bool(true)),
+                ("c".into(), Value::Char('a')),
+                ("d".into(), Value::I8(8)),
+                ("e".into(), Value::I16(16)),
+                ("f".into(), Value::I32(32)),
+                ("g".into(), Value::I64(64)),
+                ("h".into(), Value::I128(128)),
+                ("i".into(), Value::ISize(64)),
+                ("j".into(), Value::U8(8)),
+                ("k".into(), Value::U16(16)),
+                ("l".into(), Value::U32(32)),
+                ("m".into(), Value::U64(64)),
+                ("n".into(), Value::U128(128)),
+                ("o".into(), Value::USize(64)),
+                ("p".into(), Value::F32(32.)),
+                ("q".into(), Value::F64(64.)),
+                ("r".into(), Value::String("String".into())),
+                ("s".into(), Value::Option(Some(Box::new(Value::Unit)))),
+                ("t".into(), Value::Tuple(vec![Value::Unit; 2])),
+                ("u".into(), Value::Array(2, vec![Value::Unit; 2])),
+                ("v".into(), Value::Vec(vec![Value::Unit; 2])),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let result = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &Options {
+                structs: StructOptions {
+                    struct_data_const_name: Some("THINGY".into()),
+                    ..StructOptions::minimal()
+                },
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote!(
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub a: (),
+                    pub b: bool,
+                    pub c: char,
+                    pub d: i8,
+                    pub e: i16,
+                    pub f: i32,
+                    pub g: i64,
+                    pub h: i128,
+                    pub i: isize,
+                    pub j: u8,
+                    pub k: u16,
+                    pub l: u32,
+                    pub m: u64,
+                    pub n: u128,
+                    pub o: usize,
+                    pub p: f32,
+                    pub q: f64,
+                    pub r: std::borrow::Cow<'static, str>,
+                    pub s: Option<()>,
+                    pub t: ((), ()),
+                    pub u: [(); 2usize],
+                    pub v: std::borrow::Cow<'static, [()]>,
+                }
+
+                impl Struct {
+                    pub const THINGY: Struct = Struct {
+                        a: (),
+                        b: true,
+                        c: 'a',
+                        d: 8i8,
+                        e: 16i16,
+                        f: 32i32,
+                        g: 64i64,
+                        h: 128i128,
+                        i: 64isize,
+                        j: 8u8,
+                        k: 16u16,
+                        l: 32u32,
+                        m: 64u64,
+                        n: 128u128,
+                        o: 64usize,
+                        p: 32f32,
+                        q: 64f64,
+                        r: std::borrow::Cow::Borrowed("String"),
+                        s: Some(()),
+                        t: ((), ()),
+                        u: [(), (),],
+                        v: std::borrow::Cow::Borrowed(&[(), (),]),
+                    };
+                }
+            ),
+        );
+    }
+
+    #[test]
+    fn non_finite_floats() {
+        let fields = Struct::from_pairs([
+            ("a", Value::F64(f64::INFINITY)),
+            ("b", Value::F64(f64::NEG_INFINITY)),
+            ("c", Value::F32(f32::NAN)),
+        ]);
+        let result = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &Options {
+                structs: StructOptions {
+                    struct_data_const_name: Some("DATA".into()),
+                    ..StructOptions::minimal()
+                },
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote!(
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub a: f64,
+                    pub b: f64,
+                    pub c: f32,
+                }
+
+                impl Struct {
+                    pub const DATA: Struct = Struct {
+                        a: f64::INFINITY,
+                        b: f64::NEG_INFINITY,
+                        c: f32::NAN,
+                    };
+                }
+            ),
+        );
+    }
+
+    #[test]
+    fn struct_with_default_impl() {
+        let fields = Struct::from_pairs([
+            ("number", Value::I32(1)),
+            (
+                "nested",
+                Value::Struct(Struct::from_pairs([("string", Value::String("hi".into()))])),
+            ),
+        ]);
+        let result = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &Options {
+                structs: StructOptions {
+                    impl_default: true,
+                    ..StructOptions::minimal()
+                },
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub number: i32,
+                    pub nested: Struct__nested,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Struct__nested {
+                    pub string: std::borrow::Cow<'static, str>,
+                }
+
+                impl Default for Struct {
+                    fn default() -> Self {
+                        Struct {
+                            number: 1i32,
+                            nested: Struct__nested {
+                                string: std::borrow::Cow::Borrowed("hi"),
+                            },
+                        }
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn struct_with_kept_map() {
+        let fields = Struct::from_pairs([(
+            "plugins",
+            Value::Struct(Struct::from_pairs([
+                (
+                    "first",
+                    Value::Struct(Struct::from_pairs([("level", Value::I32(1))])),
+                ),
+                (
+                    "second",
+                    Value::Struct(Struct::from_pairs([("level", Value::I64(2))])),
+                ),
+            ])),
+        )]);
+        let options = Options {
+            structs: StructOptions {
+                impl_default: true,
+                keep_as_map: vec!["plugins".into()].into(),
+                ..StructOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_structs(&fields, "Struct", None, &options).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub plugins: std::collections::BTreeMap<String, Struct__plugins>,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Struct__plugins {
+                    pub level: i64,
+                }
+
+                impl Default for Struct {
+                    fn default() -> Self {
+                        Struct {
+                            plugins: std::collections::BTreeMap::from([
+                                (String::from("first"), Struct__plugins { level: 1i64, }),
+                                (String::from("second"), Struct__plugins { level: 2i64, }),
+                            ]),
+                        }
+                    }
+                }
+            },
+        );
+
+        let with_const = Options {
+            structs: StructOptions {
+                struct_data_const_name: Some("DATA".into()),
+                ..options.structs
+            },
+            ..Options::minimal()
+        };
+        assert!(matches!(
+            define_structs(&fields, "Struct", None, &with_const),
+            Err(Error::MapInConst(key_path)) if key_path == "plugins"
+        ));
+    }
+
+    #[test]
+    fn no_std_struct() {
+        let fields = Struct::from_pairs([
+            ("name", Value::String("one".into())),
+            ("list", Value::Vec(vec![Value::I32(1)])),
+            (
+                "map",
+                Value::Struct(Struct::from_pairs([("key", Value::Bool(true))])),
+            ),
+        ]);
+        let options = Options {
+            structs: StructOptions {
+                impl_default: true,
+                keep_as_map: vec!["map".into()].into(),
+                ..StructOptions::minimal()
+            },
+            output: OutputOptions {
+                no_std: true,
+                ..OutputOptions::new()
+            },
+            ..Options::minimal()
+        };
+        let result = define_structs(&fields, "Struct", None, &options).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub name: alloc::borrow::Cow<'static, str>,
+                    pub list: alloc::borrow::Cow<'static, [i32]>,
+                    pub map: alloc::collections::BTreeMap<alloc::string::String, bool>,
+                }
+
+                impl Default for Struct {
+                    fn default() -> Self {
+                        Struct {
+                            name: alloc::borrow::Cow::Borrowed("one"),
+                            list: alloc::borrow::Cow::Borrowed(&[1i32,]),
+                            map: alloc::collections::BTreeMap::from([
+                                (alloc::string::String::from("key"), true),
+                            ]),
+                        }
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn runtime_crate_struct() {
+        let fields = Struct::from_pairs([
+            ("name", Value::String("one".into())),
+            (
+                "map",
+                Value::Struct(Struct::from_pairs([("key", Value::Bool(true))])),
+            ),
+        ]);
+        let options = Options {
+            structs: StructOptions {
+                keep_as_map: vec!["map".into()].into(),
+                ..StructOptions::minimal()
+            },
+            output: OutputOptions {
+                runtime_crate: true,
+                ..OutputOptions::new()
+            },
+            ..Options::minimal()
+        };
+        let result = define_structs(&fields, "Struct", None, &options).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub name: ::edres_runtime::borrow::Cow<'static, str>,
+                    pub map: ::edres_runtime::collections::BTreeMap<
+                        ::edres_runtime::string::String,
+                        bool
+                    >,
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn decimals() {
+        assert_tokens(
+            decimal_value("-19.99").unwrap(),
+            quote!(rust_decimal::Decimal::from_parts(1999, 0, 0, true, 2)),
+        );
+        assert_tokens(
+            decimal_value("1.0000000000000000000000000001").unwrap(),
+            quote!(rust_decimal::Decimal::from_parts(
+                268435457, 1042612833, 542101086, false, 28
+            )),
+        );
+        assert!(matches!(
+            decimal_value("99999999999999999999999999999.9"),
+            Err(Error::InvalidDecimal(_))
+        ));
+    }
+
+    #[test]
+    fn chrono_datetimes() {
+        let datetime = Datetime::parse("1979-05-27T00:32:00-07:00").unwrap();
+        let date = Datetime::parse("1979-05-27").unwrap();
+
+        assert_tokens(
+            datetime_type(&datetime),
+            quote!(chrono::DateTime<chrono::FixedOffset>),
+        );
+        assert_tokens(
+            datetime_value(&datetime),
+            quote! {
+                chrono::DateTime::from_naive_utc_and_offset(
+                    chrono::NaiveDateTime::new(
+                        chrono::NaiveDate::from_ymd_opt(1979, 5, 27).unwrap(),
+                        chrono::NaiveTime::from_hms_nano_opt(7, 32, 0, 0).unwrap()
+                    ),
+                    chrono::FixedOffset::east_opt(-25200).unwrap(),
+                )
+            },
+        );
+        assert_tokens(datetime_type(&date), quote!(chrono::NaiveDate));
+        assert_tokens(
+            datetime_value(&date),
+            quote!(chrono::NaiveDate::from_ymd_opt(1979, 5, 27).unwrap()),
+        );
+
+        let late = Datetime::parse("1999-12-31T23:00:00-02:00").unwrap();
+        assert_eq!(late.to_utc().date, Some((2000, 1, 1)));
+        assert_eq!(late.to_utc().time, Some((1, 0, 0, 0)));
+    }
+
+    #[test]
+    fn struct_with_newtypes() {
+        let fields = Struct::from_pairs([
+            (
+                "wrapper",
+                Value::Struct(Struct::from_pairs([("value", Value::I64(3))])),
+            ),
+            ("other", Value::Bool(true)),
+        ]);
+        let options = Options {
+            structs: StructOptions {
+                struct_data_const_name: Some("DATA".into()),
+                newtype_single_field: true,
+                ..StructOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_structs(&fields, "Struct", None, &options).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub wrapper: Struct__wrapper,
+                    pub other: bool,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Struct__wrapper(pub i64);
+
+                impl Struct {
+                    pub const DATA: Struct = Struct {
+                        wrapper: Struct__wrapper(3i64),
+                        other: true,
+                    };
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn struct_with_private_fields_and_accessors() {
+        let fields = Struct::from_pairs([
+            ("name", Value::String("config".into())),
+            (
+                "limits",
+                Value::Struct(Struct::from_pairs([("max", Value::I64(3))])),
+            ),
+        ]);
+        let options = Options {
+            structs: StructOptions {
+                struct_data_const_name: Some("DATA".into()),
+                field_visibility: Visibility::Private,
+                generate_accessors: true,
+                ..StructOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_structs(&fields, "Struct", None, &options).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    name: std::borrow::Cow<'static, str>,
+                    limits: Struct__limits,
+                }
+
+                impl Struct {
+                    pub const fn name(&self) -> &std::borrow::Cow<'static, str> {
+                        &self.name
+                    }
+                    pub const fn limits(&self) -> &Struct__limits {
+                        &self.limits
+                    }
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Struct__limits {
+                    max: i64,
+                }
+
+                impl Struct__limits {
+                    pub const fn max(&self) -> &i64 {
+                        &self.max
+                    }
+                }
+
+                impl Struct {
+                    pub const DATA: Struct = Struct {
+                        name: std::borrow::Cow::Borrowed("config"),
+                        limits: Struct__limits {
+                            max: 3i64,
+                        },
+                    };
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn struct_with_pascal_nested_names() {
+        let fields = Struct::from_pairs([
+            (
+                "server_config",
+                Value::Struct(Struct::from_pairs([("port", Value::I32(80))])),
+            ),
+            (
+                "pair",
+                Value::Tuple(vec![
+                    Value::I32(1),
+                    Value::Struct(Struct::from_pairs([("second", Value::Bool(true))])),
+                ]),
+            ),
+        ]);
+        let result = define_structs(
+            &fields,
+            "Config",
+            None,
+            &Options {
+                structs: StructOptions {
+                    nested_struct_naming: StructNaming::Pascal,
+                    ..StructOptions::minimal()
+                },
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Config {
+                    pub server_config: ConfigServerConfig,
+                    pub pair: (i32, ConfigPair1),
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct ConfigServerConfig {
+                    pub port: i32,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct ConfigPair1 {
+                    pub second: bool,
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn struct_with_nested_name_separator() {
+        let fields = Struct::from_pairs([
+            (
+                "server",
+                Value::Struct(Struct::from_pairs([("port", Value::I32(80))])),
+            ),
+            (
+                "pair",
+                Value::Tuple(vec![
+                    Value::Struct(Struct::from_pairs([("first", Value::Bool(true))])),
+                    Value::I32(2),
+                ]),
+            ),
+        ]);
+        let options = |separator: &'static str| Options {
+            structs: StructOptions {
+                nested_name_separator: separator.into(),
+                ..StructOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_structs(&fields, "Config", None, &options("_")).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Config {
+                    pub server: Config_server,
+                    pub pair: (Config_pair_0, i32),
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Config_server {
+                    pub port: i32,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Config_pair_0 {
+                    pub first: bool,
+                }
+            },
+        );
+
+        let error = define_structs(&fields, "Config", None, &options("::")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid value `::` for option `structs.nested_name_separator`: \
+            expected only letters, digits, and underscores"
+        );
+    }
+
+    #[test]
+    fn struct_with_dedup_values() {
+        let point = |x| Value::Struct(Struct::from_pairs([("x", Value::I32(x))]));
+        let fields =
+            Struct::from_pairs([("points", Value::Vec(vec![point(1), point(2), point(1)]))]);
+        let options = Options {
+            structs: StructOptions {
+                struct_data_const_name: Some("DATA".into()),
+                ..StructOptions::minimal()
+            },
+            output: OutputOptions {
+                dedup_values: true,
+                ..OutputOptions::new()
+            },
+            ..Options::minimal()
+        };
+        let result = define_structs(&fields, "Shape", None, &options).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Shape {
+                    pub points: std::borrow::Cow<'static, [Shape__points]>,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Shape__points {
+                    pub x: i32,
+                }
+
+                impl Shape {
+                    pub const DATA: Shape = Shape {
+                        points: std::borrow::Cow::Borrowed(&[
+                            Shape__points_0,
+                            Shape__points { x: 2i32, },
+                            Shape__points_0,
+                        ]),
+                    };
+                }
+
+                #[allow(non_upper_case_globals)]
+                const Shape__points_0: Shape__points = Shape__points { x: 1i32, };
+            },
+        );
+    }
+
+    #[test]
+    fn struct_with_sorted_fields() {
+        let fields = Struct::from_pairs([
+            ("port", Value::I32(80)),
+            (
+                "limits",
+                Value::Struct(Struct::from_pairs([
+                    ("timeout", Value::U32(30)),
+                    ("connections", Value::U32(8)),
+                ])),
+            ),
+            ("host", Value::String("localhost".into())),
+        ]);
+        let options = Options {
+            structs: StructOptions {
+                struct_data_const_name: Some("DATA".into()),
+                sort_fields: FieldOrder::Alphabetical,
+                ..StructOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_structs(&fields, "Config", None, &options).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Config {
+                    pub host: std::borrow::Cow<'static, str>,
+                    pub limits: Config__limits,
+                    pub port: i32,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Config__limits {
+                    pub connections: u32,
+                    pub timeout: u32,
+                }
+
+                impl Config {
+                    pub const DATA: Config = Config {
+                        host: std::borrow::Cow::Borrowed("localhost"),
+                        limits: Config__limits {
+                            connections: 8u32,
+                            timeout: 30u32,
+                        },
+                        port: 80i32,
+                    };
+                }
+            },
+        );
+
+        let reordered = Struct::from_pairs([
+            ("host", Value::String("localhost".into())),
+            ("port", Value::I32(80)),
+            (
+                "limits",
+                Value::Struct(Struct::from_pairs([
+                    ("connections", Value::U32(8)),
+                    ("timeout", Value::U32(30)),
+                ])),
+            ),
+        ]);
+        assert_eq!(
+            define_structs(&reordered, "Config", None, &options)
+                .unwrap()
+                .to_string(),
+            define_structs(&fields, "Config", None, &options)
+                .unwrap()
+                .to_string(),
+        );
+    }
+
+    #[test]
+    fn struct_with_unsupported_derives() {
+        let fields = Struct::from_pairs([
+            ("name", Value::String("fast".into())),
+            (
+                "motion",
+                Value::Struct(Struct::from_pairs([("speed", Value::F64(1.5))])),
+            ),
+        ]);
+        let options = |skip_unsupported_derives| Options {
+            structs: StructOptions {
+                derived_traits: vec!["Debug".into(), "PartialEq".into(), "Eq".into()].into(),
+                skip_unsupported_derives,
+                ..StructOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+
+        let error = define_structs(&fields, "Config", None, &options(false)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Cannot derive `Eq` for `Config` because `motion.speed` is a float \
+            (see `StructOptions::skip_unsupported_derives`)"
+        );
+
+        let result = define_structs(&fields, "Config", None, &options(true)).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                #[derive(Debug, PartialEq)]
+                pub struct Config {
+                    pub name: std::borrow::Cow<'static, str>,
+                    pub motion: Config__motion,
+                }
+
+                #[allow(non_camel_case_types)]
+                #[derive(Debug, PartialEq)]
+                pub struct Config__motion {
+                    pub speed: f64,
+                }
+            },
+        );
+
+        let overridden = Options {
+            parse: ParseOptions {
+                type_overrides: vec![("motion.speed".into(), "Speed(f64)".into())].into(),
+                ..ParseOptions::new()
+            },
+            ..options(false)
+        };
+        let result = define_structs(&fields, "Config", None, &overridden).unwrap();
+        assert!(result.to_string().contains("Eq"));
+    }
+
+    #[test]
+    fn struct_with_builder() {
+        let fields = Struct::from_pairs([
+            ("port", Value::I32(80)),
+            ("host", Value::String("localhost".into())),
+        ]);
+        let options = Options {
+            structs: StructOptions {
+                generate_builder: true,
+                ..StructOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_structs(&fields, "Config", None, &options).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Config {
+                    pub port: i32,
+                    pub host: std::borrow::Cow<'static, str>,
+                }
+
+                impl Config {
+                    pub fn builder() -> ConfigBuilder {
+                        ConfigBuilder::new()
+                    }
+                }
+
+                pub struct ConfigBuilder(Config);
+
+                impl ConfigBuilder {
+                    pub fn new() -> Self {
+                        ConfigBuilder(Config {
+                            port: 80i32,
+                            host: std::borrow::Cow::Borrowed("localhost"),
+                        })
+                    }
+
+                    pub fn port(mut self, port: i32) -> Self {
+                        self.0.port = port;
+                        self
+                    }
+
+                    pub fn host(mut self, host: std::borrow::Cow<'static, str>) -> Self {
+                        self.0.host = host;
+                        self
+                    }
+
+                    pub fn build(self) -> Config {
+                        self.0
+                    }
+                }
+
+                impl Default for ConfigBuilder {
+                    fn default() -> Self {
+                        Self::new()
+                    }
+                }
+            },
+        );
+
+        let clashing = Struct::from_pairs([("build", Value::Bool(true))]);
+        let error = define_structs(&clashing, "Config", None, &options).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Generated name `build` is used more than once"
+        );
+    }
+
+    #[test]
+    fn struct_with_serde_defaults() {
+        let fields = Struct::from_pairs([
+            ("port", Value::I64(80)),
+            (
+                "limits",
+                Value::Struct(Struct::from_pairs([("connections", Value::U32(8))])),
+            ),
+        ]);
+        let options = |serde_defaults| Options {
+            structs: StructOptions {
+                serde_defaults,
+                ..StructOptions::minimal()
+            },
+            serde_support: SerdeSupport::Mixed {
+                serialize: false,
+                deserialize: true,
+            },
+            ..Options::minimal()
+        };
+
+        let result = define_structs(
+            &fields,
+            "Config",
+            None,
+            &options(SerdeDefaults::DefaultTrait),
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Deserialize)]
+                pub struct Config {
+                    #[serde(default)]
+                    pub port: i64,
+                    #[serde(default)]
+                    pub limits: Config__limits,
+                }
+
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Deserialize)]
+                pub struct Config__limits {
+                    #[serde(default)]
+                    pub connections: u32,
+                }
+            },
+        );
+
+        let result =
+            define_structs(&fields, "Config", None, &options(SerdeDefaults::FromConst)).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Deserialize)]
+                pub struct Config {
+                    #[serde(default = "Config::default_port")]
+                    pub port: i64,
+                    #[serde(default = "Config::default_limits")]
+                    pub limits: Config__limits,
+                }
+
+                impl Config {
+                    fn default_port() -> i64 {
+                        80i64
+                    }
+
+                    fn default_limits() -> Config__limits {
+                        Config__limits {
+                            connections: 8u32,
+                        }
+                    }
+                }
+
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Deserialize)]
+                pub struct Config__limits {
+                    #[serde(default = "Config__limits::default_connections")]
+                    pub connections: u32,
+                }
+
+                impl Config__limits {
+                    fn default_connections() -> u32 {
+                        8u32
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn struct_with_validate_fn() {
+        let fields = Struct::from_pairs([
+            (
+                "name",
+                Value::Option(Some(Box::new(Value::String("x".into())))),
+            ),
+            ("unset", Value::Option(None)),
+            (
+                "nested",
+                Value::Struct(Struct::from_pairs([(
+                    "port",
+                    Value::Option(Some(Box::new(Value::I32(80)))),
+                )])),
+            ),
+        ]);
+        let result = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &Options {
+                structs: StructOptions {
+                    validate_fn_name: Some("validate".into()),
+                    ..StructOptions::minimal()
+                },
+                serde_support: SerdeSupport::Mixed {
+                    serialize: false,
+                    deserialize: true,
+                },
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Deserialize)]
+                #[serde(deny_unknown_fields)]
+                pub struct Struct {
+                    pub name: Option<std::borrow::Cow<'static, str> >,
+                    pub unset: Option<()>,
+                    pub nested: Struct__nested,
+                }
+
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Deserialize)]
+                #[serde(deny_unknown_fields)]
+                pub struct Struct__nested {
+                    pub port: Option<i32>,
+                }
+
+                impl Struct {
+                    pub fn validate(&self) -> Result<(), String> {
+                        match &self.name {
+                            Some(_) => {}
+                            None => return Err(String::from("Missing value at `name`")),
+                        }
+                        match &self.nested.port {
+                            Some(_) => {}
+                            None => return Err(String::from("Missing value at `nested.port`")),
+                        }
+                        Ok(())
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn struct_requiring_const_compatible_values() {
+        let fields = Struct::from_pairs([
+            ("name", Value::String("edres".into())),
+            (
+                "nested",
+                Value::Struct(Struct::from_pairs([(
+                    "plugins",
+                    Value::Struct(Struct::from_pairs([("first", Value::I64(1))])),
+                )])),
+            ),
+        ]);
+        let options = |type_overrides: Vec<_>, keep_as_map: Vec<_>| Options {
+            parse: ParseOptions {
+                type_overrides: type_overrides.into(),
+                ..ParseOptions::new()
+            },
+            structs: StructOptions {
+                impl_default: true,
+                require_const_compatible: true,
+                keep_as_map: keep_as_map.into(),
+                ..StructOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+
+        assert!(define_structs(&fields, "Struct", None, &options(vec![], vec![])).is_ok());
+        assert!(define_structs(
+            &fields,
+            "Struct",
+            None,
+            &options(vec![("name".into(), "Name".into())], vec![])
+        )
+        .is_ok());
+
+        let error = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &options(vec![], vec!["nested.plugins".into()]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Value at key `nested.plugins` cannot be used in a const, because it needs a map \
+             (see `StructOptions::require_const_compatible`)"
+        );
+
+        let error = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &options(vec![("name".into(), "std::boxed::Box<str>".into())], vec![]),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::NotConstCompatible(path, reason) if path == "name" && reason == "a `Box`"
+        ));
+    }
+
+    #[test]
+    fn struct_with_tuple_structs() {
+        let vec3 = |x, y, z| Value::Array(3, vec![Value::F64(x), Value::F64(y), Value::F64(z)]);
+        let fields = Struct::from_pairs([
+            ("position", vec3(1.0, 2.0, 3.0)),
+            (
+                "velocity",
+                Value::Option(Some(Box::new(vec3(0.0, 0.0, 1.0)))),
+            ),
+            (
+                "path",
+                Value::Vec(vec![vec3(0.0, 0.0, 0.0), vec3(1.0, 1.0, 1.0)]),
+            ),
+            (
+                "label",
+                Value::Tuple(vec![Value::I64(1), Value::String("one".into())]),
+            ),
+        ]);
+        let result = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &Options {
+                parse: ParseOptions {
+                    type_overrides: vec![("label.0".into(), "u8".into())].into(),
+                    ..ParseOptions::new()
+                },
+                structs: StructOptions {
+                    struct_data_const_name: Some("DATA".into()),
+                    sort_fields: FieldOrder::Alphabetical,
+                    tuple_structs: vec![
+                        ("position".into(), "Vec3".into()),
+                        ("velocity".into(), "Vec3".into()),
+                        ("path".into(), "Vec3".into()),
+                        ("label".into(), "Label".into()),
+                    ]
+                    .into(),
+                    ..StructOptions::minimal()
+                },
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub label: Label,
+                    pub path: std::borrow::Cow<'static, [Vec3]>,
+                    pub position: Vec3,
+                    pub velocity: Option<Vec3>,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Label(pub u8, pub std::borrow::Cow<'static, str>);
+
+                #[allow(non_camel_case_types)]
+                pub struct Vec3(pub f64, pub f64, pub f64);
+
+                impl Struct {
+                    pub const DATA: Struct = Struct {
+                        label: Label(1u8, std::borrow::Cow::Borrowed("one")),
+                        path: std::borrow::Cow::Borrowed(&[
+                            Vec3(0f64, 0f64, 0f64),
+                            Vec3(1f64, 1f64, 1f64),
+                        ]),
+                        position: Vec3(1f64, 2f64, 3f64),
+                        velocity: Some(Vec3(0f64, 0f64, 1f64)),
+                    };
+                }
+            },
+        );
+
+        let error = define_structs(
+            &Struct::from_pairs([("position", Value::F64(1.0))]),
+            "Struct",
+            None,
+            &Options {
+                structs: StructOptions {
+                    tuple_structs: vec![("position".into(), "Vec3".into())].into(),
+                    ..StructOptions::minimal()
+                },
+                ..Options::minimal()
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ExpectedFixedSequence(path) if path == "position"));
+    }
+
+    #[test]
+    fn struct_with_keyword_fields() {
+        let fields =
+            Struct::from_pairs([("type", Value::Bool(true)), ("self", Value::Bool(false))]);
+        let result = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &Options {
+                structs: StructOptions {
+                    struct_data_const_name: Some("DATA".into()),
+                    generate_accessors: true,
+                    ..StructOptions::minimal()
+                },
+                serde_support: SerdeSupport::Yes,
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Serialize, serde::Deserialize)]
+                pub struct Struct {
+                    pub r#type: bool,
+                    #[serde(rename = "self")]
+                    pub self_: bool,
+                }
+
+                impl Struct {
+                    pub const fn r#type(&self) -> &bool {
+                        &self.r#type
+                    }
+                    pub const fn self_(&self) -> &bool {
+                        &self.self_
+                    }
+                }
+
+                impl Struct {
+                    pub const DATA: Struct = Struct {
+                        r#type: true,
+                        self_: false,
+                    };
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn keywords_as_field_names() {
+        let options = Options {
+            serde_support: SerdeSupport::Yes,
+            ..Options::minimal()
+        };
+        for &keyword in KEYWORDS {
+            let data = Struct::from_pairs([(keyword, Value::Bool(true))]);
+            let tokens = define_structs(&data, "Struct", None, &options)
+                .unwrap()
+                .to_string();
+            let field = field_name(keyword, keyword, &options).to_string();
+            assert_ne!(field, keyword);
+            assert!(
+                tokens.contains(&format!("pub {} : bool", field)),
+                "{}",
+                tokens
+            );
+
+            let renamed = tokens.contains(&format!("serde (rename = \"{}\")", keyword));
+            assert_eq!(renamed, NON_RAW_KEYWORDS.contains(&keyword), "{}", tokens);
+        }
+    }
+
+    #[test]
+    fn struct_with_conversion() {
+        let fields = Struct::from_pairs([
+            ("name", Value::String("edres".into())),
+            ("type", Value::I64(1)),
+        ]);
+        let result = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &Options {
+                structs: StructOptions {
+                    convert_into: Some("crate::config::Config".into()),
+                    ..StructOptions::minimal()
+                },
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub name: std::borrow::Cow<'static, str>,
+                    pub r#type: i64,
+                }
+
+                impl From<Struct> for crate::config::Config {
+                    fn from(value: Struct) -> Self {
+                        crate::config::Config {
+                            name: value.name.into(),
+                            r#type: value.r#type.into(),
+                        }
+                    }
+                }
+            },
+        );
+
+        let error = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &Options {
+                structs: StructOptions {
+                    convert_into: Some("Vec<Config>".into()),
+                    ..StructOptions::minimal()
+                },
+                ..Options::minimal()
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::InvalidOptions { field, .. } if field == "structs.convert_into"
+        ));
+    }
+
+    #[test]
+    fn struct_with_renamed_fields() {
+        let fields = Struct::from_pairs([
+            ("type", Value::String("circle".into())),
+            (
+                "nested",
+                Value::Struct(Struct::from_pairs([("match", Value::Bool(true))])),
+            ),
+        ]);
+        let result = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &Options {
+                structs: StructOptions {
+                    struct_data_const_name: Some("DATA".into()),
+                    field_renames: vec![
+                        ("type".into(), "kind".into()),
+                        ("nested.match".into(), "r#match".into()),
+                    ]
+                    .into(),
+                    ..StructOptions::minimal()
+                },
+                serde_support: SerdeSupport::Yes,
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Serialize, serde::Deserialize)]
+                pub struct Struct {
+                    #[serde(rename = "type")]
+                    pub kind: std::borrow::Cow<'static, str>,
+                    pub nested: Struct__nested,
+                }
+
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Serialize, serde::Deserialize)]
+                pub struct Struct__nested {
+                    pub r#match: bool,
+                }
+
+                impl Struct {
+                    pub const DATA: Struct = Struct {
+                        kind: std::borrow::Cow::Borrowed("circle"),
+                        nested: Struct__nested { r#match: true, },
+                    };
+                }
+            },
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn struct_with_tagged_enum() {
+        let source = r#"{
+            "shapes": [
+                {"type": "circle", "radius": 3},
+                {"type": "rect", "w": 1, "h": 2.5},
+                {"type": "circle", "radius": 4, "label": "big"}
+            ]
+        }"#;
+        let options = Options {
+            parse: ParseOptions {
+                heterogeneous_arrays: HeterogeneousArrayMode::Tagged,
+                ..ParseOptions::new()
+            },
+            structs: StructOptions {
+                struct_data_const_name: Some("DATA".into()),
+                ..StructOptions::minimal()
+            },
+            serde_support: SerdeSupport::Yes,
+            ..Options::minimal()
+        };
+        let data = crate::parsing::json::parse_source(source, &options.parse)
+            .unwrap()
+            .assume_struct()
+            .unwrap();
+        let result = define_structs(&data, "Struct", None, &options).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Serialize, serde::Deserialize)]
+                pub struct Struct {
+                    pub shapes: std::borrow::Cow<'static, [Struct__shapes]>,
+                }
+
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Serialize, serde::Deserialize)]
+                #[serde(tag = "type")]
+                pub enum Struct__shapes {
+                    #[serde(rename = "circle")]
+                    Circle {
+                        radius: i64,
+                        label: Option<std::borrow::Cow<'static, str> >,
+                    },
+                    #[serde(rename = "rect")]
+                    Rect { w: i64, h: f64, },
+                }
+
+                impl Struct {
+                    pub const DATA: Struct = Struct {
+                        shapes: std::borrow::Cow::Borrowed(&[
+                            Struct__shapes::Circle { radius: 3i64, label: None, },
+                            Struct__shapes::Rect { w: 1i64, h: 2.5f64, },
+                            Struct__shapes::Circle {
+                                radius: 4i64,
+                                label: Some(std::borrow::Cow::Borrowed("big")),
+                            },
+                        ]),
+                    };
+                }
+            },
+        );
+
+        let options = Options {
+            structs: StructOptions {
+                derived_traits: vec!["Eq".into()].into(),
+                ..StructOptions::minimal()
+            },
+            ..options
+        };
+        let error = define_structs(&data, "Struct", None, &options).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::UnsupportedDerive { key_path, .. } if key_path == "shapes.rect.h"
+        ));
+    }
+
+    #[test]
+    fn struct_with_type_overrides() {
+        let fields = Struct::from_pairs([
+            ("id", Value::I64(5)),
+            ("other_id", Value::I64(6)),
+            (
+                "server",
+                Value::Struct(Struct::from_pairs([
+                    ("port", Value::I64(8080)),
+                    ("ports", Value::Vec(vec![Value::I64(80), Value::I64(443)])),
+                ])),
+            ),
+        ]);
+        let result = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &Options {
+                parse: ParseOptions {
+                    type_overrides: vec![
+                        ("id".into(), "MyId".into()),
+                        ("other_id".into(), "crate::OtherId(u32)".into()),
+                        ("server.port".into(), "u16".into()),
+                        ("server.ports".into(), "u16".into()),
+                    ]
+                    .into(),
+                    ..ParseOptions::new()
+                },
+                structs: StructOptions {
+                    struct_data_const_name: Some("DATA".into()),
+                    ..StructOptions::minimal()
+                },
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub id: MyId,
+                    pub other_id: crate::OtherId,
+                    pub server: Struct__server,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Struct__server {
+                    pub port: u16,
+                    pub ports: std::borrow::Cow<'static, [u16]>,
+                }
+
+                impl Struct {
+                    pub const DATA: Struct = Struct {
+                        id: MyId(5i64),
+                        other_id: crate::OtherId(6u32),
+                        server: Struct__server {
+                            port: 8080u16,
+                            ports: std::borrow::Cow::Borrowed(&[80u16, 443u16,]),
+                        },
+                    };
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn incompatible_type_override() {
+        let fields = Struct::from_pairs([("port", Value::I64(70000))]);
+        let result = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &Options {
+                parse: ParseOptions {
+                    type_overrides: vec![("port".into(), "u16".into())].into(),
+                    ..ParseOptions::new()
+                },
+                ..Options::new()
+            },
+        );
+        assert!(matches!(
+            result,
+            Err(Error::IncompatibleTypeOverride(key, ty)) if key == "port" && ty == "u16"
+        ));
+    }
+
+    #[test]
+    fn struct_with_vecs() {
+        let fields = Struct(
+            [
+                ("a".into(), Value::Vec(vec![])),
+                ("b".into(), Value::Vec(vec![Value::Bool(true)])),
+                (
+                    "c".into(),
+                    Value::Vec(vec![Value::Bool(true), Value::Bool(false)]),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let result = define_structs(&fields, "Struct", None, &Options::minimal()).unwrap();
+        assert_tokens(
+            result,
+            quote!(
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub a: std::borrow::Cow<'static, [()]>,
+                    pub b: std::borrow::Cow<'static, [bool]>,
+                    pub c: std::borrow::Cow<'static, [bool]>,
+                }
+            ),
+        );
+    }
+
+    #[test]
+    fn struct_with_arrays() {
+        let fields = Struct(
+            [
+                ("a".into(), Value::Array(0, vec![])),
+                ("b".into(), Value::Array(1, vec![Value::Bool(true)])),
+                (
+                    "c".into(),
+                    Value::Array(2, vec![Value::Bool(true), Value::Bool(false)]),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let result = define_structs(&fields, "Struct", None, &Options::minimal()).unwrap();
+        assert_tokens(
+            result,
+            quote!(
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub a: [(); 0usize],
+                    pub b: [bool; 1usize],
+                    pub c: [bool; 2usize],
+                }
+            ),
+        );
+    }
+
+    #[test]
+    fn nested_struct() {
+        let fields = Struct(
+            [(
+                "nested".into(),
+                Value::Struct(Struct(
+                    [("inner".into(), Value::Bool(true))].into_iter().collect(),
+                )),
+            )]
+            .into_iter()
+            .collect(),
+        );
+        let result = define_structs(&fields, "Struct", None, &Options::minimal()).unwrap();
+        assert_tokens(
+            result,
+            quote!(
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub nested: Struct__nested,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Struct__nested {
+                    pub inner: bool,
+                }
+            ),
+        );
+    }
+
+    #[test]
+    fn nested_struct_with_derives() {
+        let fields = Struct(
+            [(
+                "nested".into(),
+                Value::Struct(Struct(
+                    [("inner".into(), Value::Bool(true))].into_iter().collect(),
+                )),
+            )]
+            .into_iter()
+            .collect(),
+        );
+        let result = define_structs(
+            &fields,
+            "Struct",
+            None,
+            &Options {
+                serde_support: SerdeSupport::Yes,
+                structs: StructOptions {
+                    derived_traits: vec!["Debug".into(), "Clone".into()].into(),
+                    ..StructOptions::minimal()
+                },
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote!(
+                #[allow(non_camel_case_types)]
+                #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+                pub struct Struct {
+                    pub nested: Struct__nested,
+                }
+
+                #[allow(non_camel_case_types)]
+                #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+                pub struct Struct__nested {
+                    pub inner: bool,
+                }
+            ),
+        );
+    }
+
+    #[test]
+    fn more_nested_struct() {
+        let fields = Struct(
+            [(
+                "nested".into(),
+                Value::Struct(Struct(
+                    [(
+                        "nested_again".into(),
+                        Value::Struct(Struct(
+                            [("inner".into(), Value::Bool(true))].into_iter().collect(),
+                        )),
+                    )]
+                    .into_iter()
+                    .collect(),
+                )),
+            )]
+            .into_iter()
+            .collect(),
+        );
+        let result = define_structs(&fields, "Struct", None, &Options::minimal()).unwrap();
+        assert_tokens(
+            result,
+            quote!(
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub nested: Struct__nested,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Struct__nested {
+                    pub nested_again: Struct__nested__nested_again,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Struct__nested__nested_again {
+                    pub inner: bool,
+                }
+            ),
+        );
+    }
+
+    #[test]
+    fn structs_in_tuples() {
+        let fields = Struct(
+            [(
+                "tuple".into(),
+                Value::Tuple(vec![
+                    Value::Bool(true),
+                    Value::Struct(Struct(
+                        [("struct_a".into(), Value::Bool(true))]
+                            .into_iter()
+                            .collect(),
+                    )),
+                    Value::Struct(Struct(
+                        [("struct_b".into(), Value::Bool(true))]
+                            .into_iter()
+                            .collect(),
+                    )),
+                ]),
+            )]
+            .into_iter()
+            .collect(),
+        );
+        let result = define_structs(&fields, "Struct", None, &Options::minimal()).unwrap();
+        assert_tokens(
+            result,
+            quote!(
+                #[allow(non_camel_case_types)]
+                pub struct Struct {
+                    pub tuple: (bool, Struct__tuple__1, Struct__tuple__2),
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Struct__tuple__1 {
+                    pub struct_a: bool,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct Struct__tuple__2 {
+                    pub struct_b: bool,
+                }
+            ),
+        );
+    }
+
+    #[test]
+    fn simple_enum() {
+        let mapping = Map([
+            ("First".into(), Value::I32(1)),
+            ("Second".into(), Value::I32(2)),
+        ]
+        .into_iter()
+        .collect());
+        let result = define_enum_from_keys(&mapping, "Enum", None, &Options::minimal()).unwrap();
+
+        assert_tokens(
+            result,
+            quote!(
+                pub enum Enum {
+                    First,
+                    Second,
+                }
+            ),
+        );
+    }
+
+    #[test]
+    fn enum_with_repr() {
+        let mapping = Map::from_pairs([("First", Value::I32(1)), ("Second", Value::I32(2))]);
+        let result = define_enum_from_keys(
+            &mapping,
+            "Enum",
+            None,
+            &Options {
+                enums: EnumOptions {
+                    repr: Some(EnumRepr::U8),
+                    ..EnumOptions::minimal()
+                },
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+
+        assert_tokens(
+            result,
+            quote! {
+                #[repr(u8)]
+                pub enum Enum {
+                    First,
+                    Second,
+                }
+
+                impl Enum {
+                    pub const fn as_index(self) -> usize {
+                        self as usize
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn nested_enums() {
+        let mapping = Map::from_pairs([
+            (
+                "Weapons",
+                Value::Struct(Struct::from_pairs([
+                    ("Sword", Value::I32(1)),
+                    ("Bow", Value::I32(2)),
+                ])),
+            ),
+            (
+                "Armor",
+                Value::Struct(Struct::from_pairs([("Helmet", Value::I32(3))])),
+            ),
+        ]);
+        let options = |sort_variants_by| Options {
+            enums: EnumOptions {
+                nested: true,
+                all_variants_const_name: Some("ALL".into()),
+                sort_variants_by,
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_enum_from_keys(
+            &mapping,
+            "Category",
+            None,
+            &options(VariantSort::SourceOrder),
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                pub enum Category {
+                    Weapons,
+                    Armor,
+                }
+
+                impl Category {
+                    pub const WEAPONS: &'static [Category__Weapons] =
+                        &[Category__Weapons::Sword, Category__Weapons::Bow,];
+                    pub const ARMOR: &'static [Category__Armor] = &[Category__Armor::Helmet,];
+                    pub const ALL: &'static [Self] = &[Self::Weapons, Self::Armor,];
+                }
+
+                pub enum Category__Weapons {
+                    Sword,
+                    Bow,
+                }
+
+                impl Category__Weapons {
+                    pub const PARENT: Category = Category::Weapons;
+                    pub const ALL: &'static [Self] = &[Self::Sword, Self::Bow,];
+                }
+
+                pub enum Category__Armor {
+                    Helmet,
+                }
+
+                impl Category__Armor {
+                    pub const PARENT: Category = Category::Armor;
+                    pub const ALL: &'static [Self] = &[Self::Helmet,];
+                }
+            },
+        );
+
+        let result = define_enum_from_keys(
+            &mapping,
+            "Category",
+            None,
+            &options(VariantSort::Alphabetical),
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                pub enum Category {
+                    Armor,
+                    Weapons,
+                }
+
+                impl Category {
+                    pub const ARMOR: &'static [Category__Armor] = &[Category__Armor::Helmet,];
+                    pub const WEAPONS: &'static [Category__Weapons] =
+                        &[Category__Weapons::Bow, Category__Weapons::Sword,];
+                    pub const ALL: &'static [Self] = &[Self::Armor, Self::Weapons,];
+                }
+
+                pub enum Category__Armor {
+                    Helmet,
+                }
+
+                impl Category__Armor {
+                    pub const PARENT: Category = Category::Armor;
+                    pub const ALL: &'static [Self] = &[Self::Helmet,];
+                }
+
+                pub enum Category__Weapons {
+                    Bow,
+                    Sword,
+                }
+
+                impl Category__Weapons {
+                    pub const PARENT: Category = Category::Weapons;
+                    pub const ALL: &'static [Self] = &[Self::Bow, Self::Sword,];
+                }
+            },
+        );
+
+        let mixed = Map::from_pairs([
+            (
+                "Weapons",
+                Value::Struct(Struct::from_pairs([("Sword", Value::I32(1))])),
+            ),
+            ("Gold", Value::I32(100)),
+        ]);
+        let error =
+            define_enum_from_keys(&mixed, "Category", None, &options(VariantSort::SourceOrder))
+                .unwrap_err();
+        assert_eq!(error.key_path(), Some("Gold"));
+        assert_eq!(
+            error.to_string(),
+            "Expected value to be a struct but found `i32` instead (at key `Gold`)"
+        );
+    }
+
+    #[test]
+    fn items_with_custom_visibility() {
+        let fields = Struct::from_pairs([(
+            "inner",
+            Value::Struct(Struct::from_pairs([("flag", Value::Bool(true))])),
+        )]);
+        let options = Options {
+            output: OutputOptions {
+                visibility: Visibility::PubCrate,
+                ..OutputOptions::new()
+            },
+            ..Options::minimal()
+        };
+        let result = define_structs(&fields, "Struct", None, &options).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                pub(crate) struct Struct {
+                    pub inner: Struct__inner,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub(crate) struct Struct__inner {
+                    pub flag: bool,
+                }
+            },
+        );
+
+        let mapping = Map::from_pairs([("First", Value::I32(1))]);
+        let options = Options {
+            enums: EnumOptions {
+                all_variants_const_name: Some("ALL".into()),
+                ..EnumOptions::minimal()
+            },
+            output: OutputOptions {
+                visibility: Visibility::Private,
+                ..OutputOptions::new()
+            },
+            ..Options::minimal()
+        };
+        let result = define_enum_from_keys(&mapping, "Enum", None, &options).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                enum Enum {
+                    First,
+                }
+
+                impl Enum {
+                    const ALL: &'static [Self] = &[Self::First,];
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn invalid_custom_visibility() {
+        let options = Options {
+            output: OutputOptions {
+                visibility: Visibility::Custom("pub(".into()),
+                ..OutputOptions::new()
+            },
+            ..Options::minimal()
+        };
+        let result = define_structs(&Struct::default(), "Struct", None, &options);
+        assert!(matches!(result, Err(Error::InvalidVisibility(_))));
+    }
+
+    #[test]
+    fn output_with_inner_attributes() {
+        let options = Options {
+            output: OutputOptions {
+                visibility: Visibility::PubCrate,
+                inner_attributes: vec!["allow(dead_code)".into(), "allow(clippy::all)".into()]
+                    .into(),
+                ..OutputOptions::new()
+            },
+            ..Options::minimal()
+        };
+        let result = wrap_output(
+            quote!(
+                struct Config;
+            ),
+            &options,
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #![allow(dead_code)]
+                #![allow(clippy::all)]
+                struct Config;
+            },
+        );
+
+        let options = Options {
+            output: OutputOptions {
+                wrap_in_module: Some("config".into()),
+                ..options.output
+            },
+            ..options
+        };
+        let result = wrap_output(
+            quote!(
+                struct Config;
+            ),
+            &options,
+        )
+        .unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                pub(crate) mod config {
+                    #![allow(dead_code)]
+                    #![allow(clippy::all)]
+                    struct Config;
+                }
+            },
+        );
+    }
 
-    for (key, value) in data.0.iter() {
-        let value = define_value(value, struct_name, Some(key), None)?;
-        let key = format_ident!("{}", key);
-        fields.push(quote!(#key: #value,));
+    #[test]
+    fn invalid_inner_attribute() {
+        let options = Options {
+            output: OutputOptions {
+                inner_attributes: vec!["allow(".into()].into(),
+                ..OutputOptions::new()
+            },
+            ..Options::minimal()
+        };
+        let result = wrap_output(
+            quote!(
+                struct Config;
+            ),
+            &options,
+        );
+        assert!(matches!(result, Err(Error::InvalidAttribute(_))));
     }
 
-    let struct_name = format_ident!("{}", struct_name);
-    Ok(quote! {
-        #struct_name {
-            #(#fields)*
-        }
-    })
-}
+    #[test]
+    fn extra_attributes() {
+        let fields = Struct::from_pairs([(
+            "server",
+            Value::Struct(Struct::from_pairs([("port", Value::I32(80))])),
+        )]);
+        let options = Options {
+            structs: StructOptions {
+                derived_traits: vec!["serde::Deserialize".into()].into(),
+                extra_attributes: vec![
+                    "non_exhaustive".into(),
+                    "serde(deny_unknown_fields)".into(),
+                ]
+                .into(),
+                ..StructOptions::minimal()
+            },
+            enums: EnumOptions {
+                extra_attributes: vec!["cfg_attr(test, derive(Debug))".into()].into(),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::options::*;
-    use pretty_assertions::assert_eq;
+        let result = define_structs(&fields, "Config", None, &options).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Deserialize)]
+                #[non_exhaustive]
+                #[serde(deny_unknown_fields)]
+                pub struct Config {
+                    pub server: Config__server,
+                }
 
-    fn assert_tokens(a: TokenStream, b: TokenStream) {
-        assert_eq!(a.to_string(), b.to_string())
+                #[allow(non_camel_case_types)]
+                #[derive(serde::Deserialize)]
+                #[non_exhaustive]
+                #[serde(deny_unknown_fields)]
+                pub struct Config__server {
+                    pub port: i32,
+                }
+            },
+        );
+
+        let mapping = Map::from(fields);
+        let result = define_enum_from_keys(&mapping, "Enum", None, &options).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                #[cfg_attr(test, derive(Debug))]
+                pub enum Enum {
+                    server,
+                }
+            },
+        );
+
+        let invalid = Options {
+            enums: EnumOptions {
+                extra_attributes: vec!["derive(Debug".into()].into(),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        assert!(matches!(
+            define_enum_from_keys(&mapping, "Enum", None, &invalid),
+            Err(Error::InvalidAttribute(_))
+        ));
     }
 
     #[test]
-    fn type_declarations() {
-        let whatever = &mut vec![];
+    fn invalid_name_in_options() {
+        let data = Struct::from_pairs([("a", Value::I32(1))]);
+        let options = Options {
+            structs: StructOptions {
+                struct_data_const_name: Some("my data".into()),
+                ..StructOptions::minimal()
+            },
+            ..Options::minimal()
+        };
 
-        fn some_struct() -> Value {
-            Value::Struct(Struct([("key".into(), Value::Unit)].into_iter().collect()))
-        }
+        assert!(matches!(
+            define_structs(&data, "Struct", None, &options),
+            Err(Error::InvalidOptions { field, .. }) if field == "structs.struct_data_const_name"
+        ));
+    }
 
-        let a_struct = some_struct();
-        let a_vec = Value::Vec(vec![some_struct(), some_struct()]);
-        let a_tuple = Value::Tuple(vec![some_struct(), some_struct()]);
+    #[test]
+    fn enum_with_sorted_variants() {
+        let task =
+            |priority| Value::Struct(Struct::from_pairs([("priority", Value::I64(priority))]));
+        let mapping = Map::from_pairs([
+            ("Write", task(2)),
+            ("Read", task(1)),
+            ("Delete", task(3)),
+            ("Append", task(2)),
+        ]);
+        let options = |sort_variants_by| Options {
+            enums: EnumOptions {
+                sort_variants_by,
+                all_variants_const_name: Some("ALL".into()),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
 
+        let result =
+            define_enum_from_keys(&mapping, "Task", None, &options(VariantSort::Alphabetical))
+                .unwrap();
         assert_tokens(
-            type_of_value(&Value::Unit, "unused", None, None, whatever).unwrap(),
-            quote!(()),
-        );
-        assert_tokens(
-            type_of_value(&Value::F32(1.), "unused", None, None, whatever).unwrap(),
-            quote!(f32),
+            result,
+            quote! {
+                pub enum Task {
+                    Append,
+                    Delete,
+                    Read,
+                    Write,
+                }
+
+                impl Task {
+                    pub const ALL: &'static [Self] = &[
+                        Self::Append,
+                        Self::Delete,
+                        Self::Read,
+                        Self::Write,
+                    ];
+                }
+            },
         );
+
+        let result = define_enum_from_keys(
+            &mapping,
+            "Task",
+            None,
+            &options(VariantSort::ByValueKey("priority".into())),
+        )
+        .unwrap();
         assert_tokens(
-            type_of_value(&a_struct, "StructName", None, None, whatever).unwrap(),
-            quote!(StructName),
+            result,
+            quote! {
+                pub enum Task {
+                    Read,
+                    Write,
+                    Append,
+                    Delete,
+                }
+
+                impl Task {
+                    pub const ALL: &'static [Self] = &[
+                        Self::Read,
+                        Self::Write,
+                        Self::Append,
+                        Self::Delete,
+                    ];
+                }
+            },
         );
-        assert_tokens(
-            type_of_value(&a_vec, "StructName", None, None, whatever).unwrap(),
-            quote!(std::borrow::Cow<'static, [StructName]>),
+
+        let error = define_enum_from_keys(
+            &mapping,
+            "Task",
+            None,
+            &options(VariantSort::ByValueKey("name".into())),
+        )
+        .unwrap_err();
+        assert_eq!(error.key_path(), Some("Write"));
+
+        let mixed = Map::from_pairs([
+            (
+                "A",
+                Value::Struct(Struct::from_pairs([("priority", Value::I64(1))])),
+            ),
+            (
+                "B",
+                Value::Struct(Struct::from_pairs([(
+                    "priority",
+                    Value::String("high".into()),
+                )])),
+            ),
+        ]);
+        let result = define_enum_from_keys(
+            &mixed,
+            "Task",
+            None,
+            &options(VariantSort::ByValueKey("priority".into())),
         );
+        assert!(matches!(result, Err(Error::IncomparableSortValues(_))));
+    }
+
+    #[test]
+    fn enum_with_variant_casing() {
+        let mapping = Map::from_pairs([("MAX_SIZE", Value::I32(1)), ("min_size", Value::I32(2))]);
+        let options = Options {
+            enums: EnumOptions {
+                variant_casing: Some(Casing::ScreamingToPascal),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_enum_from_keys(&mapping, "Enum", None, &options).unwrap();
+
         assert_tokens(
-            type_of_value(&a_tuple, "StructName", None, None, whatever).unwrap(),
-            quote!((StructName__0, StructName__1)),
+            result,
+            quote! {
+                pub enum Enum {
+                    MaxSize,
+                    MinSize,
+                }
+            },
         );
+
+        let mapping = Map::from_pairs([("a_b", Value::I32(1)), ("A_B", Value::I32(2))]);
+        assert!(matches!(
+            define_enum_from_keys(&mapping, "Enum", None, &options),
+            Err(Error::DuplicateName(name)) if name == "AB"
+        ));
+    }
+
+    #[cfg(feature = "bitflags")]
+    #[test]
+    fn bitflags_with_bitflags_crate() {
+        let data = Value::Struct(Struct::from_pairs([
+            ("canRead", Value::Option(None)),
+            ("can-write", Value::Bool(true)),
+            (
+                "all",
+                Value::Vec(vec![
+                    Value::String("canRead".into()),
+                    Value::String("can-write".into()),
+                ]),
+            ),
+        ]));
+        let options = Options {
+            flags: FlagsOptions {
+                repr: UIntSize::U8,
+                style: FlagsStyle::Bitflags,
+                ..FlagsOptions::new()
+            },
+            ..Options::minimal()
+        };
+        let result = define_bitflags(&data, "Flags", None, &options).unwrap();
+
         assert_tokens(
-            type_of_value(
-                &Value::Struct(Struct(
-                    [(
-                        "nest".into(),
-                        Value::Tuple(vec![Value::Unit, some_struct()]),
-                    )]
-                    .into_iter()
-                    .collect(),
-                )),
-                "StructName",
-                None,
-                None,
-                whatever,
-            )
-            .unwrap(),
-            quote!(StructName),
+            result,
+            quote! {
+                bitflags::bitflags! {
+                    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+                    pub struct Flags: u8 {
+                        const CAN_READ = 1 << 0;
+                        const CAN_WRITE = 1 << 1;
+                        const ALL = Self::CAN_READ.bits() | Self::CAN_WRITE.bits();
+                    }
+                }
+            },
         );
     }
 
     #[test]
-    #[cfg(rustfmt_skip)]
-    fn value_declarations() {
-        let options = &Options::default();
-
-        fn some_struct() -> Value {
-            Value::Struct(Struct([("key".into(), Value::Unit)].into_iter().collect()))
+    fn invalid_bitflags() {
+        let options = Options {
+            flags: FlagsOptions {
+                repr: UIntSize::U8,
+                ..FlagsOptions::minimal()
+            },
+            ..Options::minimal()
         };
 
-        let a_struct = some_struct();
-        let a_vec = Value::Vec(vec![some_struct(), some_struct()]);
-        let a_tuple = Value::Tuple(vec![some_struct(), some_struct()]);
-
-        assert_tokens(
-            define_value(&Value::Unit, options, "unused", None, None).unwrap(),
-            quote!(()),
+        let data = Value::Vec(
+            (0..9)
+                .map(|i| Value::String(format!("flag{}", i)))
+                .collect(),
         );
-        assert_tokens(
-            define_value(&Value::F32(1.), options, "unused", None, None).unwrap(),
-            quote!(1f32),
+        assert!(matches!(
+            define_bitflags(&data, "Flags", None, &options),
+            Err(Error::TooManyFlags(8))
+        ));
+
+        let data = Value::Struct(Struct::from_pairs([
+            ("both", Value::Vec(vec![Value::String("read".into())])),
+            ("read", Value::Option(None)),
+        ]));
+        let error = define_bitflags(&data, "Flags", None, &options).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown flag `read` (groups can only contain flags defined before them) \
+            (at key `both`)"
         );
+
+        let data = Value::Struct(Struct::from_pairs([("read", Value::I32(1))]));
+        assert!(matches!(
+            define_bitflags(&data, "Flags", None, &options),
+            Err(Error::AtKey { error, .. }) if matches!(*error, Error::InvalidFlag("i32"))
+        ));
+    }
+
+    #[cfg(feature = "phf")]
+    #[test]
+    fn enum_with_key_map() {
+        let mapping = Map::from_pairs([("first-key", Value::I32(1)), ("second", Value::I32(2))]);
+        let options = |derived_traits: Vec<_>| Options {
+            enums: EnumOptions {
+                variant_casing: Some(Casing::Pascal),
+                key_map_const_name: Some("KEYS".into()),
+                derived_traits: derived_traits.into(),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let error = define_enum_from_keys(&mapping, "Enum", None, &options(vec![])).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::RequiresDerive("EnumOptions::key_map_const_name", "Copy")
+        ));
+
+        let result = define_enum_from_keys(
+            &mapping,
+            "Enum",
+            None,
+            &options(vec!["Clone".into(), "Copy".into()]),
+        )
+        .unwrap();
+
         assert_tokens(
-            define_value(&a_struct, options, "StructName", None, None).unwrap(),
-            quote!(StructName { key: () }),
+            result,
+            quote! {
+                #[derive(Clone, Copy)]
+                pub enum Enum {
+                    FirstKey,
+                    Second,
+                }
+
+                impl Enum {
+                    pub const KEYS: phf::Map<&'static str, Self> = phf::phf_map! {
+                        "first-key" => Self::FirstKey,
+                        "second" => Self::Second,
+                    };
+
+                    pub fn from_key_str(key: &str) -> Option<Self> {
+                        Self::KEYS.get(key).copied()
+                    }
+                }
+            },
         );
+    }
+
+    #[test]
+    fn enum_with_integer_keys() {
+        let mapping = Map::from_pairs([("404", Value::I32(1)), ("500", Value::I32(2))]);
+        let options = |key_type| Options {
+            enums: EnumOptions {
+                variant_casing: Some(Casing::Pascal),
+                integer_keys: Some(IntegerKeyOptions {
+                    variant_prefix: "Status".into(),
+                    key_type,
+                    ..IntegerKeyOptions::new()
+                }),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result =
+            define_enum_from_keys(&mapping, "Enum", None, &options(UIntSize::U16)).unwrap();
+
         assert_tokens(
-            define_value(&a_vec, options, "StructName", None, None).unwrap(),
-            quote!(std::borrow::Cow::Borrowed(&[
-                StructName { key: () },
-                StructName { key: () },
-            ])),
+            result,
+            quote! {
+                pub enum Enum {
+                    Status404,
+                    Status500,
+                }
+
+                impl Enum {
+                    pub const fn code(self) -> u16 {
+                        match self {
+                            Self::Status404 => 404,
+                            Self::Status500 => 500,
+                        }
+                    }
+                }
+
+                impl std::convert::TryFrom<u16> for Enum {
+                    type Error = u16;
+
+                    fn try_from(code: u16) -> Result<Self, Self::Error> {
+                        match code {
+                            404 => Ok(Self::Status404),
+                            500 => Ok(Self::Status500),
+                            _ => Err(code),
+                        }
+                    }
+                }
+            },
         );
-        assert_tokens(
-            define_value(&a_tuple, options, "StructName", None, None).unwrap(),
-            quote!((StructName__0 { key: () }, StructName__1 { key: () })),
+
+        let error =
+            define_enum_from_keys(&mapping, "Enum", None, &options(UIntSize::U8)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Key `404` is not an unsigned integer that fits in 8 bits (at key `404`)"
         );
+    }
+
+    #[test]
+    fn enum_with_strum_support() {
+        let mapping = Map::from_pairs([("MAX_SIZE", Value::I32(1)), ("Min", Value::I32(2))]);
+        let options = Options {
+            enums: EnumOptions {
+                variant_casing: Some(Casing::ScreamingToPascal),
+                all_variants_const_name: Some("ALL".into()),
+                impl_from_str: true,
+                strum_support: true,
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_enum_from_keys(&mapping, "Enum", None, &options).unwrap();
+
         assert_tokens(
-            define_value(
-                &Value::Struct(Struct(
-                    [(
-                        "nest".into(),
-                        Value::Tuple(vec![Value::Unit, some_struct()]),
-                    )]
-                    .into_iter()
-                    .collect(),
-                )),
-                options,
-                "StructName",
-                None,
-                None,
-            )
-            .unwrap(),
-            quote!(StructName {
-                nest: ((), StructName__nest__1 { key: () }),
-            }),
+            result,
+            quote! {
+                #[derive(strum::EnumIter, strum::AsRefStr, strum::Display, strum::EnumString)]
+                pub enum Enum {
+                    #[strum(serialize = "MAX_SIZE")]
+                    MaxSize,
+                    Min,
+                }
+            },
         );
     }
 
     #[test]
-    fn empty_struct() {
-        let fields = Struct([].into_iter().collect());
-        let result = define_structs(&fields, "Struct", None, &Options::minimal()).unwrap();
+    fn enum_with_aliases() {
+        let mapping = Map::from_pairs([
+            (
+                "sword",
+                Value::Struct(Struct::from_pairs([
+                    ("_alias", Value::Vec(vec![Value::String("blade".into())])),
+                    ("damage", Value::I32(5)),
+                ])),
+            ),
+            (
+                "bow",
+                Value::Struct(Struct::from_pairs([("damage", Value::I32(3))])),
+            ),
+        ]);
+        let options = Options {
+            enums: EnumOptions {
+                variant_casing: Some(Casing::SnakeToPascal),
+                alias_key: Some("_alias".into()),
+                impl_from_str: true,
+                all_values_const_name: Some("VALUES".into()),
+                values_struct: Some(ValuesStructOptions::minimal()),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_enum_from_keys(&mapping, "Weapon", None, &options).unwrap();
         assert_tokens(
             result,
-            quote!(
+            quote! {
+                pub enum Weapon {
+                    Sword,
+                    Bow,
+                }
+
+                impl Weapon {
+                    pub const VALUES: &'static [Weapon__Value] = &[
+                        Weapon__Value { damage: 5i32, },
+                        Weapon__Value { damage: 3i32, },
+                    ];
+                }
+
+                impl std::str::FromStr for Weapon {
+                    type Err = ();
+
+                    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+                        Ok(match s {
+                            "Sword" => Self::Sword,
+                            "blade" => Self::Sword,
+                            "Bow" => Self::Bow,
+                            _ => return Err(())
+                        })
+                    }
+                }
+
                 #[allow(non_camel_case_types)]
-                pub struct Struct {}
-            ),
+                pub struct Weapon__Value {
+                    pub damage: i32,
+                }
+            },
+        );
+
+        let duplicate = Map::from_pairs([(
+            "bow",
+            Value::Struct(Struct::from_pairs([(
+                "_alias",
+                Value::String("sword".into()),
+            )])),
+        )]);
+        let mapping = Map::from_pairs(mapping.0.into_iter().chain(duplicate.0));
+        let error = define_enum_from_keys(&mapping, "Weapon", None, &options).unwrap_err();
+        assert!(matches!(error, Error::DuplicateName(name) if name == "sword"));
+
+        let invalid = Map::from_pairs([(
+            "axe",
+            Value::Struct(Struct::from_pairs([("_alias", Value::I32(1))])),
+        )]);
+        let error = define_enum_from_keys(&invalid, "Weapon", None, &options).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected value to be a string but found `i32` instead (at key `axe._alias`)"
         );
     }
 
     #[test]
-    fn empty_struct_with_path() {
-        let fields = Struct([].into_iter().collect());
-        let result = define_structs(
-            &fields,
-            "Struct",
+    fn simple_enum_with_file() {
+        let mapping = Map([
+            ("First".into(), Value::I32(1)),
+            ("Second".into(), Value::I32(2)),
+        ]
+        .into_iter()
+        .collect());
+        let result = define_enum_from_keys(
+            &mapping,
+            "Enum",
             Some("./path/to/file.toml".as_ref()),
             &Options {
                 source_path_const_name: Some("SOURCE_PATH".into()),
@@ -1065,454 +7887,658 @@ mod tests {
             },
         )
         .unwrap();
+
         assert_tokens(
             result,
-            quote!(
-                #[allow(non_camel_case_types)]
-                pub struct Struct {}
-
-                impl Struct {
-                    pub const SOURCE_PATH: &'static str = "./path/to/file.toml";
-                }
-            ),
+            quote!(pub enum Enum {
+                First,
+                Second,
+            }
+            impl Enum {
+                pub const SOURCE_PATH: &'static str = "./path/to/file.toml";
+            }),
         );
     }
 
     #[test]
-    fn big_struct() {
-        let fields = Struct(
-            [
-                ("a".into(), Value::Unit),
-                ("b".into(), Value::Bool(true)),
-                ("c".into(), Value::Char('a')),
-                ("d".into(), Value::I8(8)),
-                ("e".into(), Value::I16(16)),
-                ("f".into(), Value::I32(32)),
-                ("g".into(), Value::I64(64)),
-                ("h".into(), Value::I128(128)),
-                ("i".into(), Value::ISize(64)),
-                ("j".into(), Value::U8(8)),
-                ("k".into(), Value::U16(16)),
-                ("l".into(), Value::U32(32)),
-                ("m".into(), Value::U64(64)),
-                ("n".into(), Value::U128(128)),
-                ("o".into(), Value::USize(64)),
-                ("p".into(), Value::F32(32.)),
-                ("q".into(), Value::F64(64.)),
-                ("r".into(), Value::String("String".into())),
-                ("s".into(), Value::Option(Some(Box::new(Value::Unit)))),
-                ("t".into(), Value::Tuple(vec![Value::Unit; 2])),
-                ("u".into(), Value::Array(2, vec![Value::Unit; 2])),
-                ("v".into(), Value::Vec(vec![Value::Unit; 2])),
-            ]
-            .into_iter()
-            .collect(),
-        );
-        let result = define_structs(
-            &fields,
-            "Struct",
+    fn enum_with_derives() {
+        let mapping = Map([
+            ("First".into(), Value::I32(1)),
+            ("Second".into(), Value::I32(2)),
+        ]
+        .into_iter()
+        .collect());
+        let result = define_enum_from_keys(
+            &mapping,
+            "Enum",
             None,
             &Options {
-                structs: StructOptions {
-                    struct_data_const_name: Some("THINGY".into()),
-                    ..StructOptions::minimal()
+                serde_support: SerdeSupport::Yes,
+                enums: EnumOptions {
+                    derived_traits: vec!["Clone".into()].into(),
+                    all_variants_const_name: Some("VARIANTS".into()),
+                    impl_default: true,
+                    impl_display: true,
+                    impl_from_str: true,
+                    ..EnumOptions::minimal()
                 },
                 ..Options::minimal()
             },
         )
         .unwrap();
+
         assert_tokens(
             result,
-            quote!(
-                #[allow(non_camel_case_types)]
-                pub struct Struct {
-                    pub a: (),
-                    pub b: bool,
-                    pub c: char,
-                    pub d: i8,
-                    pub e: i16,
-                    pub f: i32,
-                    pub g: i64,
-                    pub h: i128,
-                    pub i: isize,
-                    pub j: u8,
-                    pub k: u16,
-                    pub l: u32,
-                    pub m: u64,
-                    pub n: u128,
-                    pub o: usize,
-                    pub p: f32,
-                    pub q: f64,
-                    pub r: std::borrow::Cow<'static, str>,
-                    pub s: Option<()>,
-                    pub t: ((), ()),
-                    pub u: [(); 2usize],
-                    pub v: std::borrow::Cow<'static, [()]>,
+            quote! {
+                #[derive(Clone, serde::Serialize, serde::Deserialize, Debug)]
+                pub enum Enum {
+                    First,
+                    Second,
                 }
 
-                impl Struct {
-                    pub const THINGY: Struct = Struct {
-                        a: (),
-                        b: true,
-                        c: 'a',
-                        d: 8i8,
-                        e: 16i16,
-                        f: 32i32,
-                        g: 64i64,
-                        h: 128i128,
-                        i: 64isize,
-                        j: 8u8,
-                        k: 16u16,
-                        l: 32u32,
-                        m: 64u64,
-                        n: 128u128,
-                        o: 64usize,
-                        p: 32f32,
-                        q: 64f64,
-                        r: std::borrow::Cow::Borrowed("String"),
-                        s: Some(()),
-                        t: ((), ()),
-                        u: [(), (),],
-                        v: std::borrow::Cow::Borrowed(&[(), (),]),
-                    };
+                impl Enum {
+                    pub const VARIANTS: &'static [Self] = &[Self::First, Self::Second, ];
                 }
-            ),
+
+                #[allow(clippy::derivable_impls)]
+                impl Default for Enum {
+                    fn default() -> Self {
+                        Self::First
+                    }
+                }
+
+                impl std::fmt::Display for Enum {
+                    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        <Self as std::fmt::Debug>::fmt(self, f)
+                    }
+                }
+
+                impl std::str::FromStr for Enum {
+                    type Err = ();
+
+                    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+                        Ok(match s {
+                            "First" => Self::First,
+                            "Second" => Self::Second,
+                            _ => return Err(())
+                        })
+                    }
+                }
+            },
         );
     }
 
     #[test]
-    fn struct_with_vecs() {
-        let fields = Struct(
-            [
-                ("a".into(), Value::Vec(vec![])),
-                ("b".into(), Value::Vec(vec![Value::Bool(true)])),
-                (
-                    "c".into(),
-                    Value::Vec(vec![Value::Bool(true), Value::Bool(false)]),
-                ),
-            ]
-            .into_iter()
-            .collect(),
-        );
-        let result = define_structs(&fields, "Struct", None, &Options::minimal()).unwrap();
+    fn enum_with_const_values() {
+        let mapping = Map([(
+            "First".into(),
+            Value::Struct(Struct([("key".into(), Value::Unit)].into_iter().collect())),
+        )]
+        .into_iter()
+        .collect());
+        let result = define_enum_from_keys(
+            &mapping,
+            "Enum",
+            None,
+            &Options {
+                serde_support: SerdeSupport::Yes,
+                enums: EnumOptions {
+                    derived_traits: vec!["Clone".into()].into(),
+                    all_variants_const_name: Some("VARIANTS".into()),
+                    impl_default: true,
+                    impl_display: true,
+                    impl_from_str: true,
+                    ..Default::default()
+                },
+                ..Options::default()
+            },
+        )
+        .unwrap();
+
         assert_tokens(
             result,
-            quote!(
+            quote! {
+                #[derive(Clone, serde::Serialize, serde::Deserialize, Debug)]
+                pub enum Enum {
+                    First,
+                }
+
+                impl Enum {
+                    pub const VARIANTS: &'static [Self] = &[Self::First, ];
+                    pub const VALUES: &'static [Enum__Value] = &[Enum__Value { key: (), }, ];
+                    pub const fn get(self) -> &'static Enum__Value { &Self::VALUES[self as usize] }
+                }
+
+                #[allow(clippy::derivable_impls)]
+                impl Default for Enum {
+                    fn default() -> Self {
+                        Self::First
+                    }
+                }
+
+                impl std::fmt::Display for Enum {
+                    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        <Self as std::fmt::Debug>::fmt(self, f)
+                    }
+                }
+
+                impl std::str::FromStr for Enum {
+                    type Err = ();
+
+                    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+                        Ok(match s {
+                            "First" => Self::First,
+                            _ => return Err(())
+                        })
+                    }
+                }
+
                 #[allow(non_camel_case_types)]
-                pub struct Struct {
-                    pub a: std::borrow::Cow<'static, [()]>,
-                    pub b: std::borrow::Cow<'static, [bool]>,
-                    pub c: std::borrow::Cow<'static, [bool]>,
+                #[derive(Debug, serde::Serialize, serde::Deserialize)]
+                pub struct Enum__Value {
+                    pub key: (),
                 }
-            ),
+            },
         );
     }
 
     #[test]
-    fn struct_with_arrays() {
-        let fields = Struct(
-            [
-                ("a".into(), Value::Array(0, vec![])),
-                ("b".into(), Value::Array(1, vec![Value::Bool(true)])),
-                (
-                    "c".into(),
-                    Value::Array(2, vec![Value::Bool(true), Value::Bool(false)]),
-                ),
-            ]
-            .into_iter()
-            .collect(),
-        );
-        let result = define_structs(&fields, "Struct", None, &Options::minimal()).unwrap();
+    fn enum_with_owned_values() {
+        let mapping = Map::from_pairs([(
+            "First",
+            Value::Struct(Struct::from_pairs([("key", Value::I32(1))])),
+        )]);
+        let options = |derived_traits: Vec<Cow<'static, str>>| Options {
+            enums: EnumOptions {
+                all_values_const_name: Some("VALUES".into()),
+                values_struct: Some(ValuesStructOptions {
+                    struct_options: StructOptions {
+                        derived_traits: derived_traits.into(),
+                        ..StructOptions::minimal()
+                    },
+                    ..ValuesStructOptions::minimal()
+                }),
+                get_value_fn_name: Some("get".into()),
+                owned_value_fn_name: Some("to_owned_value".into()),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result =
+            define_enum_from_keys(&mapping, "Enum", None, &options(vec!["Clone".into()])).unwrap();
+
         assert_tokens(
             result,
-            quote!(
+            quote! {
+                pub enum Enum {
+                    First,
+                }
+
+                impl Enum {
+                    pub const VALUES: &'static [Enum__Value] = &[Enum__Value { key: 1i32, }, ];
+                    pub const fn get(self) -> &'static Enum__Value { &Self::VALUES[self as usize] }
+                    pub fn to_owned_value(self) -> Enum__Value { self.get().clone() }
+                }
+
                 #[allow(non_camel_case_types)]
-                pub struct Struct {
-                    pub a: [(); 0usize],
-                    pub b: [bool; 1usize],
-                    pub c: [bool; 2usize],
+                #[derive(Clone)]
+                pub struct Enum__Value {
+                    pub key: i32,
                 }
-            ),
+            },
+        );
+
+        let error = define_enum_from_keys(&mapping, "Enum", None, &options(vec!["Debug".into()]))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`EnumOptions::owned_value_fn_name` requires `Clone` to be derived"
         );
     }
 
     #[test]
-    fn nested_struct() {
-        let fields = Struct(
-            [(
-                "nested".into(),
-                Value::Struct(Struct(
-                    [("inner".into(), Value::Bool(true))].into_iter().collect(),
-                )),
-            )]
-            .into_iter()
-            .collect(),
-        );
-        let result = define_structs(&fields, "Struct", None, &Options::minimal()).unwrap();
+    fn enum_with_separate_value_derives() {
+        let mapping = Map::from_pairs([(
+            "First",
+            Value::Struct(Struct::from_pairs([(
+                "nested",
+                Value::Struct(Struct::from_pairs([("key", Value::I32(1))])),
+            )])),
+        )]);
+        let result = define_enum_from_keys(
+            &mapping,
+            "Enum",
+            None,
+            &Options {
+                serde_support: SerdeSupport::No,
+                enums: EnumOptions {
+                    derived_traits: vec!["Clone".into(), "Copy".into(), "Hash".into()].into(),
+                    all_values_const_name: Some("VALUES".into()),
+                    values_struct: Some(ValuesStructOptions {
+                        struct_options: StructOptions {
+                            derived_traits: vec!["Debug".into(), "Default".into()].into(),
+                            extra_attributes: vec!["non_exhaustive".into()].into(),
+                            nested_derived_traits: Some(vec!["Debug".into()].into()),
+                            nested_extra_attributes: Some(vec![].into()),
+                            ..StructOptions::minimal()
+                        },
+                        serde_support: Some(SerdeSupport::Mixed {
+                            serialize: false,
+                            deserialize: true,
+                        }),
+                        ..ValuesStructOptions::minimal()
+                    }),
+                    ..EnumOptions::minimal()
+                },
+                ..Options::minimal()
+            },
+        )
+        .unwrap();
+
         assert_tokens(
             result,
-            quote!(
+            quote! {
+                #[derive(Clone, Copy, Hash)]
+                pub enum Enum {
+                    First,
+                }
+
+                impl Enum {
+                    pub const VALUES: &'static [Enum__Value] = &[
+                        Enum__Value { nested: Enum__Value__nested { key: 1i32, }, },
+                    ];
+                }
+
                 #[allow(non_camel_case_types)]
-                pub struct Struct {
-                    pub nested: Struct__nested,
+                #[derive(Debug, Default, serde::Deserialize)]
+                #[non_exhaustive]
+                pub struct Enum__Value {
+                    pub nested: Enum__Value__nested,
                 }
 
                 #[allow(non_camel_case_types)]
-                pub struct Struct__nested {
-                    pub inner: bool,
+                #[derive(Debug, serde::Deserialize)]
+                pub struct Enum__Value__nested {
+                    pub key: i32,
                 }
-            ),
+            },
         );
     }
 
     #[test]
-    fn nested_struct_with_derives() {
-        let fields = Struct(
-            [(
-                "nested".into(),
-                Value::Struct(Struct(
-                    [("inner".into(), Value::Bool(true))].into_iter().collect(),
-                )),
-            )]
-            .into_iter()
-            .collect(),
-        );
-        let result = define_structs(
-            &fields,
-            "Struct",
+    fn enum_with_static_strs() {
+        let mapping = Map::from_pairs([("First", Value::I32(1)), ("Second", Value::I32(2))]);
+        let result = define_enum_from_keys(
+            &mapping,
+            "Enum",
             None,
             &Options {
-                serde_support: SerdeSupport::Yes,
-                structs: StructOptions {
-                    derived_traits: vec!["Debug".into(), "Clone".into()].into(),
-                    ..StructOptions::minimal()
+                enums: EnumOptions {
+                    impl_as_ref_str: true,
+                    static_str_fn_name: Some("into_static_str".into()),
+                    ..EnumOptions::minimal()
                 },
                 ..Options::minimal()
             },
         )
         .unwrap();
+
         assert_tokens(
             result,
-            quote!(
-                #[allow(non_camel_case_types)]
-                #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-                pub struct Struct {
-                    pub nested: Struct__nested,
+            quote! {
+                pub enum Enum {
+                    First,
+                    Second,
                 }
 
-                #[allow(non_camel_case_types)]
-                #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-                pub struct Struct__nested {
-                    pub inner: bool,
+                impl Enum {
+                    pub const fn into_static_str(self) -> &'static str {
+                        match self {
+                            Enum::First => "First",
+                            Enum::Second => "Second",
+                        }
+                    }
                 }
-            ),
+
+                impl std::convert::AsRef<str> for Enum {
+                    fn as_ref(&self) -> &str {
+                        match *self {
+                            Enum::First => "First",
+                            Enum::Second => "Second",
+                        }
+                    }
+                }
+
+                impl std::convert::From<Enum> for &'static str {
+                    fn from(value: Enum) -> Self {
+                        match value {
+                            Enum::First => "First",
+                            Enum::Second => "Second",
+                        }
+                    }
+                }
+            },
         );
     }
 
     #[test]
-    fn more_nested_struct() {
-        let fields = Struct(
-            [(
-                "nested".into(),
-                Value::Struct(Struct(
-                    [(
-                        "nested_again".into(),
-                        Value::Struct(Struct(
-                            [("inner".into(), Value::Bool(true))].into_iter().collect(),
-                        )),
-                    )]
-                    .into_iter()
-                    .collect(),
-                )),
-            )]
-            .into_iter()
-            .collect(),
-        );
-        let result = define_structs(&fields, "Struct", None, &Options::minimal()).unwrap();
+    fn enum_with_display_and_from_str_casing() {
+        let mapping = Map::from_pairs([("max_size", Value::I32(1)), ("httpPort", Value::I32(2))]);
+        let options = Options {
+            enums: EnumOptions {
+                variant_casing: Some(Casing::SnakeToPascal),
+                impl_display: true,
+                display_casing: Some(Casing::Kebab),
+                impl_from_str: true,
+                from_str_casing: vec![Casing::Preserve, Casing::Snake, Casing::Pascal].into(),
+                impl_as_ref_str: true,
+                static_str_fn_name: Some("as_str".into()),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_enum_from_keys(&mapping, "Enum", None, &options).unwrap();
+
         assert_tokens(
             result,
-            quote!(
-                #[allow(non_camel_case_types)]
-                pub struct Struct {
-                    pub nested: Struct__nested,
+            quote! {
+                #[derive(Debug)]
+                pub enum Enum {
+                    MaxSize,
+                    HttpPort,
                 }
 
-                #[allow(non_camel_case_types)]
-                pub struct Struct__nested {
-                    pub nested_again: Struct__nested__nested_again,
+                impl Enum {
+                    pub const fn as_str(self) -> &'static str {
+                        match self {
+                            Enum::MaxSize => "max-size",
+                            Enum::HttpPort => "http-port",
+                        }
+                    }
                 }
 
-                #[allow(non_camel_case_types)]
-                pub struct Struct__nested__nested_again {
-                    pub inner: bool,
+                impl std::fmt::Display for Enum {
+                    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        f.write_str(match self {
+                            Enum::MaxSize => "max-size",
+                            Enum::HttpPort => "http-port",
+                        })
+                    }
                 }
-            ),
+
+                impl std::str::FromStr for Enum {
+                    type Err = ();
+
+                    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+                        Ok(match s {
+                            "max_size" => Self::MaxSize,
+                            "MaxSize" => Self::MaxSize,
+                            "httpPort" => Self::HttpPort,
+                            "http_port" => Self::HttpPort,
+                            "HttpPort" => Self::HttpPort,
+                            _ => return Err(())
+                        })
+                    }
+                }
+
+                impl std::convert::AsRef<str> for Enum {
+                    fn as_ref(&self) -> &str {
+                        match *self {
+                            Enum::MaxSize => "max-size",
+                            Enum::HttpPort => "http-port",
+                        }
+                    }
+                }
+
+                impl std::convert::From<Enum> for &'static str {
+                    fn from(value: Enum) -> Self {
+                        match value {
+                            Enum::MaxSize => "max-size",
+                            Enum::HttpPort => "http-port",
+                        }
+                    }
+                }
+            },
         );
+
+        let mapping = Map::from_pairs([("max_size", Value::I32(1)), ("MaxSize", Value::I32(2))]);
+        let options = Options {
+            enums: EnumOptions {
+                variant_casing: Some(Casing::Preserve),
+                impl_from_str: true,
+                from_str_casing: vec![Casing::Snake].into(),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let error = define_enum_from_keys(&mapping, "Enum", None, &options).unwrap_err();
+        assert!(matches!(error, Error::DuplicateName(name) if name == "max_size"));
     }
 
     #[test]
-    fn structs_in_tuples() {
-        let fields = Struct(
-            [(
-                "tuple".into(),
-                Value::Tuple(vec![
-                    Value::Bool(true),
-                    Value::Struct(Struct(
-                        [("struct_a".into(), Value::Bool(true))]
-                            .into_iter()
-                            .collect(),
-                    )),
-                    Value::Struct(Struct(
-                        [("struct_b".into(), Value::Bool(true))]
-                            .into_iter()
-                            .collect(),
-                    )),
-                ]),
-            )]
-            .into_iter()
-            .collect(),
+    fn enum_with_try_from_index_and_iter() {
+        let mapping = Map::from_pairs([("First", Value::I32(1)), ("Second", Value::I32(2))]);
+        let options = Options {
+            enums: EnumOptions {
+                impl_try_from_index: true,
+                impl_iter: true,
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_enum_from_keys(&mapping, "Enum", None, &options).unwrap();
+
+        assert_tokens(
+            result,
+            quote! {
+                pub enum Enum {
+                    First,
+                    Second,
+                }
+
+                impl Enum {
+                    pub fn iter() -> impl std::iter::Iterator<Item = Self> {
+                        std::iter::IntoIterator::into_iter([Self::First, Self::Second,])
+                    }
+                }
+
+                impl std::convert::TryFrom<usize> for Enum {
+                    type Error = usize;
+
+                    fn try_from(index: usize) -> std::result::Result<Self, Self::Error> {
+                        match index {
+                            0usize => Ok(Self::First),
+                            1usize => Ok(Self::Second),
+                            _ => Err(index),
+                        }
+                    }
+                }
+            },
         );
-        let result = define_structs(&fields, "Struct", None, &Options::minimal()).unwrap();
+
+        let options = |derived_traits: Vec<_>| Options {
+            enums: EnumOptions {
+                impl_iter: true,
+                all_variants_const_name: Some("ALL".into()),
+                derived_traits: derived_traits.into(),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let error = define_enum_from_keys(&mapping, "Enum", None, &options(vec![])).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::RequiresDerive("EnumOptions::impl_iter", "Copy")
+        ));
+
+        let result = define_enum_from_keys(
+            &mapping,
+            "Enum",
+            None,
+            &options(vec!["Clone".into(), "Copy".into()]),
+        )
+        .unwrap();
+
         assert_tokens(
             result,
-            quote!(
-                #[allow(non_camel_case_types)]
-                pub struct Struct {
-                    pub tuple: (bool, Struct__tuple__1, Struct__tuple__2),
+            quote! {
+                #[derive(Clone, Copy)]
+                pub enum Enum {
+                    First,
+                    Second,
                 }
 
-                #[allow(non_camel_case_types)]
-                pub struct Struct__tuple__1 {
-                    pub struct_a: bool,
-                }
+                impl Enum {
+                    pub fn iter() -> impl std::iter::Iterator<Item = Self> {
+                        Self::ALL.iter().copied()
+                    }
 
-                #[allow(non_camel_case_types)]
-                pub struct Struct__tuple__2 {
-                    pub struct_b: bool,
+                    pub const ALL: &'static [Self] = &[Self::First, Self::Second,];
                 }
-            ),
+            },
         );
     }
 
     #[test]
-    fn simple_enum() {
-        let mapping = Struct(
-            [
-                ("First".into(), Value::I32(1)),
-                ("Second".into(), Value::I32(2)),
-            ]
-            .into_iter()
-            .collect(),
-        );
-        let result = define_enum_from_keys(&mapping, "Enum", None, &Options::minimal()).unwrap();
+    fn enum_with_match_arm_accessors() {
+        let mapping = Map::from_pairs([("First", Value::I32(1)), ("Second", Value::I32(2))]);
+        let options = Options {
+            enums: EnumOptions {
+                all_values_const_name: Some("VALUES".into()),
+                values_struct: Some(ValuesStructOptions::minimal()),
+                get_value_fn_name: Some("get".into()),
+                impl_index: true,
+                accessor_style: AccessorStyle::MatchArms,
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_enum_from_keys(&mapping, "Enum", None, &options).unwrap();
 
         assert_tokens(
             result,
-            quote!(
+            quote! {
                 pub enum Enum {
                     First,
                     Second,
                 }
-            ),
+
+                impl Enum {
+                    pub const COUNT: usize = 2usize;
+
+                    pub const fn index(self) -> usize {
+                        match self {
+                            Self::First => 0usize,
+                            Self::Second => 1usize,
+                        }
+                    }
+
+                    pub const fn from_index(index: usize) -> Option<Self> {
+                        match index {
+                            0usize => Some(Self::First),
+                            1usize => Some(Self::Second),
+                            _ => None,
+                        }
+                    }
+
+                    pub const VALUES: &'static [i32] = &[1i32, 2i32,];
+
+                    pub const fn get(self) -> &'static i32 {
+                        &Self::VALUES[match self {
+                            Self::First => 0usize,
+                            Self::Second => 1usize,
+                        }]
+                    }
+                }
+            },
         );
     }
 
     #[test]
-    fn simple_enum_with_file() {
-        let mapping = Struct(
-            [
-                ("First".into(), Value::I32(1)),
-                ("Second".into(), Value::I32(2)),
-            ]
-            .into_iter()
-            .collect(),
-        );
-        let result = define_enum_from_keys(
-            &mapping,
-            "Enum",
-            Some("./path/to/file.toml".as_ref()),
-            &Options {
-                source_path_const_name: Some("SOURCE_PATH".into()),
-                ..Options::minimal()
+    fn enum_with_values_array() {
+        let mapping = Map::from_pairs([("First", Value::I32(1)), ("Second", Value::I32(2))]);
+        let options = Options {
+            enums: EnumOptions {
+                all_values_const_name: Some("VALUES".into()),
+                values_array: true,
+                values_struct: Some(ValuesStructOptions::minimal()),
+                get_value_fn_name: Some("get".into()),
+                ..EnumOptions::minimal()
             },
-        )
-        .unwrap();
+            ..Options::minimal()
+        };
+        let result = define_enum_from_keys(&mapping, "Enum", None, &options).unwrap();
 
         assert_tokens(
             result,
-            quote!(pub enum Enum {
-                First,
-                Second,
-            }
-            impl Enum {
-                pub const SOURCE_PATH: &'static str = "./path/to/file.toml";
-            }),
+            quote! {
+                pub enum Enum {
+                    First,
+                    Second,
+                }
+
+                impl Enum {
+                    pub const VALUES: [i32; 2usize] = [1i32, 2i32,];
+
+                    pub const fn get(self) -> &'static i32 {
+                        const VALUES: &[i32; 2usize] = &Enum::VALUES;
+                        &VALUES[self as usize]
+                    }
+                }
+            },
         );
     }
 
     #[test]
-    fn enum_with_derives() {
-        let mapping = Struct(
-            [
-                ("First".into(), Value::I32(1)),
-                ("Second".into(), Value::I32(2)),
-            ]
-            .into_iter()
-            .collect(),
-        );
-        let result = define_enum_from_keys(
-            &mapping,
-            "Enum",
-            None,
-            &Options {
-                serde_support: SerdeSupport::Yes,
-                enums: EnumOptions {
-                    derived_traits: vec!["Clone".into()].into(),
-                    all_variants_const_name: Some("VARIANTS".into()),
-                    impl_default: true,
-                    impl_display: true,
-                    impl_from_str: true,
-                    ..EnumOptions::minimal()
-                },
-                ..Options::minimal()
+    fn enum_with_static_values() {
+        let mapping = Map::from_pairs([("First", Value::I32(1)), ("Second", Value::I32(2))]);
+        let options = |values_array| Options {
+            enums: EnumOptions {
+                all_values_const_name: Some("VALUES".into()),
+                values_array,
+                static_values: true,
+                values_struct: Some(ValuesStructOptions::minimal()),
+                get_value_fn_name: Some("get".into()),
+                ..EnumOptions::minimal()
             },
-        )
-        .unwrap();
+            ..Options::minimal()
+        };
 
+        let result = define_enum_from_keys(&mapping, "Enum", None, &options(false)).unwrap();
         assert_tokens(
             result,
             quote! {
-                #[derive(Clone, serde::Serialize, serde::Deserialize, Debug)]
                 pub enum Enum {
                     First,
                     Second,
                 }
 
                 impl Enum {
-                    pub const VARIANTS: &'static [Self] = &[Self::First, Self::Second, ];
-                }
+                    pub const VALUES: &'static [i32] = &[1i32, 2i32,];
 
-                impl Default for Enum {
-                    fn default() -> Self {
-                        Self::First
+                    pub fn get(self) -> &'static i32 {
+                        static VALUES: &[i32] = Enum::VALUES;
+                        &VALUES[self as usize]
                     }
                 }
+            },
+        );
 
-                impl std::fmt::Display for Enum {
-                    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                        <Self as std::fmt::Debug>::fmt(self, f)
-                    }
+        let result = define_enum_from_keys(&mapping, "Enum", None, &options(true)).unwrap();
+        assert_tokens(
+            result,
+            quote! {
+                pub enum Enum {
+                    First,
+                    Second,
                 }
 
-                impl std::str::FromStr for Enum {
-                    type Err = ();
+                impl Enum {
+                    pub const VALUES: [i32; 2usize] = [1i32, 2i32,];
 
-                    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-                        Ok(match s {
-                            "First" => Self::First,
-                            "Second" => Self::Second,
-                            _ => return Err(())
-                        })
+                    pub fn get(self) -> &'static i32 {
+                        static VALUES: [i32; 2usize] = Enum::VALUES;
+                        &VALUES[self as usize]
                     }
                 }
             },
@@ -1520,87 +8546,157 @@ mod tests {
     }
 
     #[test]
-    fn enum_with_const_values() {
-        let mapping = Struct(
-            [(
-                "First".into(),
-                Value::Struct(Struct([("key".into(), Value::Unit)].into_iter().collect())),
-            )]
-            .into_iter()
-            .collect(),
+    fn enum_with_value_map() {
+        let mapping = Map::from_pairs([("First", Value::I32(1)), ("Second", Value::I32(2))]);
+        let options = Options {
+            enums: EnumOptions {
+                derived_traits: vec![
+                    "Clone".into(),
+                    "Copy".into(),
+                    "PartialEq".into(),
+                    "Eq".into(),
+                    "Hash".into(),
+                ]
+                .into(),
+                all_values_const_name: Some("VALUES".into()),
+                values_struct: Some(ValuesStructOptions::minimal()),
+                get_value_fn_name: Some("get".into()),
+                value_map_fn_name: Some("map".into()),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+        let result = define_enum_from_keys(&mapping, "Enum", None, &options).unwrap();
+
+        assert_tokens(
+            result,
+            quote! {
+                #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+                pub enum Enum {
+                    First,
+                    Second,
+                }
+
+                impl Enum {
+                    pub const VALUES: &'static [i32] = &[1i32, 2i32,];
+                    pub const fn get(self) -> &'static i32 { &Self::VALUES[self as usize] }
+                    pub fn map() -> std::collections::HashMap<Self, &'static i32> {
+                        [Self::First, Self::Second,]
+                            .into_iter()
+                            .map(|variant| (variant, variant.get()))
+                            .collect()
+                    }
+                }
+            },
         );
-        let result = define_enum_from_keys(
+
+        let error = define_enum_from_keys(
             &mapping,
             "Enum",
             None,
             &Options {
-                serde_support: SerdeSupport::Yes,
                 enums: EnumOptions {
-                    derived_traits: vec!["Clone".into()].into(),
-                    all_variants_const_name: Some("VARIANTS".into()),
-                    impl_default: true,
-                    impl_display: true,
-                    impl_from_str: true,
-                    ..Default::default()
+                    derived_traits: vec!["Clone".into(), "Copy".into()].into(),
+                    ..options.enums.clone()
                 },
-                ..Options::default()
+                ..options.clone()
             },
         )
-        .unwrap();
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`EnumOptions::value_map_fn_name` requires `Eq` to be derived"
+        );
+
+        let options = Options {
+            output: OutputOptions {
+                no_std: true,
+                ..OutputOptions::new()
+            },
+            ..options
+        };
+        let result = define_enum_from_keys(&mapping, "Enum", None, &options);
+        assert!(matches!(result, Err(Error::UnsupportedInNoStd(_))));
+    }
+
+    #[test]
+    fn enum_with_selected_value_fields() {
+        let monster = |name: &str, health| {
+            Value::Struct(Struct::from_pairs([
+                ("name", Value::String(name.into())),
+                (
+                    "stats",
+                    Value::Struct(Struct::from_pairs([
+                        ("health", Value::I64(health)),
+                        ("speed", Value::I64(1)),
+                    ])),
+                ),
+                ("sprite", Value::String("sprite.png".into())),
+            ]))
+        };
+        let mapping = Map::from_pairs([("Goblin", monster("Goblin", 5))]);
+        let options = |field_selection| Options {
+            enums: EnumOptions {
+                all_values_const_name: Some("VALUES".into()),
+                values_struct: Some(ValuesStructOptions {
+                    field_selection,
+                    ..ValuesStructOptions::minimal()
+                }),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
 
+        let included = options(FieldSelection::Include(
+            vec!["name".into(), "stats.health".into()].into(),
+        ));
+        let result = define_enum_from_keys(&mapping, "Monster", None, &included).unwrap();
         assert_tokens(
             result,
             quote! {
-                #[derive(Clone, serde::Serialize, serde::Deserialize, Debug)]
-                pub enum Enum {
-                    First,
-                }
-
-                impl Enum {
-                    pub const VARIANTS: &'static [Self] = &[Self::First, ];
-                    pub const VALUES: &'static [Enum__Value] = &[Enum__Value { key: (), }, ];
-                    pub const fn get(self) -> &'static Enum__Value { &Self::VALUES[self as usize] }
-                }
-
-                impl Default for Enum {
-                    fn default() -> Self {
-                        Self::First
-                    }
+                pub enum Monster {
+                    Goblin,
                 }
 
-                impl std::fmt::Display for Enum {
-                    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-                        <Self as std::fmt::Debug>::fmt(self, f)
-                    }
+                impl Monster {
+                    pub const VALUES: &'static [Monster__Value] = &[Monster__Value {
+                        name: std::borrow::Cow::Borrowed("Goblin"),
+                        stats: Monster__Value__stats {
+                            health: 5i64,
+                        },
+                    },];
                 }
 
-                impl std::str::FromStr for Enum {
-                    type Err = ();
-
-                    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-                        Ok(match s {
-                            "First" => Self::First,
-                            _ => return Err(())
-                        })
-                    }
+                #[allow(non_camel_case_types)]
+                pub struct Monster__Value {
+                    pub name: std::borrow::Cow<'static, str>,
+                    pub stats: Monster__Value__stats,
                 }
 
                 #[allow(non_camel_case_types)]
-                #[derive(Debug, serde::Serialize, serde::Deserialize)]
-                pub struct Enum__Value {
-                    pub key: (),
+                pub struct Monster__Value__stats {
+                    pub health: i64,
                 }
             },
         );
+
+        let excluded = options(FieldSelection::Exclude(
+            vec!["sprite".into(), "stats.speed".into()].into(),
+        ));
+        let result = define_enum_from_keys(&mapping, "Monster", None, &excluded).unwrap();
+        assert_eq!(
+            result.to_string(),
+            define_enum_from_keys(&mapping, "Monster", None, &included)
+                .unwrap()
+                .to_string()
+        );
     }
 
     #[test]
     fn define_consts_from_map_values() {
-        let fields = Struct(
-            [("Key1".into(), Value::Unit), ("Key2".into(), Value::Unit)]
-                .into_iter()
-                .collect(),
-        );
+        let fields = Map([("Key1".into(), Value::Unit), ("Key2".into(), Value::Unit)]
+            .into_iter()
+            .collect());
         let result = define_structs_from_values(&fields, "Struct", &Options::default()).unwrap();
 
         assert_tokens(
@@ -1613,11 +8709,9 @@ mod tests {
 
     #[test]
     fn define_nothing_from_map_values() {
-        let fields = Struct(
-            [("Key1".into(), Value::Unit), ("Key2".into(), Value::Unit)]
-                .into_iter()
-                .collect(),
-        );
+        let fields = Map([("Key1".into(), Value::Unit), ("Key2".into(), Value::Unit)]
+            .into_iter()
+            .collect());
         let result = define_structs_from_values(&fields, "Struct", &Options::minimal()).unwrap();
 
         assert_tokens(result, quote!());
@@ -1625,24 +8719,22 @@ mod tests {
 
     #[test]
     fn define_structs_from_map_values() {
-        let fields = Struct(
-            [
-                (
-                    "Key1".into(),
-                    Value::Struct(Struct(
-                        [("key".into(), Value::Bool(true))].into_iter().collect(),
-                    )),
-                ),
-                (
-                    "Key2".into(),
-                    Value::Struct(Struct(
-                        [("key".into(), Value::Bool(false))].into_iter().collect(),
-                    )),
-                ),
-            ]
-            .into_iter()
-            .collect(),
-        );
+        let fields = Map([
+            (
+                "Key1".into(),
+                Value::Struct(Struct(
+                    [("key".into(), Value::Bool(true))].into_iter().collect(),
+                )),
+            ),
+            (
+                "Key2".into(),
+                Value::Struct(Struct(
+                    [("key".into(), Value::Bool(false))].into_iter().collect(),
+                )),
+            ),
+        ]
+        .into_iter()
+        .collect());
         let result = define_structs_from_values(&fields, "Struct", &Options::default()).unwrap();
 
         assert_tokens(
@@ -1664,27 +8756,25 @@ mod tests {
 
     #[test]
     fn define_structs_from_complex_map_values() {
-        let fields = Struct(
-            [(
-                "Key1".into(),
-                Value::Struct(Struct(
-                    [
-                        ("none".into(), Value::Option(None)),
-                        ("some".into(), Value::Option(Some(Box::new(Value::U8(8))))),
-                        (
-                            "tuple".into(),
-                            Value::Tuple(vec![Value::I8(1), Value::F32(2.0)]),
-                        ),
-                        ("array".into(), Value::Array(2, vec![Value::I8(1); 2])),
-                        ("vec".into(), Value::Vec(vec![Value::I8(1); 2])),
-                    ]
-                    .into_iter()
-                    .collect(),
-                )),
-            )]
-            .into_iter()
-            .collect(),
-        );
+        let fields = Map([(
+            "Key1".into(),
+            Value::Struct(Struct(
+                [
+                    ("none".into(), Value::Option(None)),
+                    ("some".into(), Value::Option(Some(Box::new(Value::U8(8))))),
+                    (
+                        "tuple".into(),
+                        Value::Tuple(vec![Value::I8(1), Value::F32(2.0)]),
+                    ),
+                    ("array".into(), Value::Array(2, vec![Value::I8(1); 2])),
+                    ("vec".into(), Value::Vec(vec![Value::I8(1); 2])),
+                ]
+                .into_iter()
+                .collect(),
+            )),
+        )]
+        .into_iter()
+        .collect());
         let result = define_structs_from_values(&fields, "Struct", &Options::default()).unwrap();
 
         assert_tokens(
@@ -0,0 +1,309 @@
+//! Contains the `InlineDirectives` type, for options declared
+//! within a source file instead of in the build script.
+
+use std::borrow::Cow;
+
+use crate::{
+    error::Error,
+    options::Options,
+    parsing,
+    value::{Struct, Value},
+};
+
+/// The key of the section holding inline directives, at the top
+/// level of a source file.
+pub const DIRECTIVES_KEY: &str = "__edres";
+
+/// Options for some of the keys of a source file, declared in its
+/// top-level `__edres` section.
+///
+/// The section is a mapping which can have any of these keys, each
+/// using key paths as the options do (like `server.port`):
+///
+/// -   `types`: A mapping of key paths to types, as for
+///     [`ParseOptions::type_overrides`].
+/// -   `docs`: A mapping of key paths to doc comments, as for
+///     [`StructOptions::field_docs`].
+/// -   `renames`: A mapping of key paths to field names, as for
+///     [`StructOptions::field_renames`].
+/// -   `skip`: A key pattern, or a sequence of them, as for
+///     [`ParseOptions::ignore_keys`].
+///
+/// The directives take precedence over the options for the same
+/// keys. Every generated item that reads a file or source removes
+/// its section. When several files share their types (like the
+/// values of an enum of file names), the directives from all of them
+/// are combined, in file order.
+///
+/// [`ParseOptions::type_overrides`]: crate::options::ParseOptions::type_overrides
+/// [`ParseOptions::ignore_keys`]: crate::options::ParseOptions::ignore_keys
+/// [`StructOptions::field_docs`]: crate::options::StructOptions::field_docs
+/// [`StructOptions::field_renames`]: crate::options::StructOptions::field_renames
+///
+/// # Examples
+/// ```
+/// # use edres_core::{directives::*, Options, value::*};
+/// let mut value = Value::Struct(Struct::from_pairs([
+///     ("port", Value::I64(8080)),
+///     ("__edres", Value::Struct(Struct::from_pairs([
+///         ("types", Value::Struct(Struct::from_pairs([
+///             ("port", Value::String("u16".into())),
+///         ]))),
+///     ]))),
+/// ]));
+///
+/// let directives = InlineDirectives::extract(&mut value).unwrap();
+/// assert_eq!(directives.type_overrides, [("port".to_owned(), "u16".to_owned())]);
+/// assert_eq!(value, Value::Struct(Struct::from_pairs([("port", Value::I64(8080))])));
+///
+/// let options = directives.apply(&Options::minimal());
+/// assert_eq!(options.parse.type_overrides[0], ("port".into(), "u16".into()));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct InlineDirectives {
+    pub type_overrides: Vec<(String, String)>,
+    pub docs: Vec<(String, String)>,
+    pub renames: Vec<(String, String)>,
+    pub skip: Vec<String>,
+}
+
+impl InlineDirectives {
+    /// Removes the `__edres` section from a value, returning the
+    /// directives in it. The keys the directives skip are removed
+    /// too.
+    ///
+    /// If the value is not a struct, or has no `__edres` section,
+    /// there are no directives.
+    pub fn extract(value: &mut Value) -> Result<Self, Error> {
+        let directives = Self::take(value)?;
+        let skip_options = directives.apply(&Options::minimal()).parse;
+        parsing::remove_ignored_keys(value, &skip_options);
+        Ok(directives)
+    }
+
+    /// Removes the `__edres` section from a value, returning the
+    /// directives in it, but keeps the keys they skip.
+    ///
+    /// This is for when the value is compared against what the keys
+    /// are skipped from, like for a [`GenerationReport`].
+    ///
+    /// [`GenerationReport`]: crate::report::GenerationReport
+    pub fn take(value: &mut Value) -> Result<Self, Error> {
+        let Value::Struct(fields) = value else {
+            return Ok(Self::default());
+        };
+        let Some(section) = fields.0.shift_remove(DIRECTIVES_KEY) else {
+            return Ok(Self::default());
+        };
+        Self::from_section(section).map_err(|error| error.under_key(DIRECTIVES_KEY))
+    }
+
+    fn from_section(section: Value) -> Result<Self, Error> {
+        let mut directives = Self::default();
+        for (key, value) in section.assume_struct()?.0 {
+            let result = match key.as_str() {
+                "types" => string_pairs(value).map(|pairs| directives.type_overrides = pairs),
+                "docs" => string_pairs(value).map(|pairs| directives.docs = pairs),
+                "renames" => string_pairs(value).map(|pairs| directives.renames = pairs),
+                "skip" => strings(value).map(|strings| directives.skip = strings),
+                _ => return Err(Error::UnknownDirective(key)),
+            };
+            result.map_err(|error| error.under_key(&key))?;
+        }
+        Ok(directives)
+    }
+
+    /// Adds the directives from another file, after these ones.
+    ///
+    /// This is used when several files share the same types, so
+    /// the first directive for any key takes precedence.
+    pub fn extend(&mut self, other: Self) {
+        self.type_overrides.extend(other.type_overrides);
+        self.docs.extend(other.docs);
+        self.renames.extend(other.renames);
+        self.skip.extend(other.skip);
+    }
+
+    /// The directives for keys within the value at the given key
+    /// path (like `server.limits`), with key paths relative to that
+    /// value.
+    ///
+    /// Directives for other keys are dropped. Skip patterns which
+    /// start with `**` apply anywhere, so they are kept as they are.
+    ///
+    /// # Examples
+    /// ```
+    /// # use edres_core::directives::*;
+    /// let directives = InlineDirectives {
+    ///     type_overrides: vec![
+    ///         ("server.port".into(), "u16".into()),
+    ///         ("client.port".into(), "u16".into()),
+    ///     ],
+    ///     skip: vec!["server._notes".into(), "**._*".into()],
+    ///     ..InlineDirectives::default()
+    /// };
+    ///
+    /// let directives = directives.under_root("server");
+    /// assert_eq!(directives.type_overrides, [("port".to_owned(), "u16".to_owned())]);
+    /// assert_eq!(directives.skip, ["_notes", "**._*"]);
+    /// ```
+    pub fn under_root(&self, key_path: &str) -> Self {
+        let prefix = format!("{}.", key_path);
+        let rebase = |path: &str| path.strip_prefix(&prefix).map(str::to_owned);
+        let rebase_pairs = |pairs: &[(String, String)]| {
+            pairs
+                .iter()
+                .filter_map(|(path, value)| Some((rebase(path)?, value.clone())))
+                .collect()
+        };
+        InlineDirectives {
+            type_overrides: rebase_pairs(&self.type_overrides),
+            docs: rebase_pairs(&self.docs),
+            renames: rebase_pairs(&self.renames),
+            skip: self
+                .skip
+                .iter()
+                .filter_map(|pattern| match pattern.starts_with("**") {
+                    true => Some(pattern.clone()),
+                    false => rebase(pattern),
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether there are no directives.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// The options, with the directives added to them.
+    pub fn apply(&self, options: &Options) -> Options {
+        let mut options = options.clone();
+        prepend_pairs(&mut options.parse.type_overrides, &self.type_overrides);
+        prepend_pairs(&mut options.structs.field_docs, &self.docs);
+        prepend_pairs(&mut options.structs.field_renames, &self.renames);
+        options.parse.ignore_keys = self
+            .skip
+            .iter()
+            .cloned()
+            .map(Cow::Owned)
+            .chain(options.parse.ignore_keys.iter().cloned())
+            .collect();
+        options
+    }
+}
+
+type Pairs = Cow<'static, [(Cow<'static, str>, Cow<'static, str>)]>;
+
+fn prepend_pairs(pairs: &mut Pairs, new_pairs: &[(String, String)]) {
+    if new_pairs.is_empty() {
+        return;
+    }
+    *pairs = new_pairs
+        .iter()
+        .map(|(a, b)| (Cow::Owned(a.clone()), Cow::Owned(b.clone())))
+        .chain(pairs.iter().cloned())
+        .collect();
+}
+
+fn string(value: Value) -> Result<String, Error> {
+    match value {
+        Value::String(s) => Ok(s),
+        Value::Char(c) => Ok(c.to_string()),
+        value => Err(Error::ExpectedString(value.type_name())),
+    }
+}
+
+fn string_pairs(value: Value) -> Result<Vec<(String, String)>, Error> {
+    let Struct(fields) = value.assume_struct()?;
+    fields
+        .into_iter()
+        .map(|(key, value)| match string(value) {
+            Ok(value) => Ok((key, value)),
+            Err(error) => Err(error.under_key(&key)),
+        })
+        .collect()
+}
+
+fn strings(value: Value) -> Result<Vec<String>, Error> {
+    match value {
+        Value::Vec(items) | Value::Array(_, items) => items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| string(item).map_err(|error| error.under_index(i)))
+            .collect(),
+        value => string(value).map(|s| vec![s]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn extract_directives() {
+        let mut value = Value::Struct(Struct::from_pairs([
+            ("type", Value::String("fire".into())),
+            ("_notes", Value::String("internal".into())),
+            (
+                "__edres",
+                Value::Struct(Struct::from_pairs([
+                    (
+                        "docs",
+                        Value::Struct(Struct::from_pairs([(
+                            "type",
+                            Value::String("The element".into()),
+                        )])),
+                    ),
+                    (
+                        "renames",
+                        Value::Struct(Struct::from_pairs([(
+                            "type",
+                            Value::String("element".into()),
+                        )])),
+                    ),
+                    ("skip", Value::String("_notes".into())),
+                ])),
+            ),
+        ]));
+
+        let directives = InlineDirectives::extract(&mut value).unwrap();
+        assert_eq!(
+            directives,
+            InlineDirectives {
+                type_overrides: vec![],
+                docs: vec![("type".into(), "The element".into())],
+                renames: vec![("type".into(), "element".into())],
+                skip: vec!["_notes".into()],
+            }
+        );
+        assert_eq!(
+            value,
+            Value::Struct(Struct::from_pairs([("type", Value::String("fire".into()))]))
+        );
+        assert!(InlineDirectives::extract(&mut value).unwrap().is_empty());
+    }
+
+    #[test]
+    fn invalid_directives() {
+        let section = |directives| {
+            Value::Struct(Struct::from_pairs([(
+                DIRECTIVES_KEY,
+                Value::Struct(Struct::from_pairs([directives])),
+            )]))
+        };
+
+        let error =
+            InlineDirectives::extract(&mut section(("typos", Value::Bool(true)))).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown directive `typos` (expected `types`, `docs`, `renames`, or `skip`) (at key `__edres`)"
+        );
+
+        let types = Value::Struct(Struct::from_pairs([("port", Value::I64(16))]));
+        let error = InlineDirectives::extract(&mut section(("types", types))).unwrap_err();
+        assert_eq!(error.key_path(), Some("__edres.types.port"));
+    }
+}
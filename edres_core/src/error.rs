@@ -1,11 +1,21 @@
-use std::io::Error as IOError;
+use std::{
+    io::Error as IOError,
+    path::{Path, PathBuf},
+};
 
 use thiserror::Error as ErrorTrait;
 
+use crate::{format::Format, value::Location};
+
 /// An error type for errors while generating config struct modules.
 ///
 /// Errors can either occur during IO (when reading or creating files) or during
 /// the generation itself.
+///
+/// Where they are known, the key path and file that caused an error are
+/// recorded by wrapping it in `AtKey` and `InFile` respectively. The
+/// location within the file is recorded too, where the file's format
+/// tracks it.
 #[derive(Debug, ErrorTrait)]
 pub enum Error {
     #[error("Error parsing number")]
@@ -17,30 +27,355 @@ pub enum Error {
     #[error("Expected value to be a struct but found `{0}` instead")]
     ExpectedStruct(&'static str),
 
+    #[error("Expected value to be a string but found `{0}` instead")]
+    ExpectedString(&'static str),
+
+    #[error("Expected value to be a sequence but found `{0}` instead")]
+    ExpectedSequence(&'static str),
+
+    #[error("Invalid key path `{0}`")]
+    InvalidKeyPath(String),
+
+    #[error("Index in `{0}` is out of bounds (length {1})")]
+    IndexOutOfBounds(String, usize),
+
+    #[error("Key `{0}` not found (available keys: {1})")]
+    KeyNotFound(String, String),
+
+    #[error("Key `{0}` is not an unsigned integer that fits in {1} bits")]
+    InvalidIntegerKey(String, usize),
+
     #[error("Expected values in map, but it was empty")]
     ExpectedValuesInMap,
 
+    #[error("`{0}` requires serde `Deserialize` support to be enabled")]
+    RequiresSerdeDeserialize(&'static str),
+
+    #[error("`{0}` requires `{1}` to be derived")]
+    RequiresDerive(&'static str, &'static str),
+
+    #[error(
+        "YAML source contains {0} documents, but `ParseOptions::yaml_multi_doc` is not enabled"
+    )]
+    MultipleYamlDocuments(usize),
+
+    #[error("Map at key `{0}` cannot be stored in a const (see `StructOptions::keep_as_map`)")]
+    MapInConst(String),
+
+    #[error("Value at key `{0}` cannot be used in a const, because it needs {1} (see `StructOptions::require_const_compatible`)")]
+    NotConstCompatible(String, String),
+
+    #[error("Expected a mapping at key `{0}` (see `StructOptions::keep_as_map`)")]
+    ExpectedMapping(String),
+
+    #[error("Expected a fixed-length sequence at key `{0}` (see `StructOptions::tuple_structs`)")]
+    ExpectedFixedSequence(String),
+
+    #[error("`{0}` is not supported when generating `no_std` code")]
+    UnsupportedInNoStd(&'static str),
+
+    #[error("Generated name `{0}` is used more than once")]
+    DuplicateName(String),
+
+    #[error("Duplicate key `{0}` (see `ParseOptions::duplicate_keys`)")]
+    DuplicateKey(String),
+
+    #[error("Unknown directive `{0}` (expected `types`, `docs`, `renames`, or `skip`)")]
+    UnknownDirective(String),
+
+    #[error("`FilesOptions::lazy_values` is not supported for {0:?} files")]
+    LazyValuesUnsupported(Format),
+
+    #[error("Deserializing {0:?} files directly is not supported")]
+    DeserializeUnsupported(Format),
+
+    #[error("Cannot write {1} as {0:?}")]
+    UnwritableValue(Format, &'static str),
+
+    #[error(
+        "Cannot derive `{derive}` for `{struct_name}` because `{key_path}` is a float \
+        (see `StructOptions::skip_unsupported_derives`)"
+    )]
+    UnsupportedDerive {
+        derive: String,
+        struct_name: String,
+        key_path: String,
+    },
+
+    #[error("Invalid datetime `{0}`")]
+    InvalidDatetime(String),
+
+    #[error("Decimal `{0}` is out of range (see `ParseOptions::decimal_strings`)")]
+    InvalidDecimal(String),
+
+    #[error("Invalid visibility `{0}`")]
+    InvalidVisibility(String),
+
+    #[error("Invalid attribute `{0}`")]
+    InvalidAttribute(String),
+
+    #[error("Invalid value `{value}` for option `{field}`: {reason}")]
+    InvalidOptions {
+        field: String,
+        value: String,
+        reason: &'static str,
+    },
+
+    #[error("Expected a flag name, or a list of flag names, but found `{0}` instead")]
+    InvalidFlag(&'static str),
+
+    #[error("Unknown flag `{0}` (groups can only contain flags defined before them)")]
+    UnknownFlag(String),
+
+    #[error("Too many flags to fit in {0} bits")]
+    TooManyFlags(usize),
+
+    #[error("Locales are missing keys: {}", display_missing_keys(.0))]
+    MissingStringKeys(Vec<(String, Vec<String>)>),
+
+    #[error("Files would have the same variant name `{variant}`: {}", .paths.join(", "))]
+    VariantCollision { variant: String, paths: Vec<String> },
+
+    #[error("Values at key `{0}` cannot be compared to sort variants")]
+    IncomparableSortValues(String),
+
+    #[error("Files include each other in a cycle: {}", display_include_cycle(.0))]
+    IncludeCycle(Vec<PathBuf>),
+
+    #[error(
+        "Index file does not match the directory (missing files: [{}], unlisted files: [{}])",
+        .missing.join(", "),
+        .unlisted.join(", ")
+    )]
+    FileIndexMismatch {
+        missing: Vec<String>,
+        unlisted: Vec<String>,
+    },
+
     #[error("Provided file extension {0:?} not recognized")]
     UnknownInputFormat(Option<String>),
 
     #[error("Unsupported file path `{0}`")]
     UnsupportedFilePath(String),
 
+    #[error("File name `{0}` cannot be used as a module name")]
+    InvalidModuleName(String),
+
+    #[error("`OUT_DIR` is not set (see `OutputOptions::out_dir_relative`)")]
+    OutDirNotSet,
+
+    #[error("Generated file `{}` is out of date: {1}", .0.display())]
+    OutOfDate(PathBuf, String),
+
+    #[error("Directory `{}` not found", .0.display())]
+    DirectoryNotFound(PathBuf),
+
+    #[error("No files matched in directory `{}` (see `FilesOptions::allow_empty`)", .0.display())]
+    NoFilesMatched(PathBuf),
+
+    #[error("Values have incompatible types:{}", display_conflicts(.0))]
+    TypeConflicts(Vec<TypeConflict>),
+
+    #[error("Numbers have no common type, because a `{0}` cannot be converted to `{1}`")]
+    IncompatibleNumbers(&'static str, String),
+
+    #[error("Invalid type override `{1}` for key `{0}`")]
+    InvalidTypeOverride(String, String),
+
+    #[error("Value at key `{0}` cannot be represented as overridden type `{1}`")]
+    IncompatibleTypeOverride(String, String),
+
     #[cfg(feature = "json")]
     #[error("JSON error")]
     Json(#[from] serde_json::Error),
 
+    #[cfg(feature = "json5")]
+    #[error("JSON5 error: {0}")]
+    Json5(#[from] json5::Error),
+
     #[cfg(feature = "toml")]
     #[error("TOML error")]
     Toml(#[from] toml::de::Error),
 
+    #[cfg(feature = "toml")]
+    #[error("TOML serialization error")]
+    TomlSerialize(#[from] toml::ser::Error),
+
     #[cfg(feature = "yaml")]
     #[error("YAML error")]
     Yaml(#[from] serde_yaml::Error),
 
+    #[cfg(feature = "xml")]
+    #[error("XML error")]
+    Xml(#[from] quick_xml::Error),
+
+    #[cfg(feature = "msgpack")]
+    #[error("MessagePack error: {0}")]
+    Msgpack(#[from] rmpv::decode::Error),
+
+    #[cfg(feature = "msgpack")]
+    #[error("Unsupported MessagePack data: {0}")]
+    UnsupportedMsgpack(&'static str),
+
+    #[cfg(feature = "msgpack")]
+    #[error("{0:?} is a binary format, so it cannot be parsed from a string (parse it from bytes instead)")]
+    BinaryFormat(Format),
+
+    #[error("Invalid glob pattern")]
+    Glob(#[from] globset::Error),
+
     #[error("Error walking directories")]
     Ignore(#[from] ignore::Error),
 
     #[error("IO error")]
     Io(#[from] IOError),
+
+    #[error("{error} (at key `{key_path}`)")]
+    AtKey { key_path: String, error: Box<Error> },
+
+    #[error("{error} (in file `{}{}`)", .path.display(), display_location(.location))]
+    InFile {
+        path: PathBuf,
+        location: Option<Location>,
+        error: Box<Error>,
+    },
+}
+
+impl Error {
+    /// The key path (like `root.servers[3].port`) of the value
+    /// that caused this error, if known.
+    pub fn key_path(&self) -> Option<&str> {
+        match self {
+            Error::AtKey { key_path, .. } => Some(key_path),
+            Error::InFile { error, .. } => error.key_path(),
+            _ => None,
+        }
+    }
+
+    /// The path of the file that caused this error, if known.
+    pub fn file_path(&self) -> Option<&Path> {
+        match self {
+            Error::InFile { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Add the key (or key path, like `server.limits`) of a parent
+    /// value to the key path of this error.
+    pub fn under_key(self, key: &str) -> Self {
+        self.under_parent(key.to_owned())
+    }
+
+    /// Add the index of a parent sequence to the key path of this
+    /// error.
+    pub(crate) fn under_index(self, index: usize) -> Self {
+        self.under_parent(format!("[{}]", index))
+    }
+
+    /// Errors in other files (like included ones) keep the key path
+    /// within that file.
+    fn under_parent(self, parent: String) -> Self {
+        match self {
+            error @ Error::InFile { .. } => error,
+            Error::AtKey { key_path, error } => {
+                let separator = if key_path.starts_with('[') { "" } else { "." };
+                Error::AtKey {
+                    key_path: format!("{}{}{}", parent, separator, key_path),
+                    error,
+                }
+            }
+            error => Error::AtKey {
+                key_path: parent,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Record the file that caused this error, unless one is
+    /// already known.
+    pub fn in_file(self, path: &Path) -> Self {
+        self.in_file_at(path, None)
+    }
+
+    /// Record the file that caused this error, and where in that
+    /// file, unless a file is already known.
+    pub(crate) fn in_file_at(self, path: &Path, location: Option<Location>) -> Self {
+        match self {
+            error @ Error::InFile { .. } => error,
+            error => Error::InFile {
+                path: path.to_owned(),
+                location,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Where in its file the value that caused this error starts, if
+    /// known (see [`SpannedValue`]).
+    ///
+    /// [`SpannedValue`]: crate::value::SpannedValue
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            Error::InFile { location, .. } => *location,
+            _ => None,
+        }
+    }
+}
+
+/// A key path at which values from two different sources (usually
+/// files) have incompatible types.
+///
+/// Items of sequences are listed under the key path of the
+/// sequence followed by `[]`, like `items[]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeConflict {
+    pub key_path: String,
+    pub first_type: &'static str,
+    pub first_source: String,
+    pub second_type: &'static str,
+    pub second_source: String,
+}
+
+impl std::fmt::Display for TypeConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.key_path.is_empty() {
+            true => write!(f, "the root value")?,
+            false => write!(f, "field `{}`", self.key_path)?,
+        }
+        write!(
+            f,
+            " is {} in {} but {} in {}",
+            self.first_type, self.first_source, self.second_type, self.second_source
+        )
+    }
+}
+
+fn display_location(location: &Option<Location>) -> String {
+    match location {
+        Some(location) => format!(":{}", location),
+        None => String::new(),
+    }
+}
+
+fn display_include_cycle(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| format!("`{}`", path.display()))
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+fn display_missing_keys(missing: &[(String, Vec<String>)]) -> String {
+    missing
+        .iter()
+        .map(|(locale, keys)| format!("`{}` is missing [{}]", locale, keys.join(", ")))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn display_conflicts(conflicts: &[TypeConflict]) -> String {
+    conflicts
+        .iter()
+        .map(|conflict| format!("\n  {}", conflict))
+        .collect()
 }
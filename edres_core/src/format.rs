@@ -1,5 +1,7 @@
 use std::path::Path;
 
+use serde::{Deserialize, Serialize};
+
 use crate::error::*;
 
 /// Represents an input markup format for a config file.
@@ -7,14 +9,21 @@ use crate::error::*;
 /// The variants that exist correspond to the features that have been enabled.
 /// For example, if the `json` feature is not enabled, then the
 /// `Format::Json` variant will not exist.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Format {
     #[cfg(feature = "json")]
     Json,
+    #[cfg(feature = "json5")]
+    Json5,
     #[cfg(feature = "toml")]
     Toml,
     #[cfg(feature = "yaml")]
     Yaml,
+    #[cfg(feature = "xml")]
+    Xml,
+    #[cfg(feature = "msgpack")]
+    Msgpack,
 }
 
 impl Format {
@@ -35,12 +44,21 @@ impl Format {
                 #[cfg(feature = "json")]
                 "json" => Ok(Format::Json),
 
+                #[cfg(feature = "json5")]
+                "json5" => Ok(Format::Json5),
+
                 #[cfg(feature = "toml")]
                 "toml" => Ok(Format::Toml),
 
                 #[cfg(feature = "yaml")]
                 "yaml" | "yml" => Ok(Format::Yaml),
 
+                #[cfg(feature = "xml")]
+                "xml" => Ok(Format::Xml),
+
+                #[cfg(feature = "msgpack")]
+                "msgpack" | "mp" => Ok(Format::Msgpack),
+
                 other => Err(Error::UnknownInputFormat(Some(other.into()))),
             },
             None => Err(Error::UnknownInputFormat(None)),
@@ -1,14 +1,27 @@
 pub mod codegen;
+pub mod directives;
 pub mod options;
 pub mod parsing;
+pub mod report;
+pub mod validation;
 pub mod value;
 
 mod error;
 mod format;
 
-#[cfg(not(any(feature = "json", feature = "toml", feature = "yaml",)))]
+#[cfg(not(any(
+    feature = "json",
+    feature = "toml",
+    feature = "yaml",
+    feature = "xml",
+    feature = "msgpack",
+)))]
 compile_error!(
-    "The edres crate requires at least one parsing feature to be enabled:\n {json, toml, yaml}"
+    "The edres crate requires at least one parsing feature to be enabled:\n {json, toml, yaml, xml, msgpack}"
 );
 
-pub use crate::{error::Error, format::Format, options::*};
+pub use crate::{
+    error::{Error, TypeConflict},
+    format::Format,
+    options::*,
+};
@@ -6,12 +6,25 @@
 //! Most structs have a `new` constructor which contains sensible
 //! defaults, as well as a `minimal` constructor which generates
 //! as little code as possible.
+//!
+//! All options can also be deserialized (for example, with
+//! [`Options::from_file`]). Missing fields take their values from
+//! the `new` constructor, and enum variants are written in
+//! `snake_case`.
+
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
 
-use std::borrow::Cow;
+use crate::{error::Error, format::Format, parsing};
 
 /// Contains the full set of options for all public APIs
 /// in this crate.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Options {
     /// In present, generates a const with this name that
     /// stores the path of the original markup file.
@@ -32,6 +45,9 @@ pub struct Options {
     /// See [`FilesOptions`].
     pub files: FilesOptions,
 
+    /// See [`FlagsOptions`].
+    pub flags: FlagsOptions,
+
     /// See [`OutputOptions`].
     pub output: OutputOptions,
 }
@@ -47,6 +63,7 @@ impl Options {
     ///     structs: StructOptions::new(),
     ///     enums: EnumOptions::new(),
     ///     files: FilesOptions::new(),
+    ///     flags: FlagsOptions::new(),
     ///     output: OutputOptions::new(),
     /// });
     /// ```
@@ -59,6 +76,7 @@ impl Options {
             structs: StructOptions::new(),
             enums: EnumOptions::new(),
             files: FilesOptions::new(),
+            flags: FlagsOptions::new(),
             output: OutputOptions::new(),
         }
     }
@@ -73,6 +91,7 @@ impl Options {
     ///     structs: StructOptions::new(),
     ///     enums: EnumOptions::new(),
     ///     files: FilesOptions::new(),
+    ///     flags: FlagsOptions::new(),
     ///     output: OutputOptions::new(),
     /// });
     /// ```
@@ -85,6 +104,7 @@ impl Options {
             structs: StructOptions::new(),
             enums: EnumOptions::new(),
             files: FilesOptions::new(),
+            flags: FlagsOptions::new(),
             output: OutputOptions::new(),
         }
     }
@@ -98,6 +118,7 @@ impl Options {
     ///     structs: StructOptions::minimal(),
     ///     enums: EnumOptions::minimal(),
     ///     files: FilesOptions::minimal(),
+    ///     flags: FlagsOptions::minimal(),
     ///     output: OutputOptions::new(),
     /// });
     /// ```
@@ -110,11 +131,34 @@ impl Options {
             structs: StructOptions::minimal(),
             enums: EnumOptions::minimal(),
             files: FilesOptions::minimal(),
+            flags: FlagsOptions::minimal(),
             output: OutputOptions::new(),
         }
     }
 }
 
+impl Options {
+    /// Load options from a markup file (like `edres.toml`), whose
+    /// format is inferred from its extension.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use edres_core::options::*;
+    /// /* edres.toml:
+    /// serde_support = "yes"
+    ///
+    /// [structs]
+    /// derived_traits = ["Debug", "Clone"]
+    /// */
+    ///
+    /// let options = Options::from_file("edres.toml").unwrap();
+    /// assert_eq!(options.serde_support, SerdeSupport::Yes);
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Options, Error> {
+        parsing::deserialize_source_file(path.as_ref())
+    }
+}
+
 impl Default for Options {
     /// # Examples
     /// ```
@@ -127,7 +171,8 @@ impl Default for Options {
 }
 
 /// Options specific to how `edres` should parse markup.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ParseOptions {
     /// The floating point type to infer from input.
     ///
@@ -143,6 +188,17 @@ pub struct ParseOptions {
     /// default will instead be inferred as a larger type.
     pub default_int_size: IntSize,
 
+    /// The unsigned integer type to infer for non-negative
+    /// integers from input.
+    ///
+    /// If not present, all integers use `default_int_size`. If
+    /// present, this can be anything from `u8` to `u128`,
+    /// including `usize`. As with signed integers, values that
+    /// are too large will be inferred as a larger type. Sequences
+    /// that mix negative and non-negative numbers are unified to a
+    /// signed type.
+    pub default_uint_size: Option<UIntSize>,
+
     /// What size of sequence, if any, to consider small enough
     /// to use an array instead of a `Vec`.
     ///
@@ -151,6 +207,111 @@ pub struct ParseOptions {
     /// the resulting struct. Meanwhile, a sequence of 4 values
     /// would instead generate a `[T; 4]`.
     pub max_array_size: Option<usize>,
+
+    /// A list of key paths, and the types that should be used for
+    /// the values at those paths instead of the inferred ones.
+    ///
+    /// A key path is a list of keys separated by `.`, so for
+    /// example `server.port` refers to the `port` field of the
+    /// `server` struct in the root of the file. Sequences and
+    /// options are transparent, so an override for `ports` will
+    /// apply to each number in a `ports` sequence.
+    ///
+    /// Numeric types (like `u16`) convert the value to that type.
+    /// Any other type is treated as a tuple struct wrapping the
+    /// inferred type, so `MyId` would generate `MyId(5i64)`. The
+    /// wrapped type can also be given explicitly, so `MyId(u32)`
+    /// would generate `MyId(5u32)`.
+    pub type_overrides: Cow<'static, [(Cow<'static, str>, Cow<'static, str>)]>,
+
+    /// Whether YAML sources may contain multiple documents
+    /// (separated by `---`).
+    ///
+    /// If true, a YAML source is always parsed as a sequence with
+    /// one item per document. If false, a source containing more
+    /// than one document is an error.
+    pub yaml_multi_doc: bool,
+
+    /// A prefix added to the names of fields generated from XML
+    /// attributes.
+    ///
+    /// This can be used to tell attributes apart from child
+    /// elements with the same name. For example, with a prefix of
+    /// `attr_`, `<item id="1"/>` has a field called `attr_id`.
+    pub xml_attribute_prefix: Cow<'static, str>,
+
+    /// How datetimes (like those in TOML) are represented.
+    pub datetime_handling: DatetimeHandling,
+
+    /// How sequences containing values of incompatible types (like
+    /// `[1, "a", true]`) are represented.
+    pub heterogeneous_arrays: HeterogeneousArrayMode,
+
+    /// The key of the field which tells apart the variants of
+    /// tagged structs (see [`HeterogeneousArrayMode::Tagged`]).
+    pub tag_key: Cow<'static, str>,
+
+    /// If present, values in source files can include the contents
+    /// of other files, which are spliced in before generation.
+    ///
+    /// A value is replaced by the contents of a file if it is
+    /// either a string made of this directive, a space, and a path
+    /// (like `"!include other.yaml"`), or a map with this directive
+    /// as its only key and a path as its value (like
+    /// `{"$include": "other.json"}`). In YAML, a value tagged with
+    /// the directive (like `!include other.yaml`) also works.
+    ///
+    /// Paths are relative to the file containing them, and may be
+    /// in any supported format. Included files can include other
+    /// files, but not in a cycle. Sources that aren't files can't
+    /// include anything.
+    pub include_directive: Option<Cow<'static, str>>,
+
+    /// How sequences are merged when layering files on top of each
+    /// other (see [`parsing::merge_values`]).
+    pub layer_array_merge: ArrayMerge,
+
+    /// A list of key paths for keys which are dropped during
+    /// parsing, so they never appear in generated code (like
+    /// `$schema`, or `_comment` fields).
+    ///
+    /// Key paths are written as for `type_overrides`, and sequences
+    /// and options are transparent. In each key, `*` matches any
+    /// number of characters, and a key of `**` matches any number of
+    /// nested keys. So `*._comment` drops the `_comment` field of
+    /// every top-level struct, and `**._*` drops every field whose
+    /// name starts with `_`.
+    pub ignore_keys: Cow<'static, [Cow<'static, str>]>,
+
+    /// What happens when a mapping in a source file contains the
+    /// same key more than once.
+    ///
+    /// This applies to JSON, JSON5, YAML, and MessagePack files.
+    /// TOML files are always rejected by the TOML parser, and
+    /// repeated XML elements become sequences instead. In YAML,
+    /// keys which become the same string (like `1` and `"1"`) also
+    /// count as duplicates.
+    pub duplicate_keys: DuplicateKeyPolicy,
+
+    /// Whether strings of exactly one character should be parsed as
+    /// `char` values, as RON's `'a'` literals are.
+    ///
+    /// Where such a string is mixed with longer strings (like in a
+    /// sequence, or the same field of different files), they are
+    /// all kept as strings.
+    pub infer_char_from_single_char_strings: bool,
+
+    /// Whether strings containing decimal numbers (like `"19.99"`)
+    /// become `rust_decimal::Decimal`s, which keep their exact value.
+    ///
+    /// Only strings with a fractional part are converted, so that
+    /// strings of digits (like IDs) are left alone. The number must
+    /// have at most 28 digits after the decimal point, and fit in
+    /// 96 bits without it. The crate including the generated code
+    /// must depend on `rust_decimal` (1.26 or later, for const
+    /// construction).
+    #[cfg(feature = "decimal")]
+    pub decimal_strings: bool,
 }
 
 impl ParseOptions {
@@ -160,14 +321,42 @@ impl ParseOptions {
     /// assert_eq!(ParseOptions::new(), ParseOptions {
     ///     default_float_size: FloatSize::F64,
     ///     default_int_size: IntSize::I64,
+    ///     default_uint_size: None,
     ///     max_array_size: None,
+    ///     type_overrides: vec![].into(),
+    ///     yaml_multi_doc: false,
+    ///     xml_attribute_prefix: "".into(),
+    ///     datetime_handling: DatetimeHandling::String,
+    ///     heterogeneous_arrays: HeterogeneousArrayMode::Vec,
+    ///     tag_key: "type".into(),
+    ///     include_directive: None,
+    ///     layer_array_merge: ArrayMerge::Replace,
+    ///     ignore_keys: vec![].into(),
+    ///     duplicate_keys: DuplicateKeyPolicy::LastWins,
+    ///     infer_char_from_single_char_strings: false,
+    ///     #[cfg(feature = "decimal")]
+    ///     decimal_strings: false,
     /// });
     /// ```
     pub const fn new() -> Self {
         ParseOptions {
             default_float_size: FloatSize::F64,
             default_int_size: IntSize::I64,
+            default_uint_size: None,
             max_array_size: None,
+            type_overrides: Cow::Borrowed(&[]),
+            yaml_multi_doc: false,
+            xml_attribute_prefix: Cow::Borrowed(""),
+            datetime_handling: DatetimeHandling::String,
+            heterogeneous_arrays: HeterogeneousArrayMode::Vec,
+            tag_key: Cow::Borrowed("type"),
+            include_directive: None,
+            layer_array_merge: ArrayMerge::Replace,
+            ignore_keys: Cow::Borrowed(&[]),
+            duplicate_keys: DuplicateKeyPolicy::LastWins,
+            infer_char_from_single_char_strings: false,
+            #[cfg(feature = "decimal")]
+            decimal_strings: false,
         }
     }
 }
@@ -184,7 +373,8 @@ impl Default for ParseOptions {
 }
 
 /// Options specific to how `edres` should generate structs.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct StructOptions {
     /// A list of traits to derive.
     ///
@@ -195,10 +385,197 @@ pub struct StructOptions {
     /// set this value.
     pub derived_traits: Cow<'static, [Cow<'static, str>]>,
 
+    /// Whether to leave out derived traits which a struct's fields
+    /// don't support, instead of returning an error.
+    ///
+    /// Currently this means `Eq`, `Ord`, and `Hash`, for structs with
+    /// `f32` or `f64` fields (including inside nested structs).
+    /// Without this option, the error names the float field, rather
+    /// than the generated code failing to compile.
+    pub skip_unsupported_derives: bool,
+
     /// If present, generates a const with the given name that
     /// stores the contents of the file as a value of the generated
     /// type.
     pub struct_data_const_name: Option<Cow<'static, str>>,
+
+    /// Whether to check that every generated value can be used in
+    /// a `const`, returning an error naming the key path of the first
+    /// one that can't.
+    ///
+    /// This rejects values which would need allocating, like maps
+    /// kept as maps (see [`StructOptions::keep_as_map`]) and type
+    /// overrides wrapping values in types like `String` or `Box`.
+    /// The check applies to all generated values, including those
+    /// for `Default` impls and builders.
+    pub require_const_compatible: bool,
+
+    /// Whether the generated struct should implement the `Default`
+    /// trait.
+    ///
+    /// This uses the contents of the file as the default value.
+    pub impl_default: bool,
+
+    /// Whether to generate a builder (like `MyStructBuilder`) for
+    /// the generated struct, with a `builder` method to create one.
+    ///
+    /// The builder starts with the contents of the file, and has a
+    /// setter method for each field (named after the field) and a
+    /// `build` method which returns the struct. This makes it easy
+    /// to construct a tweaked copy of the data at runtime. Only the
+    /// outermost struct gets a builder, and builders are not
+    /// generated for structs from multiple files.
+    pub generate_builder: bool,
+
+    /// A list of key paths for mappings that should be kept as
+    /// maps, rather than becoming structs.
+    ///
+    /// The values at these paths generate a
+    /// `std::collections::BTreeMap<String, T>`, where `T` is the
+    /// unified type of the values in the mapping. Inside a map,
+    /// `*` stands for any key, so `plugins.*.name` refers to the
+    /// `name` field of every value in a `plugins` map.
+    ///
+    /// Maps cannot be built in a const, so using this with
+    /// [`StructOptions::struct_data_const_name`] (or any other
+    /// const holding the data) is an error. The `Default` impl
+    /// is still supported.
+    pub keep_as_map: Cow<'static, [Cow<'static, str>]>,
+
+    /// A list of key paths for fixed-length sequences, and the
+    /// names of tuple structs to generate for them.
+    ///
+    /// For example, `("position", "Vec3")` would generate
+    /// `pub struct Vec3(pub f64, pub f64, pub f64);` for a
+    /// `position` of `[1.0, 2.0, 3.0]`, instead of using `[f64; 3]`.
+    /// The sequence must be an array (see
+    /// [`ParseOptions::max_array_size`]) or a tuple (see
+    /// [`HeterogeneousArrayMode::Tuple`]). Options and vectors are
+    /// transparent, and the elements have key paths like
+    /// `position.0`.
+    ///
+    /// Several key paths in the same struct can use the same name,
+    /// as long as their elements have the same types.
+    pub tuple_structs: Cow<'static, [(Cow<'static, str>, Cow<'static, str>)]>,
+
+    /// Key paths of fields, and the names to give them in the
+    /// generated structs.
+    ///
+    /// For example, `("shapes.type", "kind")` would generate a `kind`
+    /// field for the `type` key of `shapes`, instead of the raw
+    /// identifier `r#type`. When serde is supported, the field gets a
+    /// `#[serde(rename = "type")]` attribute so it still reads and
+    /// writes the original key.
+    pub field_renames: Cow<'static, [(Cow<'static, str>, Cow<'static, str>)]>,
+
+    /// Key paths of fields, and doc comments to give them in the
+    /// generated structs.
+    ///
+    /// Each line of the text becomes a line of the doc comment.
+    pub field_docs: Cow<'static, [(Cow<'static, str>, Cow<'static, str>)]>,
+
+    /// Extra attributes (like `non_exhaustive`) to apply to
+    /// generated structs, without the surrounding `#[...]`.
+    ///
+    /// These are placed after the derives, so they can use derive
+    /// helper attributes like `serde(deny_unknown_fields)`.
+    pub extra_attributes: Cow<'static, [Cow<'static, str>]>,
+
+    /// If present, the traits to derive for nested structs (the
+    /// structs generated for mappings within the data), instead of
+    /// [`StructOptions::derived_traits`].
+    ///
+    /// Nested structs still need to implement any trait their
+    /// parent derives for its fields, so this is mostly useful for
+    /// leaving out traits like `Copy` or `Default`.
+    pub nested_derived_traits: Option<Cow<'static, [Cow<'static, str>]>>,
+
+    /// If present, the extra attributes for nested structs, instead
+    /// of [`StructOptions::extra_attributes`].
+    pub nested_extra_attributes: Option<Cow<'static, [Cow<'static, str>]>>,
+
+    /// How nested structs are named, based on the name of their
+    /// parent struct and the key they are under.
+    pub nested_struct_naming: StructNaming,
+
+    /// The separator between the parent name and key of nested
+    /// structs named with [`StructNaming::Underscored`].
+    ///
+    /// This can only contain letters, digits, and underscores. An
+    /// empty separator joins the names directly, like
+    /// `Configserver`.
+    pub nested_name_separator: Cow<'static, str>,
+
+    /// If present, generates a method with this name which checks
+    /// that a value (for example, one loaded at runtime) has the
+    /// same shape as the data the struct was generated from.
+    ///
+    /// It returns an error describing the first key which is
+    /// present in the original data, but missing (`None`) in the
+    /// checked value. Items in sequences and maps are not checked.
+    /// If serde `Deserialize` support is enabled,
+    /// the structs also reject unknown fields when deserializing.
+    pub validate_fn_name: Option<Cow<'static, str>>,
+
+    /// Whether the fields of generated structs get serde defaults,
+    /// so that values deserialized at runtime can leave them out.
+    ///
+    /// This only applies if serde `Deserialize` support is enabled.
+    pub serde_defaults: SerdeDefaults,
+
+    /// Whether a file containing a sequence (rather than a map) at
+    /// the top level can be used to generate structs.
+    ///
+    /// If true, a single struct is generated for the items of the
+    /// sequence, and [`StructOptions::struct_data_const_name`]
+    /// becomes a const slice of all the items. (See
+    /// [`define_structs_from_sequence`].)
+    ///
+    /// [`define_structs_from_sequence`]: crate::codegen::define_structs_from_sequence
+    pub allow_root_sequence: bool,
+
+    /// Whether mappings with a single key should generate a newtype
+    /// (like `pub struct Wrapper(pub i64)`) instead of a struct with
+    /// one named field.
+    ///
+    /// Note that serde treats newtypes as their inner value, so with
+    /// serde support enabled, these structs will not deserialize
+    /// from the mapping they were generated from.
+    pub newtype_single_field: bool,
+
+    /// The visibility of the fields of generated structs.
+    ///
+    /// Constants holding the data (like
+    /// [`StructOptions::struct_data_const_name`]) are generated in
+    /// the same module, so they work with any visibility.
+    pub field_visibility: Visibility,
+
+    /// Whether to generate an accessor method for each field, like
+    /// `pub const fn name(&self) -> &T`.
+    ///
+    /// This is mostly useful when the fields are private (see
+    /// [`StructOptions::field_visibility`]). The accessors have the
+    /// same visibility as other generated items.
+    pub generate_accessors: bool,
+
+    /// The order of the fields of generated structs, and of the
+    /// fields in their values.
+    ///
+    /// With [`FieldOrder::Alphabetical`], reordering the keys in a
+    /// source file doesn't change the generated code. (Enum
+    /// variants can be sorted with [`EnumOptions::sort_variants_by`].)
+    pub sort_fields: FieldOrder,
+
+    /// If present, the path of an existing type (like
+    /// `crate::config::Config`) to generate a `From` impl for, which
+    /// converts the generated struct into it.
+    ///
+    /// Fields are matched by name, and each one is converted with
+    /// `Into`, so nested structs need their own `From` impls. A
+    /// missing or extra field is a compile error in the generated
+    /// code, which makes this useful for checking that a handwritten
+    /// struct matches its file.
+    pub convert_into: Option<Cow<'static, str>>,
 }
 
 impl StructOptions {
@@ -207,13 +584,55 @@ impl StructOptions {
     /// # use edres_core::options::*;
     /// assert_eq!(StructOptions::new(), StructOptions {
     ///     derived_traits: vec!["Debug".into()].into(),
+    ///     skip_unsupported_derives: false,
     ///     struct_data_const_name: Some("DATA".into()),
+    ///     require_const_compatible: false,
+    ///     impl_default: false,
+    ///     generate_builder: false,
+    ///     keep_as_map: vec![].into(),
+    ///     tuple_structs: vec![].into(),
+    ///     field_renames: vec![].into(),
+    ///     field_docs: vec![].into(),
+    ///     extra_attributes: vec![].into(),
+    ///     nested_derived_traits: None,
+    ///     nested_extra_attributes: None,
+    ///     nested_struct_naming: StructNaming::Underscored,
+    ///     nested_name_separator: "__".into(),
+    ///     validate_fn_name: None,
+    ///     serde_defaults: SerdeDefaults::None,
+    ///     allow_root_sequence: false,
+    ///     newtype_single_field: false,
+    ///     field_visibility: Visibility::Pub,
+    ///     generate_accessors: false,
+    ///     sort_fields: FieldOrder::Source,
+    ///     convert_into: None,
     /// });
     /// ```
     pub const fn new() -> StructOptions {
         StructOptions {
             derived_traits: Cow::Borrowed(&[Cow::Borrowed("Debug")]),
+            skip_unsupported_derives: false,
             struct_data_const_name: Some(Cow::Borrowed("DATA")),
+            require_const_compatible: false,
+            impl_default: false,
+            generate_builder: false,
+            keep_as_map: Cow::Borrowed(&[]),
+            tuple_structs: Cow::Borrowed(&[]),
+            field_renames: Cow::Borrowed(&[]),
+            field_docs: Cow::Borrowed(&[]),
+            extra_attributes: Cow::Borrowed(&[]),
+            nested_derived_traits: None,
+            nested_extra_attributes: None,
+            nested_struct_naming: StructNaming::Underscored,
+            nested_name_separator: Cow::Borrowed("__"),
+            validate_fn_name: None,
+            serde_defaults: SerdeDefaults::None,
+            allow_root_sequence: false,
+            newtype_single_field: false,
+            field_visibility: Visibility::Pub,
+            generate_accessors: false,
+            sort_fields: FieldOrder::Source,
+            convert_into: None,
         }
     }
 
@@ -222,13 +641,55 @@ impl StructOptions {
     /// # use edres_core::options::*;
     /// assert_eq!(StructOptions::minimal(), StructOptions {
     ///     derived_traits: vec![].into(),
+    ///     skip_unsupported_derives: false,
     ///     struct_data_const_name: None,
+    ///     require_const_compatible: false,
+    ///     impl_default: false,
+    ///     generate_builder: false,
+    ///     keep_as_map: vec![].into(),
+    ///     tuple_structs: vec![].into(),
+    ///     field_renames: vec![].into(),
+    ///     field_docs: vec![].into(),
+    ///     extra_attributes: vec![].into(),
+    ///     nested_derived_traits: None,
+    ///     nested_extra_attributes: None,
+    ///     nested_struct_naming: StructNaming::Underscored,
+    ///     nested_name_separator: "__".into(),
+    ///     validate_fn_name: None,
+    ///     serde_defaults: SerdeDefaults::None,
+    ///     allow_root_sequence: false,
+    ///     newtype_single_field: false,
+    ///     field_visibility: Visibility::Pub,
+    ///     generate_accessors: false,
+    ///     sort_fields: FieldOrder::Source,
+    ///     convert_into: None,
     /// });
     /// ```
     pub const fn minimal() -> StructOptions {
         StructOptions {
             derived_traits: Cow::Borrowed(&[]),
+            skip_unsupported_derives: false,
             struct_data_const_name: None,
+            require_const_compatible: false,
+            impl_default: false,
+            generate_builder: false,
+            keep_as_map: Cow::Borrowed(&[]),
+            tuple_structs: Cow::Borrowed(&[]),
+            field_renames: Cow::Borrowed(&[]),
+            field_docs: Cow::Borrowed(&[]),
+            extra_attributes: Cow::Borrowed(&[]),
+            nested_derived_traits: None,
+            nested_extra_attributes: None,
+            nested_struct_naming: StructNaming::Underscored,
+            nested_name_separator: Cow::Borrowed("__"),
+            validate_fn_name: None,
+            serde_defaults: SerdeDefaults::None,
+            allow_root_sequence: false,
+            newtype_single_field: false,
+            field_visibility: Visibility::Pub,
+            generate_accessors: false,
+            sort_fields: FieldOrder::Source,
+            convert_into: None,
         }
     }
 }
@@ -244,8 +705,16 @@ impl Default for StructOptions {
     }
 }
 
+const STRUM_DERIVES: &[Cow<'static, str>] = &[
+    Cow::Borrowed("EnumIter"),
+    Cow::Borrowed("AsRefStr"),
+    Cow::Borrowed("Display"),
+    Cow::Borrowed("EnumString"),
+];
+
 /// Options specific to how `edres` should generate enums.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct EnumOptions {
     /// A list of traits to derive.
     ///
@@ -256,6 +725,72 @@ pub struct EnumOptions {
     /// set this value.
     pub derived_traits: Cow<'static, [Cow<'static, str>]>,
 
+    /// If present, generated enums get a `#[repr]` attribute with
+    /// the given integer type, and an `as_index` method.
+    ///
+    /// This keeps the size of the enum, and the index of each
+    /// variant (in declaration order), stable.
+    pub repr: Option<EnumRepr>,
+
+    /// Extra attributes (like `non_exhaustive`) to apply to
+    /// generated enums, without the surrounding `#[...]`.
+    ///
+    /// These are placed after the derives, so they can use derive
+    /// helper attributes like `serde(deny_unknown_fields)`.
+    pub extra_attributes: Cow<'static, [Cow<'static, str>]>,
+
+    /// How keys (or file names) are converted to variant names.
+    ///
+    /// If not present, map keys are used as they are, and file
+    /// names are converted with [`Casing::SnakeToPascal`]. It is
+    /// an error for two variants to end up with the same name.
+    pub variant_casing: Option<Casing>,
+
+    /// The order of the variants of enums generated from map keys.
+    ///
+    /// This determines the order of the variants themselves, and so
+    /// the order of consts like
+    /// [`EnumOptions::all_variants_const_name`] and
+    /// [`EnumOptions::all_values_const_name`], and the
+    /// discriminants. For nested enums (see
+    /// [`EnumOptions::nested`]), the inner enums are sorted in the
+    /// same way.
+    pub sort_variants_by: VariantSort,
+
+    /// If present, map keys are parsed as unsigned integers (like
+    /// HTTP status codes) when generating enums from keys.
+    ///
+    /// The [`IntegerKeyOptions`] defines how the variants are named,
+    /// and the integer type of the keys. [`EnumOptions::variant_casing`]
+    /// is ignored for these variants.
+    pub integer_keys: Option<IntegerKeyOptions>,
+
+    /// Whether maps of maps should generate nested enums.
+    ///
+    /// If set, [`define_enum_from_keys`] generates an outer enum for
+    /// the top-level keys, and an inner enum for the keys of each
+    /// value. Every value in the map must itself be a map.
+    ///
+    /// Each inner enum is named like a nested struct (see
+    /// [`StructOptions::nested_struct_naming`]) and has a `PARENT`
+    /// const which holds its corresponding outer variant. The outer
+    /// enum has a const for each variant, named like `WEAPONS` for a
+    /// `Weapons` variant, listing the variants of its inner enum. The
+    /// outer enum has no values struct.
+    ///
+    /// [`define_enum_from_keys`]: crate::codegen::define_enum_from_keys
+    pub nested: bool,
+
+    /// If present, a field with this name (like `_alias`) in the
+    /// value of a key lists alternative names for its variant.
+    ///
+    /// The field can be a string, or a sequence of strings. It is
+    /// removed from the value before generating the values struct,
+    /// and each alias is also accepted by the `FromStr` impl and
+    /// `from_key_str`. This allows keys to be renamed without
+    /// breaking existing data which uses the old names.
+    pub alias_key: Option<Cow<'static, str>>,
+
     /// Whether generated enums should implement the `Default`
     /// trait.
     ///
@@ -268,12 +803,99 @@ pub struct EnumOptions {
     /// For example, `MyEnum::First.to_string() == "First"`.
     pub impl_display: bool,
 
+    /// If present, [`EnumOptions::impl_display`] displays the
+    /// original key (or file name) of the variant converted to this
+    /// casing, instead of the name of the variant. For example, with
+    /// [`Casing::Kebab`], `MyEnum::MaxSize.to_string() == "max-size"`.
+    ///
+    /// The strings from [`EnumOptions::impl_as_ref_str`] and
+    /// [`EnumOptions::static_str_fn_name`] use this casing too, so
+    /// they always match `Display`.
+    pub display_casing: Option<Casing>,
+
     /// Whether generated enums should implement `FromStr`.
     ///
     /// This works by matching the name of the variant.
     /// For example, `"First".parse().unwrap() == MyEnum::First`.
     pub impl_from_str: bool,
 
+    /// The spellings [`EnumOptions::impl_from_str`] accepts for each
+    /// variant: its original key (or file name) converted to each of
+    /// these casings. [`Casing::Preserve`] accepts the key itself.
+    ///
+    /// If empty, only the name of the variant is accepted. Aliases
+    /// (see [`EnumOptions::alias_key`]) are accepted as they are.
+    pub from_str_casing: Cow<'static, [Casing]>,
+
+    /// Whether generated enums should implement `AsRef<str>`, and
+    /// `From<MyEnum> for &'static str`.
+    ///
+    /// Like [`EnumOptions::impl_display`], these give the name of
+    /// the variant, but without going through the formatting
+    /// machinery. For example, `MyEnum::First.as_ref() == "First"`.
+    pub impl_as_ref_str: bool,
+
+    /// If present, generates a `const fn` with this name (like
+    /// `into_static_str`) which returns the name of the variant as a
+    /// `&'static str` (or its key, with
+    /// [`EnumOptions::display_casing`]).
+    pub static_str_fn_name: Option<Cow<'static, str>>,
+
+    /// Whether enums generated from file names should have a
+    /// `from_path` method.
+    ///
+    /// This matches the path of the file, either with or without
+    /// its extension. For example,
+    /// `MyEnum::from_path("dir/first.yaml") == Some(MyEnum::First)`.
+    pub impl_from_path: bool,
+
+    /// Whether generated enums should have index-based accessors.
+    ///
+    /// This generates a `COUNT` const holding the number of
+    /// variants, an `index` method returning the index of a variant
+    /// (in declaration order), and a `from_index` method for the
+    /// reverse. These are all `const`, so can be used to build
+    /// arrays with one item per variant.
+    pub impl_index: bool,
+
+    /// Whether generated enums should implement `TryFrom<usize>`.
+    ///
+    /// This converts an index (in declaration order) to a variant,
+    /// returning the index as the error if it is out of range.
+    pub impl_try_from_index: bool,
+
+    /// Whether generated enums should have an `iter` function,
+    /// which iterates over all variants in declaration order.
+    ///
+    /// If [`EnumOptions::all_variants_const_name`] is set, this
+    /// copies the variants from that const, so the enum must be
+    /// `Copy`.
+    pub impl_iter: bool,
+
+    /// How accessors (like [`EnumOptions::get_value_fn_name`] and
+    /// [`FilesOptions::get_path_fn_name`]) find the item for a
+    /// variant in their const slice.
+    pub accessor_style: AccessorStyle,
+
+    /// Whether generated enums should derive the traits in
+    /// [`EnumOptions::strum_derives`] from the `strum` crate.
+    ///
+    /// Variants whose names differ from their original key (or file
+    /// name) get a `#[strum(serialize = "...")]` attribute with the
+    /// original. The crate including the generated code must depend
+    /// on `strum` with its `derive` feature.
+    ///
+    /// The hand-rolled equivalents of the derived traits are not
+    /// generated: [`EnumOptions::all_variants_const_name`] is
+    /// ignored if deriving `EnumIter`, [`EnumOptions::impl_display`]
+    /// if deriving `Display`, and [`EnumOptions::impl_from_str`] if
+    /// deriving `EnumString`.
+    pub strum_support: bool,
+
+    /// The `strum` traits to derive if
+    /// [`EnumOptions::strum_support`] is set.
+    pub strum_derives: Cow<'static, [Cow<'static, str>]>,
+
     /// If present, generates a const with this name that stores
     /// a slice of all variants of the generated enum.
     pub all_variants_const_name: Option<Cow<'static, str>>,
@@ -284,6 +906,25 @@ pub struct EnumOptions {
     /// This requires `values_struct` to be set as well.
     pub all_values_const_name: Option<Cow<'static, str>>,
 
+    /// Whether the const named by
+    /// [`EnumOptions::all_values_const_name`] is an array (like
+    /// `[MyEnum__Value; 3]`) instead of a slice.
+    ///
+    /// The length of the array is the number of variants, so it can
+    /// be used with const generics.
+    pub values_array: bool,
+
+    /// Whether the method named by [`EnumOptions::get_value_fn_name`]
+    /// returns references into a `static`, instead of into the const
+    /// named by [`EnumOptions::all_values_const_name`].
+    ///
+    /// Each use of a const can be a separate copy of it, so the same
+    /// value can be at different addresses. With a static, each value
+    /// has a single address, so references to it can be compared
+    /// with `std::ptr::eq`. The method is no longer a `const fn`, and
+    /// the values struct must be `Sync`.
+    pub static_values: bool,
+
     /// If present, structs representing the values associated with
     /// enum variants will also be generated.
     ///
@@ -294,6 +935,33 @@ pub struct EnumOptions {
     /// If present, generates a method with this name for fetching
     /// the value associated with an enum variant.
     pub get_value_fn_name: Option<Cow<'static, str>>,
+
+    /// If present, generates a method with this name which returns
+    /// an owned copy of the value associated with an enum variant.
+    ///
+    /// This requires `get_value_fn_name` to be set as well, and
+    /// the values struct to derive `Clone`.
+    pub owned_value_fn_name: Option<Cow<'static, str>>,
+
+    /// If present, generates a function with this name which
+    /// returns a `HashMap` from each enum variant to its value.
+    ///
+    /// This requires `get_value_fn_name` to be set as well, and
+    /// the enum to derive `Copy`, `Eq`, and `Hash`. It is not
+    /// supported with [`OutputOptions::no_std`].
+    pub value_map_fn_name: Option<Cow<'static, str>>,
+
+    /// If present, generates a `phf::Map` const with this name from
+    /// the original key (or file name) of each variant to the
+    /// variant, and a `from_key_str` method which looks up a
+    /// variant in it.
+    ///
+    /// This gives constant-time lookups for enums with many
+    /// variants. It requires the enum to derive `Copy`, and the
+    /// crate including the generated code must depend on `phf`
+    /// with its `macros` feature.
+    #[cfg(feature = "phf")]
+    pub key_map_const_name: Option<Cow<'static, str>>,
 }
 
 impl EnumOptions {
@@ -309,13 +977,42 @@ impl EnumOptions {
     ///         "Eq".into(),
     ///         "Hash".into(),
     ///     ].into(),
+    ///     repr: None,
+    ///     extra_attributes: vec![].into(),
+    ///     variant_casing: None,
+    ///     sort_variants_by: VariantSort::SourceOrder,
+    ///     integer_keys: None,
+    ///     nested: false,
+    ///     alias_key: None,
     ///     impl_default: true,
     ///     impl_display: true,
+    ///     display_casing: None,
     ///     impl_from_str: true,
+    ///     from_str_casing: vec![].into(),
+    ///     impl_as_ref_str: false,
+    ///     static_str_fn_name: None,
+    ///     impl_from_path: false,
+    ///     impl_index: false,
+    ///     impl_try_from_index: false,
+    ///     impl_iter: false,
+    ///     accessor_style: AccessorStyle::IndexSlice,
+    ///     strum_support: false,
+    ///     strum_derives: vec![
+    ///         "EnumIter".into(),
+    ///         "AsRefStr".into(),
+    ///         "Display".into(),
+    ///         "EnumString".into(),
+    ///     ].into(),
     ///     all_variants_const_name: Some("ALL".into()),
     ///     all_values_const_name: Some("VALUES".into()),
+    ///     values_array: false,
+    ///     static_values: false,
     ///     values_struct: Some(ValuesStructOptions::new()),
     ///     get_value_fn_name: Some("get".into()),
+    ///     owned_value_fn_name: None,
+    ///     value_map_fn_name: None,
+    ///     #[cfg(feature = "phf")]
+    ///     key_map_const_name: None,
     /// });
     /// ```
     pub const fn new() -> EnumOptions {
@@ -328,13 +1025,37 @@ impl EnumOptions {
                 Cow::Borrowed("Eq"),
                 Cow::Borrowed("Hash"),
             ]),
+            repr: None,
+            extra_attributes: Cow::Borrowed(&[]),
+            variant_casing: None,
+            sort_variants_by: VariantSort::SourceOrder,
+            integer_keys: None,
+            nested: false,
+            alias_key: None,
             impl_default: true,
             impl_display: true,
+            display_casing: None,
             impl_from_str: true,
+            from_str_casing: Cow::Borrowed(&[]),
+            impl_as_ref_str: false,
+            static_str_fn_name: None,
+            impl_from_path: false,
+            impl_index: false,
+            impl_try_from_index: false,
+            impl_iter: false,
+            accessor_style: AccessorStyle::IndexSlice,
+            strum_support: false,
+            strum_derives: Cow::Borrowed(STRUM_DERIVES),
             all_variants_const_name: Some(Cow::Borrowed("ALL")),
             all_values_const_name: Some(Cow::Borrowed("VALUES")),
+            values_array: false,
+            static_values: false,
             values_struct: Some(ValuesStructOptions::new()),
             get_value_fn_name: Some(Cow::Borrowed("get")),
+            owned_value_fn_name: None,
+            value_map_fn_name: None,
+            #[cfg(feature = "phf")]
+            key_map_const_name: None,
         }
     }
 
@@ -343,25 +1064,78 @@ impl EnumOptions {
     /// # use edres_core::options::*;
     /// assert_eq!(EnumOptions::minimal(), EnumOptions {
     ///     derived_traits: vec![].into(),
+    ///     repr: None,
+    ///     extra_attributes: vec![].into(),
+    ///     variant_casing: None,
+    ///     sort_variants_by: VariantSort::SourceOrder,
+    ///     integer_keys: None,
+    ///     nested: false,
+    ///     alias_key: None,
     ///     impl_default: false,
     ///     impl_display: false,
+    ///     display_casing: None,
     ///     impl_from_str: false,
+    ///     from_str_casing: vec![].into(),
+    ///     impl_as_ref_str: false,
+    ///     static_str_fn_name: None,
+    ///     impl_from_path: false,
+    ///     impl_index: false,
+    ///     impl_try_from_index: false,
+    ///     impl_iter: false,
+    ///     accessor_style: AccessorStyle::IndexSlice,
+    ///     strum_support: false,
+    ///     strum_derives: vec![
+    ///         "EnumIter".into(),
+    ///         "AsRefStr".into(),
+    ///         "Display".into(),
+    ///         "EnumString".into(),
+    ///     ].into(),
     ///     all_variants_const_name: None,
     ///     all_values_const_name: None,
+    ///     values_array: false,
+    ///     static_values: false,
     ///     values_struct: None,
     ///     get_value_fn_name: None,
+    ///     owned_value_fn_name: None,
+    ///     value_map_fn_name: None,
+    ///     #[cfg(feature = "phf")]
+    ///     key_map_const_name: None,
     /// });
     /// ```
     pub const fn minimal() -> EnumOptions {
         EnumOptions {
             derived_traits: Cow::Borrowed(&[]),
+            repr: None,
+            extra_attributes: Cow::Borrowed(&[]),
+            variant_casing: None,
+            sort_variants_by: VariantSort::SourceOrder,
+            integer_keys: None,
+            nested: false,
+            alias_key: None,
             impl_default: false,
             impl_display: false,
+            display_casing: None,
             impl_from_str: false,
+            from_str_casing: Cow::Borrowed(&[]),
+            impl_as_ref_str: false,
+            static_str_fn_name: None,
+            impl_from_path: false,
+            impl_index: false,
+            impl_try_from_index: false,
+            impl_iter: false,
+            accessor_style: AccessorStyle::IndexSlice,
+            strum_support: false,
+            strum_derives: Cow::Borrowed(STRUM_DERIVES),
             all_variants_const_name: None,
             all_values_const_name: None,
+            values_array: false,
+            static_values: false,
             values_struct: None,
             get_value_fn_name: None,
+            owned_value_fn_name: None,
+            value_map_fn_name: None,
+            #[cfg(feature = "phf")]
+            key_map_const_name: None,
         }
     }
 }
@@ -377,9 +1151,131 @@ impl Default for EnumOptions {
     }
 }
 
+/// Options specific to how `edres` should generate enums from
+/// integer keys.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IntegerKeyOptions {
+    /// The prefix of each variant name, followed by the key.
+    ///
+    /// For example, a prefix of `Status` generates a `Status404`
+    /// variant for the key `404`.
+    pub variant_prefix: Cow<'static, str>,
+
+    /// The integer type of the keys.
+    ///
+    /// It is an error for a key not to fit in this type.
+    pub key_type: UIntSize,
+
+    /// The name of the generated const method which returns the
+    /// key of a variant.
+    ///
+    /// A `TryFrom` impl from `key_type` is generated as well.
+    pub code_fn_name: Cow<'static, str>,
+}
+
+impl IntegerKeyOptions {
+    /// # Examples
+    /// ```
+    /// # use edres_core::options::*;
+    /// # use std::borrow::Cow;
+    /// assert_eq!(IntegerKeyOptions::new(), IntegerKeyOptions {
+    ///     variant_prefix: Cow::Borrowed("Key"),
+    ///     key_type: UIntSize::U32,
+    ///     code_fn_name: Cow::Borrowed("code"),
+    /// });
+    /// ```
+    pub const fn new() -> Self {
+        IntegerKeyOptions {
+            variant_prefix: Cow::Borrowed("Key"),
+            key_type: UIntSize::U32,
+            code_fn_name: Cow::Borrowed("code"),
+        }
+    }
+}
+
+impl Default for IntegerKeyOptions {
+    /// # Examples
+    /// ```
+    /// # use edres_core::options::*;
+    /// assert_eq!(IntegerKeyOptions::default(), IntegerKeyOptions::new());
+    /// ```
+    fn default() -> Self {
+        IntegerKeyOptions::new()
+    }
+}
+
+/// Options for using file enums as the keys of Bevy assets (see
+/// [`FilesOptions::bevy`]).
+///
+/// The crate including the generated code must depend on `bevy`.
+#[cfg(feature = "bevy")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BevyOptions {
+    /// The directory Bevy loads assets from, relative to the crate's
+    /// manifest directory.
+    ///
+    /// This is stripped from the start of each file path to get the
+    /// path to pass to `AssetServer::load`. It is an error for a file
+    /// to be outside of this directory.
+    pub asset_root: Cow<'static, str>,
+
+    /// The name of the generated const method which returns the
+    /// path of a file for `AssetServer::load`.
+    pub handle_path_fn_name: Cow<'static, str>,
+
+    /// Whether the enum derives `bevy::reflect::Reflect`, so it can
+    /// be used in components and resources which are reflected.
+    pub derive_reflect: bool,
+
+    /// If present, generates a plugin with this name which loads
+    /// every file on startup.
+    ///
+    /// The handles are kept in a resource named after the enum
+    /// (like `FileNameHandles`), in the order of the variants, so
+    /// the assets are not unloaded.
+    pub preload_plugin_name: Option<Cow<'static, str>>,
+}
+
+#[cfg(feature = "bevy")]
+impl BevyOptions {
+    /// # Examples
+    /// ```
+    /// # use edres_core::options::*;
+    /// assert_eq!(BevyOptions::new(), BevyOptions {
+    ///     asset_root: "assets".into(),
+    ///     handle_path_fn_name: "handle_path".into(),
+    ///     derive_reflect: true,
+    ///     preload_plugin_name: None,
+    /// });
+    /// ```
+    pub const fn new() -> Self {
+        BevyOptions {
+            asset_root: Cow::Borrowed("assets"),
+            handle_path_fn_name: Cow::Borrowed("handle_path"),
+            derive_reflect: true,
+            preload_plugin_name: None,
+        }
+    }
+}
+
+#[cfg(feature = "bevy")]
+impl Default for BevyOptions {
+    /// # Examples
+    /// ```
+    /// # use edres_core::options::*;
+    /// assert_eq!(BevyOptions::default(), BevyOptions::new());
+    /// ```
+    fn default() -> Self {
+        BevyOptions::new()
+    }
+}
+
 /// Options specific to how `edres` should generate structs for
 /// values associated with enum variants.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ValuesStructOptions {
     /// If present, this will be the name of the struct generated
     /// for the values corresponding to the enum variants.
@@ -387,7 +1283,22 @@ pub struct ValuesStructOptions {
 
     /// The options for generating structs based on values
     /// associated with the enum.
+    ///
+    /// These are used instead of [`Options::structs`], so the values
+    /// struct can derive different traits from the enum and from
+    /// other generated structs.
     pub struct_options: StructOptions,
+
+    /// If present, the serde support for the values struct (and
+    /// its nested structs), instead of [`Options::serde_support`].
+    ///
+    /// This lets the enum and its values derive different serde
+    /// traits, for example when only the values are deserialized.
+    pub serde_support: Option<SerdeSupport>,
+
+    /// Which fields of the values are kept in the generated struct
+    /// (and the data of the enum).
+    pub field_selection: FieldSelection,
 }
 
 impl ValuesStructOptions {
@@ -397,12 +1308,16 @@ impl ValuesStructOptions {
     /// assert_eq!(ValuesStructOptions::new(), ValuesStructOptions {
     ///     struct_name: None,
     ///     struct_options: StructOptions::new(),
+    ///     serde_support: None,
+    ///     field_selection: FieldSelection::All,
     /// });
     /// ```
     pub const fn new() -> Self {
         ValuesStructOptions {
             struct_name: None,
             struct_options: StructOptions::new(),
+            serde_support: None,
+            field_selection: FieldSelection::All,
         }
     }
 
@@ -412,12 +1327,16 @@ impl ValuesStructOptions {
     /// assert_eq!(ValuesStructOptions::minimal(), ValuesStructOptions {
     ///     struct_name: None,
     ///     struct_options: StructOptions::minimal(),
+    ///     serde_support: None,
+    ///     field_selection: FieldSelection::All,
     /// });
     /// ```
     pub const fn minimal() -> Self {
         ValuesStructOptions {
             struct_name: None,
             struct_options: StructOptions::minimal(),
+            serde_support: None,
+            field_selection: FieldSelection::All,
         }
     }
 }
@@ -434,7 +1353,8 @@ impl Default for ValuesStructOptions {
 }
 
 /// Options specific to how `edres` should handle input files.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FilesOptions {
     /// If present, generates a const with this name containing
     /// a slice of the paths of the files used to generate the
@@ -462,6 +1382,144 @@ pub struct FilesOptions {
     /// If present, generates a method which returns the bytes
     /// associated with an enum variant.
     pub get_bytes_fn_name: Option<Cow<'static, str>>,
+
+    /// If present, generates a const with this name containing
+    /// a slice of the stems of the files used to generate the
+    /// output (like `sprite.meta` for `sprite.meta.yaml`).
+    ///
+    /// Together with [`FilesOptions::file_extensions_const_name`],
+    /// this allows the paths of related files to be put together
+    /// without parsing paths at runtime.
+    pub file_stems_const_name: Option<Cow<'static, str>>,
+
+    /// If present, generates a method which returns the file stem
+    /// associated with an enum variant.
+    pub get_stem_fn_name: Option<Cow<'static, str>>,
+
+    /// If present, generates a const with this name containing
+    /// a slice of the extensions of the files used to generate the
+    /// output (like `yaml`, without the dot). Files without an
+    /// extension have an empty one.
+    pub file_extensions_const_name: Option<Cow<'static, str>>,
+
+    /// If present, generates a method which returns the file
+    /// extension associated with an enum variant.
+    pub get_extension_fn_name: Option<Cow<'static, str>>,
+
+    /// If true, the values associated with file enum variants are
+    /// not stored in a const. Instead, the file contents are
+    /// embedded as strings and deserialized on first access.
+    ///
+    /// The method named by [`EnumOptions::get_value_fn_name`]
+    /// is still generated, but it is no longer a `const fn`.
+    /// This requires serde support to be enabled, and the
+    /// relevant serde crates (for example `serde_yaml`) to be
+    /// dependencies of the crate including the generated code.
+    pub lazy_values: bool,
+
+    /// If present, generates a method with this name which re-reads
+    /// and deserializes the file of an enum variant at runtime.
+    ///
+    /// This allows files to be reloaded while the program is
+    /// running (for example, to hot-reload assets). The file is
+    /// read from the path it was found at during generation, so
+    /// that path must still be valid at runtime. The method returns
+    /// a `Result` with an error type named after the enum (like
+    /// `MyEnumLoadError`).
+    ///
+    /// This is only generated if [`EnumOptions::values_struct`] is
+    /// set. As with `lazy_values`, it requires serde support to be
+    /// enabled, and the relevant serde crates to be dependencies of
+    /// the crate including the generated code.
+    pub load_fn_name: Option<Cow<'static, str>>,
+
+    /// If not empty, only files whose names match at least one of
+    /// these glob patterns (like `*.item.yaml`) are used.
+    pub include_globs: Cow<'static, [Cow<'static, str>]>,
+
+    /// Files whose names match any of these glob patterns are
+    /// skipped, even if they match `include_globs`.
+    pub exclude_globs: Cow<'static, [Cow<'static, str>]>,
+
+    /// The formats of files whose names match these glob patterns
+    /// (like `("*.conf", Format::Toml)`), for directories which mix
+    /// different formats or use unusual extensions. The first
+    /// matching pattern is used.
+    ///
+    /// Files which don't match any pattern use the format given for
+    /// the whole directory, if there is one, or otherwise have their
+    /// format inferred from their extension.
+    pub format_overrides: Cow<'static, [(Cow<'static, str>, Format)]>,
+
+    /// Whether to skip files which neither match one of the
+    /// `format_overrides` nor have a recognized extension (like
+    /// `README.md`), instead of failing to parse them.
+    ///
+    /// Like the globs, this applies to every function which reads a
+    /// directory, even if it only uses the names of the files.
+    pub skip_unknown_formats: bool,
+
+    /// If present, and a file with this name (like `_index.yaml`)
+    /// is in the directory, it controls which files are used and
+    /// in which order.
+    ///
+    /// The index file itself is never used. It can contain a list
+    /// of `files`, in the order their variants should be declared.
+    /// Each is either a file name, or a map with the file name as
+    /// `file` and an explicit variant name as `variant`. It can also
+    /// contain a list of files to `skip`. For example:
+    ///
+    /// ```yaml
+    /// files:
+    ///   - second.yaml
+    ///   - file: first.yaml
+    ///     variant: Primary
+    /// skip:
+    ///   - notes.yaml
+    /// ```
+    ///
+    /// If `files` is given, every file in the directory (after the
+    /// globs are applied) must be listed or skipped, and every
+    /// listed file must exist - otherwise it is an error.
+    pub index_file: Option<Cow<'static, str>>,
+
+    /// Whether to add numeric suffixes to variant names which
+    /// would otherwise be the same, like `FileA` and `FileA2` for
+    /// `file_a.yaml` and `file-a.yaml`.
+    ///
+    /// If false, files which would have the same variant name are
+    /// an error.
+    pub disambiguate_variants: bool,
+
+    /// Whether [`define_enum_from_file_keys`] only uses the keys that
+    /// are in every file, instead of the keys that are in any file.
+    ///
+    /// [`define_enum_from_file_keys`]: crate::codegen::define_enum_from_file_keys
+    pub intersect_keys: bool,
+
+    /// Whether [`define_enum_from_filenames`] may generate an enum
+    /// with no variants when the directory has no matching files.
+    ///
+    /// The empty enum is marked `#[non_exhaustive]`. If false, a
+    /// directory with no matching files is an error.
+    ///
+    /// [`define_enum_from_filenames`]: crate::codegen::define_enum_from_filenames
+    pub allow_empty: bool,
+
+    /// The directory that the paths passed to `include_bytes!` and
+    /// `include_str!` are resolved from.
+    ///
+    /// The paths of the files themselves are assumed to be relative
+    /// to the crate's manifest directory, as they are when running a
+    /// build script. Absolute paths are emitted unchanged.
+    pub path_root: PathRoot,
+
+    /// If present, the enum generated by [`define_enum_from_filenames`]
+    /// can be used as typed keys for Bevy assets.
+    ///
+    /// [`define_enum_from_filenames`]: crate::codegen::define_enum_from_filenames
+    #[cfg(feature = "bevy")]
+    pub bevy: Option<BevyOptions>,
 }
 
 impl FilesOptions {
@@ -475,6 +1533,23 @@ impl FilesOptions {
     ///     get_string_fn_name: None,
     ///     file_bytes_const_name: None,
     ///     get_bytes_fn_name: None,
+    ///     file_stems_const_name: None,
+    ///     get_stem_fn_name: None,
+    ///     file_extensions_const_name: None,
+    ///     get_extension_fn_name: None,
+    ///     lazy_values: false,
+    ///     load_fn_name: None,
+    ///     include_globs: vec![].into(),
+    ///     exclude_globs: vec![].into(),
+    ///     format_overrides: vec![].into(),
+    ///     skip_unknown_formats: false,
+    ///     index_file: None,
+    ///     disambiguate_variants: false,
+    ///     intersect_keys: false,
+    ///     allow_empty: false,
+    ///     path_root: PathRoot::ManifestDir,
+    ///     #[cfg(feature = "bevy")]
+    ///     bevy: None,
     /// });
     /// ```
     pub const fn new() -> FilesOptions {
@@ -485,6 +1560,23 @@ impl FilesOptions {
             get_string_fn_name: None,
             file_bytes_const_name: None,
             get_bytes_fn_name: None,
+            file_stems_const_name: None,
+            get_stem_fn_name: None,
+            file_extensions_const_name: None,
+            get_extension_fn_name: None,
+            lazy_values: false,
+            load_fn_name: None,
+            include_globs: Cow::Borrowed(&[]),
+            exclude_globs: Cow::Borrowed(&[]),
+            format_overrides: Cow::Borrowed(&[]),
+            skip_unknown_formats: false,
+            index_file: None,
+            disambiguate_variants: false,
+            intersect_keys: false,
+            allow_empty: false,
+            path_root: PathRoot::ManifestDir,
+            #[cfg(feature = "bevy")]
+            bevy: None,
         }
     }
 
@@ -498,6 +1590,23 @@ impl FilesOptions {
     ///     get_string_fn_name: None,
     ///     file_bytes_const_name: None,
     ///     get_bytes_fn_name: None,
+    ///     file_stems_const_name: None,
+    ///     get_stem_fn_name: None,
+    ///     file_extensions_const_name: None,
+    ///     get_extension_fn_name: None,
+    ///     lazy_values: false,
+    ///     load_fn_name: None,
+    ///     include_globs: vec![].into(),
+    ///     exclude_globs: vec![].into(),
+    ///     format_overrides: vec![].into(),
+    ///     skip_unknown_formats: false,
+    ///     index_file: None,
+    ///     disambiguate_variants: false,
+    ///     intersect_keys: false,
+    ///     allow_empty: false,
+    ///     path_root: PathRoot::ManifestDir,
+    ///     #[cfg(feature = "bevy")]
+    ///     bevy: None,
     /// });
     /// ```
     pub const fn minimal() -> FilesOptions {
@@ -508,6 +1617,23 @@ impl FilesOptions {
             get_string_fn_name: None,
             file_bytes_const_name: None,
             get_bytes_fn_name: None,
+            file_stems_const_name: None,
+            get_stem_fn_name: None,
+            file_extensions_const_name: None,
+            get_extension_fn_name: None,
+            lazy_values: false,
+            load_fn_name: None,
+            include_globs: Cow::Borrowed(&[]),
+            exclude_globs: Cow::Borrowed(&[]),
+            format_overrides: Cow::Borrowed(&[]),
+            skip_unknown_formats: false,
+            index_file: None,
+            disambiguate_variants: false,
+            intersect_keys: false,
+            allow_empty: false,
+            path_root: PathRoot::ManifestDir,
+            #[cfg(feature = "bevy")]
+            bevy: None,
         }
     }
 
@@ -521,6 +1647,23 @@ impl FilesOptions {
     ///     get_string_fn_name: None,
     ///     file_bytes_const_name: Some("FILE_BYTES".into()),
     ///     get_bytes_fn_name: Some("bytes".into()),
+    ///     file_stems_const_name: None,
+    ///     get_stem_fn_name: None,
+    ///     file_extensions_const_name: None,
+    ///     get_extension_fn_name: None,
+    ///     lazy_values: false,
+    ///     load_fn_name: None,
+    ///     include_globs: vec![].into(),
+    ///     exclude_globs: vec![].into(),
+    ///     format_overrides: vec![].into(),
+    ///     skip_unknown_formats: false,
+    ///     index_file: None,
+    ///     disambiguate_variants: false,
+    ///     intersect_keys: false,
+    ///     allow_empty: false,
+    ///     path_root: PathRoot::ManifestDir,
+    ///     #[cfg(feature = "bevy")]
+    ///     bevy: None,
     /// });
     /// ```
     pub const fn file_bytes() -> FilesOptions {
@@ -531,6 +1674,23 @@ impl FilesOptions {
             get_string_fn_name: None,
             file_bytes_const_name: Some(Cow::Borrowed("FILE_BYTES")),
             get_bytes_fn_name: Some(Cow::Borrowed("bytes")),
+            file_stems_const_name: None,
+            get_stem_fn_name: None,
+            file_extensions_const_name: None,
+            get_extension_fn_name: None,
+            lazy_values: false,
+            load_fn_name: None,
+            include_globs: Cow::Borrowed(&[]),
+            exclude_globs: Cow::Borrowed(&[]),
+            format_overrides: Cow::Borrowed(&[]),
+            skip_unknown_formats: false,
+            index_file: None,
+            disambiguate_variants: false,
+            intersect_keys: false,
+            allow_empty: false,
+            path_root: PathRoot::ManifestDir,
+            #[cfg(feature = "bevy")]
+            bevy: None,
         }
     }
 
@@ -544,6 +1704,23 @@ impl FilesOptions {
     ///     get_string_fn_name: Some("string".into()),
     ///     file_bytes_const_name: None,
     ///     get_bytes_fn_name: None,
+    ///     file_stems_const_name: None,
+    ///     get_stem_fn_name: None,
+    ///     file_extensions_const_name: None,
+    ///     get_extension_fn_name: None,
+    ///     lazy_values: false,
+    ///     load_fn_name: None,
+    ///     include_globs: vec![].into(),
+    ///     exclude_globs: vec![].into(),
+    ///     format_overrides: vec![].into(),
+    ///     skip_unknown_formats: false,
+    ///     index_file: None,
+    ///     disambiguate_variants: false,
+    ///     intersect_keys: false,
+    ///     allow_empty: false,
+    ///     path_root: PathRoot::ManifestDir,
+    ///     #[cfg(feature = "bevy")]
+    ///     bevy: None,
     /// });
     /// ```
     pub const fn file_strings() -> FilesOptions {
@@ -554,6 +1731,23 @@ impl FilesOptions {
             get_string_fn_name: Some(Cow::Borrowed("string")),
             file_bytes_const_name: None,
             get_bytes_fn_name: None,
+            file_stems_const_name: None,
+            get_stem_fn_name: None,
+            file_extensions_const_name: None,
+            get_extension_fn_name: None,
+            lazy_values: false,
+            load_fn_name: None,
+            include_globs: Cow::Borrowed(&[]),
+            exclude_globs: Cow::Borrowed(&[]),
+            format_overrides: Cow::Borrowed(&[]),
+            skip_unknown_formats: false,
+            index_file: None,
+            disambiguate_variants: false,
+            intersect_keys: false,
+            allow_empty: false,
+            path_root: PathRoot::ManifestDir,
+            #[cfg(feature = "bevy")]
+            bevy: None,
         }
     }
 }
@@ -569,8 +1763,88 @@ impl Default for FilesOptions {
     }
 }
 
+/// Options specific to how `edres` should generate bitflags.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlagsOptions {
+    /// A list of traits for the generated struct to derive.
+    pub derived_traits: Cow<'static, [Cow<'static, str>]>,
+
+    /// The integer type which stores the bits.
+    ///
+    /// [`UIntSize::USize`] is treated as 32 bits wide when
+    /// checking that the flags fit.
+    pub repr: UIntSize,
+
+    /// Whether to define the flags with plain consts, or with the
+    /// `bitflags` crate.
+    pub style: FlagsStyle,
+}
+
+impl FlagsOptions {
+    /// # Examples
+    /// ```
+    /// # use edres_core::options::*;
+    /// assert_eq!(FlagsOptions::new(), FlagsOptions {
+    ///     derived_traits: vec![
+    ///         "Debug".into(),
+    ///         "Clone".into(),
+    ///         "Copy".into(),
+    ///         "PartialEq".into(),
+    ///         "Eq".into(),
+    ///         "Hash".into(),
+    ///     ].into(),
+    ///     repr: UIntSize::U32,
+    ///     style: FlagsStyle::Consts,
+    /// });
+    /// ```
+    pub const fn new() -> FlagsOptions {
+        FlagsOptions {
+            derived_traits: Cow::Borrowed(&[
+                Cow::Borrowed("Debug"),
+                Cow::Borrowed("Clone"),
+                Cow::Borrowed("Copy"),
+                Cow::Borrowed("PartialEq"),
+                Cow::Borrowed("Eq"),
+                Cow::Borrowed("Hash"),
+            ]),
+            repr: UIntSize::U32,
+            style: FlagsStyle::Consts,
+        }
+    }
+
+    /// # Examples
+    /// ```
+    /// # use edres_core::options::*;
+    /// assert_eq!(FlagsOptions::minimal(), FlagsOptions {
+    ///     derived_traits: vec![].into(),
+    ///     repr: UIntSize::U32,
+    ///     style: FlagsStyle::Consts,
+    /// });
+    /// ```
+    pub const fn minimal() -> FlagsOptions {
+        FlagsOptions {
+            derived_traits: Cow::Borrowed(&[]),
+            repr: UIntSize::U32,
+            style: FlagsStyle::Consts,
+        }
+    }
+}
+
+impl Default for FlagsOptions {
+    /// # Examples
+    /// ```
+    /// # use edres_core::options::*;
+    /// assert_eq!(FlagsOptions::default(), FlagsOptions::new());
+    /// ```
+    fn default() -> Self {
+        FlagsOptions::new()
+    }
+}
+
 /// Options specific to how `edres` should handle its output.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OutputOptions {
     /// If true, missing destination directories will be created
     /// on output.
@@ -583,6 +1857,106 @@ pub struct OutputOptions {
     /// triggering any processes that watch for changes. (For
     /// example, `cargo watch`.)
     pub write_only_if_changed: bool,
+
+    /// If true, generating structs from the files in a directory
+    /// will emit a separate module for each file, instead of a
+    /// single struct unified from all of them.
+    ///
+    /// This means the files don't need to share the same shape.
+    pub module_per_file: bool,
+
+    /// If true, the generated code only uses `core` and `alloc`,
+    /// so it can be included in a `#![no_std]` crate.
+    ///
+    /// The crate including the generated code must declare
+    /// `extern crate alloc;`.
+    pub no_std: bool,
+
+    /// If true, the generated code takes the items it uses (like
+    /// `Cow`) from the `edres_runtime` crate instead of `std` (or
+    /// `core` and `alloc`).
+    ///
+    /// The crate including the generated code must depend on
+    /// `edres_runtime`, with the same version as `edres`.
+    pub runtime_crate: bool,
+
+    /// The visibility of generated structs, enums, modules, and
+    /// their associated consts and methods.
+    ///
+    /// The visibility of fields is set separately, by
+    /// [`StructOptions::field_visibility`].
+    pub visibility: Visibility,
+
+    /// If present, all generated code is wrapped in a module with
+    /// the given name (with the same visibility as other items).
+    pub wrap_in_module: Option<Cow<'static, str>>,
+
+    /// Inner attributes (like `allow(clippy::all)`) to apply to the
+    /// generated code, without the surrounding `#![...]`.
+    ///
+    /// These are placed inside the module if
+    /// [`wrap_in_module`](Self::wrap_in_module) is set, and
+    /// otherwise at the top of the output. In that case, the output
+    /// must be used as a module (for example, with
+    /// `#[path = "..."] mod config;`), since inner attributes are
+    /// not allowed in files used with `include!`.
+    pub inner_attributes: Cow<'static, [Cow<'static, str>]>,
+
+    /// If present, this text is written as a comment at the top of
+    /// each output file.
+    pub header: Option<Cow<'static, str>>,
+
+    /// If true, a comment is written at the top of each output file
+    /// marking it as generated, with the version of this crate and
+    /// the source files it was generated from.
+    pub provenance: bool,
+
+    /// If true, the provenance comment includes the time the file
+    /// was generated.
+    ///
+    /// The time is ignored when checking if a file has changed
+    /// (see [`write_only_if_changed`](Self::write_only_if_changed)).
+    pub timestamp: bool,
+
+    /// If present, generating code from the files in a directory
+    /// stores a hash of those files (and the options) alongside the
+    /// output in this directory.
+    ///
+    /// When nothing has changed since the last generation, the
+    /// files are not parsed again and the previous output is used.
+    /// This is skipped when
+    /// [`ParseOptions::include_directive`] is set, since changes to
+    /// included files wouldn't be noticed.
+    pub cache_dir: Option<Cow<'static, Path>>,
+
+    /// If true, struct values which appear more than once in the
+    /// generated data consts (like a nested struct shared by many
+    /// files) are defined once, as private consts named after their
+    /// type (like `Item__stats_0`), and referred to by name.
+    ///
+    /// This includes tuple structs and newtypes, and subtrees which
+    /// are repeated using YAML anchors and aliases (which are
+    /// expanded by parsing, so they are found by comparing values).
+    ///
+    /// This makes the output smaller when there is a lot of
+    /// repeated data, and doesn't change the values of the consts.
+    pub dedup_values: bool,
+
+    /// If true, the destination paths passed to the `create_`
+    /// functions are relative to `$OUT_DIR`, as is usual for a build
+    /// script.
+    ///
+    /// The generated file can then be included with
+    /// `edres::include_generated!`, using the same relative path.
+    pub out_dir_relative: bool,
+
+    /// If true, the `create_` functions don't write any files, and
+    /// instead return [`Error::OutOfDate`] if the destination file
+    /// doesn't match the generated output.
+    ///
+    /// This lets CI check that committed generated code is up to
+    /// date with its source files. Timestamps are ignored.
+    pub check_only: bool,
 }
 
 impl OutputOptions {
@@ -592,12 +1966,38 @@ impl OutputOptions {
     /// assert_eq!(OutputOptions::new(), OutputOptions {
     ///     create_dirs: true,
     ///     write_only_if_changed: true,
+    ///     module_per_file: false,
+    ///     no_std: false,
+    ///     runtime_crate: false,
+    ///     visibility: Visibility::Pub,
+    ///     wrap_in_module: None,
+    ///     inner_attributes: vec![].into(),
+    ///     header: None,
+    ///     provenance: true,
+    ///     timestamp: true,
+    ///     cache_dir: None,
+    ///     dedup_values: false,
+    ///     out_dir_relative: false,
+    ///     check_only: false,
     /// });
     /// ```
     pub const fn new() -> Self {
         OutputOptions {
             create_dirs: true,
             write_only_if_changed: true,
+            module_per_file: false,
+            no_std: false,
+            runtime_crate: false,
+            visibility: Visibility::Pub,
+            wrap_in_module: None,
+            inner_attributes: Cow::Borrowed(&[]),
+            header: None,
+            provenance: true,
+            timestamp: true,
+            cache_dir: None,
+            dedup_values: false,
+            out_dir_relative: false,
+            check_only: false,
         }
     }
 }
@@ -614,7 +2014,8 @@ impl Default for OutputOptions {
 }
 
 /// Options for serde support.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SerdeSupport {
     /// Do not derive any serde traits for the struct.
     No,
@@ -659,20 +2060,323 @@ impl Default for SerdeSupport {
 
 /// Used to specify the default size of floating point values
 /// (providing they fit within the given size).
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FloatSize {
     F32,
     F64,
 }
 
+/// Used to specify how datetimes are represented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatetimeHandling {
+    /// As a string, like `"1979-05-27T07:32:00Z"`.
+    String,
+
+    /// As a generated struct, with a field for each component of
+    /// the datetime that is present: `year`, `month`, `day`,
+    /// `hour`, `minute`, `second`, `nanosecond`, and
+    /// `offset_minutes`.
+    Struct,
+
+    /// As a `chrono` type, depending on which components are
+    /// present: `DateTime<FixedOffset>`, `NaiveDateTime`,
+    /// `NaiveDate`, or `NaiveTime`.
+    ///
+    /// The crate including the generated code must depend on
+    /// `chrono` (0.4.35 or later, for const construction).
+    #[cfg(feature = "chrono")]
+    Chrono,
+}
+
+/// Used to specify how bitflags are defined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagsStyle {
+    /// As a newtype struct around the bits, with a const for each
+    /// flag and some methods for combining them.
+    Consts,
+
+    /// With the `bitflags!` macro.
+    ///
+    /// The crate including the generated code must depend on
+    /// `bitflags` (2.0 or later).
+    #[cfg(feature = "bitflags")]
+    Bitflags,
+}
+
+/// Used to specify how sequences are merged when layering files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrayMerge {
+    /// The sequence from the later file replaces the earlier one.
+    Replace,
+
+    /// The items from the later file are appended to the earlier
+    /// sequence.
+    Append,
+}
+
+/// Used to specify how sequences of incompatible values are
+/// represented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeterogeneousArrayMode {
+    /// As a sequence, like any other. The generated code will
+    /// likely fail to compile.
+    Vec,
+
+    /// As a tuple, with one item per value in the sequence. For
+    /// example, `[1, "a", true]` generates `(i64, Cow<str>, bool)`.
+    Tuple,
+
+    /// As a sequence of enums, if every value is a struct with a
+    /// string field named [`ParseOptions::tag_key`]. Otherwise, as
+    /// with [`HeterogeneousArrayMode::Vec`].
+    ///
+    /// The enum has a struct variant for each tag, named with
+    /// [`EnumOptions::variant_casing`] (or [`Casing::Pascal`] if it
+    /// is not present), and structs with the same tag are unified
+    /// with each other. This applies even to sequences of structs
+    /// which would unify. For example,
+    /// `[{"type": "circle", "r": 1}, {"type": "rect", "w": 2}]`
+    /// generates an enum with variants `Circle { r: i64 }` and
+    /// `Rect { w: i64 }`, and with serde support it is given a
+    /// `#[serde(tag = "type")]` attribute.
+    ///
+    /// The key paths of the fields of each variant include the
+    /// tag, like `shapes.circle.r`.
+    Tagged,
+}
+
+/// Used to specify what happens to keys which appear more than
+/// once in the same mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateKeyPolicy {
+    /// Return an error naming the duplicated key.
+    Error,
+
+    /// Keep the value from the first time the key appears.
+    FirstWins,
+
+    /// Keep the value from the last time the key appears (in the
+    /// position of the first).
+    LastWins,
+}
+
 /// Used to specify the default size of integer values
 /// (providing they fit within the given size).
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum IntSize {
     I8,
     I16,
     I32,
     I64,
     I128,
+    #[serde(rename = "isize")]
     ISize,
 }
+
+/// Used to specify the default size of unsigned integer values
+/// (providing they fit within the given size).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UIntSize {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    #[serde(rename = "usize")]
+    USize,
+}
+
+/// Used to specify the order of the fields of generated structs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldOrder {
+    /// The order the keys appear in the source.
+    Source,
+
+    /// Alphabetical order of the field names.
+    Alphabetical,
+}
+
+/// Used to specify where the paths of embedded files are resolved
+/// from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathRoot {
+    /// Paths are resolved from `CARGO_MANIFEST_DIR`.
+    ///
+    /// This works wherever the generated code is written, including
+    /// `OUT_DIR`.
+    ManifestDir,
+
+    /// Paths are resolved from `OUT_DIR`, for files which are
+    /// themselves generated by a build script.
+    OutDir,
+
+    /// Paths are resolved from the given directory when generating
+    /// code, and emitted in full.
+    Custom(PathBuf),
+}
+
+/// Used to specify the order of the variants of generated enums.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VariantSort {
+    /// The order the keys appear in the source.
+    SourceOrder,
+
+    /// Alphabetical order of the variant names.
+    Alphabetical,
+
+    /// Ascending order of the value at this key path (like
+    /// `priority`) within the value of each variant.
+    ///
+    /// The values must all be numbers, all be strings, or all be
+    /// `bool`s. Variants with equal values keep their source order.
+    ByValueKey(Cow<'static, str>),
+}
+
+/// Used to specify how enum accessors index into their consts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessorStyle {
+    /// Index by the discriminant of the variant (`self as usize`).
+    ///
+    /// This relies on the variants being declared in order, without
+    /// explicit discriminants.
+    IndexSlice,
+
+    /// Index with an exhaustive `match self`, with one arm per
+    /// variant.
+    ///
+    /// This still works if the variants are reordered or given
+    /// explicit discriminants (for example, in a copy of the
+    /// generated code that has been edited by hand), and is still
+    /// `const`.
+    MatchArms,
+}
+
+/// Used to specify the integer representation of generated enums.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnumRepr {
+    U8,
+    U16,
+    U32,
+    U64,
+    #[serde(rename = "usize")]
+    USize,
+}
+
+/// Used to specify how names from the input are converted to
+/// identifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Casing {
+    /// Use names exactly as they are.
+    Preserve,
+
+    /// Split names on any non-alphanumeric characters and
+    /// capitalize each word. For example, `file-a.v2` becomes
+    /// `FileAV2`.
+    Pascal,
+
+    /// Split names on underscores and capitalize each word. For
+    /// example, `iOS_config` becomes `IOSConfig`.
+    SnakeToPascal,
+
+    /// Like [`Casing::SnakeToPascal`], but lowercase the rest of
+    /// each word. For example, `MAX_SIZE` becomes `MaxSize`.
+    ScreamingToPascal,
+
+    /// Split names into words like [`Casing::Pascal`] (and also
+    /// before capital letters), and join them in lowercase with
+    /// underscores. For example, `FileA.v2` becomes `file_a_v2`.
+    Snake,
+
+    /// Like [`Casing::Snake`], but join words with hyphens. For
+    /// example, `FileA.v2` becomes `file-a-v2`.
+    ///
+    /// This can't be used for identifiers, so only for text (like
+    /// [`EnumOptions::display_casing`]).
+    Kebab,
+}
+
+/// Used to specify the serde defaults of the fields of generated
+/// structs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SerdeDefaults {
+    /// Fields are required.
+    None,
+
+    /// Missing fields are set to the `Default` of their type, with
+    /// `#[serde(default)]`. The types of nested structs must
+    /// implement `Default` too (see [`StructOptions::derived_traits`]).
+    DefaultTrait,
+
+    /// Missing fields are set to the values they had in the data the
+    /// struct was generated from. Each field gets a function (like
+    /// `default_port`) returning its value, which is used with
+    /// `#[serde(default = "...")]`.
+    FromConst,
+}
+
+/// Used to specify how nested structs are named.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StructNaming {
+    /// Join the parent name and key with
+    /// [`StructOptions::nested_name_separator`] (`__` by default).
+    /// For example, `Config__server`.
+    Underscored,
+
+    /// Append the key to the parent name, converted with
+    /// [`Casing::Pascal`]. For example, `ConfigServer`.
+    Pascal,
+}
+
+/// Used to select a subset of the fields of the values associated
+/// with enum variants.
+///
+/// Fields are given as key paths relative to each value, like
+/// `stats.health`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldSelection {
+    /// Keep every field.
+    All,
+
+    /// Keep only the fields at these key paths (and everything
+    /// within them). Parents of these fields are kept, but only
+    /// with the selected fields.
+    Include(Cow<'static, [Cow<'static, str>]>),
+
+    /// Keep every field except those at these key paths.
+    Exclude(Cow<'static, [Cow<'static, str>]>),
+}
+
+/// Used to specify the visibility of generated items.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// `pub`
+    Pub,
+
+    /// `pub(crate)`
+    PubCrate,
+
+    /// No visibility modifier, so items are private to the module
+    /// they are included in.
+    Private,
+
+    /// Any other visibility, like `pub(super)`.
+    Custom(Cow<'static, str>),
+}
@@ -1,32 +1,45 @@
 //! This module provides utilities for parsing JSON files
 //! to generic `Value`s.
 
-use serde_json::{self, Value as JsonValue};
+use std::fmt;
+
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{self, Map, Number, Value as JsonValue};
 
 use crate::{
     error::Error,
     options::ParseOptions,
-    parsing,
+    parsing::{self, DuplicateKeys},
     value::{Struct, Value},
 };
 
 /// Parse JSON source, producing a generic `Value`.
+///
+/// Duplicate keys are handled following
+/// [`ParseOptions::duplicate_keys`].
 pub fn parse_source(source: &str, options: &ParseOptions) -> Result<Value, Error> {
-    let raw_value: JsonValue = serde_json::from_str(source)?;
+    let raw_value = DuplicateKeys::deserialize(options.duplicate_keys, |duplicates| {
+        let mut deserializer = serde_json::Deserializer::from_str(source);
+        let value = RawValue(duplicates).deserialize(&mut deserializer)?;
+        deserializer.end().map(|()| value)
+    })?;
     parse_value(raw_value, options)
 }
 
 /// Parse a JSON value, producing a generic `Value`.
 pub fn parse_value(raw_value: JsonValue, options: &ParseOptions) -> Result<Value, Error> {
     let mut result = parse_value_non_unified(raw_value, options)?;
+    parsing::remove_ignored_keys(&mut result, options);
+    parsing::convert_decimal_strings(&mut result, options)?;
+    parsing::convert_char_strings(&mut result, options);
     parsing::unify_value(&mut result)?;
     Ok(result)
 }
 
 /// Parse a JSON value, producing a generic `Value`.
 ///
-/// This bypasses the unification step, which currently does
-/// nothing anyway.
+/// This bypasses the unification step, so sequences may contain
+/// values of different types. (See [`parsing::unify_values`].)
 pub fn parse_value_non_unified(
     raw_value: JsonValue,
     options: &ParseOptions,
@@ -35,8 +48,8 @@ pub fn parse_value_non_unified(
         JsonValue::Null => Value::Option(None),
         JsonValue::Bool(value) => Value::Bool(value),
         JsonValue::Number(value) => match (value.as_i64(), value.as_u64(), value.as_f64()) {
-            (Some(x), _, _) => parsing::preferred_int(x as i128, options.default_int_size),
-            (None, Some(x), _) => parsing::preferred_int(x as i128, options.default_int_size),
+            (Some(x), _, _) => parsing::preferred_integer(x as i128, options),
+            (None, Some(x), _) => parsing::preferred_integer(x as i128, options),
             (None, None, Some(x)) => parsing::preferred_float(x, options.default_float_size),
             _ => return Err(Error::ErrorParsingNumber),
         },
@@ -44,24 +57,104 @@ pub fn parse_value_non_unified(
         JsonValue::Array(values) => parsing::array_or_vec(
             values
                 .into_iter()
-                .map(|value| parse_value_non_unified(value, options))
+                .enumerate()
+                .map(|(i, value)| {
+                    parse_value_non_unified(value, options).map_err(|error| error.under_index(i))
+                })
                 .collect::<Result<Vec<_>, _>>()?,
-            options.max_array_size,
+            options,
         ),
         JsonValue::Object(values) => Value::Struct(Struct(
             values
                 .into_iter()
-                .map(|(key, value)| {
-                    parse_value_non_unified(value, options).map(|value| (key, value))
-                })
+                .map(
+                    |(key, value)| match parse_value_non_unified(value, options) {
+                        Ok(value) => Ok((key, value)),
+                        Err(error) => Err(error.under_key(&key)),
+                    },
+                )
                 .collect::<Result<_, Error>>()?,
         )),
     })
 }
 
+/// Deserializes a `serde_json::Value`, keeping track of duplicate
+/// keys in objects.
+#[derive(Clone, Copy)]
+struct RawValue<'a>(DuplicateKeys<'a>);
+
+impl<'de> DeserializeSeed<'de> for RawValue<'_> {
+    type Value = JsonValue;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<JsonValue, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for RawValue<'_> {
+    type Value = JsonValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<JsonValue, E> {
+        Ok(JsonValue::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<JsonValue, E> {
+        Ok(value.into())
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<JsonValue, E> {
+        Ok(value.into())
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<JsonValue, E> {
+        Ok(Number::from_f64(value).map_or(JsonValue::Null, JsonValue::Number))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(value.to_owned()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<JsonValue, E> {
+        Ok(JsonValue::String(value))
+    }
+
+    fn visit_unit<E>(self) -> Result<JsonValue, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<JsonValue, A::Error> {
+        let mut values = vec![];
+        loop {
+            match seq.next_element_seed(self) {
+                Ok(Some(value)) => values.push(value),
+                Ok(None) => return Ok(JsonValue::Array(values)),
+                Err(error) => return Err(self.0.under_index(error, values.len())),
+            }
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<JsonValue, A::Error> {
+        let mut values = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let value = map
+                .next_value_seed(self)
+                .map_err(|error| self.0.under_key(error, &key))?;
+            if !values.contains_key(&key) || self.0.replace(&key)? {
+                values.insert(key, value);
+            }
+        }
+        Ok(JsonValue::Object(values))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::{DuplicateKeyPolicy, HeterogeneousArrayMode, UIntSize};
 
     use pretty_assertions::assert_eq;
 
@@ -108,4 +201,87 @@ mod tests {
 
         assert_eq!(value, expected);
     }
+
+    #[test]
+    fn unsigned_preference() {
+        let json_source = r#"{"small": 255, "large": 256, "mixed": [1, -1]}"#;
+        let options = ParseOptions {
+            default_uint_size: Some(UIntSize::U8),
+            ..ParseOptions::new()
+        };
+
+        let expected = Value::Struct(Struct::from_pairs([
+            ("small", Value::U8(255)),
+            ("large", Value::U16(256)),
+            ("mixed", Value::Vec(vec![Value::I64(1), Value::I64(-1)])),
+        ]));
+
+        assert_eq!(parse_source(json_source, &options).unwrap(), expected);
+    }
+
+    #[test]
+    fn heterogeneous_arrays_as_tuples() {
+        let json_source = r#"{"mixed": [1, "a", true], "same": [1, 2]}"#;
+        let options = ParseOptions {
+            heterogeneous_arrays: HeterogeneousArrayMode::Tuple,
+            ..ParseOptions::new()
+        };
+
+        let expected = Value::Struct(Struct::from_pairs([
+            (
+                "mixed",
+                Value::Tuple(vec![
+                    Value::I64(1),
+                    Value::String("a".into()),
+                    Value::Bool(true),
+                ]),
+            ),
+            ("same", Value::Vec(vec![Value::I64(1), Value::I64(2)])),
+        ]));
+
+        assert_eq!(parse_source(json_source, &options).unwrap(), expected);
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let json_source = r#"{"a": 1, "b": {"c": 2, "c": 3}, "a": 4}"#;
+        let parse = |duplicate_keys| {
+            parse_source(
+                json_source,
+                &ParseOptions {
+                    duplicate_keys,
+                    ..ParseOptions::new()
+                },
+            )
+        };
+        let expected = |a, c| {
+            Value::Struct(Struct::from_pairs([
+                ("a", Value::I64(a)),
+                (
+                    "b",
+                    Value::Struct(Struct::from_pairs([("c", Value::I64(c))])),
+                ),
+            ]))
+        };
+
+        assert_eq!(parse(DuplicateKeyPolicy::LastWins).unwrap(), expected(4, 3));
+        assert_eq!(
+            parse(DuplicateKeyPolicy::FirstWins).unwrap(),
+            expected(1, 2)
+        );
+        assert_eq!(
+            parse(DuplicateKeyPolicy::Error).unwrap_err().to_string(),
+            "Duplicate key `c` (see `ParseOptions::duplicate_keys`) (at key `b`)"
+        );
+
+        let error = parse_source(
+            "[{}, {\"x\": 1, \"x\": 1}]",
+            &ParseOptions {
+                duplicate_keys: DuplicateKeyPolicy::Error,
+                ..ParseOptions::new()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(error.key_path(), Some("[1]"));
+    }
 }
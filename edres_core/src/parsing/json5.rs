@@ -0,0 +1,276 @@
+//! This module provides utilities for parsing JSON5 files
+//! to generic `Value`s.
+//!
+//! JSON5 sources are read with the `json5` crate, and converted in
+//! the same way as JSON files. Unlike JSON, they can contain
+//! `Infinity` and `NaN`.
+
+use std::fmt;
+
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+
+use crate::{
+    error::Error,
+    options::ParseOptions,
+    parsing::{self, DuplicateKeys},
+    value::{Struct, Value},
+};
+
+/// Parse JSON5 source, producing a generic `Value`.
+///
+/// Duplicate keys are handled following
+/// [`ParseOptions::duplicate_keys`].
+pub fn parse_source(source: &str, options: &ParseOptions) -> Result<Value, Error> {
+    let mut result = parse_value_non_unified(source, options)?;
+    parsing::remove_ignored_keys(&mut result, options);
+    parsing::convert_decimal_strings(&mut result, options)?;
+    parsing::convert_char_strings(&mut result, options);
+    parsing::unify_value(&mut result)?;
+    Ok(result)
+}
+
+/// Parse JSON5 source, producing a generic `Value`.
+///
+/// This bypasses the unification step, so sequences may contain
+/// values of different types. (See [`parsing::unify_values`].)
+pub fn parse_value_non_unified(source: &str, options: &ParseOptions) -> Result<Value, Error> {
+    // The deserializer doesn't check for trailing characters itself,
+    // so the syntax is checked as a whole first.
+    ::json5::from_str::<IgnoredAny>(source)?;
+    DuplicateKeys::deserialize(options.duplicate_keys, |duplicates| {
+        RawValue {
+            duplicates,
+            options,
+        }
+        .deserialize(&mut ::json5::Deserializer::from_str(source))
+    })
+}
+
+/// Deserializes a `Value`, keeping track of duplicate keys in
+/// objects.
+#[derive(Clone, Copy)]
+struct RawValue<'a> {
+    duplicates: DuplicateKeys<'a>,
+    options: &'a ParseOptions,
+}
+
+impl<'de> DeserializeSeed<'de> for RawValue<'_> {
+    type Value = Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for RawValue<'_> {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any JSON5 value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(parsing::preferred_integer(value.into(), self.options))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(parsing::preferred_integer(value.into(), self.options))
+    }
+
+    fn visit_i128<E>(self, value: i128) -> Result<Value, E> {
+        Ok(parsing::preferred_integer(value, self.options))
+    }
+
+    fn visit_u128<E>(self, value: u128) -> Result<Value, E> {
+        Ok(match i128::try_from(value) {
+            Ok(value) => parsing::preferred_integer(value, self.options),
+            Err(_) => Value::U128(value),
+        })
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(parsing::preferred_float(
+            value,
+            self.options.default_float_size,
+        ))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_owned()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Option(None))
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Option(None))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut values = vec![];
+        loop {
+            match seq.next_element_seed(self) {
+                Ok(Some(value)) => values.push(value),
+                Ok(None) => return Ok(parsing::array_or_vec(values, self.options)),
+                Err(error) => return Err(self.duplicates.under_index(error, values.len())),
+            }
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut fields = Struct::default();
+        while let Some(key) = map.next_key::<String>()? {
+            let value = map
+                .next_value_seed(self)
+                .map_err(|error| self.duplicates.under_key(error, &key))?;
+            if !fields.0.contains_key(&key) || self.duplicates.replace(&key)? {
+                fields.0.insert(key, value);
+            }
+        }
+        Ok(Value::Struct(fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    use crate::{options::DuplicateKeyPolicy, parsing::json};
+
+    fn assert_matches_json(json5_source: &str, json_source: &str) {
+        let options = ParseOptions::new();
+        assert_eq!(
+            parse_source(json5_source, &options).unwrap(),
+            json::parse_source(json_source, &options).unwrap(),
+            "{}",
+            json5_source
+        );
+    }
+
+    #[test]
+    fn json5_syntax() {
+        let source = r#"
+            // Line comments
+            {
+                unquoted: 'single quoted',
+                "quoted": "with \"escapes\" \x41",
+                /* block
+                   comments */
+                continued: 'line \
+continuation',
+                hex: 0xFF,
+                negative_hex: -0x10,
+                positive: +1,
+                leading_point: .5,
+                trailing_point: 2.,
+                exponent: 1e3,
+                array: [1, 2, 3,],
+                $dollar_key: null,
+                \u0061b: 3,
+                nested: { a: true, b: false, },
+            }
+        "#;
+        let json = r#"{
+            "unquoted": "single quoted",
+            "quoted": "with \"escapes\" A",
+            "continued": "line continuation",
+            "hex": 255,
+            "negative_hex": -16,
+            "positive": 1,
+            "leading_point": 0.5,
+            "trailing_point": 2.0,
+            "exponent": 1000.0,
+            "array": [1, 2, 3],
+            "$dollar_key": null,
+            "ab": 3,
+            "nested": { "a": true, "b": false }
+        }"#;
+        assert_matches_json(source, json);
+    }
+
+    #[test]
+    fn preserves_key_order() {
+        assert_matches_json("{ z: 1, a: 2, m: 3 }", r#"{ "z": 1, "a": 2, "m": 3 }"#);
+    }
+
+    #[test]
+    fn non_finite_numbers() {
+        let value = parse_source(
+            "[Infinity, -Infinity, +Infinity, NaN]",
+            &ParseOptions::new(),
+        );
+        let Ok(Value::Vec(values)) = value else {
+            panic!("Expected a Vec, got {:?}", value);
+        };
+        assert_eq!(values[0], Value::F64(f64::INFINITY));
+        assert_eq!(values[1], Value::F64(f64::NEG_INFINITY));
+        assert_eq!(values[2], Value::F64(f64::INFINITY));
+        assert!(matches!(values[3], Value::F64(x) if x.is_nan()));
+    }
+
+    #[test]
+    fn large_integers() {
+        let value = |source| parse_source(source, &ParseOptions::new()).unwrap();
+        assert_eq!(value("18446744073709551615"), Value::I128(u64::MAX.into()));
+        assert_eq!(
+            value("-9223372036854775809"),
+            Value::I128(i128::from(i64::MIN) - 1)
+        );
+        assert_eq!(
+            value("340282366920938463463374607431768211455"),
+            Value::U128(u128::MAX)
+        );
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let errors = [
+            ("{ a: 1 ", "EOF parsing object at line 1 column 1"),
+            ("{\n  a: 1,\n  b 2\n}", "expected colon at line 3 column 5"),
+            ("[1, 2] 3", "trailing characters at line 1 column 8"),
+            ("{ a: 01 }", "leading zero at line 1 column 6"),
+            ("{ a: Infinite }", "expected number at line 1 column 13"),
+            ("/* unterminated", "EOF parsing value"),
+            ("'unterminated", "EOF parsing string"),
+        ];
+
+        for (source, message) in errors {
+            match parse_source(source, &ParseOptions::new()) {
+                Err(Error::Json5(error)) => assert_eq!(error.to_string(), message, "{}", source),
+                other => panic!("Expected a JSON5 error for {:?}, got {:?}", source, other),
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let source = "{ list: [{ a: 1, a: 2 }] }";
+        let options = |duplicate_keys| ParseOptions {
+            duplicate_keys,
+            ..ParseOptions::new()
+        };
+
+        assert_matches_json(source, r#"{ "list": [{ "a": 2 }] }"#);
+        assert_eq!(
+            parse_source(source, &options(DuplicateKeyPolicy::FirstWins)).unwrap(),
+            json::parse_source(r#"{ "list": [{ "a": 1 }] }"#, &ParseOptions::new()).unwrap()
+        );
+        assert_eq!(
+            parse_source(source, &options(DuplicateKeyPolicy::Error))
+                .unwrap_err()
+                .to_string(),
+            "Duplicate key `a` (see `ParseOptions::duplicate_keys`) (at key `list[0]`)"
+        );
+    }
+}
@@ -8,27 +8,41 @@
 #[cfg(feature = "json")]
 pub mod json;
 
+#[cfg(feature = "json5")]
+pub mod json5;
+
 #[cfg(feature = "toml")]
 pub mod toml;
 
 #[cfg(feature = "yaml")]
 pub mod yaml;
 
-use std::path::Path;
+#[cfg(feature = "xml")]
+pub mod xml;
+
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
 
 use crate::{
-    error::Error,
+    error::{Error, TypeConflict},
     format::Format,
-    options::{FloatSize, IntSize, ParseOptions},
-    value::Value,
+    options::{ArrayMerge, FloatSize, HeterogeneousArrayMode, IntSize, ParseOptions, UIntSize},
+    value::{KeyPath, Location, MergeStrategy, SequenceMerge, SpannedValue, Value},
 };
 
+#[cfg(any(feature = "json", feature = "yaml", feature = "msgpack"))]
+use crate::options::DuplicateKeyPolicy;
+
 /// Parses a source file into a generic `Value`, inferring its
 /// format.
+///
+/// Any error will include the path of the file.
 pub fn parse_source_file(file: &Path, options: &ParseOptions) -> Result<Value, Error> {
-    let source = std::fs::read_to_string(file)?;
-    let format = Format::from_filename(file)?;
-    parse_source(&source, format, options)
+    parse_source_file_with_format(file, None, options)
 }
 
 pub(crate) fn parse_source_file_with_format(
@@ -36,25 +50,559 @@ pub(crate) fn parse_source_file_with_format(
     format: Option<Format>,
     options: &ParseOptions,
 ) -> Result<Value, Error> {
-    let source = std::fs::read_to_string(file)?;
-    let format = match format {
-        None => Format::from_filename(file)?,
-        Some(x) => x,
+    parse_source_file_with_includes(file, format, options, &mut vec![], None)
+}
+
+/// Parses a source file into a generic `Value`, inferring its
+/// format, and keeping track of where its parts came from.
+///
+/// Locations are only known for formats which track them (see
+/// [`SpannedValue`]). Values spliced in from included files have
+/// the location of the include directive.
+pub fn parse_source_file_spanned(
+    file: &Path,
+    options: &ParseOptions,
+) -> Result<SpannedValue, Error> {
+    let mut locations = IndexMap::new();
+    let value =
+        parse_source_file_with_includes(file, None, options, &mut vec![], Some(&mut locations))?;
+    Ok(SpannedValue {
+        value,
+        file_path: Some(file.to_owned()),
+        locations,
+    })
+}
+
+/// Parses a source file, splicing in any files it includes (see
+/// [`ParseOptions::include_directive`]).
+///
+/// `including` holds the (canonical) paths of the files currently
+/// being parsed, to detect cycles. If `locations` is given, it is
+/// filled with the locations of the file's values, where known.
+fn parse_source_file_with_includes(
+    file: &Path,
+    format: Option<Format>,
+    options: &ParseOptions,
+    including: &mut Vec<PathBuf>,
+    mut locations: Option<&mut IndexMap<KeyPath, Location>>,
+) -> Result<Value, Error> {
+    let mut parse = || -> Result<Value, Error> {
+        let format = match format {
+            None => Format::from_filename(file)?,
+            Some(x) => x,
+        };
+        let mut value = match format {
+            #[cfg(feature = "msgpack")]
+            Format::Msgpack => msgpack::parse_bytes(&std::fs::read(file)?, options)?,
+
+            #[allow(unreachable_patterns)]
+            format => {
+                let source = std::fs::read_to_string(file)?;
+                match locations.as_deref_mut() {
+                    Some(locations) => {
+                        let spanned = parse_source_spanned(&source, format, options)?;
+                        *locations = spanned.locations;
+                        spanned.value
+                    }
+                    None => parse_source(&source, format, options)?,
+                }
+            }
+        };
+
+        if let Some(directive) = &options.include_directive {
+            including.push(file.canonicalize()?);
+            let dir = file.parent().unwrap_or(Path::new(""));
+            let result = resolve_includes(&mut value, dir, directive, options, including);
+            including.pop();
+            result?;
+            unify_value(&mut value)?;
+        }
+        Ok(value)
+    };
+    parse().map_err(|error| error.in_file(file))
+}
+
+fn resolve_includes(
+    value: &mut Value,
+    dir: &Path,
+    directive: &str,
+    options: &ParseOptions,
+    including: &mut Vec<PathBuf>,
+) -> Result<(), Error> {
+    let included_path = match &*value {
+        Value::String(string) => string
+            .strip_prefix(directive)
+            .and_then(|rest| rest.strip_prefix(' ')),
+        Value::Struct(fields) if fields.0.len() == 1 => match fields.0.get(directive) {
+            Some(Value::String(path)) => Some(path.as_str()),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    if let Some(path) = included_path {
+        let path = dir.join(path.trim());
+        let canonical = path
+            .canonicalize()
+            .map_err(|error| Error::from(error).in_file(&path))?;
+        if let Some(start) = including.iter().position(|file| *file == canonical) {
+            let mut cycle = including[start..].to_vec();
+            cycle.push(canonical);
+            return Err(Error::IncludeCycle(cycle));
+        }
+        *value = parse_source_file_with_includes(&path, None, options, including, None)?;
+        return Ok(());
+    }
+
+    match value {
+        Value::Option(Some(inner)) => resolve_includes(inner, dir, directive, options, including)?,
+        Value::Tuple(items) | Value::Array(_, items) | Value::Vec(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                resolve_includes(item, dir, directive, options, including)
+                    .map_err(|error| error.under_index(i))?;
+            }
+        }
+        Value::Struct(fields) | Value::Tagged(_, fields) => {
+            for (key, field) in fields.0.iter_mut() {
+                resolve_includes(field, dir, directive, options, including)
+                    .map_err(|error| error.under_key(key))?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Parses a sequence of source files (inferring their formats) and
+/// deep-merges them in order, so later files override earlier ones.
+///
+/// See [`merge_values`] for how values are merged, using
+/// [`ParseOptions::layer_array_merge`] for sequences. The result is
+/// unified, and any error will include the path of the file.
+pub fn parse_layered_source_files<P: AsRef<Path>>(
+    files: &[P],
+    options: &ParseOptions,
+) -> Result<Value, Error> {
+    let mut layers = files
+        .iter()
+        .map(|file| parse_source_file(file.as_ref(), options));
+    let mut value = match layers.next() {
+        Some(value) => value?,
+        None => Value::Struct(Default::default()),
+    };
+    for layer in layers {
+        merge_values(&mut value, layer?, options.layer_array_merge);
+    }
+    unify_value(&mut value)?;
+    Ok(value)
+}
+
+/// Deserializes a source file directly into a type (like
+/// [`Options`]), inferring its format.
+///
+/// Any error will include the path of the file.
+///
+/// [`Options`]: crate::options::Options
+pub fn deserialize_source_file<T: serde::de::DeserializeOwned>(file: &Path) -> Result<T, Error> {
+    let deserialize = || -> Result<T, Error> {
+        let source = std::fs::read_to_string(file)?;
+        parse_into(&source, Format::from_filename(file)?)
     };
-    parse_source(&source, format, options)
+    deserialize().map_err(|error| error.in_file(file))
+}
+
+/// Deserializes source of a given format directly into a type.
+///
+/// This is useful in build scripts which generate code from a
+/// file, but also need to check that it can be loaded into an
+/// existing, hand-written type. XML and MessagePack sources
+/// are not supported.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "json")]
+/// # {
+/// # use edres_core::{parsing, Format};
+/// #[derive(Debug, PartialEq, serde::Deserialize)]
+/// struct Config {
+///     name: String,
+///     retries: u32,
+/// }
+///
+/// let config: Config =
+///     parsing::parse_into(r#"{"name": "test", "retries": 3}"#, Format::Json).unwrap();
+/// assert_eq!(config, Config { name: "test".into(), retries: 3 });
+/// # }
+/// ```
+#[cfg_attr(
+    not(any(feature = "json", feature = "toml", feature = "yaml")),
+    allow(unused_variables)
+)]
+pub fn parse_into<T: serde::de::DeserializeOwned>(
+    source: &str,
+    format: Format,
+) -> Result<T, Error> {
+    match format {
+        #[cfg(feature = "json")]
+        Format::Json => Ok(serde_json::from_str(source)?),
+
+        #[cfg(feature = "json5")]
+        Format::Json5 => Ok(::json5::from_str(source)?),
+
+        #[cfg(feature = "toml")]
+        Format::Toml => Ok(::toml::from_str(source)?),
+
+        #[cfg(feature = "yaml")]
+        Format::Yaml => Ok(serde_yaml::from_str(source)?),
+
+        #[cfg(feature = "xml")]
+        Format::Xml => Err(Error::DeserializeUnsupported(format)),
+
+        #[cfg(feature = "msgpack")]
+        Format::Msgpack => Err(Error::DeserializeUnsupported(format)),
+    }
 }
 
 /// Parse source of a given format, producing a generic `Value`.
+///
+/// Binary formats (like MessagePack) cannot be parsed from a string,
+/// so they give an error. Use [`parse_bytes`] for them instead.
+#[cfg_attr(
+    not(any(feature = "json", feature = "toml", feature = "yaml", feature = "xml")),
+    allow(unused_variables)
+)]
 pub fn parse_source(source: &str, format: Format, options: &ParseOptions) -> Result<Value, Error> {
     match format {
         #[cfg(feature = "json")]
         Format::Json => json::parse_source(source, options),
 
+        #[cfg(feature = "json5")]
+        Format::Json5 => json5::parse_source(source, options),
+
         #[cfg(feature = "toml")]
         Format::Toml => toml::parse_source(source, options),
 
         #[cfg(feature = "yaml")]
         Format::Yaml => yaml::parse_source(source, options),
+
+        #[cfg(feature = "xml")]
+        Format::Xml => xml::parse_source(source, options),
+
+        #[cfg(feature = "msgpack")]
+        Format::Msgpack => Err(Error::BinaryFormat(format)),
+    }
+}
+
+/// Parse the raw bytes of a given format, producing a generic
+/// `Value`.
+///
+/// Bytes for text formats must be valid UTF-8.
+pub fn parse_bytes(bytes: &[u8], format: Format, options: &ParseOptions) -> Result<Value, Error> {
+    match format {
+        #[cfg(feature = "msgpack")]
+        Format::Msgpack => msgpack::parse_bytes(bytes, options),
+
+        #[allow(unreachable_patterns)]
+        format => {
+            let source = std::str::from_utf8(bytes)
+                .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+            parse_source(source, format, options)
+        }
+    }
+}
+
+/// Parse source of a given format, producing a generic `Value` along
+/// with the locations of its parts, where the format's parser tracks
+/// them (see [`SpannedValue`]).
+pub fn parse_source_spanned(
+    source: &str,
+    format: Format,
+    options: &ParseOptions,
+) -> Result<SpannedValue, Error> {
+    match format {
+        #[cfg(feature = "toml")]
+        Format::Toml => toml::parse_source_spanned(source, options),
+
+        #[allow(unreachable_patterns)]
+        format => parse_source(source, format, options).map(SpannedValue::new),
+    }
+}
+
+/// Deep-merges `overlay` into `base`, as if `overlay` came from a
+/// file layered on top of `base` (like `production.yaml` on top of
+/// `base.yaml`).
+///
+/// Structs are merged field by field, with new fields added after
+/// the existing ones. Sequences are merged according to
+/// `array_merge`. Any other value in `overlay` replaces the value in
+/// `base`.
+///
+/// # Examples
+/// ```
+/// # use edres_core::{parsing, options::ArrayMerge, value::*};
+/// let mut base = Value::Struct(Struct::from_pairs([
+///     ("host", Value::String("localhost".into())),
+///     ("port", Value::I64(80)),
+/// ]));
+/// let overlay = Value::Struct(Struct::from_pairs([
+///     ("port", Value::I64(443)),
+///     ("tls", Value::Bool(true)),
+/// ]));
+/// parsing::merge_values(&mut base, overlay, ArrayMerge::Replace);
+///
+/// assert_eq!(base, Value::Struct(Struct::from_pairs([
+///     ("host", Value::String("localhost".into())),
+///     ("port", Value::I64(443)),
+///     ("tls", Value::Bool(true)),
+/// ])));
+/// ```
+pub fn merge_values(base: &mut Value, overlay: Value, array_merge: ArrayMerge) {
+    let sequences = match array_merge {
+        ArrayMerge::Replace => SequenceMerge::Replace,
+        ArrayMerge::Append => SequenceMerge::Append,
+    };
+    base.merge(
+        overlay,
+        MergeStrategy {
+            sequences,
+            ..MergeStrategy::new()
+        },
+    );
+}
+
+/// Whether the value for a key which is already in a mapping
+/// should replace the existing one, following
+/// [`ParseOptions::duplicate_keys`].
+#[cfg(any(feature = "json", feature = "yaml", feature = "msgpack"))]
+pub(crate) fn replace_duplicate_key(key: &str, policy: DuplicateKeyPolicy) -> Result<bool, Error> {
+    match policy {
+        DuplicateKeyPolicy::Error => Err(Error::DuplicateKey(key.to_owned())),
+        DuplicateKeyPolicy::FirstWins => Ok(false),
+        DuplicateKeyPolicy::LastWins => Ok(true),
+    }
+}
+
+/// Tracks duplicate keys while a value is deserialized with serde.
+///
+/// Serde errors can't hold an `Error`, so when a duplicate key is
+/// an error, it is stored here instead (collecting its key path
+/// as it passes up through the parent values). The serde error is
+/// only used to stop deserializing.
+#[cfg(any(feature = "json", feature = "yaml"))]
+#[derive(Clone, Copy)]
+pub(crate) struct DuplicateKeys<'a> {
+    policy: DuplicateKeyPolicy,
+    error: &'a std::cell::RefCell<Option<Error>>,
+}
+
+#[cfg(any(feature = "json", feature = "yaml"))]
+impl DuplicateKeys<'_> {
+    /// Runs a deserialization, returning the duplicate key error
+    /// in place of the serde error if there is one.
+    pub(crate) fn deserialize<T, E>(
+        policy: DuplicateKeyPolicy,
+        deserialize: impl FnOnce(DuplicateKeys) -> Result<T, E>,
+    ) -> Result<T, Error>
+    where
+        Error: From<E>,
+    {
+        let error = std::cell::RefCell::new(None);
+        let result = deserialize(DuplicateKeys {
+            policy,
+            error: &error,
+        });
+        match error.into_inner() {
+            Some(error) => Err(error),
+            None => Ok(result?),
+        }
+    }
+
+    pub(crate) fn replace<E: serde::de::Error>(self, key: &str) -> Result<bool, E> {
+        replace_duplicate_key(key, self.policy).map_err(|error| {
+            let message = E::custom(&error);
+            *self.error.borrow_mut() = Some(error);
+            message
+        })
+    }
+
+    pub(crate) fn under_key<E>(self, error: E, key: &str) -> E {
+        let mut stored = self.error.borrow_mut();
+        *stored = stored.take().map(|stored| stored.under_key(key));
+        error
+    }
+
+    pub(crate) fn under_index<E>(self, error: E, index: usize) -> E {
+        let mut stored = self.error.borrow_mut();
+        *stored = stored.take().map(|stored| stored.under_index(index));
+        error
+    }
+}
+
+/// Replaces strings containing decimal numbers with
+/// [`Value::Decimal`]s, if [`ParseOptions::decimal_strings`] is set.
+///
+/// Without the `decimal` feature, this does nothing.
+#[cfg_attr(not(feature = "decimal"), allow(unused_variables))]
+pub(crate) fn convert_decimal_strings(
+    value: &mut Value,
+    options: &ParseOptions,
+) -> Result<(), Error> {
+    #[cfg(feature = "decimal")]
+    if options.decimal_strings {
+        return convert_decimals(value);
+    }
+    Ok(())
+}
+
+/// Replaces strings of one character with [`Value::Char`]s, if
+/// [`ParseOptions::infer_char_from_single_char_strings`] is set.
+pub(crate) fn convert_char_strings(value: &mut Value, options: &ParseOptions) {
+    if options.infer_char_from_single_char_strings {
+        convert_chars(value);
+    }
+}
+
+fn convert_chars(value: &mut Value) {
+    match value {
+        Value::String(source) => {
+            let mut chars = source.chars();
+            if let (Some(c), None) = (chars.next(), chars.next()) {
+                *value = Value::Char(c);
+            }
+        }
+        Value::Option(Some(inner)) => convert_chars(inner),
+        Value::Tuple(items) | Value::Array(_, items) | Value::Vec(items) => {
+            items.iter_mut().for_each(convert_chars)
+        }
+        Value::Struct(fields) | Value::Tagged(_, fields) => {
+            fields.0.values_mut().for_each(convert_chars)
+        }
+        _ => (),
+    }
+}
+
+#[cfg(feature = "decimal")]
+fn convert_decimals(value: &mut Value) -> Result<(), Error> {
+    match value {
+        Value::String(source) if is_decimal(source) => {
+            if decimal_parts(source).is_none() {
+                return Err(Error::InvalidDecimal(source.clone()));
+            }
+            *value = Value::Decimal(std::mem::take(source));
+        }
+        Value::Option(Some(inner)) => convert_decimals(inner)?,
+        Value::Tuple(items) | Value::Array(_, items) | Value::Vec(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                convert_decimals(item).map_err(|error| error.under_index(i))?;
+            }
+        }
+        Value::Struct(fields) | Value::Tagged(_, fields) => {
+            for (key, field) in fields.0.iter_mut() {
+                convert_decimals(field).map_err(|error| error.under_key(key))?;
+            }
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+/// Whether a string is a decimal number with a fractional part,
+/// like `-19.99`.
+#[cfg(feature = "decimal")]
+fn is_decimal(source: &str) -> bool {
+    let digits = source.strip_prefix('-').unwrap_or(source);
+    match digits.split_once('.') {
+        Some((whole, fraction)) => {
+            !whole.is_empty()
+                && !fraction.is_empty()
+                && whole.bytes().all(|b| b.is_ascii_digit())
+                && fraction.bytes().all(|b| b.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// The sign, mantissa, and scale of a decimal number, if it can be
+/// represented as a `rust_decimal::Decimal`.
+pub(crate) fn decimal_parts(source: &str) -> Option<(bool, u128, u32)> {
+    let (negative, digits) = match source.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, source),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let scale = fraction.len() as u32;
+    let mantissa: u128 = format!("{}{}", whole, fraction).parse().ok()?;
+    (scale <= 28 && mantissa < 1 << 96).then_some((negative, mantissa, scale))
+}
+
+/// Removes the keys matching [`ParseOptions::ignore_keys`] from
+/// the given value.
+pub fn remove_ignored_keys(value: &mut Value, options: &ParseOptions) {
+    if options.ignore_keys.is_empty() {
+        return;
+    }
+
+    let patterns = options
+        .ignore_keys
+        .iter()
+        .map(|pattern| pattern.split('.').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    remove_matching_keys(value, &patterns, &mut vec![]);
+}
+
+fn remove_matching_keys(value: &mut Value, patterns: &[Vec<&str>], path: &mut Vec<String>) {
+    match value {
+        Value::Option(Some(inner)) => remove_matching_keys(inner, patterns, path),
+        Value::Tuple(items) | Value::Array(_, items) | Value::Vec(items) => {
+            for item in items {
+                remove_matching_keys(item, patterns, path);
+            }
+        }
+        Value::Struct(fields) | Value::Tagged(_, fields) => {
+            fields.0.retain(|key, _| {
+                path.push(key.clone());
+                let ignored = patterns
+                    .iter()
+                    .any(|pattern| key_path_matches(pattern, path));
+                path.pop();
+                !ignored
+            });
+            for (key, field) in fields.0.iter_mut() {
+                path.push(key.clone());
+                remove_matching_keys(field, patterns, path);
+                path.pop();
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Whether a key path matches a pattern from
+/// [`ParseOptions::ignore_keys`].
+fn key_path_matches(pattern: &[&str], path: &[String]) -> bool {
+    match (pattern.split_first(), path.split_first()) {
+        (None, None) => true,
+        (Some((&"**", rest)), _) => {
+            key_path_matches(rest, path)
+                || (!path.is_empty() && key_path_matches(pattern, &path[1..]))
+        }
+        (Some((segment, rest)), Some((key, keys))) => {
+            key_matches(segment, key) && key_path_matches(rest, keys)
+        }
+        _ => false,
+    }
+}
+
+/// Whether a key matches a pattern, where `*` matches any number
+/// of characters.
+fn key_matches(pattern: &str, key: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == key,
+        Some((prefix, rest)) => key.strip_prefix(prefix).is_some_and(|key| {
+            (0..=key.len())
+                .filter(|&i| key.is_char_boundary(i))
+                .any(|i| key_matches(rest, &key[i..]))
+        }),
     }
 }
 
@@ -66,15 +614,15 @@ pub fn unify_value(value: &mut Value) -> Result<(), Error> {
     match value {
         Value::Option(Some(inner)) => unify_value(inner)?,
         Value::Tuple(items) => {
-            for value in items.iter_mut() {
-                unify_value(value)?;
+            for (i, value) in items.iter_mut().enumerate() {
+                unify_value(value).map_err(|error| error.under_index(i))?;
             }
         }
         Value::Array(_, items) => unify_values(items)?,
         Value::Vec(items) => unify_values(items)?,
-        Value::Struct(inner) => {
-            for value in inner.0.values_mut() {
-                unify_value(value)?;
+        Value::Struct(inner) | Value::Tagged(_, inner) => {
+            for (key, value) in inner.0.iter_mut() {
+                unify_value(value).map_err(|error| error.under_key(key))?;
             }
         }
         _ => (),
@@ -88,18 +636,519 @@ pub fn unify_value(value: &mut Value) -> Result<(), Error> {
 /// Currently this only ensures the following:
 ///
 /// 1.  If any of the values are null, all the values are
-///     converted to Option types.
-/// 2.  This function is applied recursively to sequences within
+///     converted to Option types, and the values inside them
+///     are unified with each other.
+/// 2.  If the values are all numbers of different types, they
+///     are all converted to a common type that can hold them.
+///     Integers are widened as necessary, and mixing integers
+///     with floats converts them all to floats. It is an error
+///     to mix signed integers with a `u128` too large for `i128`.
+/// 3.  If the values are all structs, the values of each field
+///     are unified with the same field in the other structs.
+///     Fields missing from some of the structs are added to them
+///     as `None`, so they become optional.
+/// 4.  If the values are all sequences, their items are unified
+///     with the items of the other sequences.
+/// 5.  If the values are a mix of chars and strings, the chars are
+///     converted to strings.
+/// 6.  This function is applied recursively to sequences within
 ///     the given values.
 pub fn unify_values(values: &mut [Value]) -> Result<(), Error> {
     for v in values.iter_mut() {
         unify_value(v)?;
     }
 
-    // Unify Options
-    {
-        if values.iter().any(|v| matches!(v, Value::Option(_))) {
-            values.iter_mut().for_each(Value::wrap_in_option);
+    // Unify Options, and then their contents
+    if values.iter().any(|v| matches!(v, Value::Option(_))) {
+        values.iter_mut().for_each(Value::wrap_in_option);
+
+        let mut inners = values
+            .iter_mut()
+            .filter_map(|v| match v {
+                Value::Option(Some(inner)) => Some(std::mem::replace(&mut **inner, Value::Unit)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        unify_values(&mut inners)?;
+
+        let slots = values.iter_mut().filter_map(|v| match v {
+            Value::Option(Some(inner)) => Some(inner),
+            _ => None,
+        });
+        for (slot, unified) in slots.zip(inners) {
+            **slot = unified;
+        }
+        return Ok(());
+    }
+
+    unify_numbers(values)?;
+    unify_chars(values);
+    unify_struct_fields(values)?;
+    unify_tagged_structs(values)?;
+    unify_sequence_items(values)?;
+
+    Ok(())
+}
+
+/// Finds the key paths at which values from different sources
+/// have incompatible types.
+///
+/// Each item is the name of a source (like a file path) and the
+/// value parsed from it. The values should already have been
+/// unified (see [`unify_values`]), so that, for example, integers
+/// and floats are not reported as conflicting. Missing fields, and
+/// `None` values, are not considered conflicts.
+///
+/// At most one conflict is reported for each key path.
+pub fn find_type_conflicts<'a, I>(sources: I) -> Vec<TypeConflict>
+where
+    I: IntoIterator<Item = (&'a str, &'a Value)>,
+{
+    let mut seen = IndexMap::new();
+    let mut conflicts = vec![];
+    for (source, value) in sources {
+        collect_type_conflicts(value, "", source, &mut seen, &mut conflicts);
+    }
+    conflicts
+}
+
+fn collect_type_conflicts<'a>(
+    value: &Value,
+    key_path: &str,
+    source: &'a str,
+    seen: &mut IndexMap<String, (&'static str, &'a str)>,
+    conflicts: &mut Vec<TypeConflict>,
+) {
+    let type_name = match value {
+        Value::Option(None) => return,
+        Value::Option(Some(value)) => {
+            return collect_type_conflicts(value, key_path, source, seen, conflicts)
+        }
+        Value::Array(..) | Value::Vec(_) => "a sequence",
+        Value::Tuple(_) => "a tuple",
+        Value::Struct(_) => "a struct",
+        Value::Tagged(..) => "a tagged struct",
+        value => value.type_name(),
+    };
+
+    match seen.get(key_path) {
+        None => {
+            seen.insert(key_path.to_owned(), (type_name, source));
+        }
+        Some(&(first_type, first_source)) if first_type != type_name => {
+            if !conflicts.iter().any(|c| c.key_path == key_path) {
+                conflicts.push(TypeConflict {
+                    key_path: key_path.to_owned(),
+                    first_type,
+                    first_source: first_source.to_owned(),
+                    second_type: type_name,
+                    second_source: source.to_owned(),
+                });
+            }
+            return;
+        }
+        Some(_) => (),
+    }
+
+    let child_path = |key: &str| match key_path.is_empty() {
+        true => key.to_owned(),
+        false => format!("{}.{}", key_path, key),
+    };
+    match value {
+        Value::Array(_, items) | Value::Vec(items) => {
+            let items_path = format!("{}[]", key_path);
+            for item in items {
+                collect_type_conflicts(item, &items_path, source, seen, conflicts);
+            }
+        }
+        Value::Tuple(items) => {
+            for (i, item) in items.iter().enumerate() {
+                collect_type_conflicts(item, &child_path(&i.to_string()), source, seen, conflicts);
+            }
+        }
+        Value::Struct(fields) => {
+            for (key, field) in fields.0.iter() {
+                collect_type_conflicts(field, &child_path(key), source, seen, conflicts);
+            }
+        }
+        Value::Tagged(_, fields) => {
+            // Structs with different tags can have different types
+            // for the same fields.
+            let variant_path = child_path(value.tag().unwrap_or_default());
+            for (key, field) in fields.0.iter() {
+                let key_path = format!("{}.{}", variant_path, key);
+                collect_type_conflicts(field, &key_path, source, seen, conflicts);
+            }
+        }
+        _ => (),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumericType {
+    Int { signed: bool, bits: u32 },
+    Float { bits: u32 },
+}
+
+fn numeric_type(value: &Value) -> Option<NumericType> {
+    use NumericType::*;
+
+    // NOTE: `isize` and `usize` are treated as 64-bit here, since
+    // the target of the generated code isn't known.
+    Some(match value {
+        Value::I8(_) => Int {
+            signed: true,
+            bits: 8,
+        },
+        Value::I16(_) => Int {
+            signed: true,
+            bits: 16,
+        },
+        Value::I32(_) => Int {
+            signed: true,
+            bits: 32,
+        },
+        Value::I64(_) | Value::ISize(_) => Int {
+            signed: true,
+            bits: 64,
+        },
+        Value::I128(_) => Int {
+            signed: true,
+            bits: 128,
+        },
+        Value::U8(_) => Int {
+            signed: false,
+            bits: 8,
+        },
+        Value::U16(_) => Int {
+            signed: false,
+            bits: 16,
+        },
+        Value::U32(_) => Int {
+            signed: false,
+            bits: 32,
+        },
+        Value::U64(_) | Value::USize(_) => Int {
+            signed: false,
+            bits: 64,
+        },
+        Value::U128(_) => Int {
+            signed: false,
+            bits: 128,
+        },
+        Value::F32(_) => Float { bits: 32 },
+        Value::F64(_) => Float { bits: 64 },
+        _ => return None,
+    })
+}
+
+pub(crate) fn number_as_i128(value: &Value) -> Option<i128> {
+    Some(match *value {
+        Value::I8(x) => x as i128,
+        Value::I16(x) => x as i128,
+        Value::I32(x) => x as i128,
+        Value::I64(x) => x as i128,
+        Value::I128(x) => x,
+        Value::ISize(x) => x as i128,
+        Value::U8(x) => x as i128,
+        Value::U16(x) => x as i128,
+        Value::U32(x) => x as i128,
+        Value::U64(x) => x as i128,
+        Value::U128(x) => i128::try_from(x).ok()?,
+        Value::USize(x) => x as i128,
+        _ => return None,
+    })
+}
+
+fn number_as_u128(value: &Value) -> Option<u128> {
+    match *value {
+        Value::U128(x) => Some(x),
+        _ => number_as_i128(value).and_then(|x| u128::try_from(x).ok()),
+    }
+}
+
+pub(crate) fn number_as_f64(value: &Value) -> Option<f64> {
+    match *value {
+        Value::F32(x) => Some(x as f64),
+        Value::F64(x) => Some(x),
+        Value::U128(x) => Some(x as f64),
+        _ => number_as_i128(value).map(|x| x as f64),
+    }
+}
+
+fn fits_signed(value: &Value, bits: u32) -> bool {
+    match number_as_i128(value) {
+        Some(_) if bits >= 128 => true,
+        Some(x) => {
+            let max = 1_i128 << (bits - 1);
+            x >= -max && x < max
+        }
+        None => false,
+    }
+}
+
+fn convert_number(value: &Value, target: NumericType) -> Option<Value> {
+    use NumericType::*;
+
+    Some(match target {
+        Float { bits: 32 } => Value::F32(number_as_f64(value)? as f32),
+        Float { .. } => Value::F64(number_as_f64(value)?),
+        Int { signed: true, bits } => {
+            let x = number_as_i128(value)?;
+            match bits {
+                8 => Value::I8(x.try_into().ok()?),
+                16 => Value::I16(x.try_into().ok()?),
+                32 => Value::I32(x.try_into().ok()?),
+                64 => Value::I64(x.try_into().ok()?),
+                _ => Value::I128(x),
+            }
+        }
+        Int {
+            signed: false,
+            bits,
+        } => {
+            let x = number_as_u128(value)?;
+            match bits {
+                8 => Value::U8(x.try_into().ok()?),
+                16 => Value::U16(x.try_into().ok()?),
+                32 => Value::U32(x.try_into().ok()?),
+                64 => Value::U64(x.try_into().ok()?),
+                _ => Value::U128(x),
+            }
+        }
+    })
+}
+
+/// Converts a number to the primitive numeric type with the given
+/// name, if it can be represented by that type.
+pub(crate) fn cast_number(value: &Value, type_name: &str) -> Option<Value> {
+    use NumericType::*;
+
+    let target = match type_name {
+        "i8" => Int {
+            signed: true,
+            bits: 8,
+        },
+        "i16" => Int {
+            signed: true,
+            bits: 16,
+        },
+        "i32" => Int {
+            signed: true,
+            bits: 32,
+        },
+        "i64" => Int {
+            signed: true,
+            bits: 64,
+        },
+        "i128" => Int {
+            signed: true,
+            bits: 128,
+        },
+        "u8" => Int {
+            signed: false,
+            bits: 8,
+        },
+        "u16" => Int {
+            signed: false,
+            bits: 16,
+        },
+        "u32" => Int {
+            signed: false,
+            bits: 32,
+        },
+        "u64" => Int {
+            signed: false,
+            bits: 64,
+        },
+        "u128" => Int {
+            signed: false,
+            bits: 128,
+        },
+        "f32" => Float { bits: 32 },
+        "f64" => Float { bits: 64 },
+        "isize" => {
+            return number_as_i128(value)
+                .and_then(|x| isize::try_from(x).ok())
+                .map(Value::ISize)
+        }
+        "usize" => {
+            return number_as_u128(value)
+                .and_then(|x| usize::try_from(x).ok())
+                .map(Value::USize)
+        }
+        _ => return None,
+    };
+    convert_number(value, target)
+}
+
+/// Converts chars to strings, if they are mixed with strings.
+fn unify_chars(values: &mut [Value]) {
+    let is_char = |v: &Value| matches!(v, Value::Char(_));
+    let is_string = |v: &Value| matches!(v, Value::String(_));
+    if values.iter().all(|v| is_char(v) || is_string(v)) && values.iter().any(is_string) {
+        for value in values.iter_mut() {
+            if let Value::Char(c) = value {
+                *value = Value::String(c.to_string());
+            }
+        }
+    }
+}
+
+fn unify_numbers(values: &mut [Value]) -> Result<(), Error> {
+    use NumericType::*;
+
+    let types = match values.iter().map(numeric_type).collect::<Option<Vec<_>>>() {
+        Some(types) if !types.is_empty() => types,
+        _ => return Ok(()),
+    };
+
+    let first = std::mem::discriminant(&values[0]);
+    if values.iter().all(|v| std::mem::discriminant(v) == first) {
+        return Ok(());
+    }
+
+    let target = if types.iter().any(|t| matches!(t, Float { .. })) {
+        let fits_f32 = types.iter().all(|t| match *t {
+            Float { bits } => bits == 32,
+            Int { bits, .. } => bits <= 16,
+        });
+        Float {
+            bits: if fits_f32 { 32 } else { 64 },
+        }
+    } else {
+        let signed = types.iter().any(|t| matches!(t, Int { signed: true, .. }));
+        let mut bits = types
+            .iter()
+            .map(|t| match *t {
+                Int { bits, .. } | Float { bits } => bits,
+            })
+            .max()
+            .unwrap_or(8);
+        if signed {
+            while bits < 128 && !values.iter().all(|v| fits_signed(v, bits)) {
+                bits *= 2;
+            }
+        }
+        Int { signed, bits }
+    };
+
+    // The only numbers which don't fit in the target are `u128`s
+    // too large for `i128`, mixed with signed integers.
+    for value in values.iter_mut() {
+        match convert_number(value, target) {
+            Some(converted) => *value = converted,
+            None => {
+                let target = match target {
+                    Int { signed, bits } => format!("{}{}", if signed { "i" } else { "u" }, bits),
+                    Float { bits } => format!("f{}", bits),
+                };
+                return Err(Error::IncompatibleNumbers(value.type_name(), target));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn unify_struct_fields(values: &mut [Value]) -> Result<(), Error> {
+    if values.len() < 2 || !values.iter().all(|v| matches!(v, Value::Struct(_))) {
+        return Ok(());
+    }
+
+    let mut keys = indexmap::IndexSet::new();
+    for value in values.iter() {
+        if let Value::Struct(fields) = value {
+            keys.extend(fields.0.keys().cloned());
+        }
+    }
+
+    for key in &keys {
+        // Fields missing from some of the structs become optional,
+        // and `None` in those structs.
+        let mut column = vec![];
+        for value in values.iter_mut() {
+            if let Value::Struct(fields) = value {
+                let field = fields
+                    .0
+                    .entry(key.to_owned())
+                    .or_insert(Value::Option(None));
+                column.push(std::mem::replace(field, Value::Unit));
+            }
+        }
+
+        unify_values(&mut column).map_err(|error| error.under_key(key))?;
+
+        let mut column = column.into_iter();
+        for value in values.iter_mut() {
+            if let Value::Struct(fields) = value {
+                fields.0[key] = column.next().unwrap();
+            }
+        }
+    }
+
+    // Keep the fields in the same order in every struct.
+    for value in values.iter_mut() {
+        if let Value::Struct(fields) = value {
+            fields
+                .0
+                .sort_by(|a, _, b, _| keys.get_index_of(a).cmp(&keys.get_index_of(b)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Unifies each tagged struct with the other tagged structs that
+/// have the same tag (see [`HeterogeneousArrayMode::Tagged`]).
+fn unify_tagged_structs(values: &mut [Value]) -> Result<(), Error> {
+    let mut groups: IndexMap<String, Vec<usize>> = IndexMap::new();
+    for (i, value) in values.iter().enumerate() {
+        if let Some(tag) = value.tag() {
+            groups.entry(tag.to_owned()).or_default().push(i);
+        }
+    }
+
+    for indices in groups.values().filter(|indices| indices.len() > 1) {
+        let mut group = indices
+            .iter()
+            .map(|&i| match &mut values[i] {
+                Value::Tagged(_, fields) => Value::Struct(std::mem::take(fields)),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        unify_struct_fields(&mut group)?;
+        for (&i, unified) in indices.iter().zip(group) {
+            if let (Value::Tagged(_, fields), Value::Struct(unified)) = (&mut values[i], unified) {
+                *fields = unified;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn unify_sequence_items(values: &mut [Value]) -> Result<(), Error> {
+    let is_sequence = |v: &Value| matches!(v, Value::Array(..) | Value::Vec(_));
+    if values.len() < 2 || !values.iter().all(is_sequence) {
+        return Ok(());
+    }
+
+    let mut lengths = vec![];
+    let mut items = vec![];
+    for value in values.iter_mut() {
+        if let Value::Array(_, seq) | Value::Vec(seq) = value {
+            lengths.push(seq.len());
+            items.append(seq);
+        }
+    }
+
+    unify_values(&mut items)?;
+
+    let mut items = items.into_iter();
+    for (value, len) in values.iter_mut().zip(lengths) {
+        if let Value::Array(_, seq) | Value::Vec(seq) = value {
+            seq.extend(items.by_ref().take(len));
         }
     }
 
@@ -132,7 +1181,37 @@ pub(crate) const fn preferred_int(value: i128, preferred: IntSize) -> Value {
     }
 }
 
-pub(crate) fn array_or_vec(seq: Vec<Value>, max_array_size: Option<usize>) -> Value {
+pub(crate) const fn preferred_uint(value: u128, preferred: UIntSize) -> Value {
+    use UIntSize::*;
+    match preferred {
+        USize => Value::USize(value as usize),
+        U8 if value <= (u8::MAX as u128) => Value::U8(value as u8),
+        U8 | U16 if value <= (u16::MAX as u128) => Value::U16(value as u16),
+        U8 | U16 | U32 if value <= (u32::MAX as u128) => Value::U32(value as u32),
+        U8 | U16 | U32 | U64 if value <= (u64::MAX as u128) => Value::U64(value as u64),
+        _ => Value::U128(value),
+    }
+}
+
+/// Infers the type of an integer, preferring unsigned types for
+/// non-negative values if `default_uint_size` is set.
+pub(crate) const fn preferred_integer(value: i128, options: &ParseOptions) -> Value {
+    match options.default_uint_size {
+        Some(preferred) if value >= 0 => preferred_uint(value as u128, preferred),
+        _ => preferred_int(value, options.default_int_size),
+    }
+}
+
+pub(crate) fn array_or_vec(seq: Vec<Value>, options: &ParseOptions) -> Value {
+    let seq = match options.heterogeneous_arrays {
+        HeterogeneousArrayMode::Tagged => tag_structs(seq, &options.tag_key),
+        _ => seq,
+    };
+    if options.heterogeneous_arrays == HeterogeneousArrayMode::Tuple && is_heterogeneous(&seq) {
+        return Value::Tuple(seq);
+    }
+
+    let max_array_size = options.max_array_size;
     if max_array_size.is_some() && seq.len() <= max_array_size.unwrap() {
         Value::Array(seq.len(), seq)
     } else {
@@ -140,27 +1219,324 @@ pub(crate) fn array_or_vec(seq: Vec<Value>, max_array_size: Option<usize>) -> Va
     }
 }
 
+/// Turns the items of a sequence into tagged structs, if every item
+/// is a struct with a string field at the tag key.
+fn tag_structs(seq: Vec<Value>, tag_key: &str) -> Vec<Value> {
+    let is_tagged = |value: &Value| match value {
+        Value::Struct(fields) => matches!(fields.0.get(tag_key), Some(Value::String(_))),
+        _ => false,
+    };
+    if seq.is_empty() || !seq.iter().all(is_tagged) {
+        return seq;
+    }
+    seq.into_iter()
+        .map(|value| match value {
+            Value::Struct(fields) => Value::Tagged(tag_key.to_owned(), fields),
+            value => value,
+        })
+        .collect()
+}
+
+/// Whether the items of a sequence have incompatible types, even
+/// after unification.
+fn is_heterogeneous(seq: &[Value]) -> bool {
+    let mut items = seq.to_vec();
+    if unify_values(&mut items).is_err() {
+        return true;
+    }
+    let sources = (0..items.len()).map(|i| i.to_string()).collect::<Vec<_>>();
+    !find_type_conflicts(sources.iter().map(String::as_str).zip(&items)).is_empty()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::value::Struct;
+
+    #[test]
+    fn source_file_errors_include_path() {
+        let path = Path::new("does/not/exist.yaml");
+        let error = parse_source_file(path, &ParseOptions::new()).unwrap_err();
+        assert_eq!(error.file_path(), Some(path));
+        assert!(error.to_string().contains("does/not/exist.yaml"));
+    }
+
+    #[cfg(feature = "decimal")]
+    #[test]
+    fn decimal_strings() {
+        let mut value = Value::Struct(Struct::from_pairs([
+            ("price", Value::String("19.99".into())),
+            (
+                "rates",
+                Value::Vec(vec![Value::String("-0.0001".into()), Value::F64(0.5)]),
+            ),
+            ("id", Value::String("1234".into())),
+            ("version", Value::String("1.2.3".into())),
+        ]));
+        let options = ParseOptions {
+            decimal_strings: true,
+            ..ParseOptions::new()
+        };
+        convert_decimal_strings(&mut value, &options).unwrap();
+        assert_eq!(
+            value,
+            Value::Struct(Struct::from_pairs([
+                ("price", Value::Decimal("19.99".into())),
+                (
+                    "rates",
+                    Value::Vec(vec![Value::Decimal("-0.0001".into()), Value::F64(0.5)]),
+                ),
+                ("id", Value::String("1234".into())),
+                ("version", Value::String("1.2.3".into())),
+            ]))
+        );
+
+        let mut value = Value::Struct(Struct::from_pairs([(
+            "tiny",
+            Value::String(format!("0.{}1", "0".repeat(28))),
+        )]));
+        let error = convert_decimal_strings(&mut value, &options).unwrap_err();
+        assert!(matches!(error.key_path(), Some(path) if path == "tiny"));
+    }
+
+    #[test]
+    fn char_strings() {
+        let mut value = Value::Struct(Struct::from_pairs([
+            ("symbol", Value::String("é".into())),
+            ("name", Value::String("Acute".into())),
+            (
+                "keys",
+                Value::Vec(vec![Value::String("a".into()), Value::String("b".into())]),
+            ),
+            (
+                "mixed",
+                Value::Vec(vec![Value::String("a".into()), Value::String("bc".into())]),
+            ),
+        ]));
+        let options = ParseOptions {
+            infer_char_from_single_char_strings: true,
+            ..ParseOptions::new()
+        };
+        convert_char_strings(&mut value, &options);
+        unify_value(&mut value).unwrap();
+        assert_eq!(
+            value,
+            Value::Struct(Struct::from_pairs([
+                ("symbol", Value::Char('é')),
+                ("name", Value::String("Acute".into())),
+                ("keys", Value::Vec(vec![Value::Char('a'), Value::Char('b')])),
+                (
+                    "mixed",
+                    Value::Vec(vec![Value::String("a".into()), Value::String("bc".into())]),
+                ),
+            ]))
+        );
+
+        let mut values = vec![
+            Value::Struct(Struct::from_pairs([("key", Value::Char('a'))])),
+            Value::Struct(Struct::from_pairs([("key", Value::String("ab".into()))])),
+        ];
+        unify_values(&mut values).unwrap();
+        assert_eq!(
+            values[0],
+            Value::Struct(Struct::from_pairs([("key", Value::String("a".into()))]))
+        );
+    }
+
+    #[test]
+    fn ignored_keys() {
+        let mut value = Value::Struct(Struct::from_pairs([
+            ("$schema", Value::String("schema.json".into())),
+            (
+                "servers",
+                Value::Vec(vec![Value::Struct(Struct::from_pairs([
+                    ("_comment", Value::String("main".into())),
+                    ("port", Value::I64(80)),
+                    ("vscode_folding", Value::Bool(true)),
+                ]))]),
+            ),
+            (
+                "client",
+                Value::Struct(Struct::from_pairs([
+                    ("_comment", Value::I64(1)),
+                    (
+                        "retry",
+                        Value::Struct(Struct::from_pairs([
+                            ("_note", Value::Unit),
+                            ("count", Value::I64(3)),
+                        ])),
+                    ),
+                ])),
+            ),
+        ]));
+        let options = ParseOptions {
+            ignore_keys: vec!["$schema".into(), "**._*".into(), "servers.vscode_*".into()].into(),
+            ..ParseOptions::new()
+        };
+        remove_ignored_keys(&mut value, &options);
+
+        assert_eq!(
+            value,
+            Value::Struct(Struct::from_pairs([
+                (
+                    "servers",
+                    Value::Vec(vec![Value::Struct(Struct::from_pairs([(
+                        "port",
+                        Value::I64(80)
+                    )]))]),
+                ),
+                (
+                    "client",
+                    Value::Struct(Struct::from_pairs([(
+                        "retry",
+                        Value::Struct(Struct::from_pairs([("count", Value::I64(3))])),
+                    )])),
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn key_patterns() {
+        assert!(key_matches("_*", "_comment"));
+        assert!(key_matches("*_id", "user_id"));
+        assert!(key_matches("a*b*c", "abbbc"));
+        assert!(key_matches("*", ""));
+        assert!(!key_matches("_*", "comment"));
+        assert!(!key_matches("a*c", "abcd"));
+    }
+
+    #[test]
+    fn type_conflicts() {
+        let a = Value::Struct(Struct::from_pairs([
+            ("same", Value::I64(1)),
+            ("different", Value::I64(1)),
+            ("missing", Value::Bool(true)),
+            ("none", Value::Option(None)),
+            ("tuple", Value::Tuple(vec![Value::Bool(true), Value::Unit])),
+        ]));
+        let b = Value::Struct(Struct::from_pairs([
+            ("same", Value::I64(2)),
+            ("different", Value::String("2".into())),
+            ("none", Value::Option(Some(Box::new(Value::I64(2))))),
+            (
+                "tuple",
+                Value::Tuple(vec![Value::Bool(false), Value::Bool(true)]),
+            ),
+        ]));
+        let conflicts = find_type_conflicts([("a", &a), ("b", &b)]);
+        let conflicts = conflicts
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            conflicts,
+            [
+                "field `different` is i64 in a but String in b",
+                "field `tuple.1` is () in a but bool in b",
+            ]
+        );
+
+        let c = Value::Vec(vec![]);
+        let conflicts = find_type_conflicts([("a", &a), ("c", &c)]);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "the root value is a struct in a but a sequence in c"
+        );
+    }
 
     #[test]
     fn max_array_size() {
         let u = Value::Unit;
+        let options = |max_array_size| ParseOptions {
+            max_array_size,
+            ..ParseOptions::new()
+        };
         assert_eq!(
-            array_or_vec(vec![u.clone(); 3], None),
+            array_or_vec(vec![u.clone(); 3], &options(None)),
             Value::Vec(vec![u.clone(); 3])
         );
         assert_eq!(
-            array_or_vec(vec![u.clone(); 3], Some(3)),
+            array_or_vec(vec![u.clone(); 3], &options(Some(3))),
             Value::Array(3, vec![u.clone(); 3])
         );
         assert_eq!(
-            array_or_vec(vec![u.clone(); 4], Some(3)),
+            array_or_vec(vec![u.clone(); 4], &options(Some(3))),
             Value::Vec(vec![u.clone(); 4])
         );
     }
 
+    #[test]
+    fn heterogeneous_arrays() {
+        let options = ParseOptions {
+            heterogeneous_arrays: HeterogeneousArrayMode::Tuple,
+            ..ParseOptions::new()
+        };
+        let mixed = vec![Value::I64(1), Value::String("a".into()), Value::Bool(true)];
+        assert_eq!(
+            array_or_vec(mixed.clone(), &options),
+            Value::Tuple(mixed.clone())
+        );
+        assert_eq!(
+            array_or_vec(mixed.clone(), &ParseOptions::new()),
+            Value::Vec(mixed)
+        );
+
+        let numbers = vec![Value::I64(1), Value::F64(2.5)];
+        assert_eq!(array_or_vec(numbers.clone(), &options), Value::Vec(numbers));
+    }
+
+    #[test]
+    fn tagged_structs() {
+        let options = ParseOptions {
+            heterogeneous_arrays: HeterogeneousArrayMode::Tagged,
+            tag_key: "kind".into(),
+            ..ParseOptions::new()
+        };
+        let tagged = |kind: &str, fields: Vec<(&str, Value)>| {
+            let mut fields = Struct::from_pairs(fields);
+            fields.0.insert("kind".into(), Value::String(kind.into()));
+            fields.0.move_index(fields.0.len() - 1, 0);
+            fields
+        };
+        let items = vec![
+            Value::Struct(tagged("a", vec![("x", Value::I64(1))])),
+            Value::Struct(tagged("b", vec![("x", Value::String("text".into()))])),
+            Value::Struct(tagged(
+                "a",
+                vec![("x", Value::F64(2.5)), ("y", Value::Bool(true))],
+            )),
+        ];
+
+        let mut value = array_or_vec(items.clone(), &options);
+        unify_value(&mut value).unwrap();
+        let tagged_value = |kind, fields| Value::Tagged("kind".into(), tagged(kind, fields));
+        assert_eq!(
+            value,
+            Value::Vec(vec![
+                tagged_value(
+                    "a",
+                    vec![("x", Value::F64(1.0)), ("y", Value::Option(None))]
+                ),
+                tagged_value("b", vec![("x", Value::String("text".into()))]),
+                tagged_value(
+                    "a",
+                    vec![
+                        ("x", Value::F64(2.5)),
+                        ("y", Value::Option(Some(Box::new(Value::Bool(true))))),
+                    ]
+                ),
+            ])
+        );
+
+        let mut untagged = items;
+        untagged.push(Value::Struct(Struct::from_pairs([("x", Value::I64(3))])));
+        assert!(matches!(
+            array_or_vec(untagged, &options),
+            Value::Vec(items) if matches!(items[0], Value::Struct(_))
+        ));
+    }
+
     #[test]
     fn correct_preferred_int() {
         assert_eq!(preferred_int(-1, IntSize::I32), Value::I32(-1));
@@ -169,6 +1545,28 @@ mod tests {
         assert_eq!(preferred_int(65537, IntSize::I16), Value::I32(65537));
     }
 
+    #[test]
+    fn correct_preferred_uint() {
+        assert_eq!(preferred_uint(255, UIntSize::U8), Value::U8(255));
+        assert_eq!(preferred_uint(256, UIntSize::U8), Value::U16(256));
+        assert_eq!(preferred_uint(1, UIntSize::U64), Value::U64(1));
+        assert_eq!(preferred_uint(70000, UIntSize::USize), Value::USize(70000));
+    }
+
+    #[test]
+    fn correct_preferred_integer() {
+        let options = ParseOptions {
+            default_uint_size: Some(UIntSize::U8),
+            ..ParseOptions::new()
+        };
+        assert_eq!(preferred_integer(255, &options), Value::U8(255));
+        assert_eq!(preferred_integer(-1, &options), Value::I64(-1));
+        assert_eq!(
+            preferred_integer(255, &ParseOptions::new()),
+            Value::I64(255)
+        );
+    }
+
     #[test]
     fn correct_preferred_float() {
         assert_eq!(preferred_float(2.5, FloatSize::F32), Value::F32(2.5));
@@ -284,4 +1682,162 @@ mod unify_tests {
             assert_eq!(inputs, expected);
         }
     }
+
+    #[test]
+    fn unify_option_inner_types() {
+        let some = |v| Value::Option(Some(Box::new(v)));
+        let speed = |v| Value::Struct(Struct::from_pairs([("speed", v)]));
+
+        let mut values = vec![
+            speed(Value::Option(None)),
+            speed(Value::F64(1.5)),
+            speed(Value::I64(2)),
+        ];
+        unify_values(&mut values).unwrap();
+        assert_eq!(
+            values,
+            [
+                speed(Value::Option(None)),
+                speed(some(Value::F64(1.5))),
+                speed(some(Value::F64(2.0))),
+            ]
+        );
+
+        let mut values = vec![
+            Value::Option(None),
+            some(Value::Struct(Struct::from_pairs([("a", Value::U8(1))]))),
+            Value::Struct(Struct::from_pairs([("b", Value::Bool(true))])),
+        ];
+        unify_values(&mut values).unwrap();
+        assert_eq!(
+            values,
+            [
+                Value::Option(None),
+                some(Value::Struct(Struct::from_pairs([
+                    ("a", some(Value::U8(1))),
+                    ("b", Value::Option(None)),
+                ]))),
+                some(Value::Struct(Struct::from_pairs([
+                    ("b", some(Value::Bool(true))),
+                    ("a", Value::Option(None)),
+                ]))),
+            ]
+        );
+    }
+
+    #[test]
+    fn unify_missing_struct_fields() {
+        let mut values = vec![
+            Value::Struct(Struct::from_pairs([
+                ("name", Value::String("a".into())),
+                ("speed", Value::I64(1)),
+            ])),
+            Value::Struct(Struct::from_pairs([
+                ("name", Value::String("b".into())),
+                ("color", Value::String("red".into())),
+            ])),
+        ];
+        unify_values(&mut values).unwrap();
+
+        let some = |v| Value::Option(Some(Box::new(v)));
+        assert_eq!(
+            values,
+            vec![
+                Value::Struct(Struct::from_pairs([
+                    ("name", Value::String("a".into())),
+                    ("speed", some(Value::I64(1))),
+                    ("color", Value::Option(None)),
+                ])),
+                Value::Struct(Struct::from_pairs([
+                    ("name", Value::String("b".into())),
+                    ("color", some(Value::String("red".into()))),
+                    ("speed", Value::Option(None)),
+                ])),
+            ]
+        );
+    }
+
+    #[test]
+    fn unify_numeric_types() {
+        let cases = [
+            (
+                vec![Value::I8(1), Value::I16(300)],
+                vec![Value::I16(1), Value::I16(300)],
+            ),
+            (
+                vec![Value::U8(1), Value::I8(-1)],
+                vec![Value::I8(1), Value::I8(-1)],
+            ),
+            (
+                vec![Value::U8(200), Value::I8(-1)],
+                vec![Value::I16(200), Value::I16(-1)],
+            ),
+            (
+                vec![Value::U8(1), Value::U32(70000)],
+                vec![Value::U32(1), Value::U32(70000)],
+            ),
+            (
+                vec![Value::I64(1), Value::F64(2.5)],
+                vec![Value::F64(1.0), Value::F64(2.5)],
+            ),
+            (
+                vec![Value::I8(1), Value::F32(2.5)],
+                vec![Value::F32(1.0), Value::F32(2.5)],
+            ),
+            (
+                vec![Value::I64(1), Value::F32(2.5)],
+                vec![Value::F64(1.0), Value::F64(2.5)],
+            ),
+            (
+                vec![Value::I64(1), Value::String("two".into())],
+                vec![Value::I64(1), Value::String("two".into())],
+            ),
+        ];
+
+        for (mut inputs, expected) in cases {
+            unify_values(&mut inputs).unwrap();
+            assert_eq!(inputs, expected);
+        }
+    }
+
+    #[test]
+    fn unify_numbers_across_structs_and_sequences() {
+        let mut values = vec![
+            Value::Struct(Struct::from_pairs([
+                ("value", Value::I8(1)),
+                ("seq", Value::Vec(vec![Value::I8(1)])),
+            ])),
+            Value::Struct(Struct::from_pairs([
+                ("value", Value::I16(300)),
+                ("seq", Value::Vec(vec![Value::F64(0.5), Value::I8(2)])),
+            ])),
+        ];
+        unify_values(&mut values).unwrap();
+
+        let expected = vec![
+            Value::Struct(Struct::from_pairs([
+                ("value", Value::I16(1)),
+                ("seq", Value::Vec(vec![Value::F64(1.0)])),
+            ])),
+            Value::Struct(Struct::from_pairs([
+                ("value", Value::I16(300)),
+                ("seq", Value::Vec(vec![Value::F64(0.5), Value::F64(2.0)])),
+            ])),
+        ];
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn unify_numbers_without_common_type() {
+        let mut values = vec![
+            Value::Struct(Struct::from_pairs([("value", Value::U128(u128::MAX))])),
+            Value::Struct(Struct::from_pairs([("value", Value::I8(-1))])),
+        ];
+        let error = unify_values(&mut values).unwrap_err();
+        assert!(matches!(
+            &error,
+            Error::AtKey { error, .. } if matches!(**error, Error::IncompatibleNumbers("u128", _))
+        ));
+        assert_eq!(error.key_path(), Some("value"));
+    }
 }
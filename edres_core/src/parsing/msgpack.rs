@@ -0,0 +1,195 @@
+//! This module provides utilities for parsing MessagePack files
+//! to generic `Value`s.
+//!
+//! Unlike the other formats, MessagePack is binary, so files are
+//! read as bytes rather than strings. They are decoded with the
+//! `rmpv` crate.
+
+use rmpv::Value as RawValue;
+
+use crate::{
+    error::Error,
+    options::ParseOptions,
+    parsing,
+    value::{Struct, Value},
+};
+
+/// Parse MessagePack data, producing a generic `Value`.
+pub fn parse_bytes(bytes: &[u8], options: &ParseOptions) -> Result<Value, Error> {
+    let mut result = parse_bytes_non_unified(bytes, options)?;
+    parsing::remove_ignored_keys(&mut result, options);
+    parsing::convert_decimal_strings(&mut result, options)?;
+    parsing::convert_char_strings(&mut result, options);
+    parsing::unify_value(&mut result)?;
+    Ok(result)
+}
+
+/// Parse MessagePack data, producing a generic `Value`.
+///
+/// This bypasses the unification step, so sequences may contain
+/// values of different types. (See [`parsing::unify_values`].)
+pub fn parse_bytes_non_unified(mut bytes: &[u8], options: &ParseOptions) -> Result<Value, Error> {
+    let raw = rmpv::decode::read_value(&mut bytes)?;
+    if !bytes.is_empty() {
+        return Err(Error::UnsupportedMsgpack("trailing bytes after value"));
+    }
+    convert_value(raw, options)
+}
+
+fn convert_value(raw: RawValue, options: &ParseOptions) -> Result<Value, Error> {
+    Ok(match raw {
+        RawValue::Nil => Value::Option(None),
+        RawValue::Boolean(b) => Value::Bool(b),
+        RawValue::Integer(x) => parsing::preferred_integer(integer(x), options),
+        RawValue::F32(x) => parsing::preferred_float(x as f64, options.default_float_size),
+        RawValue::F64(x) => parsing::preferred_float(x, options.default_float_size),
+        RawValue::String(s) => Value::String(
+            s.into_str()
+                .ok_or(Error::UnsupportedMsgpack("string is not valid UTF-8"))?,
+        ),
+        RawValue::Binary(bytes) => parsing::array_or_vec(
+            bytes
+                .into_iter()
+                .map(|byte| parsing::preferred_integer(byte as i128, options))
+                .collect(),
+            options,
+        ),
+        RawValue::Array(values) => {
+            let values = values
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    convert_value(value, options).map_err(|error| error.under_index(i))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            parsing::array_or_vec(values, options)
+        }
+        RawValue::Map(entries) => {
+            let mut fields = Struct::default();
+            for (key, value) in entries {
+                let key = match key {
+                    RawValue::Integer(key) => integer(key).to_string(),
+                    RawValue::String(key) => key
+                        .into_str()
+                        .ok_or(Error::UnsupportedMsgpack("string is not valid UTF-8"))?,
+                    _ => return Err(Error::ExpectedStringKey),
+                };
+                let value = convert_value(value, options).map_err(|error| error.under_key(&key))?;
+                if !fields.0.contains_key(&key)
+                    || parsing::replace_duplicate_key(&key, options.duplicate_keys)?
+                {
+                    fields.0.insert(key, value);
+                }
+            }
+            Value::Struct(fields)
+        }
+        RawValue::Ext(..) => {
+            return Err(Error::UnsupportedMsgpack(
+                "extension types are not supported",
+            ))
+        }
+    })
+}
+
+/// Every MessagePack integer fits in either an `i64` or a `u64`.
+fn integer(x: rmpv::Integer) -> i128 {
+    match (x.as_i64(), x.as_u64()) {
+        (Some(x), _) => x.into(),
+        (None, Some(x)) => x.into(),
+        (None, None) => unreachable!("MessagePack integer out of range"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::UIntSize;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn value_conversion() {
+        #[rustfmt::skip]
+        let bytes = [
+            0x87,
+            0xa4, b'n', b'u', b'l', b'l', 0xc0,
+            0xa4, b'b', b'o', b'o', b'l', 0xc3,
+            0xa6, b's', b'i', b'g', b'n', b'e', b'd', 0xd0, 0x9c,
+            0xa8, b'u', b'n', b's', b'i', b'g', b'n', b'e', b'd', 0xcd, 0x01, 0x00,
+            0xa5, b'f', b'l', b'o', b'a', b't', 0xcb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0,
+            0xa4, b'l', b'i', b's', b't', 0x93, 0x01, 0x02, 0xff,
+            0xa6, b'n', b'e', b's', b't', b'e', b'd', 0x81,
+                0xa4, b't', b'e', b'x', b't', 0xd9, 0x02, b'h', b'i',
+        ];
+
+        let value = parse_bytes(&bytes, &ParseOptions::new()).unwrap();
+        assert_eq!(
+            value,
+            Value::Struct(Struct::from_pairs([
+                ("null", Value::Option(None)),
+                ("bool", Value::Bool(true)),
+                ("signed", Value::I64(-100)),
+                ("unsigned", Value::I64(256)),
+                ("float", Value::F64(1.5)),
+                (
+                    "list",
+                    Value::Vec(vec![Value::I64(1), Value::I64(2), Value::I64(-1)])
+                ),
+                (
+                    "nested",
+                    Value::Struct(Struct::from_pairs([("text", Value::String("hi".into()))]))
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn integer_keys_and_preferences() {
+        let bytes = [0x81, 0x07, 0xcc, 0xff];
+        let options = ParseOptions {
+            default_uint_size: Some(UIntSize::U8),
+            ..ParseOptions::new()
+        };
+        assert_eq!(
+            parse_bytes(&bytes, &options).unwrap(),
+            Value::Struct(Struct::from_pairs([("7", Value::U8(255))]))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let error = parse_bytes(&[0x81, 0xa1, b'a', 0xcd, 0x01], &ParseOptions::new()).unwrap_err();
+        assert!(matches!(error, Error::Msgpack(_)), "{:?}", error);
+
+        let error = parse_bytes(&[0x01, 0x02], &ParseOptions::new()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unsupported MessagePack data: trailing bytes after value"
+        );
+
+        let error =
+            parse_bytes(&[0x81, 0xa1, b'a', 0xd4, 0x01, 0x02], &ParseOptions::new()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unsupported MessagePack data: extension types are not supported (at key `a`)"
+        );
+
+        let error = parse_bytes(&[0x91, 0xa1, 0xff], &ParseOptions::new()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unsupported MessagePack data: string is not valid UTF-8 (at key `[0]`)"
+        );
+
+        let error = parse_bytes(&[0x81, 0xc2, 0x01], &ParseOptions::new()).unwrap_err();
+        assert!(matches!(error, Error::ExpectedStringKey));
+    }
+
+    #[test]
+    fn large_unsigned_integers() {
+        let bytes = [0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
+        assert_eq!(
+            parse_bytes(&bytes, &ParseOptions::new()).unwrap(),
+            Value::I128(u64::MAX.into())
+        );
+    }
+}
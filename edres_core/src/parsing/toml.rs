@@ -1,13 +1,17 @@
 //! This module provides utilities for parsing TOML files
 //! to generic `Value`s.
 
-use toml::{self, Value as TomlValue};
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde::de::{Deserialize, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use toml::{self, Spanned, Value as TomlValue};
 
 use crate::{
     error::Error,
-    options::ParseOptions,
+    options::{DatetimeHandling, ParseOptions},
     parsing,
-    value::{Struct, Value},
+    value::{Datetime, KeyPath, Location, SpannedValue, Struct, Value},
 };
 
 /// Parse TOML source, producing a generic `Value`.
@@ -16,47 +20,184 @@ pub fn parse_source(source: &str, options: &ParseOptions) -> Result<Value, Error
     parse_value(raw_value, options)
 }
 
+/// Parse TOML source, producing a generic `Value` along with the
+/// location of each of its parts.
+///
+/// The locations are found by the `toml` crate, which doesn't track
+/// where the root table starts.
+pub fn parse_source_spanned(source: &str, options: &ParseOptions) -> Result<SpannedValue, Error> {
+    let value = parse_source(source, options)?;
+    let mut locations = IndexMap::new();
+    let tree: SpanTree = toml::from_str(source)?;
+    tree.collect_locations(source, &mut KeyPath::new(), &mut locations);
+    Ok(SpannedValue {
+        value,
+        file_path: None,
+        locations,
+    })
+}
+
 /// Parse a TOML value, producing a generic `Value`.
 pub fn parse_value(raw_value: TomlValue, options: &ParseOptions) -> Result<Value, Error> {
     let mut result = parse_value_non_unified(raw_value, options)?;
+    parsing::remove_ignored_keys(&mut result, options);
+    parsing::convert_decimal_strings(&mut result, options)?;
+    parsing::convert_char_strings(&mut result, options);
     parsing::unify_value(&mut result)?;
     Ok(result)
 }
 
 /// Parse a TOML value, producing a generic `Value`.
 ///
-/// This bypasses the unification step, which currently does
-/// nothing anyway.
+/// This bypasses the unification step, so sequences may contain
+/// values of different types. (See [`parsing::unify_values`].)
 pub fn parse_value_non_unified(
     raw_value: TomlValue,
     options: &ParseOptions,
 ) -> Result<Value, Error> {
     Ok(match raw_value {
         TomlValue::Boolean(value) => Value::Bool(value),
-        TomlValue::Integer(value) => {
-            parsing::preferred_int(value as i128, options.default_int_size)
-        }
+        TomlValue::Integer(value) => parsing::preferred_integer(value as i128, options),
         TomlValue::Float(value) => parsing::preferred_float(value, options.default_float_size),
         TomlValue::String(value) => Value::String(value),
-        TomlValue::Datetime(value) => Value::String(value.to_string()),
+        TomlValue::Datetime(value) => {
+            let value = value.to_string();
+            match options.datetime_handling {
+                DatetimeHandling::String => Value::String(value),
+                handling => {
+                    let datetime = Datetime::parse(&value).ok_or(Error::InvalidDatetime(value))?;
+                    match handling {
+                        #[cfg(feature = "chrono")]
+                        DatetimeHandling::Chrono => Value::Datetime(datetime),
+                        _ => Value::Struct(datetime.into_struct()),
+                    }
+                }
+            }
+        }
         TomlValue::Array(values) => parsing::array_or_vec(
             values
                 .into_iter()
-                .map(|value| parse_value_non_unified(value, options))
+                .enumerate()
+                .map(|(i, value)| {
+                    parse_value_non_unified(value, options).map_err(|error| error.under_index(i))
+                })
                 .collect::<Result<Vec<_>, _>>()?,
-            options.max_array_size,
+            options,
         ),
         TomlValue::Table(values) => Value::Struct(Struct(
             values
                 .into_iter()
-                .map(|(key, value)| {
-                    parse_value_non_unified(value, options).map(|value| (key, value))
-                })
+                .map(
+                    |(key, value)| match parse_value_non_unified(value, options) {
+                        Ok(value) => Ok((key, value)),
+                        Err(error) => Err(error.under_key(&key)),
+                    },
+                )
                 .collect::<Result<_, Error>>()?,
         )),
     })
 }
 
+/// The spans of the values in TOML source.
+enum SpanTree {
+    Scalar,
+    Array(Vec<Spanned<SpanTree>>),
+    Table(Vec<(String, Spanned<SpanTree>)>),
+}
+
+impl SpanTree {
+    fn collect_locations(
+        self,
+        source: &str,
+        key_path: &mut KeyPath,
+        locations: &mut IndexMap<KeyPath, Location>,
+    ) {
+        let mut collect = |key_path: &mut KeyPath, spanned: Spanned<SpanTree>| {
+            locations.insert(
+                key_path.clone(),
+                Location::at_offset(source, spanned.start()),
+            );
+            spanned
+                .into_inner()
+                .collect_locations(source, key_path, locations);
+        };
+        match self {
+            SpanTree::Scalar => (),
+            SpanTree::Array(items) => {
+                for (index, item) in items.into_iter().enumerate() {
+                    key_path.push_index(index);
+                    collect(key_path, item);
+                    key_path.pop();
+                }
+            }
+            SpanTree::Table(fields) => {
+                for (key, field) in fields {
+                    key_path.push_key(key);
+                    collect(key_path, field);
+                    key_path.pop();
+                }
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SpanTree {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(SpanTreeVisitor)
+    }
+}
+
+struct SpanTreeVisitor;
+
+impl<'de> Visitor<'de> for SpanTreeVisitor {
+    type Value = SpanTree;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "any TOML value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<SpanTree, E> {
+        Ok(SpanTree::Scalar)
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<SpanTree, E> {
+        Ok(SpanTree::Scalar)
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<SpanTree, E> {
+        Ok(SpanTree::Scalar)
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<SpanTree, E> {
+        Ok(SpanTree::Scalar)
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<SpanTree, E> {
+        Ok(SpanTree::Scalar)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<SpanTree, A::Error> {
+        let mut items = vec![];
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(SpanTree::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<SpanTree, A::Error> {
+        let mut fields = vec![];
+        while let Some(key) = map.next_key::<String>()? {
+            // Datetimes are deserialized as a map with a private key.
+            if key.starts_with("$__toml_private") {
+                map.next_value::<IgnoredAny>()?;
+                return Ok(SpanTree::Scalar);
+            }
+            fields.push((key, map.next_value()?));
+        }
+        Ok(SpanTree::Table(fields))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,4 +247,65 @@ mod tests {
 
         assert_eq!(value, expected);
     }
+
+    #[test]
+    fn datetime_structs() {
+        let toml_source = r#"
+            local_date = 1979-05-27
+            offset_datetime = 1979-05-27T00:32:00.5-07:00
+        "#;
+        let options = ParseOptions {
+            datetime_handling: DatetimeHandling::Struct,
+            ..ParseOptions::new()
+        };
+
+        let expected = Value::Struct(Struct::from_pairs([
+            (
+                "local_date",
+                Value::Struct(Struct::from_pairs([
+                    ("year", Value::I32(1979)),
+                    ("month", Value::U8(5)),
+                    ("day", Value::U8(27)),
+                ])),
+            ),
+            (
+                "offset_datetime",
+                Value::Struct(Struct::from_pairs([
+                    ("year", Value::I32(1979)),
+                    ("month", Value::U8(5)),
+                    ("day", Value::U8(27)),
+                    ("hour", Value::U8(0)),
+                    ("minute", Value::U8(32)),
+                    ("second", Value::U8(0)),
+                    ("nanosecond", Value::U32(500_000_000)),
+                    ("offset_minutes", Value::I16(-420)),
+                ])),
+            ),
+        ]));
+
+        assert_eq!(parse_source(toml_source, &options).unwrap(), expected);
+    }
+
+    #[test]
+    fn locations() {
+        let source =
+            "name = 'x'\nlist = [1, { a = true }]\nwhen = 1979-05-27\n\n[server]\nport = 80\n";
+        let location = |line, column| Some(Location { line, column });
+        let key_path = |key_path| KeyPath::parse(key_path).unwrap();
+
+        let spanned = parse_source_spanned(source, &ParseOptions::new()).unwrap();
+        assert_eq!(
+            spanned.value,
+            parse_source(source, &ParseOptions::new()).unwrap()
+        );
+        assert_eq!(spanned.location(&KeyPath::new()), None);
+        assert_eq!(spanned.location(&key_path("name")), location(1, 8));
+        assert_eq!(spanned.location(&key_path("list[0]")), location(2, 9));
+        assert_eq!(spanned.location(&key_path("list[1].a")), location(2, 18));
+        assert_eq!(spanned.location(&key_path("when")), location(3, 8));
+        assert_eq!(spanned.location(&key_path("server.port")), location(6, 8));
+
+        let error = Error::ExpectedStringKey.under_index(1).under_key("list");
+        assert_eq!(spanned.error_location(&error), location(2, 12));
+    }
 }
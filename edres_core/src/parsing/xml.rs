@@ -0,0 +1,209 @@
+//! This module provides utilities for parsing XML files
+//! to generic `Value`s.
+//!
+//! XML does not map onto `Value`s as directly as the other
+//! formats, so the following rules are used:
+//!
+//! 1.  The root element of the document becomes the root value.
+//! 2.  An element with attributes or child elements becomes a
+//!     struct. Attributes become fields (named with the
+//!     [`ParseOptions::xml_attribute_prefix`]), as do child
+//!     elements.
+//! 3.  A child element which appears more than once becomes a
+//!     sequence. (One which appears once does not.)
+//! 4.  Any text inside an element with attributes or child
+//!     elements is stored in a `text` field.
+//! 5.  Text (in an element with no attributes or children, or in
+//!     an attribute) is inferred to be a `bool`, number, or string.
+//!     An empty element is `None`.
+
+use indexmap::IndexMap;
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::{
+    error::Error,
+    options::ParseOptions,
+    parsing,
+    value::{Struct, Value},
+};
+
+/// Parse XML source, producing a generic `Value`.
+pub fn parse_source(source: &str, options: &ParseOptions) -> Result<Value, Error> {
+    let mut result = parse_source_non_unified(source, options)?;
+    parsing::remove_ignored_keys(&mut result, options);
+    parsing::convert_decimal_strings(&mut result, options)?;
+    parsing::convert_char_strings(&mut result, options);
+    parsing::unify_value(&mut result)?;
+    Ok(result)
+}
+
+/// Parse XML source, producing a generic `Value`.
+///
+/// This bypasses the unification step, so sequences may contain
+/// values of different types. (See [`parsing::unify_values`].)
+pub fn parse_source_non_unified(source: &str, options: &ParseOptions) -> Result<Value, Error> {
+    let mut reader = Reader::from_str(source);
+    reader.config_mut().trim_text(true);
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(start) => return parse_element(&mut reader, &start, false, options),
+            Event::Empty(start) => return parse_element(&mut reader, &start, true, options),
+            Event::Eof => return Ok(Value::Option(None)),
+            _ => (),
+        }
+    }
+}
+
+fn parse_element(
+    reader: &mut Reader<&[u8]>,
+    start: &BytesStart,
+    empty: bool,
+    options: &ParseOptions,
+) -> Result<Value, Error> {
+    let mut fields: IndexMap<String, Vec<Value>> = IndexMap::new();
+    let mut text = String::new();
+
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        let key = format!(
+            "{}{}",
+            options.xml_attribute_prefix,
+            String::from_utf8_lossy(attribute.key.as_ref())
+        );
+        let value = parse_text(&attribute.unescape_value()?, options);
+        fields.entry(key).or_default().push(value);
+    }
+
+    if !empty {
+        loop {
+            let (child, empty) = match reader.read_event()? {
+                Event::Start(child) => (child, false),
+                Event::Empty(child) => (child, true),
+                Event::Text(content) => {
+                    text.push_str(&content.unescape()?);
+                    continue;
+                }
+                Event::CData(content) => {
+                    text.push_str(&String::from_utf8_lossy(&content));
+                    continue;
+                }
+                Event::End(_) | Event::Eof => break,
+                _ => continue,
+            };
+            let key = String::from_utf8_lossy(child.name().as_ref()).into_owned();
+            let value = parse_element(reader, &child, empty, options)
+                .map_err(|error| error.under_key(&key))?;
+            fields.entry(key).or_default().push(value);
+        }
+    }
+
+    if fields.is_empty() {
+        return Ok(match text.is_empty() {
+            true => Value::Option(None),
+            false => parse_text(&text, options),
+        });
+    }
+    if !text.is_empty() {
+        fields
+            .entry("text".into())
+            .or_default()
+            .push(parse_text(&text, options));
+    }
+
+    Ok(Value::Struct(Struct(
+        fields
+            .into_iter()
+            .map(|(key, mut values)| {
+                let value = match values.len() {
+                    1 => values.pop().unwrap(),
+                    _ => parsing::array_or_vec(values, options),
+                };
+                (key, value)
+            })
+            .collect(),
+    )))
+}
+
+fn parse_text(text: &str, options: &ParseOptions) -> Value {
+    if let Ok(value) = text.parse::<bool>() {
+        Value::Bool(value)
+    } else if let Ok(value) = text.parse::<i128>() {
+        parsing::preferred_integer(value, options)
+    } else if let Some(value) = text.parse::<f64>().ok().filter(|x| x.is_finite()) {
+        parsing::preferred_float(value, options.default_float_size)
+    } else {
+        Value::String(text.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn value_conversion() {
+        let xml_source = r#"
+            <?xml version="1.0"?>
+            <root name="root" enabled="true">
+                <empty/>
+                <number>-100</number>
+                <float>2.5</float>
+                <string>hello &amp; goodbye</string>
+                <item id="1">First</item>
+                <item id="2">Second</item>
+            </root>
+        "#;
+
+        let item = |id, text: &str| {
+            Value::Struct(Struct::from_pairs([
+                ("id", Value::I64(id)),
+                ("text", Value::String(text.into())),
+            ]))
+        };
+        let expected = Value::Struct(Struct::from_pairs([
+            ("name", Value::String("root".into())),
+            ("enabled", Value::Bool(true)),
+            ("empty", Value::Option(None)),
+            ("number", Value::I64(-100)),
+            ("float", Value::F64(2.5)),
+            ("string", Value::String("hello & goodbye".into())),
+            (
+                "item",
+                Value::Vec(vec![item(1, "First"), item(2, "Second")]),
+            ),
+        ]));
+
+        let value = parse_source_non_unified(xml_source, &Default::default()).unwrap();
+
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn attribute_prefix() {
+        let xml_source = r#"<item id="1"><id>one</id></item>"#;
+        let options = ParseOptions {
+            xml_attribute_prefix: "attr_".into(),
+            ..ParseOptions::new()
+        };
+
+        let expected = Value::Struct(Struct::from_pairs([
+            ("attr_id", Value::I64(1)),
+            ("id", Value::String("one".into())),
+        ]));
+
+        assert_eq!(parse_source(xml_source, &options).unwrap(), expected);
+    }
+
+    #[test]
+    fn error_key_path() {
+        let xml_source = r#"<root><items><item a="1" a="2"/></items></root>"#;
+        let error = parse_source(xml_source, &Default::default()).unwrap_err();
+        assert_eq!(error.key_path(), Some("items.item"));
+    }
+}
@@ -1,32 +1,57 @@
 //! This module provides utilities for parsing YAML files
 //! to generic `Value`s.
 
-use serde_yaml::{self, Value as YamlValue};
+use std::fmt;
+
+use serde::de::{DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor};
+use serde_yaml::{
+    self,
+    value::{Tag, TaggedValue},
+    Deserializer, Mapping, Value as YamlValue,
+};
 
 use crate::{
     error::Error,
     options::ParseOptions,
-    parsing,
+    parsing::{self, DuplicateKeys},
     value::{Struct, Value},
 };
 
 /// Parse YAML source, producing a generic `Value`.
+///
+/// If [`ParseOptions::yaml_multi_doc`] is enabled, the result is a
+/// sequence containing each document in the source. Duplicate keys
+/// are handled following [`ParseOptions::duplicate_keys`].
 pub fn parse_source(source: &str, options: &ParseOptions) -> Result<Value, Error> {
-    let raw_value: YamlValue = serde_yaml::from_str(source)?;
-    parse_value(raw_value, options)
+    let mut documents = DuplicateKeys::deserialize(options.duplicate_keys, |duplicates| {
+        Deserializer::from_str(source)
+            .map(|document| RawValue(duplicates).deserialize(document))
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+
+    if options.yaml_multi_doc {
+        parse_value(YamlValue::Sequence(documents), options)
+    } else if documents.len() > 1 {
+        Err(Error::MultipleYamlDocuments(documents.len()))
+    } else {
+        parse_value(documents.pop().unwrap_or(YamlValue::Null), options)
+    }
 }
 
 /// Parse a YAML value, producing a generic `Value`.
 pub fn parse_value(raw_value: YamlValue, options: &ParseOptions) -> Result<Value, Error> {
     let mut result = parse_value_non_unified(raw_value, options)?;
+    parsing::remove_ignored_keys(&mut result, options);
+    parsing::convert_decimal_strings(&mut result, options)?;
+    parsing::convert_char_strings(&mut result, options);
     parsing::unify_value(&mut result)?;
     Ok(result)
 }
 
 /// Parse a YAML value, producing a generic `Value`.
 ///
-/// This bypasses the unification step, which currently does
-/// nothing anyway.
+/// This bypasses the unification step, so sequences may contain
+/// values of different types. (See [`parsing::unify_values`].)
 pub fn parse_value_non_unified(
     raw_value: YamlValue,
     options: &ParseOptions,
@@ -38,35 +63,169 @@ pub fn parse_value_non_unified(
             if value.is_f64() {
                 parsing::preferred_float(value.as_f64().unwrap(), options.default_float_size)
             } else if value.is_i64() {
-                parsing::preferred_int(value.as_i64().unwrap() as i128, options.default_int_size)
+                parsing::preferred_integer(value.as_i64().unwrap() as i128, options)
             } else {
-                parsing::preferred_int(value.as_u64().unwrap() as i128, options.default_int_size)
+                parsing::preferred_integer(value.as_u64().unwrap() as i128, options)
             }
         }
         YamlValue::String(value) => Value::String(value),
         YamlValue::Sequence(values) => parsing::array_or_vec(
             values
                 .into_iter()
-                .map(|value| parse_value_non_unified(value, options))
+                .enumerate()
+                .map(|(i, value)| {
+                    parse_value_non_unified(value, options).map_err(|error| error.under_index(i))
+                })
                 .collect::<Result<Vec<_>, _>>()?,
-            options.max_array_size,
+            options,
         ),
-        YamlValue::Mapping(values) => Value::Struct(Struct(
-            values
-                .into_iter()
-                .map(|(key, value)| {
-                    let key = key.as_str().ok_or(Error::ExpectedStringKey)?.to_owned();
-                    parse_value_non_unified(value, options).map(|value| (key, value))
-                })
-                .collect::<Result<_, Error>>()?,
-        )),
-        YamlValue::Tagged(tagged_value) => parse_value_non_unified(tagged_value.value, options)?,
+        YamlValue::Mapping(values) => {
+            let mut fields = Struct::default();
+            for (key, value) in values {
+                let key = match key {
+                    YamlValue::String(key) => key,
+                    YamlValue::Number(key) if !key.is_f64() => key.to_string(),
+                    _ => return Err(Error::ExpectedStringKey),
+                };
+                let value = parse_value_non_unified(value, options)
+                    .map_err(|error| error.under_key(&key))?;
+                if !fields.0.contains_key(&key)
+                    || parsing::replace_duplicate_key(&key, options.duplicate_keys)?
+                {
+                    fields.0.insert(key, value);
+                }
+            }
+            Value::Struct(fields)
+        }
+        YamlValue::Tagged(tagged_value) => match (&options.include_directive, tagged_value.value) {
+            (Some(directive), YamlValue::String(path))
+                if tagged_value.tag == directive.as_ref() =>
+            {
+                Value::String(format!("{} {}", directive, path))
+            }
+            (_, value) => parse_value_non_unified(value, options)?,
+        },
     })
 }
 
+/// Deserializes a `serde_yaml::Value`, keeping track of duplicate
+/// keys in mappings.
+///
+/// Unlike deserializing a `serde_yaml::Value` directly, this allows
+/// duplicate keys (if the policy does).
+#[derive(Clone, Copy)]
+struct RawValue<'a>(DuplicateKeys<'a>);
+
+impl<'de> DeserializeSeed<'de> for RawValue<'_> {
+    type Value = YamlValue;
+
+    fn deserialize<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<YamlValue, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for RawValue<'_> {
+    type Value = YamlValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any YAML value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<YamlValue, E> {
+        Ok(YamlValue::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<YamlValue, E> {
+        Ok(YamlValue::Number(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<YamlValue, E> {
+        Ok(YamlValue::Number(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<YamlValue, E> {
+        Ok(YamlValue::Number(value.into()))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<YamlValue, E> {
+        Ok(YamlValue::String(value.to_owned()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<YamlValue, E> {
+        Ok(YamlValue::String(value))
+    }
+
+    fn visit_unit<E>(self) -> Result<YamlValue, E> {
+        Ok(YamlValue::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<YamlValue, E> {
+        Ok(YamlValue::Null)
+    }
+
+    fn visit_some<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<YamlValue, D::Error> {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<YamlValue, A::Error> {
+        let mut values = vec![];
+        loop {
+            match seq.next_element_seed(self) {
+                Ok(Some(value)) => values.push(value),
+                Ok(None) => return Ok(YamlValue::Sequence(values)),
+                Err(error) => return Err(self.0.under_index(error, values.len())),
+            }
+        }
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<YamlValue, A::Error> {
+        let mut values = Mapping::new();
+        while let Some(key) = map.next_key_seed(self)? {
+            let name = key_name(&key);
+            let value = map
+                .next_value_seed(self)
+                .map_err(|error| self.0.under_key(error, &name))?;
+            if !values.contains_key(&key) || self.0.replace(&name)? {
+                values.insert(key, value);
+            }
+        }
+        Ok(YamlValue::Mapping(values))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<YamlValue, A::Error> {
+        let (tag, contents) = data.variant::<String>()?;
+        if tag.is_empty() {
+            return Err(serde::de::Error::custom("empty YAML tag is not allowed"));
+        }
+        let value = contents.newtype_variant_seed(self)?;
+        Ok(YamlValue::Tagged(Box::new(TaggedValue {
+            tag: Tag::new(tag),
+            value,
+        })))
+    }
+}
+
+/// The name of a mapping key, as used in key paths.
+fn key_name(key: &YamlValue) -> String {
+    match key {
+        YamlValue::String(key) => key.clone(),
+        YamlValue::Number(key) => key.to_string(),
+        key => serde_yaml::to_string(key)
+            .map(|key| key.trim_end().to_owned())
+            .unwrap_or_default(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::DuplicateKeyPolicy;
 
     use pretty_assertions::assert_eq;
 
@@ -117,4 +276,109 @@ mod tests {
 
         assert_eq!(value, expected);
     }
+
+    #[test]
+    fn error_key_path() {
+        let yaml_source = r#"
+            root:
+                servers:
+                  - port: 80
+                  - 1.5: 2
+            "#;
+
+        let error = parse_source(yaml_source, &ParseOptions::new()).unwrap_err();
+        assert!(matches!(
+            &error,
+            Error::AtKey { error, .. } if matches!(**error, Error::ExpectedStringKey)
+        ));
+        assert_eq!(error.key_path(), Some("root.servers[1]"));
+    }
+
+    #[test]
+    fn integer_keys() {
+        let yaml_source = "404: Not Found\n500: Internal Server Error\n";
+
+        let expected = Value::Struct(Struct(
+            [
+                ("404".into(), Value::String("Not Found".into())),
+                ("500".into(), Value::String("Internal Server Error".into())),
+            ]
+            .into_iter()
+            .collect(),
+        ));
+
+        assert_eq!(
+            parse_source(yaml_source, &ParseOptions::new()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn multiple_documents() {
+        let yaml_source = "number: 1\n---\nnumber: 2\n";
+
+        let expected = Value::Vec(vec![
+            Value::Struct(Struct(
+                [("number".into(), Value::I64(1))].into_iter().collect(),
+            )),
+            Value::Struct(Struct(
+                [("number".into(), Value::I64(2))].into_iter().collect(),
+            )),
+        ]);
+
+        let options = ParseOptions {
+            yaml_multi_doc: true,
+            ..ParseOptions::new()
+        };
+        assert_eq!(parse_source(yaml_source, &options).unwrap(), expected);
+
+        assert!(matches!(
+            parse_source(yaml_source, &ParseOptions::new()),
+            Err(Error::MultipleYamlDocuments(2))
+        ));
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let yaml_source = "
+            a: 1
+            1: first
+            b: !Nice
+              c: 2
+              c: 3
+            '1': second
+        ";
+        let parse = |duplicate_keys| {
+            parse_source(
+                yaml_source,
+                &ParseOptions {
+                    duplicate_keys,
+                    ..ParseOptions::new()
+                },
+            )
+        };
+        let expected = |one: &str, c| {
+            Value::Struct(Struct::from_pairs([
+                ("a", Value::I64(1)),
+                ("1", Value::String(one.into())),
+                (
+                    "b",
+                    Value::Struct(Struct::from_pairs([("c", Value::I64(c))])),
+                ),
+            ]))
+        };
+
+        assert_eq!(
+            parse(DuplicateKeyPolicy::LastWins).unwrap(),
+            expected("second", 3)
+        );
+        assert_eq!(
+            parse(DuplicateKeyPolicy::FirstWins).unwrap(),
+            expected("first", 2)
+        );
+        assert_eq!(
+            parse(DuplicateKeyPolicy::Error).unwrap_err().to_string(),
+            "Duplicate key `c` (see `ParseOptions::duplicate_keys`) (at key `b`)"
+        );
+    }
 }
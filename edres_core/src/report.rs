@@ -0,0 +1,293 @@
+//! Contains the `GenerationReport` type, which lists non-fatal
+//! problems noticed while generating code.
+
+use std::borrow::Cow;
+
+use thiserror::Error as ErrorTrait;
+
+use crate::{
+    codegen,
+    options::{FloatSize, Options, ParseOptions},
+    parsing,
+    value::{Struct, Value},
+};
+
+/// A non-fatal problem with generated code, which might make its
+/// types surprising.
+///
+/// Key paths are the ones used by the options (like
+/// [`StructOptions::field_renames`]), so items of sequences share the
+/// key path of the sequence.
+///
+/// [`StructOptions::field_renames`]: crate::options::StructOptions::field_renames
+#[derive(Debug, Clone, PartialEq, ErrorTrait)]
+pub enum Warning {
+    #[error("`{key_path}`: {value} can't be represented exactly as `{type_name}`")]
+    LossyNumber {
+        key_path: String,
+        value: String,
+        type_name: &'static str,
+    },
+
+    #[error("`{key_path}`: Key was skipped (see `ParseOptions::ignore_keys`)")]
+    SkippedKey { key_path: String },
+
+    #[error("`{key_path}`: Field was renamed to `{name}`")]
+    RenamedField { key_path: String, name: String },
+
+    #[error("`{key_path}`: Sequence is empty, so its items have the type `()`")]
+    EmptySequence { key_path: String },
+}
+
+/// The warnings for some generated code.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GenerationReport {
+    pub warnings: Vec<Warning>,
+}
+
+impl GenerationReport {
+    /// The options to parse a source with to get the `raw` value
+    /// for [`GenerationReport::for_value`]: no keys are ignored and
+    /// floats are never narrowed.
+    pub fn raw_parse_options(options: &ParseOptions) -> ParseOptions {
+        ParseOptions {
+            ignore_keys: Cow::Borrowed(&[]),
+            default_float_size: FloatSize::F64,
+            ..options.clone()
+        }
+    }
+
+    /// Checks the `value` code is being generated from, against the
+    /// `raw` value parsed from the same source with
+    /// [`GenerationReport::raw_parse_options`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use edres_core::{options::*, report::*, value::*};
+    /// let raw = Value::Struct(Struct::from_pairs([("type", Value::Vec(vec![]))]));
+    /// let report = GenerationReport::for_value(&raw, &raw, &Options::minimal());
+    /// assert_eq!(report.warnings, [
+    ///     Warning::RenamedField { key_path: "type".into(), name: "r#type".into() },
+    ///     Warning::EmptySequence { key_path: "type".into() },
+    /// ]);
+    /// ```
+    pub fn for_value(raw: &Value, value: &Value, options: &Options) -> Self {
+        let mut warnings = vec![];
+        check_value(raw, value, "", options, &mut warnings);
+        GenerationReport { warnings }
+    }
+
+    /// Whether there are no warnings.
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    /// Prints each warning as a `cargo:warning=` line, so that cargo
+    /// shows it when run from a build script.
+    pub fn print_cargo_warnings(&self) {
+        for warning in &self.warnings {
+            println!("cargo:warning={}", warning);
+        }
+    }
+}
+
+fn check_value(
+    raw: &Value,
+    value: &Value,
+    key_path: &str,
+    options: &Options,
+    warnings: &mut Vec<Warning>,
+) {
+    if let Some(converted) = converted_number(value, key_path, options) {
+        if is_lossy(raw, &converted) {
+            warnings.push(Warning::LossyNumber {
+                key_path: key_path.to_owned(),
+                value: number_string(raw),
+                type_name: converted.type_name(),
+            });
+        }
+        return;
+    }
+
+    match (raw, value) {
+        (Value::Option(Some(raw)), Value::Option(Some(value))) => {
+            check_value(raw, value, key_path, options, warnings)
+        }
+        (
+            Value::Array(_, raws) | Value::Vec(raws),
+            Value::Array(_, values) | Value::Vec(values),
+        ) => {
+            if values.is_empty() && codegen::type_override(key_path, options).is_none() {
+                warnings.push(Warning::EmptySequence {
+                    key_path: key_path.to_owned(),
+                });
+            }
+            for (raw, value) in raws.iter().zip(values) {
+                check_value(raw, value, key_path, options, warnings);
+            }
+        }
+        (Value::Tuple(raws), Value::Tuple(values)) => {
+            for (i, (raw, value)) in raws.iter().zip(values).enumerate() {
+                let key_path = codegen::child_path(key_path, &i.to_string());
+                check_value(raw, value, &key_path, options, warnings);
+            }
+        }
+        (Value::Struct(raws), Value::Struct(values)) if codegen::keep_as_map(key_path, options) => {
+            let key_path = codegen::child_path(key_path, "*");
+            for (key, raw) in &raws.0 {
+                if let Some(value) = values.0.get(key) {
+                    check_value(raw, value, &key_path, options, warnings);
+                }
+            }
+        }
+        (Value::Struct(raws), Value::Struct(values)) => {
+            check_fields(raws, values, key_path, None, options, warnings)
+        }
+        (Value::Tagged(_, raws), Value::Tagged(tag_key, values)) => {
+            let key_path = codegen::child_path(key_path, value.tag().unwrap_or_default());
+            check_fields(raws, values, &key_path, Some(tag_key), options, warnings)
+        }
+        _ => (),
+    }
+}
+
+fn check_fields(
+    raws: &Struct,
+    values: &Struct,
+    key_path: &str,
+    tag_key: Option<&str>,
+    options: &Options,
+    warnings: &mut Vec<Warning>,
+) {
+    for (key, raw) in &raws.0 {
+        let key_path = codegen::child_path(key_path, key);
+        let Some(value) = values.0.get(key) else {
+            warnings.push(Warning::SkippedKey { key_path });
+            continue;
+        };
+        if tag_key == Some(key) {
+            continue;
+        }
+        let name = codegen::field_name(key, &key_path, options).to_string();
+        if name != *key {
+            warnings.push(Warning::RenamedField {
+                key_path: key_path.clone(),
+                name,
+            });
+        }
+        check_value(raw, value, &key_path, options, warnings);
+    }
+}
+
+/// The number a value is converted to in generated code, if it is
+/// a number. This takes [`ParseOptions::type_overrides`] into
+/// account.
+fn converted_number(value: &Value, key_path: &str, options: &Options) -> Option<Value> {
+    parsing::number_as_f64(value)?;
+    match codegen::type_override(key_path, options) {
+        Some(type_name) if codegen::is_numeric_type(type_name) => {
+            parsing::cast_number(value, type_name)
+        }
+        _ => Some(value.clone()),
+    }
+}
+
+fn is_lossy(raw: &Value, converted: &Value) -> bool {
+    let Some(converted_f64) = parsing::number_as_f64(converted) else {
+        return false;
+    };
+    match (
+        parsing::number_as_i128(raw),
+        parsing::number_as_i128(converted),
+    ) {
+        (Some(x), Some(y)) => x != y,
+        (Some(x), None) => converted_f64 as i128 != x,
+        (None, _) => match parsing::number_as_f64(raw) {
+            Some(x) if x.is_nan() => !converted_f64.is_nan(),
+            Some(x) => x != converted_f64,
+            None => false,
+        },
+    }
+}
+
+fn number_string(value: &Value) -> String {
+    match (
+        parsing::number_as_i128(value),
+        parsing::number_as_f64(value),
+    ) {
+        (Some(x), _) => x.to_string(),
+        (None, Some(x)) => x.to_string(),
+        (None, None) => value.type_name().to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn warnings() {
+        let raw = Value::Struct(Struct::from_pairs([
+            ("ratio", Value::F64(0.1)),
+            ("half", Value::F64(0.5)),
+            ("secret", Value::String("hunter2".into())),
+            ("big", Value::I64((1 << 53) + 1)),
+            (
+                "nested",
+                Value::Struct(Struct::from_pairs([("self", Value::Vec(vec![]))])),
+            ),
+        ]));
+        let value = Value::Struct(Struct::from_pairs([
+            ("ratio", Value::F32(0.1)),
+            ("half", Value::F32(0.5)),
+            ("big", Value::I64((1 << 53) + 1)),
+            (
+                "nested",
+                Value::Struct(Struct::from_pairs([("self", Value::Vec(vec![]))])),
+            ),
+        ]));
+        let options = Options {
+            parse: ParseOptions {
+                type_overrides: vec![("big".into(), "f64".into())].into(),
+                ..ParseOptions::new()
+            },
+            ..Options::minimal()
+        };
+
+        let report = GenerationReport::for_value(&raw, &value, &options);
+        assert_eq!(
+            report.warnings,
+            [
+                Warning::LossyNumber {
+                    key_path: "ratio".into(),
+                    value: "0.1".into(),
+                    type_name: "f32",
+                },
+                Warning::SkippedKey {
+                    key_path: "secret".into(),
+                },
+                Warning::LossyNumber {
+                    key_path: "big".into(),
+                    value: "9007199254740993".into(),
+                    type_name: "f64",
+                },
+                Warning::RenamedField {
+                    key_path: "nested.self".into(),
+                    name: "self_".into(),
+                },
+                Warning::EmptySequence {
+                    key_path: "nested.self".into(),
+                },
+            ]
+        );
+        assert_eq!(
+            report.warnings[3].to_string(),
+            "`nested.self`: Field was renamed to `self_`"
+        );
+
+        let report = GenerationReport::for_value(&value, &value, &Options::minimal());
+        assert_eq!(report.warnings.len(), 2);
+    }
+}
@@ -0,0 +1,285 @@
+//! This module checks the names given in [`Options`] before any
+//! code is generated.
+//!
+//! Names like [`StructOptions::struct_data_const_name`] become
+//! identifiers in the generated code, so they must be valid Rust
+//! identifiers. Checking them up front means a typo results in an
+//! [`Error::InvalidOptions`] naming the option, instead of a panic
+//! partway through generation.
+
+use std::borrow::Cow;
+
+use crate::{
+    error::Error,
+    options::{Casing, Options, StructOptions},
+};
+
+const NOT_AN_IDENTIFIER: &str = "expected a valid Rust identifier";
+const NOT_A_TRAIT_PATH: &str = "expected a trait name, optionally prefixed by a crate name";
+const NOT_A_SEPARATOR: &str = "expected only letters, digits, and underscores";
+const NOT_AN_IDENTIFIER_CASING: &str = "expected a casing which produces valid Rust identifiers";
+const NOT_A_TYPE_PATH: &str = "expected a type path, like `crate::config::Config`";
+
+/// Check that every name in the options can be used in generated
+/// code.
+///
+/// # Examples
+/// ```
+/// # use edres_core::{validation, Error, Options, StructOptions};
+/// let options = Options {
+///     structs: StructOptions {
+///         struct_data_const_name: Some("MY-DATA".into()),
+///         ..StructOptions::minimal()
+///     },
+///     ..Options::minimal()
+/// };
+///
+/// let error = validation::validate_options(&options).unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "Invalid value `MY-DATA` for option `structs.struct_data_const_name`: \
+///     expected a valid Rust identifier"
+/// );
+/// ```
+pub fn validate_options(options: &Options) -> Result<(), Error> {
+    identifier("source_path_const_name", &options.source_path_const_name)?;
+
+    validate_struct_options("structs", &options.structs)?;
+
+    let enums = &options.enums;
+    trait_paths("enums.derived_traits", &enums.derived_traits)?;
+    if enums.variant_casing == Some(Casing::Kebab) {
+        return Err(invalid(
+            "enums.variant_casing",
+            "kebab",
+            NOT_AN_IDENTIFIER_CASING,
+        ));
+    }
+    for derive in enums.strum_derives.iter() {
+        identifier("enums.strum_derives", &Some(derive.clone()))?;
+    }
+    identifier(
+        "enums.all_variants_const_name",
+        &enums.all_variants_const_name,
+    )?;
+    identifier("enums.all_values_const_name", &enums.all_values_const_name)?;
+    identifier("enums.get_value_fn_name", &enums.get_value_fn_name)?;
+    identifier("enums.owned_value_fn_name", &enums.owned_value_fn_name)?;
+    identifier("enums.value_map_fn_name", &enums.value_map_fn_name)?;
+    identifier("enums.static_str_fn_name", &enums.static_str_fn_name)?;
+    #[cfg(feature = "phf")]
+    identifier("enums.key_map_const_name", &enums.key_map_const_name)?;
+    if let Some(integer_keys) = &enums.integer_keys {
+        // Variants are named like `Status404`, so the prefix must be
+        // an identifier on its own.
+        identifier(
+            "enums.integer_keys.variant_prefix",
+            &Some(integer_keys.variant_prefix.clone()),
+        )?;
+        identifier(
+            "enums.integer_keys.code_fn_name",
+            &Some(integer_keys.code_fn_name.clone()),
+        )?;
+    }
+    if let Some(values_struct) = &enums.values_struct {
+        identifier(
+            "enums.values_struct.struct_name",
+            &values_struct.struct_name,
+        )?;
+        validate_struct_options(
+            "enums.values_struct.struct_options",
+            &values_struct.struct_options,
+        )?;
+    }
+
+    let files = &options.files;
+    identifier("files.file_paths_const_name", &files.file_paths_const_name)?;
+    identifier("files.get_path_fn_name", &files.get_path_fn_name)?;
+    identifier(
+        "files.file_strings_const_name",
+        &files.file_strings_const_name,
+    )?;
+    identifier("files.get_string_fn_name", &files.get_string_fn_name)?;
+    identifier("files.file_bytes_const_name", &files.file_bytes_const_name)?;
+    identifier("files.get_bytes_fn_name", &files.get_bytes_fn_name)?;
+    identifier("files.file_stems_const_name", &files.file_stems_const_name)?;
+    identifier("files.get_stem_fn_name", &files.get_stem_fn_name)?;
+    identifier(
+        "files.file_extensions_const_name",
+        &files.file_extensions_const_name,
+    )?;
+    identifier("files.get_extension_fn_name", &files.get_extension_fn_name)?;
+    identifier("files.load_fn_name", &files.load_fn_name)?;
+    #[cfg(feature = "bevy")]
+    if let Some(bevy) = &files.bevy {
+        identifier(
+            "files.bevy.handle_path_fn_name",
+            &Some(bevy.handle_path_fn_name.clone()),
+        )?;
+        identifier("files.bevy.preload_plugin_name", &bevy.preload_plugin_name)?;
+    }
+
+    trait_paths("flags.derived_traits", &options.flags.derived_traits)?;
+
+    identifier("output.wrap_in_module", &options.output.wrap_in_module)?;
+
+    Ok(())
+}
+
+fn validate_struct_options(prefix: &str, options: &StructOptions) -> Result<(), Error> {
+    trait_paths(
+        &format!("{}.derived_traits", prefix),
+        &options.derived_traits,
+    )?;
+    if let Some(nested_derived_traits) = &options.nested_derived_traits {
+        trait_paths(
+            &format!("{}.nested_derived_traits", prefix),
+            nested_derived_traits,
+        )?;
+    }
+    identifier(
+        &format!("{}.struct_data_const_name", prefix),
+        &options.struct_data_const_name,
+    )?;
+    identifier(
+        &format!("{}.validate_fn_name", prefix),
+        &options.validate_fn_name,
+    )?;
+    for (_, name) in options.tuple_structs.iter() {
+        identifier(&format!("{}.tuple_structs", prefix), &Some(name.clone()))?;
+    }
+    for (_, name) in options.field_renames.iter() {
+        identifier(&format!("{}.field_renames", prefix), &Some(name.clone()))?;
+    }
+    type_path(&format!("{}.convert_into", prefix), &options.convert_into)?;
+    let separator = &options.nested_name_separator;
+    if !separator.chars().all(|c| c == '_' || c.is_alphanumeric()) {
+        return Err(invalid(
+            &format!("{}.nested_name_separator", prefix),
+            separator,
+            NOT_A_SEPARATOR,
+        ));
+    }
+    Ok(())
+}
+
+fn identifier(field: &str, value: &Option<Cow<'static, str>>) -> Result<(), Error> {
+    match value {
+        Some(value) if !is_identifier(value) => Err(invalid(field, value, NOT_AN_IDENTIFIER)),
+        _ => Ok(()),
+    }
+}
+
+fn trait_paths(field: &str, values: &[Cow<'static, str>]) -> Result<(), Error> {
+    for value in values {
+        let valid = match value.split_once("::") {
+            Some((crate_name, trait_name)) => {
+                is_identifier(crate_name) && is_identifier(trait_name)
+            }
+            None => is_identifier(value),
+        };
+        if !valid {
+            return Err(invalid(field, value, NOT_A_TRAIT_PATH));
+        }
+    }
+    Ok(())
+}
+
+fn type_path(field: &str, value: &Option<Cow<'static, str>>) -> Result<(), Error> {
+    match value {
+        Some(value) => {
+            let path = value.strip_prefix("::").unwrap_or(value);
+            match path.split("::").all(is_identifier) {
+                true => Ok(()),
+                false => Err(invalid(field, value, NOT_A_TYPE_PATH)),
+            }
+        }
+        None => Ok(()),
+    }
+}
+
+fn invalid(field: &str, value: &str, reason: &'static str) -> Error {
+    Error::InvalidOptions {
+        field: field.to_owned(),
+        value: value.to_owned(),
+        reason,
+    }
+}
+
+/// Whether `name` can be used as an identifier (or a raw
+/// identifier, like `r#type`).
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let name = name.strip_prefix("r#").unwrap_or(name);
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first == '_' || first.is_alphabetic() => {
+            chars.all(|c| c == '_' || c.is_alphanumeric())
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{EnumOptions, ValuesStructOptions};
+
+    #[test]
+    fn identifiers() {
+        assert!(is_identifier("DATA"));
+        assert!(is_identifier("_get"));
+        assert!(is_identifier("r#type"));
+        assert!(is_identifier("données"));
+        assert!(!is_identifier(""));
+        assert!(!is_identifier("1st"));
+        assert!(!is_identifier("my-data"));
+        assert!(!is_identifier("get value"));
+    }
+
+    #[test]
+    fn default_options_are_valid() {
+        assert!(validate_options(&Options::new()).is_ok());
+        assert!(validate_options(&Options::minimal()).is_ok());
+    }
+
+    #[test]
+    fn invalid_nested_options() {
+        let options = Options {
+            enums: EnumOptions {
+                values_struct: Some(ValuesStructOptions {
+                    struct_options: StructOptions {
+                        derived_traits: vec!["serde::Serialize".into(), "Partial Eq".into()].into(),
+                        ..StructOptions::minimal()
+                    },
+                    ..ValuesStructOptions::minimal()
+                }),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+
+        assert!(matches!(
+            validate_options(&options),
+            Err(Error::InvalidOptions { field, value, reason })
+                if field == "enums.values_struct.struct_options.derived_traits"
+                    && value == "Partial Eq"
+                    && reason == NOT_A_TRAIT_PATH
+        ));
+    }
+
+    #[test]
+    fn kebab_variant_casing() {
+        let options = Options {
+            enums: EnumOptions {
+                variant_casing: Some(Casing::Kebab),
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        };
+
+        assert!(matches!(
+            validate_options(&options),
+            Err(Error::InvalidOptions { field, .. }) if field == "enums.variant_casing"
+        ));
+    }
+}
@@ -1,10 +1,13 @@
 //! Contains the `Value` type - a markup-agnostic generic value.
 
+use std::path::PathBuf;
+
 use indexmap::IndexMap;
 
 use crate::error::Error;
 
-/// A key-value object for representing both maps and structs.
+/// A key-value object for representing structs, whose keys are
+/// field names.
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct Struct(pub(crate) IndexMap<String, Value>);
 
@@ -37,8 +40,67 @@ impl Struct {
     }
 }
 
-/// A type alias for `Struct`.
-pub type Map = Struct;
+/// An ordered key-value map, like a file whose keys become the
+/// variants of an enum.
+///
+/// Unlike the fields of a [`Struct`], the keys of a `Map` don't
+/// need to be valid identifiers. Markup files are always parsed to
+/// structs, which can be converted to maps with [`From`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Map(pub(crate) IndexMap<String, Value>);
+
+impl Map {
+    /// Convenience method for creating a `Map` directly.
+    ///
+    /// # Examples
+    /// ```
+    /// # use edres_core::value::*;
+    /// Map::from_pairs([
+    ///     ("first key", Value::I32(1)),
+    ///     ("second key", Value::I32(2)),
+    /// ]);
+    /// ```
+    pub fn from_pairs<S: Into<String>, V: Into<Value>>(
+        pairs: impl IntoIterator<Item = (S, V)>,
+    ) -> Self {
+        Map(pairs
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect())
+    }
+
+    /// The entries of this map, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.0.iter().map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// The number of entries in this map.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether this map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The value with the given key, if there is one.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0.get(key)
+    }
+}
+
+impl From<Struct> for Map {
+    fn from(fields: Struct) -> Self {
+        Map(fields.0)
+    }
+}
+
+impl From<Map> for Struct {
+    fn from(map: Map) -> Self {
+        Struct(map.0)
+    }
+}
 
 /// A generic value.
 #[derive(Debug, Clone, PartialEq)]
@@ -66,43 +128,794 @@ pub enum Value {
     Array(usize, Vec<Value>),
     Vec(Vec<Value>),
     Struct(Struct),
+
+    /// One of a sequence of structs which each have a tag field
+    /// (see [`HeterogeneousArrayMode::Tagged`]), which becomes a
+    /// variant of an enum named after its tag.
+    ///
+    /// This holds the key of the tag field, and all of the fields
+    /// (including the tag itself).
+    ///
+    /// [`HeterogeneousArrayMode::Tagged`]: crate::options::HeterogeneousArrayMode::Tagged
+    Tagged(String, Struct),
+    Datetime(Datetime),
+
+    /// An exact decimal number, as written in the source (see
+    /// [`ParseOptions::decimal_strings`]).
+    ///
+    /// [`ParseOptions::decimal_strings`]: crate::options::ParseOptions
+    Decimal(String),
+}
+
+/// A path to a value nested within another value, like
+/// `servers[2].port`.
+///
+/// This is the same syntax used for the key paths of errors (see
+/// [`Error::key_path`]).
+///
+/// # Examples
+/// ```
+/// # use edres_core::value::*;
+/// let key_path: KeyPath = "servers[2].port".parse().unwrap();
+/// assert_eq!(key_path.segments(), &[
+///     KeySegment::Key("servers".into()),
+///     KeySegment::Index(2),
+///     KeySegment::Key("port".into()),
+/// ]);
+/// assert_eq!(key_path.to_string(), "servers[2].port");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct KeyPath(Vec<KeySegment>);
+
+/// A single step of a [`KeyPath`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KeySegment {
+    /// A field of a struct (or map).
+    Key(String),
+
+    /// An item of a sequence (or tuple).
+    Index(usize),
+}
+
+impl KeyPath {
+    /// An empty key path, which refers to the root value.
+    pub const fn new() -> Self {
+        KeyPath(Vec::new())
+    }
+
+    /// Parse a key path, like `servers[2].port`.
+    ///
+    /// An empty string is the empty key path.
+    pub fn parse(key_path: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidKeyPath(key_path.to_owned());
+        let mut segments = vec![];
+        if key_path.is_empty() {
+            return Ok(KeyPath(segments));
+        }
+
+        for (i, part) in key_path.split('.').enumerate() {
+            let (key, mut indices) = match part.find('[') {
+                Some(start) => part.split_at(start),
+                None => (part, ""),
+            };
+            if key.is_empty() && (i > 0 || indices.is_empty()) {
+                return Err(invalid());
+            }
+            if !key.is_empty() {
+                segments.push(KeySegment::Key(key.to_owned()));
+            }
+            while !indices.is_empty() {
+                let (index, rest) = indices
+                    .strip_prefix('[')
+                    .and_then(|indices| indices.split_once(']'))
+                    .ok_or_else(invalid)?;
+                segments.push(KeySegment::Index(index.parse().map_err(|_| invalid())?));
+                indices = rest;
+            }
+        }
+        Ok(KeyPath(segments))
+    }
+
+    /// The steps of this key path, from the root value.
+    pub fn segments(&self) -> &[KeySegment] {
+        &self.0
+    }
+
+    /// Whether this key path refers to the root value.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Extend this key path with a struct field.
+    pub fn push_key<S: Into<String>>(&mut self, key: S) {
+        self.0.push(KeySegment::Key(key.into()));
+    }
+
+    /// Extend this key path with a sequence item.
+    pub fn push_index(&mut self, index: usize) {
+        self.0.push(KeySegment::Index(index));
+    }
+
+    /// Remove the last step of this key path, returning it.
+    pub fn pop(&mut self) -> Option<KeySegment> {
+        self.0.pop()
+    }
+}
+
+impl std::str::FromStr for KeyPath {
+    type Err = Error;
+
+    fn from_str(key_path: &str) -> Result<Self, Error> {
+        KeyPath::parse(key_path)
+    }
+}
+
+impl std::fmt::Display for KeyPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                KeySegment::Key(key) if i == 0 => write!(f, "{}", key)?,
+                KeySegment::Key(key) => write!(f, ".{}", key)?,
+                KeySegment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A position in a source file. Lines and columns count from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    /// The location of a byte offset within some source.
+    pub fn at_offset(source: &str, offset: usize) -> Self {
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Location {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A [`Value`], along with where it came from.
+///
+/// The locations of values are only known for formats whose parsers
+/// track them (currently only TOML). For other formats,
+/// `locations` is empty.
+///
+/// # Examples
+/// ```
+/// # use edres_core::{options::ParseOptions, parsing, value::*};
+/// # #[cfg(feature = "toml")] {
+/// let source = "name = 'edres'\n\n[[servers]]\nport = 80\n";
+/// let spanned = parsing::toml::parse_source_spanned(source, &ParseOptions::new()).unwrap();
+///
+/// let key_path = KeyPath::parse("servers[0].port").unwrap();
+/// assert_eq!(
+///     spanned.location(&key_path),
+///     Some(Location { line: 4, column: 8 })
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedValue {
+    /// The value itself.
+    pub value: Value,
+
+    /// The file the value was parsed from, if any.
+    pub file_path: Option<PathBuf>,
+
+    /// Where each part of the value starts, by key path.
+    pub locations: IndexMap<KeyPath, Location>,
+}
+
+impl SpannedValue {
+    /// A value with no known provenance.
+    pub fn new(value: Value) -> Self {
+        SpannedValue {
+            value,
+            file_path: None,
+            locations: IndexMap::new(),
+        }
+    }
+
+    /// Where the value at a key path starts.
+    ///
+    /// If that value's location isn't known (for example because it
+    /// was included from another file), this is the location of its
+    /// closest parent which is known.
+    pub fn location(&self, key_path: &KeyPath) -> Option<Location> {
+        let mut key_path = key_path.clone();
+        loop {
+            if let Some(&location) = self.locations.get(&key_path) {
+                return Some(location);
+            }
+            key_path.pop()?;
+        }
+    }
+
+    /// Where the value that caused an error starts, if the error has
+    /// a key path (see [`Error::key_path`]) within this value.
+    pub fn error_location(&self, error: &Error) -> Option<Location> {
+        let key_path = KeyPath::parse(error.key_path()?).ok()?;
+        self.location(&key_path)
+    }
+
+    /// Records the file this value came from in an error about it,
+    /// along with where the value that caused it starts.
+    ///
+    /// Errors which already have a file (like errors from included
+    /// files) are left as they are.
+    pub fn locate_error(&self, error: Error) -> Error {
+        match &self.file_path {
+            Some(path) => {
+                let location = self.error_location(&error);
+                error.in_file_at(path, location)
+            }
+            None => error,
+        }
+    }
+}
+
+/// Controls how [`Value::merge`] combines two values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergeStrategy {
+    /// How two structs (or maps) are combined.
+    pub maps: MapMerge,
+
+    /// How two sequences are combined.
+    pub sequences: SequenceMerge,
+
+    /// How any other two values are combined - including values
+    /// of different kinds, like a struct and a number.
+    pub scalars: ScalarMerge,
+}
+
+impl MergeStrategy {
+    /// # Examples
+    /// ```
+    /// # use edres_core::value::*;
+    /// assert_eq!(MergeStrategy::new(), MergeStrategy {
+    ///     maps: MapMerge::Union,
+    ///     sequences: SequenceMerge::Replace,
+    ///     scalars: ScalarMerge::Override,
+    /// });
+    /// ```
+    pub const fn new() -> MergeStrategy {
+        MergeStrategy {
+            maps: MapMerge::Union,
+            sequences: SequenceMerge::Replace,
+            scalars: ScalarMerge::Override,
+        }
+    }
+}
+
+impl Default for MergeStrategy {
+    fn default() -> Self {
+        MergeStrategy::new()
+    }
+}
+
+/// How [`Value::merge`] combines two structs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapMerge {
+    /// Fields in both are merged, and fields only in the other
+    /// struct are added after the existing ones.
+    Union,
+
+    /// The other struct replaces this one.
+    Override,
+}
+
+/// How [`Value::merge`] combines two sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceMerge {
+    /// The other sequence replaces this one.
+    Replace,
+
+    /// The items of the other sequence are appended to this one.
+    Append,
+
+    /// Items at the same index are merged, and any extra items of
+    /// the other sequence are appended.
+    ByIndex,
+}
+
+/// How [`Value::merge`] combines two values which aren't both
+/// structs or both sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarMerge {
+    /// The other value replaces this one.
+    Override,
+
+    /// This value is kept, so the other value only fills in
+    /// missing fields.
+    Keep,
+}
+
+/// A date and/or time, with an optional UTC offset - like those
+/// found in TOML.
+///
+/// Dates are `(year, month, day)` and times are `(hour, minute,
+/// second, nanosecond)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Datetime {
+    pub date: Option<(i32, u32, u32)>,
+    pub time: Option<(u32, u32, u32, u32)>,
+    pub offset_minutes: Option<i32>,
+}
+
+impl Datetime {
+    /// Parse an RFC 3339 style date and/or time, like
+    /// `1979-05-27T07:32:00-08:00`, `1979-05-27`, or `07:32:00.5`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use edres_core::value::*;
+    /// assert_eq!(
+    ///     Datetime::parse("1979-05-27T07:32:00.5Z"),
+    ///     Some(Datetime {
+    ///         date: Some((1979, 5, 27)),
+    ///         time: Some((7, 32, 0, 500_000_000)),
+    ///         offset_minutes: Some(0),
+    ///     }),
+    /// );
+    /// assert_eq!(Datetime::parse("not a date"), None);
+    /// ```
+    pub fn parse(source: &str) -> Option<Datetime> {
+        let number = |s: &str| s.parse::<u32>().ok();
+        let mut rest = source;
+
+        let mut date = None;
+        if rest.len() >= 10 && rest.as_bytes()[4] == b'-' {
+            let year = rest[0..4].parse().ok()?;
+            let month = number(rest.get(5..7)?)?;
+            let day = number(rest.get(8..10)?)?;
+            date = Some((year, month, day));
+            rest = &rest[10..];
+            if rest.is_empty() {
+                return Some(Datetime {
+                    date,
+                    time: None,
+                    offset_minutes: None,
+                });
+            }
+            rest = rest.strip_prefix(['T', 't', ' '])?;
+        }
+
+        let hour = number(rest.get(0..2)?)?;
+        let minute = number(rest.get(3..5)?)?;
+        let second = number(rest.get(6..8)?)?;
+        if rest.as_bytes()[2] != b':' || rest.as_bytes()[5] != b':' {
+            return None;
+        }
+        rest = &rest[8..];
+
+        let mut nanosecond = 0;
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let digits = fraction.len()
+                - fraction
+                    .trim_start_matches(|c: char| c.is_ascii_digit())
+                    .len();
+            if digits == 0 {
+                return None;
+            }
+            let padded = format!("{:0<9}", &fraction[..digits.min(9)]);
+            nanosecond = number(&padded)?;
+            rest = &fraction[digits..];
+        }
+
+        let offset_minutes = match rest {
+            "" => None,
+            "Z" | "z" => Some(0),
+            _ => {
+                let sign = match rest.as_bytes()[0] {
+                    b'+' => 1,
+                    b'-' => -1,
+                    _ => return None,
+                };
+                let hours = number(rest.get(1..3)?)? as i32;
+                let minutes = number(rest.get(4..6)?)? as i32;
+                if rest.len() != 6 || rest.as_bytes()[3] != b':' {
+                    return None;
+                }
+                Some(sign * (hours * 60 + minutes))
+            }
+        };
+        if offset_minutes.is_some() && date.is_none() {
+            return None;
+        }
+
+        Some(Datetime {
+            date,
+            time: Some((hour, minute, second, nanosecond)),
+            offset_minutes,
+        })
+    }
+
+    /// Convert this datetime to a struct with a field for each
+    /// component that is present.
+    pub fn into_struct(self) -> Struct {
+        let mut fields = vec![];
+        if let Some((year, month, day)) = self.date {
+            fields.push(("year", Value::I32(year)));
+            fields.push(("month", Value::U8(month as u8)));
+            fields.push(("day", Value::U8(day as u8)));
+        }
+        if let Some((hour, minute, second, nanosecond)) = self.time {
+            fields.push(("hour", Value::U8(hour as u8)));
+            fields.push(("minute", Value::U8(minute as u8)));
+            fields.push(("second", Value::U8(second as u8)));
+            fields.push(("nanosecond", Value::U32(nanosecond)));
+        }
+        if let Some(offset_minutes) = self.offset_minutes {
+            fields.push(("offset_minutes", Value::I16(offset_minutes as i16)));
+        }
+        Struct::from_pairs(fields)
+    }
+
+    /// The UTC datetime a number of seconds after the Unix epoch
+    /// (`1970-01-01T00:00:00Z`).
+    ///
+    /// # Examples
+    /// ```
+    /// # use edres_core::value::*;
+    /// assert_eq!(
+    ///     Datetime::from_unix_timestamp(296638320),
+    ///     Datetime::parse("1979-05-27T07:32:00Z").unwrap(),
+    /// );
+    /// ```
+    pub fn from_unix_timestamp(seconds: i64) -> Datetime {
+        let time_of_day = seconds.rem_euclid(86400);
+        Datetime {
+            date: Some(civil_from_days(seconds.div_euclid(86400))),
+            time: Some((
+                (time_of_day / 3600) as u32,
+                (time_of_day % 3600 / 60) as u32,
+                (time_of_day % 60) as u32,
+                0,
+            )),
+            offset_minutes: Some(0),
+        }
+    }
+
+    /// The same instant, converted to UTC (with no offset).
+    pub(crate) fn to_utc(self) -> Datetime {
+        let (Some((year, month, day)), Some((hour, minute, second, nanosecond)), Some(offset)) =
+            (self.date, self.time, self.offset_minutes)
+        else {
+            return self;
+        };
+
+        let days = days_from_civil(year as i64, month as i64, day as i64);
+        let seconds = days * 86400 + (hour as i64) * 3600 + (minute as i64) * 60 + second as i64
+            - (offset as i64) * 60;
+        let mut utc = Datetime::from_unix_timestamp(seconds);
+        if let Some(time) = &mut utc.time {
+            time.3 = nanosecond;
+        }
+        utc
+    }
+}
+
+impl std::fmt::Display for Datetime {
+    /// Formats the datetime in the same RFC 3339 style accepted by
+    /// [`Datetime::parse`].
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some((year, month, day)) = self.date {
+            write!(f, "{:04}-{:02}-{:02}", year, month, day)?;
+            if self.time.is_some() {
+                write!(f, "T")?;
+            }
+        }
+        if let Some((hour, minute, second, nanosecond)) = self.time {
+            write!(f, "{:02}:{:02}:{:02}", hour, minute, second)?;
+            if nanosecond != 0 {
+                let fraction = format!("{:09}", nanosecond);
+                write!(f, ".{}", fraction.trim_end_matches('0'))?;
+            }
+        }
+        match self.offset_minutes {
+            None => Ok(()),
+            Some(0) => write!(f, "Z"),
+            Some(offset) => {
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.abs();
+                write!(f, "{}{:02}:{:02}", sign, offset / 60, offset % 60)
+            }
+        }
+    }
+}
+
+// Conversions between dates and days since 1970-01-01, using
+// Howard Hinnant's algorithms.
+
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * (m + if m > 2 { -3 } else { 9 }) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(z: i64) -> (i32, u32, u32) {
+    let z = z + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y as i32, m as u32, d as u32)
 }
 
 impl Value {
     pub fn assume_struct(self) -> Result<Struct, Error> {
         match self {
             Value::Struct(s) => Ok(s),
-            x => {
-                let tag = match x {
-                    Value::Unit => "()",
-                    Value::Bool(_) => "bool",
-                    Value::Char(_) => "char",
-                    Value::I8(_) => "i8",
-                    Value::I16(_) => "i16",
-                    Value::I32(_) => "i32",
-                    Value::I64(_) => "i64",
-                    Value::I128(_) => "i128",
-                    Value::ISize(_) => "isize",
-                    Value::U8(_) => "u8",
-                    Value::U16(_) => "u16",
-                    Value::U32(_) => "u32",
-                    Value::U64(_) => "u64",
-                    Value::U128(_) => "u128",
-                    Value::USize(_) => "usize",
-                    Value::F32(_) => "f32",
-                    Value::F64(_) => "f64",
-                    Value::String(_) => "String",
-                    Value::Option(_) => "Option",
-                    Value::Tuple(_) => "tuple",
-                    Value::Array(..) => "array",
-                    Value::Vec(_) => "Vec",
-                    Value::Struct(_) => unreachable!(),
+            x => Err(Error::ExpectedStruct(x.type_name())),
+        }
+    }
+
+    pub fn assume_map(self) -> Result<Map, Error> {
+        self.assume_struct().map(Map::from)
+    }
+
+    /// A short description of the type of this value, like `i64`
+    /// or `Vec`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Unit => "()",
+            Value::Bool(_) => "bool",
+            Value::Char(_) => "char",
+            Value::I8(_) => "i8",
+            Value::I16(_) => "i16",
+            Value::I32(_) => "i32",
+            Value::I64(_) => "i64",
+            Value::I128(_) => "i128",
+            Value::ISize(_) => "isize",
+            Value::U8(_) => "u8",
+            Value::U16(_) => "u16",
+            Value::U32(_) => "u32",
+            Value::U64(_) => "u64",
+            Value::U128(_) => "u128",
+            Value::USize(_) => "usize",
+            Value::F32(_) => "f32",
+            Value::F64(_) => "f64",
+            Value::String(_) => "String",
+            Value::Option(_) => "Option",
+            Value::Tuple(_) => "tuple",
+            Value::Array(..) => "array",
+            Value::Vec(_) => "Vec",
+            Value::Struct(_) => "struct",
+            Value::Tagged(..) => "tagged struct",
+            Value::Datetime(_) => "datetime",
+            Value::Decimal(_) => "decimal",
+        }
+    }
+
+    /// Take the value at the given key path (like `server.limits`)
+    /// out of this value.
+    ///
+    /// An empty key path refers to the value itself.
+    ///
+    /// # Examples
+    /// ```
+    /// # use edres_core::value::*;
+    /// let value = Value::Struct(Struct::from_pairs([(
+    ///     "server",
+    ///     Value::Struct(Struct::from_pairs([("port", Value::I64(80))])),
+    /// )]));
+    ///
+    /// assert_eq!(value.clone().into_key_path("server.port").unwrap(), Value::I64(80));
+    /// assert!(value.into_key_path("server.host").is_err());
+    /// ```
+    pub fn into_key_path(self, key_path: &str) -> Result<Value, Error> {
+        let key_path = KeyPath::parse(key_path)?;
+        let mut value = self;
+        let mut path = KeyPath::new();
+        for segment in key_path.segments() {
+            value = match (segment, value.without_option()) {
+                (KeySegment::Key(key), Value::Struct(mut fields))
+                | (KeySegment::Key(key), Value::Tagged(_, mut fields)) => {
+                    path.push_key(key);
+                    match fields.0.shift_remove(key) {
+                        Some(value) => value,
+                        None => {
+                            let available = fields.0.keys().map(String::as_str).collect::<Vec<_>>();
+                            return Err(Error::KeyNotFound(path.to_string(), available.join(", ")));
+                        }
+                    }
+                }
+                (KeySegment::Index(index), Value::Vec(mut items))
+                | (KeySegment::Index(index), Value::Array(_, mut items))
+                | (KeySegment::Index(index), Value::Tuple(mut items)) => {
+                    path.push_index(*index);
+                    if *index >= items.len() {
+                        return Err(Error::IndexOutOfBounds(path.to_string(), items.len()));
+                    }
+                    items.swap_remove(*index)
+                }
+                (KeySegment::Key(_), value) => {
+                    return Err(Error::ExpectedStruct(value.type_name()))
+                }
+                (KeySegment::Index(_), value) => {
+                    return Err(Error::ExpectedSequence(value.type_name()))
+                }
+            };
+        }
+        Ok(value)
+    }
+
+    /// The value at the given key path within this value, if there
+    /// is one.
+    ///
+    /// Optional values (which are `Some`) are looked through.
+    ///
+    /// # Examples
+    /// ```
+    /// # use edres_core::value::*;
+    /// let value = Value::Struct(Struct::from_pairs([(
+    ///     "servers",
+    ///     Value::Vec(vec![Value::Struct(Struct::from_pairs([("port", Value::I64(80))]))]),
+    /// )]));
+    ///
+    /// let key_path = "servers[0].port".parse().unwrap();
+    /// assert_eq!(value.get_path(&key_path), Some(&Value::I64(80)));
+    ///
+    /// let key_path = "servers[1].port".parse().unwrap();
+    /// assert_eq!(value.get_path(&key_path), None);
+    /// ```
+    pub fn get_path(&self, key_path: &KeyPath) -> Option<&Value> {
+        let mut value = self;
+        for segment in key_path.segments() {
+            while let Value::Option(Some(inner)) = value {
+                value = inner;
+            }
+            value = match (segment, value) {
+                (KeySegment::Key(key), Value::Struct(fields))
+                | (KeySegment::Key(key), Value::Tagged(_, fields)) => fields.0.get(key)?,
+                (KeySegment::Index(index), Value::Vec(items))
+                | (KeySegment::Index(index), Value::Array(_, items))
+                | (KeySegment::Index(index), Value::Tuple(items)) => items.get(*index)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    /// Like [`Value::get_path`], but returns a mutable reference.
+    pub fn get_path_mut(&mut self, key_path: &KeyPath) -> Option<&mut Value> {
+        let mut value = self;
+        for segment in key_path.segments() {
+            while let Value::Option(Some(inner)) = value {
+                value = inner;
+            }
+            value = match (segment, value) {
+                (KeySegment::Key(key), Value::Struct(fields))
+                | (KeySegment::Key(key), Value::Tagged(_, fields)) => fields.0.get_mut(key)?,
+                (KeySegment::Index(index), Value::Vec(items))
+                | (KeySegment::Index(index), Value::Array(_, items))
+                | (KeySegment::Index(index), Value::Tuple(items)) => items.get_mut(*index)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    /// Deep-merge `other` into this value, according to the given
+    /// strategy.
+    ///
+    /// Two `Some` values are merged by merging their contents.
+    ///
+    /// # Examples
+    /// ```
+    /// # use edres_core::value::*;
+    /// let mut value = Value::Struct(Struct::from_pairs([
+    ///     ("name", Value::String("base".into())),
+    ///     ("tags", Value::Vec(vec![Value::String("a".into())])),
+    /// ]));
+    /// let other = Value::Struct(Struct::from_pairs([
+    ///     ("tags", Value::Vec(vec![Value::String("b".into())])),
+    ///     ("extra", Value::Bool(true)),
+    /// ]));
+    ///
+    /// value.merge(other, MergeStrategy {
+    ///     sequences: SequenceMerge::Append,
+    ///     ..MergeStrategy::new()
+    /// });
+    ///
+    /// assert_eq!(value, Value::Struct(Struct::from_pairs([
+    ///     ("name", Value::String("base".into())),
+    ///     ("tags", Value::Vec(vec![Value::String("a".into()), Value::String("b".into())])),
+    ///     ("extra", Value::Bool(true)),
+    /// ])));
+    /// ```
+    pub fn merge(&mut self, other: Value, strategy: MergeStrategy) {
+        match (self, other) {
+            (Value::Struct(fields), Value::Struct(other)) if strategy.maps == MapMerge::Union => {
+                for (key, value) in other.0 {
+                    match fields.0.get_mut(&key) {
+                        Some(existing) => existing.merge(value, strategy),
+                        None => {
+                            fields.0.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (Value::Struct(fields), Value::Struct(other)) => *fields = other,
+            (Value::Option(Some(inner)), Value::Option(Some(other))) => {
+                inner.merge(*other, strategy)
+            }
+            (
+                this @ (Value::Vec(_) | Value::Array(..) | Value::Tuple(_)),
+                other @ (Value::Vec(_) | Value::Array(..) | Value::Tuple(_)),
+            ) => {
+                let other = match other {
+                    Value::Vec(items) | Value::Array(_, items) | Value::Tuple(items) => items,
+                    _ => unreachable!(),
                 };
-                Err(Error::ExpectedStruct(tag))
+                let (items, len) = match this {
+                    Value::Vec(items) | Value::Tuple(items) => (items, None),
+                    Value::Array(len, items) => (items, Some(len)),
+                    _ => unreachable!(),
+                };
+                match strategy.sequences {
+                    SequenceMerge::Replace => *items = other,
+                    SequenceMerge::Append => items.extend(other),
+                    SequenceMerge::ByIndex => {
+                        let mut other = other.into_iter();
+                        for (item, other) in items.iter_mut().zip(other.by_ref()) {
+                            item.merge(other, strategy);
+                        }
+                        items.extend(other);
+                    }
+                }
+                if let Some(len) = len {
+                    *len = items.len();
+                }
+            }
+            (this, other) => {
+                if strategy.scalars == ScalarMerge::Override {
+                    *this = other;
+                }
             }
         }
     }
 
+    /// This value, or the value inside it if it is a `Some`.
+    fn without_option(self) -> Value {
+        match self {
+            Value::Option(Some(inner)) => inner.without_option(),
+            value => value,
+        }
+    }
+
+    /// The tag of a tagged struct, if this is one.
+    pub(crate) fn tag(&self) -> Option<&str> {
+        match self {
+            Value::Tagged(key, fields) => match fields.0.get(key) {
+                Some(Value::String(tag)) => Some(tag),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     pub(crate) fn wrap_in_option(&mut self) {
         if !matches!(self, Value::Option(_)) {
             let contents = std::mem::replace(self, Value::Unit);
@@ -111,6 +924,199 @@ impl Value {
     }
 }
 
+/// Write a generic `Value` back out as pretty-printed JSON.
+///
+/// Units and `None`s are written as `null`, and chars, datetimes
+/// and decimals as strings.
+///
+/// # Examples
+/// ```
+/// # use edres_core::value::*;
+/// let value = Value::Struct(Struct::from_pairs([
+///     ("name", Value::String("Ferris".into())),
+///     ("legs", Value::U8(10)),
+/// ]));
+/// assert_eq!(
+///     to_json_string(&value).unwrap(),
+///     "{\n  \"name\": \"Ferris\",\n  \"legs\": 10\n}",
+/// );
+/// ```
+#[cfg(feature = "json")]
+pub fn to_json_string(value: &Value) -> Result<String, Error> {
+    Ok(serde_json::to_string_pretty(&json_value(value)?)?)
+}
+
+#[cfg(feature = "json")]
+fn json_value(value: &Value) -> Result<serde_json::Value, Error> {
+    use serde_json::{Number, Value as JsonValue};
+
+    let unwritable = |what| Error::UnwritableValue(crate::format::Format::Json, what);
+    Ok(match value {
+        Value::Unit | Value::Option(None) => JsonValue::Null,
+        Value::Option(Some(inner)) => json_value(inner)?,
+        Value::Bool(x) => JsonValue::Bool(*x),
+        Value::Char(x) => JsonValue::String(x.to_string()),
+        Value::String(x) | Value::Decimal(x) => JsonValue::String(x.clone()),
+        Value::Datetime(x) => JsonValue::String(x.to_string()),
+        Value::F32(_) | Value::F64(_) => Number::from_f64(float_value(value))
+            .map(JsonValue::Number)
+            .ok_or_else(|| unwritable("a non-finite float"))?,
+        Value::Tuple(values) | Value::Array(_, values) | Value::Vec(values) => {
+            JsonValue::Array(sequence_values(values, json_value)?)
+        }
+        Value::Struct(fields) | Value::Tagged(_, fields) => JsonValue::Object(
+            fields
+                .0
+                .iter()
+                .map(|(key, value)| match json_value(value) {
+                    Ok(value) => Ok((key.clone(), value)),
+                    Err(error) => Err(error.under_key(key)),
+                })
+                .collect::<Result<_, Error>>()?,
+        ),
+        integer => match integer_value(integer) {
+            Some(Ok(x)) => x.into(),
+            Some(Err(x)) => x.into(),
+            None => return Err(unwritable("an integer larger than 64 bits")),
+        },
+    })
+}
+
+/// Write a generic `Value` back out as YAML.
+///
+/// Units and `None`s are written as `null`, and chars, datetimes
+/// and decimals as strings.
+#[cfg(feature = "yaml")]
+pub fn to_yaml_string(value: &Value) -> Result<String, Error> {
+    Ok(serde_yaml::to_string(&yaml_value(value)?)?)
+}
+
+#[cfg(feature = "yaml")]
+fn yaml_value(value: &Value) -> Result<serde_yaml::Value, Error> {
+    use serde_yaml::{Mapping, Value as YamlValue};
+
+    Ok(match value {
+        Value::Unit | Value::Option(None) => YamlValue::Null,
+        Value::Option(Some(inner)) => yaml_value(inner)?,
+        Value::Bool(x) => YamlValue::Bool(*x),
+        Value::Char(x) => YamlValue::String(x.to_string()),
+        Value::String(x) | Value::Decimal(x) => YamlValue::String(x.clone()),
+        Value::Datetime(x) => YamlValue::String(x.to_string()),
+        Value::F32(_) | Value::F64(_) => float_value(value).into(),
+        Value::Tuple(values) | Value::Array(_, values) | Value::Vec(values) => {
+            YamlValue::Sequence(sequence_values(values, yaml_value)?)
+        }
+        Value::Struct(fields) | Value::Tagged(_, fields) => {
+            let mut mapping = Mapping::new();
+            for (key, value) in &fields.0 {
+                let value = yaml_value(value).map_err(|error| error.under_key(key))?;
+                mapping.insert(YamlValue::String(key.clone()), value);
+            }
+            YamlValue::Mapping(mapping)
+        }
+        integer => match integer_value(integer) {
+            Some(Ok(x)) => x.into(),
+            Some(Err(x)) => x.into(),
+            None => {
+                return Err(Error::UnwritableValue(
+                    crate::format::Format::Yaml,
+                    "an integer larger than 64 bits",
+                ))
+            }
+        },
+    })
+}
+
+/// Write a generic `Value` back out as TOML.
+///
+/// The value must be a struct. Since TOML has no null value, fields
+/// that are units or `None`s are left out, and an error is returned
+/// for any inside sequences. Datetimes are written as TOML datetimes.
+#[cfg(feature = "toml")]
+pub fn to_toml_string(value: &Value) -> Result<String, Error> {
+    let unwritable = |what| Error::UnwritableValue(crate::format::Format::Toml, what);
+    match toml_value(value)? {
+        Some(value @ toml::Value::Table(_)) => Ok(toml::to_string(&value)?),
+        _ => Err(unwritable("a value other than a struct at the top level")),
+    }
+}
+
+/// The TOML equivalent of a value, or `None` if it should be left
+/// out.
+#[cfg(feature = "toml")]
+fn toml_value(value: &Value) -> Result<Option<toml::Value>, Error> {
+    use toml::Value as TomlValue;
+
+    let unwritable = |what| Error::UnwritableValue(crate::format::Format::Toml, what);
+    Ok(Some(match value {
+        Value::Unit | Value::Option(None) => return Ok(None),
+        Value::Option(Some(inner)) => return toml_value(inner),
+        Value::Bool(x) => TomlValue::Boolean(*x),
+        Value::Char(x) => TomlValue::String(x.to_string()),
+        Value::String(x) | Value::Decimal(x) => TomlValue::String(x.clone()),
+        Value::Datetime(x) => match x.to_string().parse() {
+            Ok(datetime) => TomlValue::Datetime(datetime),
+            Err(_) => TomlValue::String(x.to_string()),
+        },
+        Value::F32(_) | Value::F64(_) => TomlValue::Float(float_value(value)),
+        Value::Tuple(values) | Value::Array(_, values) | Value::Vec(values) => {
+            TomlValue::Array(sequence_values(values, |value| {
+                toml_value(value)?.ok_or_else(|| unwritable("a null value in a sequence"))
+            })?)
+        }
+        Value::Struct(fields) | Value::Tagged(_, fields) => {
+            let mut table = toml::value::Table::new();
+            for (key, value) in &fields.0 {
+                if let Some(value) = toml_value(value).map_err(|error| error.under_key(key))? {
+                    table.insert(key.clone(), value);
+                }
+            }
+            TomlValue::Table(table)
+        }
+        integer => match integer_value(integer) {
+            Some(Ok(x)) => TomlValue::Integer(x),
+            _ => return Err(unwritable("an integer larger than 64 signed bits")),
+        },
+    }))
+}
+
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+fn sequence_values<T>(
+    values: &[Value],
+    convert: impl Fn(&Value) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| convert(value).map_err(|error| error.under_index(i)))
+        .collect()
+}
+
+/// An integer value as an `i64` if it fits, or otherwise a `u64`
+/// if it fits.
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+fn integer_value(value: &Value) -> Option<Result<i64, u64>> {
+    let value = crate::parsing::number_as_i128(value)?;
+    match i64::try_from(value) {
+        Ok(x) => Some(Ok(x)),
+        Err(_) => u64::try_from(value).ok().map(Err),
+    }
+}
+
+/// A float value as an `f64`.
+///
+/// `f32`s are converted through their shortest representation,
+/// so that `0.1f32` is written as `0.1` instead of
+/// `0.10000000149011612`.
+#[cfg(any(feature = "json", feature = "toml", feature = "yaml"))]
+fn float_value(value: &Value) -> f64 {
+    match *value {
+        Value::F32(x) => x.to_string().parse().unwrap_or(x as f64),
+        Value::F64(x) => x,
+        _ => unreachable!(),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -148,4 +1154,236 @@ mod test {
             assert!(bad.assume_struct().is_err());
         }
     }
+
+    #[test]
+    fn assume_map() {
+        let value = Value::Struct(Struct::from_pairs([("not an ident", Value::I32(1))]));
+        let map = value.assume_map().unwrap();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("not an ident"), Some(&Value::I32(1)));
+        assert_eq!(
+            map.iter().collect::<Vec<_>>(),
+            [("not an ident", &Value::I32(1))]
+        );
+        assert_eq!(Map::from(Struct::from(map.clone())), map);
+        assert!(Value::Unit.assume_map().is_err());
+    }
+
+    #[test]
+    fn key_path_parsing() {
+        let valid = ["", "a", "a.b", "a[2].c", "[0][1]", "a[0][1].b"];
+        for source in valid {
+            assert_eq!(KeyPath::parse(source).unwrap().to_string(), source);
+        }
+
+        let invalid = ["a..b", ".a", "a.", "a[", "a[x]", "a[1]b", "a.[1]"];
+        for source in invalid {
+            assert!(
+                matches!(KeyPath::parse(source), Err(Error::InvalidKeyPath(path)) if path == source),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn value_navigation() {
+        let mut value = Value::Struct(Struct::from_pairs([(
+            "items",
+            Value::Option(Some(Box::new(Value::Vec(vec![
+                Value::I64(1),
+                Value::Struct(Struct::from_pairs([("name", Value::String("two".into()))])),
+            ])))),
+        )]));
+        let key_path = KeyPath::parse("items[1].name").unwrap();
+
+        assert_eq!(
+            value.get_path(&key_path),
+            Some(&Value::String("two".into()))
+        );
+        assert_eq!(value.get_path(&KeyPath::new()), Some(&value));
+        assert_eq!(
+            value.get_path(&KeyPath::parse("items[0].name").unwrap()),
+            None
+        );
+        assert_eq!(value.get_path(&KeyPath::parse("items.name").unwrap()), None);
+
+        *value.get_path_mut(&key_path).unwrap() = Value::String("three".into());
+        assert_eq!(
+            value.clone().into_key_path("items[1].name").unwrap(),
+            Value::String("three".into())
+        );
+        assert!(matches!(
+            value.into_key_path("items[2]"),
+            Err(Error::IndexOutOfBounds(path, 2)) if path == "items[2]"
+        ));
+    }
+
+    #[test]
+    fn merge_strategies() {
+        let items = |items: &[i64]| Value::Vec(items.iter().copied().map(Value::I64).collect());
+        let base = Value::Struct(Struct::from_pairs([
+            ("a", Value::I64(1)),
+            ("list", items(&[1, 2])),
+            (
+                "nested",
+                Value::Struct(Struct::from_pairs([("x", Value::Bool(true))])),
+            ),
+        ]));
+        let other = Value::Struct(Struct::from_pairs([
+            ("a", Value::I64(2)),
+            ("list", items(&[3])),
+            (
+                "nested",
+                Value::Struct(Struct::from_pairs([("y", Value::Bool(false))])),
+            ),
+        ]));
+        let merged = |strategy| {
+            let mut value = base.clone();
+            value.merge(other.clone(), strategy);
+            value
+        };
+
+        assert_eq!(
+            merged(MergeStrategy::new()),
+            Value::Struct(Struct::from_pairs([
+                ("a", Value::I64(2)),
+                ("list", items(&[3])),
+                (
+                    "nested",
+                    Value::Struct(Struct::from_pairs([
+                        ("x", Value::Bool(true)),
+                        ("y", Value::Bool(false)),
+                    ]))
+                ),
+            ]))
+        );
+        assert_eq!(
+            merged(MergeStrategy {
+                maps: MapMerge::Override,
+                ..MergeStrategy::new()
+            }),
+            other
+        );
+
+        let merged_list = |sequences| {
+            let value = merged(MergeStrategy {
+                sequences,
+                scalars: ScalarMerge::Keep,
+                ..MergeStrategy::new()
+            });
+            value.get_path(&KeyPath::parse("list").unwrap()).cloned()
+        };
+        assert_eq!(merged_list(SequenceMerge::Replace), Some(items(&[3])));
+        assert_eq!(merged_list(SequenceMerge::Append), Some(items(&[1, 2, 3])));
+        assert_eq!(merged_list(SequenceMerge::ByIndex), Some(items(&[1, 2])));
+
+        let mut array = Value::Array(1, vec![Value::I64(1)]);
+        array.merge(
+            items(&[2, 3]),
+            MergeStrategy {
+                sequences: SequenceMerge::ByIndex,
+                ..MergeStrategy::new()
+            },
+        );
+        assert_eq!(array, Value::Array(2, vec![Value::I64(2), Value::I64(3)]));
+    }
+
+    #[test]
+    fn datetime_display() {
+        let sources = [
+            "1979-05-27T07:32:00Z",
+            "1979-05-27T00:32:00.999999-07:00",
+            "1979-05-27T07:32:00",
+            "1979-05-27",
+            "07:32:00.5",
+        ];
+        for source in sources {
+            let datetime = Datetime::parse(source).unwrap();
+            assert_eq!(datetime.to_string(), source);
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_round_trip() {
+        use crate::{options::ParseOptions, parsing::json::parse_source};
+
+        let source = r#"{
+            "name": "Ferris",
+            "float": 1.5,
+            "big": 18446744073709551615,
+            "nothing": null,
+            "nested": { "list": [1, -2, 3], "flags": [true, false] }
+        }"#;
+        let value = parse_source(source, &ParseOptions::new()).unwrap();
+        let written = to_json_string(&value).unwrap();
+        assert_eq!(parse_source(&written, &ParseOptions::new()).unwrap(), value);
+
+        let error = to_json_string(&Value::Vec(vec![Value::F64(f64::NAN)])).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Cannot write a non-finite float as Json (at key `[0]`)"
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn yaml_round_trip() {
+        use crate::{options::ParseOptions, parsing::yaml::parse_source};
+
+        let source = "
+            name: Ferris
+            float: 1.5
+            nothing: ~
+            nested:
+              list: [1, -2, 3]
+              points:
+                - { x: 1.0, y: 2.0 }
+                - { x: 3.0, y: 4.0 }
+        ";
+        let value = parse_source(source, &ParseOptions::new()).unwrap();
+        let written = to_yaml_string(&value).unwrap();
+        assert_eq!(parse_source(&written, &ParseOptions::new()).unwrap(), value);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_round_trip() {
+        use crate::{options::ParseOptions, parsing::toml::parse_source};
+
+        let source = r#"
+            name = "Ferris"
+            float = 1.5
+            born = 1979-05-27T07:32:00Z
+
+            [nested]
+            list = [1, -2, 3]
+
+            [[nested.points]]
+            x = 1.0
+            y = 2.0
+        "#;
+        let value = parse_source(source, &ParseOptions::new()).unwrap();
+        let written = to_toml_string(&value).unwrap();
+        assert_eq!(parse_source(&written, &ParseOptions::new()).unwrap(), value);
+
+        let with_none = Value::Struct(Struct::from_pairs([
+            ("some", Value::Option(Some(Box::new(Value::I32(1))))),
+            ("none", Value::Option(None)),
+        ]));
+        assert_eq!(to_toml_string(&with_none).unwrap(), "some = 1\n");
+
+        let error = to_toml_string(&Value::Vec(vec![])).unwrap_err();
+        assert!(matches!(error, Error::UnwritableValue(..)));
+        let error = to_toml_string(&Value::Struct(Struct::from_pairs([(
+            "list",
+            Value::Vec(vec![Value::Unit]),
+        )])))
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Cannot write a null value in a sequence as Toml (at key `list[0]`)"
+        );
+    }
 }
@@ -2,7 +2,9 @@ use pretty_assertions::assert_eq;
 use proc_macro2::TokenStream;
 use quote::quote;
 
-use edres_core::{codegen, options::*};
+#[cfg(all(feature = "json", feature = "toml", feature = "yaml"))]
+use edres_core::Format;
+use edres_core::{codegen, options::*, Error};
 
 fn assert_tokens(a: TokenStream, b: TokenStream) {
     assert_eq!(a.to_string(), b.to_string())
@@ -27,6 +29,45 @@ fn enum_from_filenames() {
     );
 }
 
+#[test]
+fn enum_from_filenames_with_globs() {
+    let only_a = Options {
+        files: FilesOptions {
+            include_globs: vec!["*_a.yaml".into()].into(),
+            ..FilesOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let result =
+        codegen::define_enum_from_filenames("tests/yamls".as_ref(), "FileName", &only_a).unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub enum FileName {
+                FileA,
+            }
+        },
+    );
+
+    let not_a = Options {
+        files: FilesOptions {
+            exclude_globs: vec!["*_a.*".into()].into(),
+            ..FilesOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let result =
+        codegen::define_enum_from_filenames("tests/yamls".as_ref(), "FileName", &not_a).unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub enum FileName {
+                FileB,
+            }
+        },
+    );
+}
+
 #[test]
 fn enum_from_filenames_with_values() {
     let result = codegen::define_enum_from_filenames(
@@ -145,6 +186,10 @@ fn enum_from_filenames_with_consts() {
                 get_string_fn_name: Some("string".into()),
                 file_bytes_const_name: Some("BYTES".into()),
                 get_bytes_fn_name: Some("bytes".into()),
+                file_stems_const_name: Some("STEMS".into()),
+                get_stem_fn_name: Some("stem".into()),
+                file_extensions_const_name: Some("EXTENSIONS".into()),
+                get_extension_fn_name: Some("extension".into()),
                 ..FilesOptions::default()
             },
             enums: EnumOptions {
@@ -171,21 +216,1134 @@ fn enum_from_filenames_with_consts() {
                     Self::FILE_PATHS[self as usize]
                 }
                 pub const BYTES: &'static [&'static [u8]] = &[
-                    include_bytes!("tests/yamls/file_a.yaml"),
-                    include_bytes!("tests/yamls/file_b.yaml"),
+                    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "tests/yamls/file_a.yaml")),
+                    include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "tests/yamls/file_b.yaml")),
                 ];
                 pub const fn bytes(self) -> &'static [u8] {
                     Self::BYTES[self as usize]
                 }
                 pub const STRINGS: &'static [&'static str] = &[
-                    include_str!("tests/yamls/file_a.yaml"),
-                    include_str!("tests/yamls/file_b.yaml"),
+                    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "tests/yamls/file_a.yaml")),
+                    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "tests/yamls/file_b.yaml")),
                 ];
                 pub const fn string(self) -> &'static str {
                     Self::STRINGS[self as usize]
                 }
+                pub const STEMS: &'static [&'static str] = &[
+                    "file_a",
+                    "file_b",
+                ];
+                pub const fn stem(self) -> &'static str {
+                    Self::STEMS[self as usize]
+                }
+                pub const EXTENSIONS: &'static [&'static str] = &[
+                    "yaml",
+                    "yaml",
+                ];
+                pub const fn extension(self) -> &'static str {
+                    Self::EXTENSIONS[self as usize]
+                }
                 pub const SOURCE_PATH: &'static str = "tests/yamls";
             }
         },
     );
 }
+
+#[test]
+fn modules_from_files() {
+    let result = codegen::define_modules_from_files(
+        "tests/yamls".as_ref(),
+        "FileContent",
+        None,
+        &Options {
+            source_path_const_name: Some("SOURCE_PATH".into()),
+            ..Options::minimal()
+        },
+    )
+    .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub mod file_a {
+                #[allow(non_camel_case_types)]
+                pub struct FileContent {
+                    pub number: i64,
+                    pub text: std::borrow::Cow<'static, str>,
+                    pub nested: FileContent__nested,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct FileContent__nested {
+                    pub text_again: std::borrow::Cow<'static, str>,
+                }
+
+                impl FileContent {
+                    pub const SOURCE_PATH: &'static str = "tests/yamls/file_a.yaml";
+                }
+            }
+
+            pub mod file_b {
+                #[allow(non_camel_case_types)]
+                pub struct FileContent {
+                    pub number: i64,
+                    pub text: std::borrow::Cow<'static, str>,
+                    pub nested: FileContent__nested,
+                }
+
+                #[allow(non_camel_case_types)]
+                pub struct FileContent__nested {
+                    pub text_again: std::borrow::Cow<'static, str>,
+                }
+
+                impl FileContent {
+                    pub const SOURCE_PATH: &'static str = "tests/yamls/file_b.yaml";
+                }
+            }
+        },
+    );
+}
+
+#[test]
+fn modules_from_keyword_files() {
+    let result = codegen::define_modules_from_files(
+        "tests/module_names/keywords".as_ref(),
+        "Config",
+        None,
+        &Options::minimal(),
+    )
+    .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub mod self_ {
+                #[allow(non_camel_case_types)]
+                pub struct Config {
+                    pub name: std::borrow::Cow<'static, str>,
+                }
+            }
+
+            pub mod r#type {
+                #[allow(non_camel_case_types)]
+                pub struct Config {
+                    pub name: std::borrow::Cow<'static, str>,
+                }
+            }
+        },
+    );
+}
+
+#[test]
+fn modules_from_files_with_invalid_names() {
+    let error = codegen::define_modules_from_files(
+        "tests/module_names/numeric".as_ref(),
+        "Config",
+        None,
+        &Options::minimal(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "File name `2024` cannot be used as a module name \
+        (in file `tests/module_names/numeric/2024.yaml`)"
+    );
+
+    let error = codegen::define_modules_from_files(
+        "tests/module_names/same_stem".as_ref(),
+        "Config",
+        None,
+        &Options::minimal(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Generated name `x` is used more than once"
+    );
+}
+
+#[test]
+fn enum_from_filenames_with_from_path() {
+    let result = codegen::define_enum_from_filenames(
+        "tests/yamls".as_ref(),
+        "FileName",
+        &Options {
+            enums: EnumOptions {
+                impl_from_path: true,
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        },
+    )
+    .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub enum FileName {
+                FileA,
+                FileB,
+            }
+
+            impl FileName {
+                pub fn from_path(path: &str) -> Option<Self> {
+                    match path {
+                        "tests/yamls/file_a.yaml" | "tests/yamls/file_a" => Some(Self::FileA),
+                        "tests/yamls/file_b.yaml" | "tests/yamls/file_b" => Some(Self::FileB),
+                        _ => None,
+                    }
+                }
+            }
+
+            impl<'a> std::convert::TryFrom<&'a str> for FileName {
+                type Error = &'a str;
+
+                fn try_from(path: &'a str) -> Result<Self, Self::Error> {
+                    Self::from_path(path).ok_or(path)
+                }
+            }
+        },
+    );
+}
+
+#[test]
+fn enum_from_filenames_with_load_fn() {
+    let options = Options {
+        enums: EnumOptions {
+            values_struct: Some(ValuesStructOptions {
+                field_selection: FieldSelection::Include(vec!["number".into()].into()),
+                ..ValuesStructOptions::minimal()
+            }),
+            ..EnumOptions::minimal()
+        },
+        files: FilesOptions {
+            load_fn_name: Some("load".into()),
+            ..FilesOptions::minimal()
+        },
+        serde_support: SerdeSupport::Mixed {
+            serialize: false,
+            deserialize: true,
+        },
+        ..Options::minimal()
+    };
+    let result =
+        codegen::define_enum_from_filenames("tests/yamls".as_ref(), "FileName", &options).unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            #[derive(serde::Deserialize)]
+            pub enum FileName {
+                FileA,
+                FileB,
+            }
+
+            impl FileName {
+                pub fn load(self) -> Result<FileName__Value, FileNameLoadError> {
+                    match self {
+                        Self::FileA => {
+                            let source = std::fs::read_to_string("tests/yamls/file_a.yaml")
+                                .map_err(FileNameLoadError::Io)?;
+                            serde_yaml::from_str(&source)
+                                .map_err(|error| FileNameLoadError::Deserialize(Box::new(error)))
+                        }
+                        Self::FileB => {
+                            let source = std::fs::read_to_string("tests/yamls/file_b.yaml")
+                                .map_err(FileNameLoadError::Io)?;
+                            serde_yaml::from_str(&source)
+                                .map_err(|error| FileNameLoadError::Deserialize(Box::new(error)))
+                        }
+                    }
+                }
+            }
+
+            #[allow(non_camel_case_types)]
+            #[derive(serde::Deserialize)]
+            pub struct FileName__Value {
+                pub number: i64,
+            }
+
+            #[derive(Debug)]
+            pub enum FileNameLoadError {
+                Io(std::io::Error),
+                Deserialize(Box<dyn std::error::Error + Send + Sync>),
+            }
+
+            impl std::fmt::Display for FileNameLoadError {
+                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    match self {
+                        Self::Io(error) => write!(f, "Failed to read file: {}", error),
+                        Self::Deserialize(error) => write!(f, "Failed to deserialize file: {}", error),
+                    }
+                }
+            }
+
+            impl std::error::Error for FileNameLoadError {
+                fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                    match self {
+                        Self::Io(error) => Some(error),
+                        Self::Deserialize(error) => Some(&**error),
+                    }
+                }
+            }
+        },
+    );
+
+    let without_serde = Options {
+        serde_support: SerdeSupport::No,
+        ..options
+    };
+    assert!(matches!(
+        codegen::define_enum_from_filenames("tests/yamls".as_ref(), "FileName", &without_serde),
+        Err(Error::RequiresSerdeDeserialize(
+            "FilesOptions::load_fn_name"
+        ))
+    ));
+}
+
+#[test]
+fn enum_from_filenames_with_index() {
+    let result = codegen::define_enum_from_filenames(
+        "tests/yamls".as_ref(),
+        "FileName",
+        &Options {
+            enums: EnumOptions {
+                impl_index: true,
+                ..EnumOptions::minimal()
+            },
+            ..Options::minimal()
+        },
+    )
+    .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub enum FileName {
+                FileA,
+                FileB,
+            }
+
+            impl FileName {
+                pub const COUNT: usize = 2usize;
+
+                pub const fn index(self) -> usize {
+                    self as usize
+                }
+
+                pub const fn from_index(index: usize) -> Option<Self> {
+                    match index {
+                        0usize => Some(Self::FileA),
+                        1usize => Some(Self::FileB),
+                        _ => None,
+                    }
+                }
+            }
+        },
+    );
+}
+
+#[test]
+fn enum_from_filenames_with_lazy_values() {
+    let options = Options {
+        enums: EnumOptions {
+            values_struct: Some(ValuesStructOptions::minimal()),
+            get_value_fn_name: Some("get".into()),
+            ..EnumOptions::minimal()
+        },
+        files: FilesOptions {
+            lazy_values: true,
+            ..FilesOptions::minimal()
+        },
+        serde_support: SerdeSupport::Mixed {
+            serialize: false,
+            deserialize: true,
+        },
+        ..Options::minimal()
+    };
+    let result =
+        codegen::define_enum_from_filenames("tests/yamls".as_ref(), "FileName", &options).unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            #[derive(serde::Deserialize)]
+            pub enum FileName {
+                FileA,
+                FileB,
+            }
+
+            impl FileName {
+                pub fn get(self) -> &'static FileName__Value {
+                    static VALUES: [std::sync::OnceLock<FileName__Value>; 2usize] = [
+                        std::sync::OnceLock::new(),
+                        std::sync::OnceLock::new(),
+                    ];
+                    VALUES[self as usize].get_or_init(|| match self {
+                        Self::FileA => serde_yaml::from_str(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "tests/yamls/file_a.yaml")))
+                            .expect(concat!("Failed to deserialize ", "tests/yamls/file_a.yaml")),
+                        Self::FileB => serde_yaml::from_str(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "tests/yamls/file_b.yaml")))
+                            .expect(concat!("Failed to deserialize ", "tests/yamls/file_b.yaml")),
+                    })
+                }
+            }
+
+            #[allow(non_camel_case_types)]
+            #[derive(serde::Deserialize)]
+            pub struct FileName__Value {
+                pub number: i64,
+                pub text: std::borrow::Cow<'static, str>,
+                pub nested: FileName__Value__nested,
+            }
+
+            #[allow(non_camel_case_types)]
+            #[derive(serde::Deserialize)]
+            pub struct FileName__Value__nested {
+                pub text_again: std::borrow::Cow<'static, str>,
+            }
+        },
+    );
+
+    let without_serde = Options {
+        serde_support: SerdeSupport::No,
+        ..options
+    };
+    assert!(codegen::define_enum_from_filenames(
+        "tests/yamls".as_ref(),
+        "FileName",
+        &without_serde
+    )
+    .is_err());
+}
+
+#[test]
+fn structs_from_conflicting_file_contents() {
+    let error = codegen::define_structs_from_file_contents(
+        "tests/conflicting_yamls".as_ref(),
+        "FileStruct",
+        None,
+        &Options::minimal(),
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "Values have incompatible types:\
+        \n  field `speed` is f64 in tests/conflicting_yamls/a.yaml \
+        but String in tests/conflicting_yamls/b.yaml\
+        \n  field `tags[]` is String in tests/conflicting_yamls/a.yaml \
+        but i64 in tests/conflicting_yamls/b.yaml"
+    );
+}
+
+#[test]
+fn structs_from_file_contents_with_optional_fields() {
+    let result = codegen::define_structs_from_file_contents(
+        "tests/optional_fields".as_ref(),
+        "FileStruct",
+        None,
+        &Options {
+            structs: StructOptions {
+                struct_data_const_name: Some("DATA".into()),
+                ..StructOptions::minimal()
+            },
+            ..Options::minimal()
+        },
+    )
+    .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            #[allow(non_camel_case_types)]
+            pub struct FileStruct {
+                pub name: std::borrow::Cow<'static, str>,
+                pub speed: Option<i64>,
+                pub color: Option<std::borrow::Cow<'static, str> >,
+            }
+
+            pub const DATA: &[FileStruct] = &[
+                FileStruct {
+                    name: std::borrow::Cow::Borrowed("a"),
+                    speed: Some(1i64),
+                    color: None,
+                },
+                FileStruct {
+                    name: std::borrow::Cow::Borrowed("b"),
+                    speed: None,
+                    color: Some(std::borrow::Cow::Borrowed("red")),
+                },
+            ];
+        },
+    );
+}
+
+#[cfg(all(feature = "json", feature = "toml"))]
+#[test]
+fn structs_from_included_files() {
+    let options = Options {
+        parse: ParseOptions {
+            include_directive: Some("!include".into()),
+            ..ParseOptions::new()
+        },
+        ..Options::minimal()
+    };
+    let path = std::path::Path::new("tests/includes/root.yaml");
+    let value = edres_core::parsing::parse_source_file(path, &options.parse)
+        .unwrap()
+        .assume_struct()
+        .unwrap();
+    let result = codegen::define_structs(&value, "Config", None, &options).unwrap();
+
+    assert_tokens(
+        result,
+        quote! {
+            #[allow(non_camel_case_types)]
+            pub struct Config {
+                pub name: std::borrow::Cow<'static, str>,
+                pub stats: Config__stats,
+                pub items: std::borrow::Cow<'static, [Config__items]>,
+            }
+
+            #[allow(non_camel_case_types)]
+            pub struct Config__stats {
+                pub health: i64,
+                pub bonus: Config__stats__bonus,
+            }
+
+            #[allow(non_camel_case_types)]
+            pub struct Config__stats__bonus {
+                pub armor: i64,
+            }
+
+            #[allow(non_camel_case_types)]
+            pub struct Config__items {
+                pub id: i64,
+            }
+        },
+    );
+
+    let path = std::path::Path::new("tests/include_cycle/a.yaml");
+    let error = edres_core::parsing::parse_source_file(path, &options.parse).unwrap_err();
+    assert_eq!(
+        error.file_path(),
+        Some(std::path::Path::new("tests/include_cycle/b.yaml"))
+    );
+    assert_eq!(error.key_path(), Some("a"));
+    assert!(error
+        .to_string()
+        .starts_with("Files include each other in a cycle:"));
+}
+
+#[test]
+fn duplicate_keys_in_file() {
+    let path = std::path::Path::new("tests/duplicate_keys/config.json");
+    let options = ParseOptions {
+        duplicate_keys: DuplicateKeyPolicy::Error,
+        ..ParseOptions::new()
+    };
+    let error = edres_core::parsing::parse_source_file(path, &options).unwrap_err();
+    assert_eq!(error.file_path(), Some(path));
+    assert_eq!(error.key_path(), Some("settings"));
+    assert!(error.to_string().starts_with("Duplicate key `volume`"));
+
+    let options = ParseOptions {
+        duplicate_keys: DuplicateKeyPolicy::FirstWins,
+        ..ParseOptions::new()
+    };
+    assert!(edres_core::parsing::parse_source_file(path, &options).is_ok());
+}
+
+#[test]
+fn enum_from_indexed_filenames() {
+    let options = Options {
+        enums: EnumOptions {
+            all_values_const_name: Some("VALUES".into()),
+            values_struct: Some(ValuesStructOptions::minimal()),
+            ..EnumOptions::minimal()
+        },
+        files: FilesOptions {
+            index_file: Some("_index.yaml".into()),
+            ..FilesOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let result =
+        codegen::define_enum_from_filenames("tests/indexed".as_ref(), "FileName", &options)
+            .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub enum FileName {
+                Second,
+                Primary,
+            }
+
+            impl FileName {
+                pub const VALUES: &'static [FileName__Value] = &[
+                    FileName__Value { value: 2i64, },
+                    FileName__Value { value: 1i64, },
+                ];
+            }
+
+            #[allow(non_camel_case_types)]
+            pub struct FileName__Value {
+                pub value: i64,
+            }
+        },
+    );
+
+    let error =
+        codegen::define_enum_from_filenames("tests/bad_index".as_ref(), "FileName", &options)
+            .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Index file does not match the directory \
+        (missing files: [missing.yaml], unlisted files: [second.yaml]) \
+        (in file `tests/bad_index/_index.yaml`)"
+    );
+}
+
+#[test]
+fn string_table() {
+    let options = Options {
+        enums: EnumOptions {
+            all_variants_const_name: Some("ALL".into()),
+            ..EnumOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let result =
+        codegen::define_string_table("tests/strings".as_ref(), "Lang", "StringKey", &options)
+            .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub enum Lang {
+                De,
+                En,
+            }
+
+            impl Lang {
+                pub const STRINGS: [[&'static str; 3usize]; 2usize] = [
+                    ["Hallo", "Menü", "Beenden"],
+                    ["Hello", "Menu", "Quit"]
+                ];
+
+                pub const fn get(self, key: StringKey) -> &'static str {
+                    Self::STRINGS[self as usize][key as usize]
+                }
+
+                pub const ALL: &'static [Self] = &[Self::De, Self::En,];
+            }
+
+            pub enum StringKey {
+                Greeting,
+                MenuTitle,
+                MenuQuit,
+            }
+
+            impl StringKey {
+                pub const ALL: &'static [Self] = &[Self::Greeting, Self::MenuTitle, Self::MenuQuit,];
+            }
+        },
+    );
+
+    let error = codegen::define_string_table(
+        "tests/strings_missing".as_ref(),
+        "Lang",
+        "StringKey",
+        &options,
+    )
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Locales are missing keys: `de` is missing [greeting]; \
+        `fr` is missing [farewell]"
+    );
+}
+
+#[test]
+fn string_table_with_match_arm_accessors() {
+    let options = Options {
+        enums: EnumOptions {
+            accessor_style: AccessorStyle::MatchArms,
+            ..EnumOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let result =
+        codegen::define_string_table("tests/strings".as_ref(), "Lang", "StringKey", &options)
+            .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub enum Lang {
+                De,
+                En,
+            }
+
+            impl Lang {
+                pub const STRINGS: [[&'static str; 3usize]; 2usize] = [
+                    ["Hallo", "Menü", "Beenden"],
+                    ["Hello", "Menu", "Quit"]
+                ];
+
+                pub const fn get(self, key: StringKey) -> &'static str {
+                    Self::STRINGS[match self {
+                        Self::De => 0usize,
+                        Self::En => 1usize,
+                    }][match key {
+                        StringKey::Greeting => 0usize,
+                        StringKey::MenuTitle => 1usize,
+                        StringKey::MenuQuit => 2usize,
+                    }]
+                }
+            }
+
+            pub enum StringKey {
+                Greeting,
+                MenuTitle,
+                MenuQuit,
+            }
+        },
+    );
+}
+
+#[test]
+fn enum_from_colliding_filenames() {
+    let options = Options {
+        enums: EnumOptions {
+            variant_casing: Some(Casing::Pascal),
+            ..EnumOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let error =
+        codegen::define_enum_from_filenames("tests/colliding".as_ref(), "FileName", &options)
+            .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Files would have the same variant name `FileA`: \
+        tests/colliding/file-a.yaml, tests/colliding/file_a.yaml"
+    );
+
+    let options = Options {
+        files: FilesOptions {
+            disambiguate_variants: true,
+            ..FilesOptions::minimal()
+        },
+        ..options
+    };
+    let result =
+        codegen::define_enum_from_filenames("tests/colliding".as_ref(), "FileName", &options)
+            .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub enum FileName {
+                FileA,
+                FileA3,
+                FileA2,
+            }
+        },
+    );
+}
+
+#[test]
+fn enum_from_filenames_with_path_root() {
+    let options = |path_root| Options {
+        files: FilesOptions {
+            path_root,
+            ..FilesOptions::file_bytes()
+        },
+        ..Options::minimal()
+    };
+    let result = codegen::define_enum_from_filenames(
+        "tests/yamls".as_ref(),
+        "FileName",
+        &options(PathRoot::OutDir),
+    )
+    .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub enum FileName {
+                FileA,
+                FileB,
+            }
+
+            impl FileName {
+                pub const FILE_BYTES: &'static [&'static [u8]] = &[
+                    include_bytes!(concat!(env!("OUT_DIR"), "/", "tests/yamls/file_a.yaml")),
+                    include_bytes!(concat!(env!("OUT_DIR"), "/", "tests/yamls/file_b.yaml")),
+                ];
+                pub const fn bytes(self) -> &'static [u8] {
+                    Self::FILE_BYTES[self as usize]
+                }
+            }
+        },
+    );
+
+    let result = codegen::define_enum_from_filenames(
+        "tests/yamls".as_ref(),
+        "FileName",
+        &options(PathRoot::Custom("/data".into())),
+    )
+    .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub enum FileName {
+                FileA,
+                FileB,
+            }
+
+            impl FileName {
+                pub const FILE_BYTES: &'static [&'static [u8]] = &[
+                    include_bytes!("/data/tests/yamls/file_a.yaml"),
+                    include_bytes!("/data/tests/yamls/file_b.yaml"),
+                ];
+                pub const fn bytes(self) -> &'static [u8] {
+                    Self::FILE_BYTES[self as usize]
+                }
+            }
+        },
+    );
+}
+
+#[cfg(feature = "bevy")]
+#[test]
+fn enum_from_filenames_for_bevy() {
+    let options = Options {
+        files: FilesOptions {
+            bevy: Some(BevyOptions {
+                asset_root: "tests/assets".into(),
+                preload_plugin_name: Some("SpritePlugin".into()),
+                ..BevyOptions::new()
+            }),
+            ..FilesOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let result =
+        codegen::define_enum_from_filenames("tests/assets/sprites".as_ref(), "Sprite", &options)
+            .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            #[derive(bevy::reflect::Reflect)]
+            pub enum Sprite {
+                Enemy,
+                Player,
+            }
+
+            impl Sprite {
+                pub const fn handle_path(self) -> &'static str {
+                    const HANDLE_PATHS: &[&str] = &["sprites/enemy.png", "sprites/player.png",];
+                    HANDLE_PATHS[self as usize]
+                }
+            }
+
+            pub struct SpritePlugin;
+
+            #[derive(bevy::prelude::Resource)]
+            pub struct SpriteHandles(pub Vec<bevy::asset::UntypedHandle>);
+
+            impl bevy::prelude::Plugin for SpritePlugin {
+                fn build(&self, app: &mut bevy::prelude::App) {
+                    app.add_systems(
+                        bevy::prelude::Startup,
+                        |mut commands: bevy::prelude::Commands,
+                         server: bevy::prelude::Res<bevy::prelude::AssetServer>| {
+                            let handles = ["sprites/enemy.png", "sprites/player.png",]
+                                .into_iter()
+                                .map(|path| server.load_untyped(path).untyped())
+                                .collect();
+                            commands.insert_resource(SpriteHandles(handles));
+                        },
+                    );
+                }
+            }
+        },
+    );
+
+    let error = codegen::define_enum_from_filenames(
+        "tests/assets/sprites".as_ref(),
+        "Sprite",
+        &Options {
+            files: FilesOptions {
+                bevy: Some(BevyOptions {
+                    asset_root: "assets".into(),
+                    ..BevyOptions::new()
+                }),
+                ..FilesOptions::minimal()
+            },
+            ..Options::minimal()
+        },
+    )
+    .unwrap_err();
+    assert!(matches!(error, Error::UnsupportedFilePath(_)));
+}
+
+#[test]
+fn enum_from_missing_or_empty_dir() {
+    let error = codegen::define_enum_from_filenames(
+        "tests/missing".as_ref(),
+        "FileName",
+        &Options::minimal(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, Error::DirectoryNotFound(_)));
+
+    let options = Options {
+        files: FilesOptions {
+            include_globs: vec!["*.json".into()].into(),
+            ..FilesOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let error = codegen::define_enum_from_filenames("tests/yamls".as_ref(), "FileName", &options)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "No files matched in directory `tests/yamls` (see `FilesOptions::allow_empty`)"
+    );
+
+    let options = Options {
+        files: FilesOptions {
+            allow_empty: true,
+            ..options.files
+        },
+        ..options
+    };
+    let result =
+        codegen::define_enum_from_filenames("tests/yamls".as_ref(), "FileName", &options).unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            #[non_exhaustive]
+            pub enum FileName {}
+        },
+    );
+}
+
+#[cfg(all(feature = "json", feature = "toml", feature = "yaml"))]
+#[test]
+fn structs_from_mixed_formats() {
+    let options = Options {
+        structs: StructOptions {
+            struct_data_const_name: Some("DATA".into()),
+            ..StructOptions::minimal()
+        },
+        files: FilesOptions {
+            format_overrides: vec![("*.conf".into(), Format::Toml)].into(),
+            skip_unknown_formats: true,
+            ..FilesOptions::minimal()
+        },
+        ..Options::minimal()
+    };
+    let result = codegen::define_structs_from_file_contents(
+        "tests/mixed".as_ref(),
+        "FileStruct",
+        None,
+        &options,
+    )
+    .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            #[allow(non_camel_case_types)]
+            pub struct FileStruct {
+                pub name: std::borrow::Cow<'static, str>,
+                pub speed: i64,
+            }
+
+            pub const DATA: &[FileStruct] = &[
+                FileStruct {
+                    name: std::borrow::Cow::Borrowed("a"),
+                    speed: 1i64,
+                },
+                FileStruct {
+                    name: std::borrow::Cow::Borrowed("b"),
+                    speed: 2i64,
+                },
+                FileStruct {
+                    name: std::borrow::Cow::Borrowed("c"),
+                    speed: 3i64,
+                },
+            ];
+        },
+    );
+
+    let error = codegen::define_structs_from_file_contents(
+        "tests/mixed".as_ref(),
+        "FileStruct",
+        None,
+        &Options {
+            files: FilesOptions {
+                skip_unknown_formats: false,
+                ..options.files.clone()
+            },
+            ..options.clone()
+        },
+    )
+    .unwrap_err();
+    assert_eq!(error.file_path(), Some("tests/mixed/notes.txt".as_ref()));
+    assert!(matches!(
+        error,
+        Error::InFile { error, .. } if matches!(*error, Error::UnknownInputFormat(_))
+    ));
+}
+
+#[test]
+fn enum_from_file_keys() {
+    let result = codegen::define_enum_from_file_keys(
+        "tests/file_keys".as_ref(),
+        "Setting",
+        &Options::minimal(),
+    )
+    .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub enum Setting {
+                debug,
+                port,
+                workers,
+            }
+        },
+    );
+
+    let result = codegen::define_enum_from_file_keys(
+        "tests/file_keys".as_ref(),
+        "Setting",
+        &Options {
+            enums: EnumOptions {
+                variant_casing: Some(Casing::SnakeToPascal),
+                ..EnumOptions::minimal()
+            },
+            files: FilesOptions {
+                intersect_keys: true,
+                ..FilesOptions::minimal()
+            },
+            ..Options::minimal()
+        },
+    )
+    .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub enum Setting {
+                Port,
+            }
+        },
+    );
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn structs_from_msgpack_files() {
+    let result = codegen::define_structs_from_file_contents(
+        "tests/msgpack".as_ref(),
+        "FileStruct",
+        None,
+        &Options {
+            structs: StructOptions {
+                struct_data_const_name: Some("DATA".into()),
+                ..StructOptions::minimal()
+            },
+            ..Options::minimal()
+        },
+    )
+    .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            #[allow(non_camel_case_types)]
+            pub struct FileStruct {
+                pub name: std::borrow::Cow<'static, str>,
+                pub speed: i64,
+            }
+
+            pub const DATA: &[FileStruct] = &[
+                FileStruct {
+                    name: std::borrow::Cow::Borrowed("a"),
+                    speed: 1i64,
+                },
+                FileStruct {
+                    name: std::borrow::Cow::Borrowed("b"),
+                    speed: 256i64,
+                },
+            ];
+        },
+    );
+}
+
+#[cfg(all(feature = "toml", feature = "yaml"))]
+#[test]
+fn spanned_values_from_file() {
+    use edres_core::value::{KeyPath, Location};
+
+    let options = ParseOptions {
+        include_directive: Some("!include".into()),
+        ..ParseOptions::new()
+    };
+    let path = std::path::Path::new("tests/spanned/config.toml");
+    let spanned = edres_core::parsing::parse_source_file_spanned(path, &options).unwrap();
+    let location = |key_path| spanned.location(&KeyPath::parse(key_path).unwrap());
+
+    assert_eq!(
+        spanned.value,
+        edres_core::parsing::parse_source_file(path, &options).unwrap()
+    );
+    assert_eq!(spanned.file_path.as_deref(), Some(path));
+    assert_eq!(location("port"), Some(Location { line: 2, column: 8 }));
+    assert_eq!(
+        location("extra.debug"),
+        Some(Location { line: 3, column: 9 })
+    );
+}
+
+#[test]
+fn enums_from_subdirs() {
+    let result = codegen::define_enums_from_subdirs(
+        "tests/subdirs".as_ref(),
+        "AssetKind",
+        &Options::minimal(),
+    )
+    .unwrap();
+    assert_tokens(
+        result,
+        quote! {
+            pub enum Items {
+                Shield,
+                Sword,
+            }
+
+            pub enum MagicSpells {
+                Fireball,
+            }
+
+            pub enum AssetKind {
+                Items,
+                MagicSpells,
+            }
+
+            impl AssetKind {
+                pub const fn paths(self) -> &'static [&'static str] {
+                    match self {
+                        Self::Items => &["tests/subdirs/items/shield.yaml", "tests/subdirs/items/sword.yaml"],
+                        Self::MagicSpells => &["tests/subdirs/magic_spells/fireball.yaml"],
+                    }
+                }
+            }
+        },
+    );
+
+    let error = codegen::define_enums_from_subdirs(
+        "tests/yamls".as_ref(),
+        "AssetKind",
+        &Options::minimal(),
+    )
+    .unwrap_err();
+    assert!(matches!(error, Error::NoFilesMatched(_)));
+
+    let error =
+        codegen::define_enums_from_subdirs("tests/subdirs".as_ref(), "Items", &Options::minimal())
+            .unwrap_err();
+    assert!(matches!(error, Error::DuplicateName(name) if name == "Items"));
+}
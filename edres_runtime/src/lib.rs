@@ -0,0 +1,25 @@
+//! Runtime support for code generated by `edres`.
+//!
+//! With [`OutputOptions::runtime_crate`] set, generated code takes the
+//! items it uses (like `Cow`) from this crate instead of from `std`,
+//! so later versions of `edres` can change what they need at runtime
+//! without breaking crates which include generated code.
+//!
+//! This crate is `no_std`, so it also supports code generated with
+//! [`OutputOptions::no_std`].
+//!
+//! [`OutputOptions::runtime_crate`]: https://docs.rs/edres/0.6.0/edres/options/struct.OutputOptions.html#structfield.runtime_crate
+//! [`OutputOptions::no_std`]: https://docs.rs/edres/0.6.0/edres/options/struct.OutputOptions.html#structfield.no_std
+
+#![no_std]
+
+extern crate alloc;
+
+pub use alloc::{borrow, collections, string, vec};
+pub use core::{convert, fmt, iter, result, str};
+
+/// The type of string fields in generated structs.
+pub type StaticStr = borrow::Cow<'static, str>;
+
+/// The type of sequence fields in generated structs.
+pub type StaticSlice<T> = borrow::Cow<'static, [T]>;
@@ -1,4 +1,8 @@
-use edres::{codegen, parsing, value::Value, Options, StructOptions};
+use edres::{
+    codegen, parsing,
+    value::{Map, Value},
+    EnumOptions, FilesOptions, Options, OutputOptions, StructOptions,
+};
 
 fn main() {
     build().unwrap();
@@ -22,8 +26,23 @@ fn build() -> Result<(), Box<dyn std::error::Error>> {
                 _ => panic!("Not a struct!"),
             };
 
-            let source =
-                codegen::define_structs(&value, "Struct", Some(path.as_ref()), &options).unwrap();
+            let source = codegen::define_structs(
+                &value,
+                "Struct",
+                Some(path.as_ref()),
+                &Options {
+                    structs: StructOptions {
+                        generate_builder: true,
+                        ..options.structs.clone()
+                    },
+                    output: OutputOptions {
+                        runtime_crate: true,
+                        ..options.output.clone()
+                    },
+                    ..options.clone()
+                },
+            )
+            .unwrap();
             writeln!(&mut buffer, "{}", source).unwrap();
         }
 
@@ -31,13 +50,27 @@ fn build() -> Result<(), Box<dyn std::error::Error>> {
         {
             let path = format!("data/{}/map.{}", dir, dir);
             let value = match parsing::parse_source_file(path.as_ref(), &options.parse).unwrap() {
-                Value::Struct(s) => s,
+                Value::Struct(s) => Map::from(s),
                 _ => panic!("Not a struct!"),
             };
 
-            let source =
-                codegen::define_enum_from_keys(&value, "Enum", Some(path.as_ref()), &options)
-                    .unwrap();
+            let source = codegen::define_enum_from_keys(
+                &value,
+                "Enum",
+                Some(path.as_ref()),
+                &Options {
+                    enums: EnumOptions {
+                        impl_as_ref_str: true,
+                        static_str_fn_name: Some("into_static_str".into()),
+                        static_values: true,
+                        impl_try_from_index: true,
+                        impl_iter: true,
+                        ..options.enums.clone()
+                    },
+                    ..options.clone()
+                },
+            )
+            .unwrap();
             writeln!(&mut buffer, "{}", source).unwrap();
         }
 
@@ -45,7 +78,7 @@ fn build() -> Result<(), Box<dyn std::error::Error>> {
         {
             let path = format!("data/{}/map.{}", dir, dir);
             let value = match parsing::parse_source_file(path.as_ref(), &options.parse).unwrap() {
-                Value::Struct(s) => s,
+                Value::Struct(s) => Map::from(s),
                 _ => panic!("Not a struct!"),
             };
 
@@ -56,8 +89,18 @@ fn build() -> Result<(), Box<dyn std::error::Error>> {
         // define_enum_from_filenames
         {
             let path = format!("data/{}/files", dir);
-            let source =
-                codegen::define_enum_from_filenames(path.as_ref(), "FileEnum", &options).unwrap();
+            let source = codegen::define_enum_from_filenames(
+                path.as_ref(),
+                "FileEnum",
+                &Options {
+                    files: FilesOptions {
+                        load_fn_name: Some("load".into()),
+                        ..Default::default()
+                    },
+                    ..options.clone()
+                },
+            )
+            .unwrap();
             writeln!(&mut buffer, "{}", source).unwrap();
         }
 
@@ -80,6 +123,16 @@ fn build() -> Result<(), Box<dyn std::error::Error>> {
             writeln!(&mut buffer, "{}", source).unwrap();
         }
 
+        // define_bitflags
+        {
+            let path = format!("data/{}/flags.{}", dir, dir);
+            let value = parsing::parse_source_file(path.as_ref(), &options.parse).unwrap();
+
+            let source =
+                codegen::define_bitflags(&value, "Flags", Some(path.as_ref()), &options).unwrap();
+            writeln!(&mut buffer, "{}", source).unwrap();
+        }
+
         std::fs::create_dir_all("src/gen").unwrap();
         std::fs::write(format!("src/gen/{}.rs", dir), buffer).unwrap();
     }
@@ -30,6 +30,17 @@ macro_rules! gen_tests {
                 let data: Struct = de::$modname(&src);
                 assert_eq!(data.number, 100_i64);
                 assert_eq!(data.text, $ext);
+                assert_eq!(data.r#type, "struct");
+                assert_eq!(data.nested.array.as_ref(), [1, 2, 3_i64]);
+            }
+
+            #[test]
+            fn struct_builder() {
+                use crate::gen::$modname::Struct;
+
+                let data = Struct::builder().number(5).build();
+                assert_eq!(data.number, 5_i64);
+                assert_eq!(data.text, $ext);
                 assert_eq!(data.nested.array.as_ref(), [1, 2, 3_i64]);
             }
 
@@ -40,6 +51,13 @@ macro_rules! gen_tests {
                 assert_eq!(Enum::ALL, &[Enum::Variant1, Enum::Variant2]);
                 assert_eq!(Enum::Variant1.get().value, 1);
                 assert_eq!(Enum::Variant2.get().value, 2);
+                assert!(std::ptr::eq(Enum::Variant1.get(), Enum::Variant1.get()));
+                assert_eq!(Enum::Variant1.as_ref(), "Variant1");
+                assert_eq!(<&'static str>::from(Enum::Variant2), "Variant2");
+                assert_eq!(Enum::Variant2.into_static_str(), "Variant2");
+                assert_eq!(Enum::try_from(1), Ok(Enum::Variant2));
+                assert_eq!(Enum::try_from(2), Err(2));
+                assert!(Enum::iter().eq(Enum::ALL.iter().copied()));
             }
 
             #[test]
@@ -74,6 +92,14 @@ macro_rules! gen_tests {
                 assert_eq!(FileEnum::FileB.get().name, "file_b");
             }
 
+            #[test]
+            fn file_enum_load() {
+                use crate::gen::$modname::FileEnum;
+
+                assert_eq!(FileEnum::FileA.load().unwrap().name, "file_a");
+                assert_eq!(FileEnum::FileB.load().unwrap().name, "file_b");
+            }
+
             #[test]
             fn deserialize_file_structs() {
                 use crate::gen::$modname::{FileEnum, FileStruct, FILE_VALUES};
@@ -97,6 +123,19 @@ macro_rules! gen_tests {
                 assert_eq!(FILE_VALUES[0].name, "file_a");
                 assert_eq!(FILE_VALUES[1].name, "file_b");
             }
+
+            #[test]
+            fn bitflags() {
+                use crate::gen::$modname::Flags;
+
+                assert_eq!(Flags::READ.bits(), 1);
+                assert_eq!(Flags::EXECUTE.bits(), 4);
+                assert_eq!(Flags::READ | Flags::WRITE, Flags::READ_WRITE);
+                assert!(Flags::READ_WRITE.contains(Flags::WRITE));
+                assert!(!Flags::READ_WRITE.contains(Flags::EXECUTE));
+                assert_eq!(Flags::all().bits(), 7);
+                assert_eq!(Flags::empty(), Flags::from_bits_retain(0));
+            }
         }
     };
 }